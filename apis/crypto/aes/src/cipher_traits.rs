@@ -0,0 +1,173 @@
+//! Implementations of the RustCrypto `cipher` traits.
+//!
+//! The traits cannot report errors, so these ciphers panic if the driver
+//! returns one. The driver runs one operation at a time, so only one cipher may
+//! be in use at once; use [`Aes`] directly to handle errors.
+
+use crate::{Aes, AesMode, Config, Direction, BLOCK_LEN, KEY_LEN};
+use cipher::consts::{U1, U16};
+use cipher::inout::{InOut, InOutBuf};
+use cipher::{
+    Block, BlockBackend, BlockCipher, BlockClosure, BlockDecrypt, BlockEncrypt, BlockSizeUser, Iv,
+    IvSizeUser, Key, KeyInit, KeyIvInit, KeySizeUser, ParBlocksSizeUser, StreamCipher,
+    StreamCipherError,
+};
+use core::marker::PhantomData;
+use libtock_platform::{DefaultConfig, Syscalls};
+
+/// The AES-128 block cipher, computed by the AES driver in ECB mode. The
+/// RustCrypto block mode crates can build other modes on top of it.
+pub struct Aes128<S: Syscalls, C: Config = DefaultConfig> {
+    key: [u8; KEY_LEN],
+    _syscalls: PhantomData<(S, C)>,
+}
+
+impl<S: Syscalls, C: Config> Aes128<S, C> {
+    fn with_backend(&self, direction: Direction, f: impl BlockClosure<BlockSize = U16>) {
+        Aes::<S, C>::start(AesMode::Ecb, direction, &self.key, &[0; BLOCK_LEN])
+            .expect("Unable to start AES");
+        f.call(&mut Backend::<S, C>(PhantomData));
+        Aes::<S, C>::finish().expect("AES finish failed");
+    }
+}
+
+impl<S: Syscalls, C: Config> KeySizeUser for Aes128<S, C> {
+    type KeySize = U16;
+}
+
+impl<S: Syscalls, C: Config> KeyInit for Aes128<S, C> {
+    fn new(key: &Key<Self>) -> Self {
+        Aes128 {
+            key: (*key).into(),
+            _syscalls: PhantomData,
+        }
+    }
+}
+
+impl<S: Syscalls, C: Config> BlockSizeUser for Aes128<S, C> {
+    type BlockSize = U16;
+}
+
+impl<S: Syscalls, C: Config> BlockCipher for Aes128<S, C> {}
+
+impl<S: Syscalls, C: Config> BlockEncrypt for Aes128<S, C> {
+    fn encrypt_with_backend(&self, f: impl BlockClosure<BlockSize = U16>) {
+        self.with_backend(Direction::Encrypt, f);
+    }
+}
+
+impl<S: Syscalls, C: Config> BlockDecrypt for Aes128<S, C> {
+    fn decrypt_with_backend(&self, f: impl BlockClosure<BlockSize = U16>) {
+        self.with_backend(Direction::Decrypt, f);
+    }
+}
+
+// Passes blocks to the operation started by `Aes128::with_backend`.
+struct Backend<S: Syscalls, C: Config>(PhantomData<(S, C)>);
+
+impl<S: Syscalls, C: Config> BlockSizeUser for Backend<S, C> {
+    type BlockSize = U16;
+}
+
+impl<S: Syscalls, C: Config> ParBlocksSizeUser for Backend<S, C> {
+    type ParBlocksSize = U1;
+}
+
+impl<S: Syscalls, C: Config> BlockBackend for Backend<S, C> {
+    fn proc_block(&mut self, mut block: InOut<'_, '_, Block<Self>>) {
+        let mut data = block.clone_in();
+        Aes::<S, C>::crypt(&mut data).expect("AES crypt failed");
+        *block.get_out() = data;
+    }
+}
+
+/// AES-128 in CTR mode with a 128-bit big-endian counter, computed by the AES
+/// driver. The operation starts on first use and finishes when the cipher is
+/// dropped.
+///
+/// The driver only processes whole blocks, so the cipher keeps the keystream
+/// of a block that was partly used, and applies the rest of it to the data of
+/// the next call.
+pub struct Aes128Ctr<S: Syscalls, C: Config = DefaultConfig> {
+    key: [u8; KEY_LEN],
+    iv: [u8; BLOCK_LEN],
+    // Whether the driver has been set up for this cipher.
+    started: bool,
+    // The keystream of the last block, of which the bytes from `used` on have
+    // not been applied yet.
+    keystream: [u8; BLOCK_LEN],
+    used: usize,
+    _syscalls: PhantomData<(S, C)>,
+}
+
+impl<S: Syscalls, C: Config> KeySizeUser for Aes128Ctr<S, C> {
+    type KeySize = U16;
+}
+
+impl<S: Syscalls, C: Config> IvSizeUser for Aes128Ctr<S, C> {
+    type IvSize = U16;
+}
+
+impl<S: Syscalls, C: Config> KeyIvInit for Aes128Ctr<S, C> {
+    fn new(key: &Key<Self>, iv: &Iv<Self>) -> Self {
+        Aes128Ctr {
+            key: (*key).into(),
+            iv: (*iv).into(),
+            started: false,
+            keystream: [0; BLOCK_LEN],
+            used: BLOCK_LEN,
+            _syscalls: PhantomData,
+        }
+    }
+}
+
+impl<S: Syscalls, C: Config> StreamCipher for Aes128Ctr<S, C> {
+    fn try_apply_keystream_inout(
+        &mut self,
+        mut buf: InOutBuf<'_, '_, u8>,
+    ) -> Result<(), StreamCipherError> {
+        if !self.started {
+            Aes::<S, C>::start(AesMode::Ctr, Direction::Encrypt, &self.key, &self.iv)
+                .expect("Unable to start AES");
+            self.started = true;
+        }
+        // The driver works in place, so copy the input to the output first.
+        for i in 0..buf.len() {
+            let mut byte = buf.get(i);
+            let value = *byte.get_in();
+            *byte.get_out() = value;
+        }
+        let data = buf.into_out();
+
+        let (head, rest) = data.split_at_mut((BLOCK_LEN - self.used).min(data.len()));
+        xor(head, &self.keystream[self.used..]);
+        self.used += head.len();
+
+        let (blocks, tail) = rest.split_at_mut(rest.len() - rest.len() % BLOCK_LEN);
+        if !blocks.is_empty() {
+            Aes::<S, C>::crypt(blocks).expect("AES crypt failed");
+        }
+        if !tail.is_empty() {
+            // Encrypting zeros yields the keystream itself.
+            self.keystream = [0; BLOCK_LEN];
+            Aes::<S, C>::crypt(&mut self.keystream).expect("AES crypt failed");
+            xor(tail, &self.keystream);
+            self.used = tail.len();
+        }
+        Ok(())
+    }
+}
+
+impl<S: Syscalls, C: Config> Drop for Aes128Ctr<S, C> {
+    fn drop(&mut self) {
+        if self.started {
+            let _ = Aes::<S, C>::finish();
+        }
+    }
+}
+
+fn xor(data: &mut [u8], keystream: &[u8]) {
+    for (byte, key) in data.iter_mut().zip(keystream) {
+        *byte ^= key;
+    }
+}
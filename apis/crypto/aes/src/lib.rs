@@ -0,0 +1,247 @@
+#![no_std]
+
+use libtock_platform as platform;
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::share;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+#[cfg(feature = "rust_crypto")]
+mod cipher_traits;
+#[cfg(feature = "rust_crypto")]
+pub use cipher_traits::{Aes128, Aes128Ctr};
+
+/// The AES-128 driver.
+///
+/// The driver runs one operation at a time, encrypting or decrypting data in
+/// place. [`Aes::encrypt`] and [`Aes::decrypt`] handle data that is all in one
+/// buffer; [`Aes::start`], [`Aes::crypt`] and [`Aes::finish`] handle data that
+/// arrives in pieces, carrying the chaining state from one piece to the next.
+/// [`Aes::ccm_encrypt`] and [`Aes::ccm_decrypt`] provide authenticated
+/// encryption.
+///
+/// # Example
+/// ```ignore
+/// use libtock::aes::{Aes, AesMode, Direction};
+///
+/// Aes::encrypt(AesMode::Cbc, &KEY, &iv, &mut blocks)?;
+///
+/// // Or, with the data in pieces:
+/// Aes::start(AesMode::Ctr, Direction::Decrypt, &KEY, &counter)?;
+/// Aes::crypt(&mut header)?;
+/// Aes::crypt(&mut body)?;
+/// Aes::finish()?;
+///
+/// // Authenticated encryption of `frame`, which holds 8 bytes of header, the
+/// // message, and 8 bytes of space for the MIC.
+/// Aes::ccm_encrypt(&KEY, &nonce, &mut frame, 8, 8)?;
+/// if !Aes::ccm_decrypt(&KEY, &nonce, &mut frame, 8, 8)? {
+///     return Err(ErrorCode::Invalid);
+/// }
+/// ```
+pub struct Aes<S: Syscalls, C: Config = DefaultConfig>(S, C);
+
+/// A mode of operation for unauthenticated encryption.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AesMode {
+    Ctr = 0,
+    Cbc = 1,
+    Ecb = 2,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Direction {
+    Encrypt,
+    Decrypt,
+}
+
+/// The length of a key, in bytes.
+pub const KEY_LEN: usize = 16;
+
+/// The length of a block, and of an initialization vector, in bytes.
+pub const BLOCK_LEN: usize = 16;
+
+/// The length of a CCM nonce, in bytes.
+pub const CCM_NONCE_LEN: usize = 13;
+
+impl<S: Syscalls, C: Config> Aes<S, C> {
+    /// Starts encrypting or decrypting with `key`. `iv` is the initialization
+    /// vector in CBC mode and the initial counter block in CTR mode, and is
+    /// ignored in ECB mode.
+    pub fn start(
+        mode: AesMode,
+        direction: Direction,
+        key: &[u8; KEY_LEN],
+        iv: &[u8; BLOCK_LEN],
+    ) -> Result<(), ErrorCode> {
+        Self::set_algorithm(mode as u32, direction)?;
+        Self::setup(key, iv)
+    }
+
+    /// Encrypts or decrypts `data` in place, continuing the operation begun by
+    /// [`Aes::start`]. `data` must be a whole number of blocks long.
+    pub fn crypt(data: &mut [u8]) -> Result<(), ErrorCode> {
+        Self::run(data).map(|_| ())
+    }
+
+    /// Ends the operation, so that another may start.
+    pub fn finish() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::FINISH, 0, 0).to_result()
+    }
+
+    /// Encrypts `data` in place. `data` must be a whole number of blocks long.
+    pub fn encrypt(
+        mode: AesMode,
+        key: &[u8; KEY_LEN],
+        iv: &[u8; BLOCK_LEN],
+        data: &mut [u8],
+    ) -> Result<(), ErrorCode> {
+        Self::start(mode, Direction::Encrypt, key, iv)?;
+        let result = Self::crypt(data);
+        result.and(Self::finish())
+    }
+
+    /// Decrypts `data` in place. `data` must be a whole number of blocks long.
+    pub fn decrypt(
+        mode: AesMode,
+        key: &[u8; KEY_LEN],
+        iv: &[u8; BLOCK_LEN],
+        data: &mut [u8],
+    ) -> Result<(), ErrorCode> {
+        Self::start(mode, Direction::Decrypt, key, iv)?;
+        let result = Self::crypt(data);
+        result.and(Self::finish())
+    }
+
+    /// Encrypts and authenticates a message with AES-CCM. `buffer` holds
+    /// `aad_len` bytes of additional data, which is authenticated but not
+    /// encrypted, then the message, then `mic_len` bytes of space for the
+    /// message integrity code. The message is encrypted in place and the MIC
+    /// written after it.
+    ///
+    /// `mic_len` must be 4, 6, 8, 10, 12, 14 or 16.
+    pub fn ccm_encrypt(
+        key: &[u8; KEY_LEN],
+        nonce: &[u8; CCM_NONCE_LEN],
+        buffer: &mut [u8],
+        aad_len: usize,
+        mic_len: usize,
+    ) -> Result<(), ErrorCode> {
+        Self::ccm(Direction::Encrypt, key, nonce, buffer, aad_len, mic_len).map(|_| ())
+    }
+
+    /// Decrypts and authenticates a message encrypted by
+    /// [`Aes::ccm_encrypt`], laid out the same way in `buffer`. Returns
+    /// `Ok(false)` if the MIC does not match, in which case the message must be
+    /// discarded.
+    pub fn ccm_decrypt(
+        key: &[u8; KEY_LEN],
+        nonce: &[u8; CCM_NONCE_LEN],
+        buffer: &mut [u8],
+        aad_len: usize,
+        mic_len: usize,
+    ) -> Result<bool, ErrorCode> {
+        Self::ccm(Direction::Decrypt, key, nonce, buffer, aad_len, mic_len)
+    }
+
+    fn ccm(
+        direction: Direction,
+        key: &[u8; KEY_LEN],
+        nonce: &[u8; CCM_NONCE_LEN],
+        buffer: &mut [u8],
+        aad_len: usize,
+        mic_len: usize,
+    ) -> Result<bool, ErrorCode> {
+        Self::set_algorithm(algorithm::CCM, direction)?;
+        S::command(DRIVER_NUM, command::CCM_SET_AAD_LEN, aad_len as u32, 0)
+            .to_result::<(), ErrorCode>()?;
+        S::command(DRIVER_NUM, command::CCM_SET_MIC_LEN, mic_len as u32, 0)
+            .to_result::<(), ErrorCode>()?;
+        Self::setup(key, nonce)?;
+        let result = Self::run(buffer);
+        let finished = Self::finish();
+        result.and_then(|authentic| finished.map(|()| authentic))
+    }
+
+    fn set_algorithm(algorithm: u32, direction: Direction) -> Result<(), ErrorCode> {
+        let encrypting = (direction == Direction::Encrypt) as u32;
+        S::command(DRIVER_NUM, command::SET_ALGORITHM, algorithm, encrypting).to_result()
+    }
+
+    // Loads the key and IV (or nonce) into the engine.
+    fn setup(key: &[u8], iv: &[u8]) -> Result<(), ErrorCode> {
+        share::scope::<
+            (
+                AllowRo<_, DRIVER_NUM, { allow_ro::KEY }>,
+                AllowRo<_, DRIVER_NUM, { allow_ro::IV }>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_key, allow_iv) = handle.split();
+            S::allow_ro::<C, DRIVER_NUM, { allow_ro::KEY }>(allow_key, key)?;
+            S::allow_ro::<C, DRIVER_NUM, { allow_ro::IV }>(allow_iv, iv)?;
+            S::command(DRIVER_NUM, command::SETUP, 0, 0).to_result()
+        })
+    }
+
+    // Processes `data` in place. Returns whether the MIC matched, which is only
+    // meaningful when decrypting with CCM.
+    fn run(data: &mut [u8]) -> Result<bool, ErrorCode> {
+        share::scope::<AllowRw<_, DRIVER_NUM, { allow_rw::DATA }>, _, _>(|allow| {
+            S::allow_rw::<C, DRIVER_NUM, { allow_rw::DATA }>(allow, data)?;
+            S::command(DRIVER_NUM, command::CRYPT, 0, 0).to_result::<(), ErrorCode>()?;
+            match S::yield_wait_for(DRIVER_NUM, subscribe::DONE) {
+                (0, authentic, _) => Ok(authentic != 0),
+                (status, _, _) => Err(status.try_into().unwrap_or(ErrorCode::Fail)),
+            }
+        })
+    }
+}
+
+/// System call configuration trait for `Aes`.
+pub trait Config:
+    platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config
+{
+}
+impl<T: platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config>
+    Config for T
+{
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x40006;
+
+// Algorithms beyond those in `AesMode`.
+mod algorithm {
+    pub const CCM: u32 = 3;
+}
+
+// Command IDs
+mod command {
+    pub const SET_ALGORITHM: u32 = 1;
+    pub const SETUP: u32 = 2;
+    pub const CRYPT: u32 = 3;
+    pub const FINISH: u32 = 4;
+    pub const CCM_SET_AAD_LEN: u32 = 5;
+    pub const CCM_SET_MIC_LEN: u32 = 6;
+}
+
+mod subscribe {
+    pub const DONE: u32 = 0;
+}
+
+mod allow_ro {
+    pub const KEY: u32 = 0;
+    pub const IV: u32 = 1;
+}
+
+mod allow_rw {
+    pub const DATA: u32 = 0;
+}
@@ -0,0 +1,208 @@
+use super::{AesMode, Direction};
+use hex_literal::hex;
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+type Aes = super::Aes<fake::Syscalls>;
+
+// Test vectors from NIST SP 800-38A, appendix F.
+const KEY: [u8; 16] = hex!("2b7e151628aed2a6abf7158809cf4f3c");
+const PLAINTEXT: [u8; 64] = hex!(
+    "6bc1bee22e409f96e93d7e117393172a"
+    "ae2d8a571e03ac9c9eb76fac45af8e51"
+    "30c81c46a35ce411e5fbc1191a0a52ef"
+    "f69f2445df4f9b17ad2b417be66c3710"
+);
+const ECB_CIPHERTEXT: [u8; 64] = hex!(
+    "3ad77bb40d7a3660a89ecaf32466ef97"
+    "f5d3d58503b9699de785895a96fdbaaf"
+    "43b1cd7f598ece23881b00e3ed030688"
+    "7b0c785e27e8ad3f8223207104725dd4"
+);
+const CBC_IV: [u8; 16] = hex!("000102030405060708090a0b0c0d0e0f");
+const CBC_CIPHERTEXT: [u8; 64] = hex!(
+    "7649abac8119b246cee98e9b12e9197d"
+    "5086cb9b507219ee95db113a917678b2"
+    "73bed6b8e3c1743b7116e69e22229516"
+    "3ff1caa1681fac09120eca307586e1a7"
+);
+const CTR_IV: [u8; 16] = hex!("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+const CTR_CIPHERTEXT: [u8; 64] = hex!(
+    "874d6191b620e3261bef6864990db6ce"
+    "9806f66b7970fdff8617187bb9fffdff"
+    "5ae4df3edbd5d35e5b4f09020db03eab"
+    "1e031dda2fbe03d1792170a0f3009cee"
+);
+
+// Packet vector #1 from RFC 3610: 8 bytes of additional data, a 23 byte
+// message and an 8 byte MIC.
+const CCM_KEY: [u8; 16] = hex!("c0c1c2c3c4c5c6c7c8c9cacbcccdcecf");
+const CCM_NONCE: [u8; 13] = hex!("00000003020100a0a1a2a3a4a5");
+const CCM_PLAINTEXT: [u8; 39] = hex!(
+    "0001020304050607"
+    "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e"
+    "0000000000000000"
+);
+const CCM_CIPHERTEXT: [u8; 39] = hex!(
+    "0001020304050607"
+    "588c979a61c663d2f066d0c2c0f989806d5f6b61dac384"
+    "17e8d12cfdf926e0"
+);
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    let mut data = PLAINTEXT;
+    assert_eq!(
+        Aes::encrypt(AesMode::Ecb, &KEY, &[0; 16], &mut data),
+        Err(ErrorCode::NoDevice)
+    );
+}
+
+#[test]
+fn modes() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Aes::new();
+    kernel.add_driver(&driver);
+
+    for (mode, iv, ciphertext) in [
+        (AesMode::Ecb, [0; 16], ECB_CIPHERTEXT),
+        (AesMode::Cbc, CBC_IV, CBC_CIPHERTEXT),
+        (AesMode::Ctr, CTR_IV, CTR_CIPHERTEXT),
+    ] {
+        let mut data = PLAINTEXT;
+        assert_eq!(Aes::encrypt(mode, &KEY, &iv, &mut data), Ok(()));
+        assert_eq!(data, ciphertext);
+        assert_eq!(Aes::decrypt(mode, &KEY, &iv, &mut data), Ok(()));
+        assert_eq!(data, PLAINTEXT);
+    }
+    assert!(!driver.is_busy());
+}
+
+#[test]
+fn partial_blocks() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Aes::new();
+    kernel.add_driver(&driver);
+
+    // Every mode only works on whole blocks, and the operation is finished
+    // even if it fails.
+    let mut data = PLAINTEXT;
+    for (mode, iv) in [(AesMode::Cbc, CBC_IV), (AesMode::Ctr, CTR_IV)] {
+        assert_eq!(
+            Aes::encrypt(mode, &KEY, &iv, &mut data[..20]),
+            Err(ErrorCode::Size)
+        );
+        assert!(!driver.is_busy());
+        assert_eq!(data, PLAINTEXT);
+    }
+}
+
+#[test]
+fn streaming() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Aes::new();
+    kernel.add_driver(&driver);
+
+    // The chaining state carries over from one piece to the next.
+    let mut data = PLAINTEXT;
+    assert_eq!(
+        Aes::start(AesMode::Cbc, Direction::Encrypt, &KEY, &CBC_IV),
+        Ok(())
+    );
+    for chunk in data.chunks_mut(32) {
+        assert_eq!(Aes::crypt(chunk), Ok(()));
+    }
+    assert_eq!(Aes::finish(), Ok(()));
+    assert_eq!(data, CBC_CIPHERTEXT);
+
+    let mut data = CTR_CIPHERTEXT;
+    assert_eq!(
+        Aes::start(AesMode::Ctr, Direction::Decrypt, &KEY, &CTR_IV),
+        Ok(())
+    );
+    for chunk in data.chunks_mut(16) {
+        assert_eq!(Aes::crypt(chunk), Ok(()));
+    }
+    assert_eq!(Aes::finish(), Ok(()));
+    assert_eq!(data, PLAINTEXT);
+    assert_eq!(driver.crypts(), 6);
+}
+
+#[test]
+fn ccm() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Aes::new();
+    kernel.add_driver(&driver);
+
+    let mut buffer = CCM_PLAINTEXT;
+    assert_eq!(
+        Aes::ccm_encrypt(&CCM_KEY, &CCM_NONCE, &mut buffer, 8, 8),
+        Ok(())
+    );
+    assert_eq!(buffer, CCM_CIPHERTEXT);
+    assert_eq!(
+        Aes::ccm_decrypt(&CCM_KEY, &CCM_NONCE, &mut buffer, 8, 8),
+        Ok(true)
+    );
+    assert_eq!(buffer[..31], CCM_PLAINTEXT[..31]);
+
+    // Changing the additional data, the ciphertext or the MIC is detected.
+    for index in [0, 8, 38] {
+        let mut buffer = CCM_CIPHERTEXT;
+        buffer[index] ^= 1;
+        assert_eq!(
+            Aes::ccm_decrypt(&CCM_KEY, &CCM_NONCE, &mut buffer, 8, 8),
+            Ok(false)
+        );
+    }
+    assert!(!driver.is_busy());
+
+    assert_eq!(
+        Aes::ccm_encrypt(&CCM_KEY, &CCM_NONCE, &mut buffer, 8, 7),
+        Err(ErrorCode::Invalid)
+    );
+    assert_eq!(
+        Aes::ccm_encrypt(&CCM_KEY, &CCM_NONCE, &mut buffer[..15], 8, 8),
+        Err(ErrorCode::Size)
+    );
+}
+
+#[cfg(feature = "rust_crypto")]
+#[test]
+fn cipher_traits() {
+    use cipher::{BlockDecrypt, BlockEncrypt, KeyInit, KeyIvInit, StreamCipher};
+    let kernel = fake::Kernel::new();
+    let driver = fake::Aes::new();
+    kernel.add_driver(&driver);
+
+    type Aes128 = super::Aes128<fake::Syscalls>;
+
+    let aes = Aes128::new(&KEY.into());
+    let mut blocks = [cipher::Block::<Aes128>::default(); 4];
+    for (block, plaintext) in blocks.iter_mut().zip(PLAINTEXT.chunks(16)) {
+        block.copy_from_slice(plaintext);
+    }
+    aes.encrypt_blocks(&mut blocks);
+    for (block, ciphertext) in blocks.iter().zip(ECB_CIPHERTEXT.chunks(16)) {
+        assert_eq!(block[..], *ciphertext);
+    }
+    aes.decrypt_block(&mut blocks[2]);
+    assert_eq!(blocks[2][..], PLAINTEXT[32..48]);
+    assert!(!driver.is_busy());
+
+    let mut data = PLAINTEXT;
+    let mut ctr = super::Aes128Ctr::<fake::Syscalls>::new(&KEY.into(), &CTR_IV.into());
+    // The cipher keeps the rest of a block's keystream for the next call, so
+    // the driver only ever sees whole blocks.
+    let crypts = driver.crypts();
+    ctr.apply_keystream(&mut data[..5]);
+    ctr.apply_keystream(&mut data[5..7]);
+    ctr.apply_keystream(&mut data[7..40]);
+    ctr.apply_keystream(&mut data[40..]);
+    assert_eq!(data, CTR_CIPHERTEXT);
+    assert_eq!(driver.crypts() - crypts, 4);
+    assert!(driver.is_busy());
+    drop(ctr);
+    assert!(!driver.is_busy());
+}
@@ -0,0 +1,81 @@
+#![no_std]
+
+use libtock_platform as platform;
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::share;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+/// The CRC driver, which computes CRCs with the chip's CRC unit.
+///
+/// # Example
+/// ```ignore
+/// use libtock::crc::{Crc, CrcAlgorithm};
+///
+/// let crc = Crc::compute(CrcAlgorithm::Crc32, b"123456789")?;
+/// assert_eq!(crc, 0xCBF43926);
+/// ```
+pub struct Crc<S: Syscalls, C: Config = DefaultConfig>(S, C);
+
+/// The CRC algorithms the driver computes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CrcAlgorithm {
+    /// CRC-32, as used by Ethernet: polynomial 0x04C11DB7, reflected, with
+    /// the output inverted.
+    Crc32 = 0,
+    /// CRC-32C (Castagnoli): polynomial 0x1EDC6F41, reflected, with the output
+    /// inverted.
+    Crc32C = 1,
+    /// CRC-16-CCITT: polynomial 0x1021, initial value 0xFFFF, neither
+    /// reflected nor inverted. This is also known as CRC-16/CCITT-FALSE.
+    Crc16Ccitt = 2,
+}
+
+impl<S: Syscalls, C: Config> Crc<S, C> {
+    /// Run a check against the CRC capsule to ensure it is present.
+    #[inline(always)]
+    pub fn exists() -> bool {
+        S::command(DRIVER_NUM, command::EXISTS, 0, 0).is_success()
+    }
+
+    /// Computes the CRC of `data` with `algorithm`. 16-bit CRCs are returned
+    /// in the low bits.
+    pub fn compute(algorithm: CrcAlgorithm, data: &[u8]) -> Result<u32, ErrorCode> {
+        let len = u32::try_from(data.len()).map_err(|_| ErrorCode::Size)?;
+        share::scope::<AllowRo<_, DRIVER_NUM, { allow_ro::DATA }>, _, _>(|allow| {
+            S::allow_ro::<C, DRIVER_NUM, { allow_ro::DATA }>(allow, data)?;
+            S::command(DRIVER_NUM, command::COMPUTE, algorithm as u32, len)
+                .to_result::<(), ErrorCode>()?;
+            match S::yield_wait_for(DRIVER_NUM, subscribe::DONE) {
+                (0, crc, _) => Ok(crc),
+                (status, _, _) => Err(status.try_into().unwrap_or(ErrorCode::Fail)),
+            }
+        })
+    }
+}
+
+/// System call configuration trait for `Crc`.
+pub trait Config: platform::allow_ro::Config + platform::subscribe::Config {}
+impl<T: platform::allow_ro::Config + platform::subscribe::Config> Config for T {}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x40002;
+
+// Command IDs
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const COMPUTE: u32 = 1;
+}
+
+mod subscribe {
+    pub const DONE: u32 = 0;
+}
+
+mod allow_ro {
+    pub const DATA: u32 = 0;
+}
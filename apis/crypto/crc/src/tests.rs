@@ -0,0 +1,38 @@
+use super::CrcAlgorithm;
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+type Crc = super::Crc<fake::Syscalls>;
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert!(!Crc::exists());
+    assert_eq!(
+        Crc::compute(CrcAlgorithm::Crc32, b"123456789"),
+        Err(ErrorCode::NoDevice)
+    );
+}
+
+#[test]
+fn compute() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Crc::new();
+    kernel.add_driver(&driver);
+
+    assert!(Crc::exists());
+    // The check values of the CRC catalogue.
+    assert_eq!(
+        Crc::compute(CrcAlgorithm::Crc32, b"123456789"),
+        Ok(0xCBF4_3926)
+    );
+    assert_eq!(
+        Crc::compute(CrcAlgorithm::Crc32C, b"123456789"),
+        Ok(0xE306_9283)
+    );
+    assert_eq!(
+        Crc::compute(CrcAlgorithm::Crc16Ccitt, b"123456789"),
+        Ok(0x29B1)
+    );
+    assert_eq!(Crc::compute(CrcAlgorithm::Crc16Ccitt, b""), Ok(0xFFFF));
+}
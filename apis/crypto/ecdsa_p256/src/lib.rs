@@ -0,0 +1,167 @@
+#![no_std]
+
+use core::cell::Cell;
+use libtock_platform as platform;
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::subscribe::{OneId, Subscribe};
+use libtock_platform::{share, DefaultConfig, ErrorCode, Syscalls, Upcall};
+
+/// The ECDSA P-256 signature verification driver.
+///
+/// The driver checks a signature over a SHA-256 hash against a public key. All
+/// three are shared with the kernel, and the result arrives through an upcall.
+///
+/// # Example
+/// ```ignore
+/// use libtock::ecdsa_p256::EcdsaP256;
+///
+/// // Check an image received over the radio before installing it.
+/// let mut hash = [0; 32];
+/// Sha::digest(ShaAlgorithm::Sha256, &image, &mut hash)?;
+/// if !EcdsaP256::verify_sync(&hash, &signature, &VENDOR_KEY)? {
+///     return Err(ErrorCode::Invalid);
+/// }
+/// ```
+pub struct EcdsaP256<S: Syscalls, C: Config = DefaultConfig>(S, C);
+
+/// The length of a SHA-256 hash, in bytes.
+pub const HASH_LEN: usize = 32;
+
+/// The length of a signature, in bytes: the big-endian `r` followed by the
+/// big-endian `s`.
+pub const SIGNATURE_LEN: usize = 64;
+
+/// The length of a public key, in bytes: the big-endian `x` coordinate
+/// followed by the big-endian `y` coordinate.
+pub const PUBLIC_KEY_LEN: usize = 64;
+
+impl<S: Syscalls, C: Config> EcdsaP256<S, C> {
+    /// Returns Ok() if the driver was present. This does not necessarily mean
+    /// that the driver is working.
+    pub fn exists() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::EXISTS, 0, 0).to_result()
+    }
+
+    /// Shares the hash of the signed message with the kernel.
+    pub fn allow_hash<'share>(
+        hash: &'share [u8; HASH_LEN],
+        allow_ro: share::Handle<AllowRo<'share, S, DRIVER_NUM, { allow_ro::HASH }>>,
+    ) -> Result<(), ErrorCode> {
+        S::allow_ro::<C, DRIVER_NUM, { allow_ro::HASH }>(allow_ro, hash)
+    }
+
+    /// Shares the signature with the kernel.
+    pub fn allow_signature<'share>(
+        signature: &'share [u8; SIGNATURE_LEN],
+        allow_ro: share::Handle<AllowRo<'share, S, DRIVER_NUM, { allow_ro::SIGNATURE }>>,
+    ) -> Result<(), ErrorCode> {
+        S::allow_ro::<C, DRIVER_NUM, { allow_ro::SIGNATURE }>(allow_ro, signature)
+    }
+
+    /// Shares the public key with the kernel.
+    pub fn allow_public_key<'share>(
+        public_key: &'share [u8; PUBLIC_KEY_LEN],
+        allow_ro: share::Handle<AllowRo<'share, S, DRIVER_NUM, { allow_ro::PUBLIC_KEY }>>,
+    ) -> Result<(), ErrorCode> {
+        S::allow_ro::<C, DRIVER_NUM, { allow_ro::PUBLIC_KEY }>(allow_ro, public_key)
+    }
+
+    /// Registers a listener to be called with the result of each
+    /// verification.
+    pub fn register_listener<'share, F: Fn(Result<bool, ErrorCode>)>(
+        listener: &'share VerifyListener<F>,
+        subscribe: share::Handle<Subscribe<'share, S, DRIVER_NUM, { subscribe::DONE }>>,
+    ) -> Result<(), ErrorCode> {
+        S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::DONE }>(subscribe, listener)
+    }
+
+    /// Unregisters the listener.
+    pub fn unregister_listener() {
+        S::unsubscribe(DRIVER_NUM, subscribe::DONE)
+    }
+
+    /// Starts verifying the shared signature of the shared hash against the
+    /// shared public key. The registered listener is called with the result.
+    pub fn verify() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::VERIFY, 0, 0).to_result()
+    }
+
+    /// Verifies `signature` of `hash` against `public_key`, waiting for the
+    /// result. Returns `Ok(false)` if the signature is rejected.
+    pub fn verify_sync(
+        hash: &[u8; HASH_LEN],
+        signature: &[u8; SIGNATURE_LEN],
+        public_key: &[u8; PUBLIC_KEY_LEN],
+    ) -> Result<bool, ErrorCode> {
+        let result: Cell<Option<Result<bool, ErrorCode>>> = Cell::new(None);
+        let listener = VerifyListener(|verified| result.set(Some(verified)));
+        share::scope::<
+            (
+                AllowRo<_, DRIVER_NUM, { allow_ro::HASH }>,
+                AllowRo<_, DRIVER_NUM, { allow_ro::SIGNATURE }>,
+                AllowRo<_, DRIVER_NUM, { allow_ro::PUBLIC_KEY }>,
+                Subscribe<_, DRIVER_NUM, { subscribe::DONE }>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_hash, allow_signature, allow_public_key, subscribe) = handle.split();
+            Self::allow_hash(hash, allow_hash)?;
+            Self::allow_signature(signature, allow_signature)?;
+            Self::allow_public_key(public_key, allow_public_key)?;
+            Self::register_listener(&listener, subscribe)?;
+            Self::verify()?;
+            loop {
+                S::yield_wait();
+                if let Some(verified) = result.get() {
+                    return verified;
+                }
+            }
+        })
+    }
+}
+
+/// A listener for the result of a verification: `Ok(true)` if the signature
+/// was verified, `Ok(false)` if it was rejected, or the error that prevented
+/// verification.
+pub struct VerifyListener<F: Fn(Result<bool, ErrorCode>)>(pub F);
+
+impl<F: Fn(Result<bool, ErrorCode>)> Upcall<OneId<DRIVER_NUM, { subscribe::DONE }>>
+    for VerifyListener<F>
+{
+    fn upcall(&self, status: u32, verified: u32, _arg2: u32) {
+        match status {
+            0 => self.0(Ok(verified != 0)),
+            _ => self.0(Err(status.try_into().unwrap_or(ErrorCode::Fail))),
+        }
+    }
+}
+
+/// System call configuration trait for `EcdsaP256`.
+pub trait Config: platform::allow_ro::Config + platform::subscribe::Config {}
+impl<T: platform::allow_ro::Config + platform::subscribe::Config> Config for T {}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x40007;
+
+// Command IDs
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const VERIFY: u32 = 1;
+}
+
+mod subscribe {
+    pub const DONE: u32 = 0;
+}
+
+mod allow_ro {
+    pub const HASH: u32 = 0;
+    pub const SIGNATURE: u32 = 1;
+    pub const PUBLIC_KEY: u32 = 2;
+}
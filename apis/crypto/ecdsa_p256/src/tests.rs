@@ -0,0 +1,114 @@
+use super::{allow_ro, subscribe, DRIVER_NUM};
+use core::cell::Cell;
+use hex_literal::hex;
+use libtock_platform::{share, AllowRo, ErrorCode, Subscribe, Syscalls, YieldNoWaitReturn};
+use libtock_unittest::fake;
+
+type EcdsaP256 = super::EcdsaP256<fake::Syscalls>;
+
+// Test vectors from RFC 6979, appendix A.2.5: ECDSA over P-256 with SHA-256.
+const PUBLIC_KEY: [u8; 64] = hex!(
+    "60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6"
+    "7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299"
+);
+// SHA-256("sample")
+const SAMPLE_HASH: [u8; 32] =
+    hex!("af2bdbe1aa9b6ec1e2ade1d694f41fc71a831d0268e9891562113d8a62add1bf");
+const SAMPLE_SIGNATURE: [u8; 64] = hex!(
+    "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716"
+    "f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8"
+);
+// SHA-256("test")
+const TEST_HASH: [u8; 32] =
+    hex!("9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08");
+const TEST_SIGNATURE: [u8; 64] = hex!(
+    "f1abb023518351cd71d881567b1ea663ed3efcf6c5132b354f28d3b0b7d38367"
+    "019f4113742a2b14bd25926b49c649155f267e60d3814b4c0cc84250e46f0083"
+);
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert_eq!(EcdsaP256::exists(), Err(ErrorCode::NoDevice));
+}
+
+#[test]
+fn exists() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::EcdsaP256::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(EcdsaP256::exists(), Ok(()));
+}
+
+#[test]
+fn verify_sync() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::EcdsaP256::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(
+        EcdsaP256::verify_sync(&SAMPLE_HASH, &SAMPLE_SIGNATURE, &PUBLIC_KEY),
+        Ok(true)
+    );
+    assert_eq!(
+        EcdsaP256::verify_sync(&TEST_HASH, &TEST_SIGNATURE, &PUBLIC_KEY),
+        Ok(true)
+    );
+    // Each signature only matches its own hash.
+    assert_eq!(
+        EcdsaP256::verify_sync(&TEST_HASH, &SAMPLE_SIGNATURE, &PUBLIC_KEY),
+        Ok(false)
+    );
+    let mut public_key = PUBLIC_KEY;
+    public_key[63] ^= 1;
+    assert_eq!(
+        EcdsaP256::verify_sync(&SAMPLE_HASH, &SAMPLE_SIGNATURE, &public_key),
+        Err(ErrorCode::Invalid)
+    );
+    assert_eq!(driver.verifications(), 4);
+}
+
+#[test]
+fn register_unregister_listener() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::EcdsaP256::new();
+    kernel.add_driver(&driver);
+
+    let result = Cell::new(None);
+    let listener = crate::VerifyListener(|verified| result.set(Some(verified)));
+    share::scope::<
+        (
+            AllowRo<_, DRIVER_NUM, { allow_ro::HASH }>,
+            AllowRo<_, DRIVER_NUM, { allow_ro::SIGNATURE }>,
+            AllowRo<_, DRIVER_NUM, { allow_ro::PUBLIC_KEY }>,
+            Subscribe<_, DRIVER_NUM, { subscribe::DONE }>,
+        ),
+        _,
+        _,
+    >(|handle| {
+        let (allow_hash, allow_signature, allow_public_key, subscribe) = handle.split();
+        assert_eq!(EcdsaP256::allow_hash(&SAMPLE_HASH, allow_hash), Ok(()));
+        assert_eq!(
+            EcdsaP256::allow_signature(&SAMPLE_SIGNATURE, allow_signature),
+            Ok(())
+        );
+        assert_eq!(
+            EcdsaP256::allow_public_key(&PUBLIC_KEY, allow_public_key),
+            Ok(())
+        );
+        assert_eq!(EcdsaP256::verify(), Ok(()));
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+
+        assert_eq!(EcdsaP256::register_listener(&listener, subscribe), Ok(()));
+        assert_eq!(EcdsaP256::verify(), Ok(()));
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(result.get(), Some(Ok(true)));
+
+        EcdsaP256::unregister_listener();
+        assert_eq!(EcdsaP256::verify(), Ok(()));
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+    });
+    // Once the scope ends the buffers are no longer shared.
+    assert_eq!(EcdsaP256::verify(), Err(ErrorCode::Size));
+}
@@ -0,0 +1,235 @@
+#![no_std]
+
+use core::marker::PhantomData;
+use libtock_platform as platform;
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::share;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+#[cfg(feature = "rust_crypto")]
+mod mac_traits;
+#[cfg(feature = "rust_crypto")]
+pub use mac_traits::{HmacSha256, HmacSha384, HmacSha512};
+
+/// The HMAC driver.
+///
+/// The driver computes one MAC at a time. [`Hmac::mac`] and [`Hmac::verify`]
+/// handle a message that is all in one buffer; [`Hmac::start`] returns an
+/// [`HmacOperation`] that accepts the message in pieces.
+///
+/// # Example
+/// ```ignore
+/// use libtock::hmac::{Hmac, HmacAlgorithm};
+///
+/// let mut tag = [0; 32];
+/// Hmac::mac(HmacAlgorithm::Sha256, KEY, &telemetry, &mut tag)?;
+///
+/// // On the receiving side:
+/// if !Hmac::verify(HmacAlgorithm::Sha256, KEY, &telemetry, &tag)? {
+///     return Err(ErrorCode::Invalid);
+/// }
+///
+/// // Or, with the message in pieces:
+/// let mut hmac = Hmac::start(HmacAlgorithm::Sha256, KEY)?;
+/// hmac.update(&header)?;
+/// hmac.update(&body)?;
+/// hmac.finish(&mut tag)?;
+/// ```
+pub struct Hmac<S: Syscalls, C: Config = DefaultConfig>(S, C);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HmacAlgorithm {
+    Sha256 = 0,
+    Sha384 = 1,
+    Sha512 = 2,
+}
+
+impl HmacAlgorithm {
+    /// The length of the MAC, in bytes.
+    pub const fn mac_len(self) -> usize {
+        match self {
+            HmacAlgorithm::Sha256 => 32,
+            HmacAlgorithm::Sha384 => 48,
+            HmacAlgorithm::Sha512 => 64,
+        }
+    }
+}
+
+/// The length of the longest MAC, in bytes.
+pub const MAX_MAC_LEN: usize = 64;
+
+impl<S: Syscalls, C: Config> Hmac<S, C> {
+    /// Computes the MAC of `data` with a single system call, writing it to the
+    /// start of `mac`, which must be at least [`HmacAlgorithm::mac_len`] bytes
+    /// long.
+    pub fn mac(
+        algorithm: HmacAlgorithm,
+        key: &[u8],
+        data: &[u8],
+        mac: &mut [u8],
+    ) -> Result<(), ErrorCode> {
+        Self::set_algorithm(algorithm)?;
+        share::scope::<
+            (
+                AllowRo<_, DRIVER_NUM, { allow_ro::KEY }>,
+                AllowRo<_, DRIVER_NUM, { allow_ro::DATA }>,
+                AllowRw<_, DRIVER_NUM, { allow_rw::DEST }>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_key, allow_data, allow_dest) = handle.split();
+            S::allow_ro::<C, DRIVER_NUM, { allow_ro::KEY }>(allow_key, key)?;
+            S::allow_ro::<C, DRIVER_NUM, { allow_ro::DATA }>(allow_data, data)?;
+            S::allow_rw::<C, DRIVER_NUM, { allow_rw::DEST }>(allow_dest, mac)?;
+            S::command(DRIVER_NUM, command::RUN, 0, 0).to_result::<(), ErrorCode>()?;
+            S::yield_wait_for_status(DRIVER_NUM, subscribe::DONE)
+        })
+    }
+
+    /// Computes the MAC of `data` and compares it with `expected`, in constant
+    /// time. Returns `Ok(false)` if they differ.
+    pub fn verify(
+        algorithm: HmacAlgorithm,
+        key: &[u8],
+        data: &[u8],
+        expected: &[u8],
+    ) -> Result<bool, ErrorCode> {
+        let mut mac = [0; MAX_MAC_LEN];
+        Self::mac(algorithm, key, data, &mut mac)?;
+        Ok(constant_time_eq(&mac[..algorithm.mac_len()], expected))
+    }
+
+    /// Starts computing a MAC with `key`, which stays shared with the driver
+    /// for each step of the operation.
+    pub fn start(
+        algorithm: HmacAlgorithm,
+        key: &[u8],
+    ) -> Result<HmacOperation<'_, S, C>, ErrorCode> {
+        Self::set_algorithm(algorithm)?;
+        Ok(HmacOperation {
+            algorithm,
+            key,
+            _syscalls: PhantomData,
+        })
+    }
+
+    fn set_algorithm(algorithm: HmacAlgorithm) -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::SET_ALGORITHM, algorithm as u32, 0).to_result()
+    }
+}
+
+/// A MAC being computed from a message that arrives in pieces, returned by
+/// [`Hmac::start`].
+pub struct HmacOperation<'k, S: Syscalls, C: Config = DefaultConfig> {
+    algorithm: HmacAlgorithm,
+    key: &'k [u8],
+    _syscalls: PhantomData<(S, C)>,
+}
+
+impl<S: Syscalls, C: Config> HmacOperation<'_, S, C> {
+    /// Adds `data` to the message.
+    pub fn update(&mut self, data: &[u8]) -> Result<(), ErrorCode> {
+        update::<S, C>(self.key, data)
+    }
+
+    /// Writes the MAC of the message to the start of `mac`, which must be at
+    /// least [`HmacAlgorithm::mac_len`] bytes long.
+    pub fn finish(self, mac: &mut [u8]) -> Result<(), ErrorCode> {
+        finish::<S, C>(self.key, mac)
+    }
+
+    /// Compares the MAC of the message with `expected`, in constant time.
+    /// Returns `Ok(false)` if they differ.
+    pub fn verify(self, expected: &[u8]) -> Result<bool, ErrorCode> {
+        let mut mac = [0; MAX_MAC_LEN];
+        let len = self.algorithm.mac_len();
+        self.finish(&mut mac)?;
+        Ok(constant_time_eq(&mac[..len], expected))
+    }
+}
+
+fn update<S: Syscalls, C: Config>(key: &[u8], data: &[u8]) -> Result<(), ErrorCode> {
+    share::scope::<
+        (
+            AllowRo<_, DRIVER_NUM, { allow_ro::KEY }>,
+            AllowRo<_, DRIVER_NUM, { allow_ro::DATA }>,
+        ),
+        _,
+        _,
+    >(|handle| {
+        let (allow_key, allow_data) = handle.split();
+        S::allow_ro::<C, DRIVER_NUM, { allow_ro::KEY }>(allow_key, key)?;
+        S::allow_ro::<C, DRIVER_NUM, { allow_ro::DATA }>(allow_data, data)?;
+        S::command(DRIVER_NUM, command::UPDATE, 0, 0).to_result::<(), ErrorCode>()?;
+        S::yield_wait_for_status(DRIVER_NUM, subscribe::DONE)
+    })
+}
+
+fn finish<S: Syscalls, C: Config>(key: &[u8], mac: &mut [u8]) -> Result<(), ErrorCode> {
+    share::scope::<
+        (
+            AllowRo<_, DRIVER_NUM, { allow_ro::KEY }>,
+            AllowRw<_, DRIVER_NUM, { allow_rw::DEST }>,
+        ),
+        _,
+        _,
+    >(|handle| {
+        let (allow_key, allow_dest) = handle.split();
+        S::allow_ro::<C, DRIVER_NUM, { allow_ro::KEY }>(allow_key, key)?;
+        S::allow_rw::<C, DRIVER_NUM, { allow_rw::DEST }>(allow_dest, mac)?;
+        S::command(DRIVER_NUM, command::FINISH, 0, 0).to_result::<(), ErrorCode>()?;
+        S::yield_wait_for_status(DRIVER_NUM, subscribe::DONE)
+    })
+}
+
+/// Compares two byte strings in time that depends only on their lengths, so
+/// that an attacker cannot learn how much of a forged MAC is correct.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let difference = a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y));
+    core::hint::black_box(difference) == 0
+}
+
+/// System call configuration trait for `Hmac`.
+pub trait Config:
+    platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config
+{
+}
+impl<T: platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config>
+    Config for T
+{
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x40003;
+
+// Command IDs
+mod command {
+    pub const SET_ALGORITHM: u32 = 0;
+    pub const RUN: u32 = 1;
+    pub const UPDATE: u32 = 2;
+    pub const FINISH: u32 = 3;
+}
+
+mod subscribe {
+    pub const DONE: u32 = 0;
+}
+
+mod allow_ro {
+    pub const KEY: u32 = 0;
+    pub const DATA: u32 = 1;
+}
+
+mod allow_rw {
+    pub const DEST: u32 = 0;
+}
@@ -0,0 +1,136 @@
+//! Implementations of the RustCrypto `digest` MAC traits.
+//!
+//! The traits cannot report errors, so these MACs panic if the driver returns
+//! one. The driver computes one MAC at a time, so only one MAC may be in use at
+//! once; use [`Hmac`] directly to handle errors.
+//!
+//! Each MAC keeps a copy of its key, which is shared with the driver for every
+//! update. As HMAC specifies, keys longer than the hash's block size are
+//! replaced by their digest, which the SHA driver computes.
+
+use crate::{finish, update, Config, Hmac, HmacAlgorithm};
+use core::marker::PhantomData;
+use digest::consts::{U128, U32, U48, U64};
+use digest::crypto_common::{Key, KeySizeUser};
+use digest::typenum::Unsigned;
+use digest::{
+    FixedOutput, FixedOutputReset, InvalidLength, KeyInit, MacMarker, Output, OutputSizeUser,
+    Reset, Update,
+};
+use libtock_platform::Syscalls;
+use libtock_sha::{Sha, ShaAlgorithm};
+
+// The largest block size, which is the longest key kept as is.
+const MAX_KEY_LEN: usize = 128;
+
+macro_rules! mac {
+    ($(#[$attr:meta])* $name:ident, $algorithm:ident, $key_size:ty, $output_size:ty) => {
+        $(#[$attr])*
+        pub struct $name<S: Syscalls, C: Config = libtock_platform::DefaultConfig> {
+            key: [u8; MAX_KEY_LEN],
+            key_len: usize,
+            // Whether the driver has been set up for this MAC.
+            started: bool,
+            _syscalls: PhantomData<(S, C)>,
+        }
+
+        impl<S: Syscalls, C: Config> $name<S, C> {
+            fn key(&self) -> &[u8] {
+                &self.key[..self.key_len]
+            }
+
+            fn start(&mut self) {
+                if !self.started {
+                    Hmac::<S, C>::set_algorithm(HmacAlgorithm::$algorithm)
+                        .expect("Unable to select the HMAC algorithm");
+                    self.started = true;
+                }
+            }
+        }
+
+        impl<S: Syscalls, C: Config> KeySizeUser for $name<S, C> {
+            type KeySize = $key_size;
+        }
+
+        impl<S: Syscalls, C: Config> KeyInit for $name<S, C> {
+            fn new(key: &Key<Self>) -> Self {
+                Self::new_from_slice(key).unwrap()
+            }
+
+            fn new_from_slice(key: &[u8]) -> Result<Self, InvalidLength> {
+                let mut mac = $name {
+                    key: [0; MAX_KEY_LEN],
+                    key_len: key.len(),
+                    started: false,
+                    _syscalls: PhantomData,
+                };
+                // The key size is the block size.
+                if key.len() > <$key_size>::USIZE {
+                    let algorithm = ShaAlgorithm::$algorithm;
+                    Sha::<S, C>::digest(algorithm, key, &mut mac.key)
+                        .expect("Unable to hash the HMAC key");
+                    mac.key_len = algorithm.digest_len();
+                } else {
+                    mac.key[..key.len()].copy_from_slice(key);
+                }
+                Ok(mac)
+            }
+        }
+
+        impl<S: Syscalls, C: Config> MacMarker for $name<S, C> {}
+
+        impl<S: Syscalls, C: Config> OutputSizeUser for $name<S, C> {
+            type OutputSize = $output_size;
+        }
+
+        impl<S: Syscalls, C: Config> Update for $name<S, C> {
+            fn update(&mut self, data: &[u8]) {
+                self.start();
+                update::<S, C>(self.key(), data).expect("HMAC update failed");
+            }
+        }
+
+        impl<S: Syscalls, C: Config> FixedOutput for $name<S, C> {
+            fn finalize_into(mut self, out: &mut Output<Self>) {
+                self.finalize_into_reset(out);
+            }
+        }
+
+        impl<S: Syscalls, C: Config> Reset for $name<S, C> {
+            fn reset(&mut self) {
+                // Selecting the algorithm again discards the MAC in progress.
+                self.started = false;
+            }
+        }
+
+        impl<S: Syscalls, C: Config> FixedOutputReset for $name<S, C> {
+            fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+                self.start();
+                finish::<S, C>(self.key(), out).expect("HMAC finish failed");
+                self.started = false;
+            }
+        }
+    };
+}
+
+mac!(
+    /// HMAC-SHA-256, computed by the HMAC driver.
+    HmacSha256,
+    Sha256,
+    U64,
+    U32
+);
+mac!(
+    /// HMAC-SHA-384, computed by the HMAC driver.
+    HmacSha384,
+    Sha384,
+    U128,
+    U48
+);
+mac!(
+    /// HMAC-SHA-512, computed by the HMAC driver.
+    HmacSha512,
+    Sha512,
+    U128,
+    U64
+);
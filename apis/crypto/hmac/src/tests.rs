@@ -0,0 +1,277 @@
+use super::HmacAlgorithm;
+use hex_literal::hex;
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+type Hmac = super::Hmac<fake::Syscalls>;
+
+// Test cases from RFC 4231. Each case lists its key, its data, and the
+// HMAC-SHA-256, HMAC-SHA-384 and HMAC-SHA-512 of the data.
+struct Case {
+    key: &'static [u8],
+    data: &'static [u8],
+    sha256: &'static [u8],
+    sha384: &'static [u8],
+    sha512: &'static [u8],
+}
+
+const LONG_KEY: [u8; 131] = [0xaa; 131];
+
+const CASES: [Case; 7] = [
+    Case {
+        key: &[0x0b; 20],
+        data: b"Hi There",
+        sha256: &hex!("b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"),
+        sha384: &hex!(
+            "afd03944d84895626b0825f4ab46907f15f9dadbe4101ec6"
+            "82aa034c7cebc59cfaea9ea9076ede7f4af152e8b2fa9cb6"
+        ),
+        sha512: &hex!(
+            "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cde"
+            "daa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854"
+        ),
+    },
+    Case {
+        key: b"Jefe",
+        data: b"what do ya want for nothing?",
+        sha256: &hex!("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"),
+        sha384: &hex!(
+            "af45d2e376484031617f78d2b58a6b1b9c7ef464f5a01b47"
+            "e42ec3736322445e8e2240ca5e69e2c78b3239ecfab21649"
+        ),
+        sha512: &hex!(
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554"
+            "9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+        ),
+    },
+    Case {
+        key: &[0xaa; 20],
+        data: &[0xdd; 50],
+        sha256: &hex!("773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe"),
+        sha384: &hex!(
+            "88062608d3e6ad8a0aa2ace014c8a86f0aa635d947ac9feb"
+            "e83ef4e55966144b2a5ab39dc13814b94e3ab6e101a34f27"
+        ),
+        sha512: &hex!(
+            "fa73b0089d56a284efb0f0756c890be9b1b5dbdd8ee81a3655f83e33b2279d39"
+            "bf3e848279a722c806b485a47e67c807b946a337bee8942674278859e13292fb"
+        ),
+    },
+    Case {
+        key: &hex!("0102030405060708090a0b0c0d0e0f10111213141516171819"),
+        data: &[0xcd; 50],
+        sha256: &hex!("82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b"),
+        sha384: &hex!(
+            "3e8a69b7783c25851933ab6290af6ca77a9981480850009c"
+            "c5577c6e1f573b4e6801dd23c4a7d679ccf8a386c674cffb"
+        ),
+        sha512: &hex!(
+            "b0ba465637458c6990e5a8c5f61d4af7e576d97ff94b872de76f8050361ee3db"
+            "a91ca5c11aa25eb4d679275cc5788063a5f19741120c4f2de2adebeb10a298dd"
+        ),
+    },
+    // Test case 5 truncates the MACs to 128 bits.
+    Case {
+        key: &[0x0c; 20],
+        data: b"Test With Truncation",
+        sha256: &hex!("a3b6167473100ee06e0c796c2955552b"),
+        sha384: &hex!("3abf34c3503b2a23a46efc619baef897"),
+        sha512: &hex!("415fad6271580a531d4179bc891d87a6"),
+    },
+    Case {
+        key: &LONG_KEY,
+        data: b"Test Using Larger Than Block-Size Key - Hash Key First",
+        sha256: &hex!("60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"),
+        sha384: &hex!(
+            "4ece084485813e9088d2c63a041bc5b44f9ef1012a2b588f"
+            "3cd11f05033ac4c60c2ef6ab4030fe8296248df163f44952"
+        ),
+        sha512: &hex!(
+            "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f352"
+            "6b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598"
+        ),
+    },
+    Case {
+        key: &LONG_KEY,
+        data: b"This is a test using a larger than block-size key and a larger than block-size \
+                data. The key needs to be hashed before being used by the HMAC algorithm.",
+        sha256: &hex!("9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2"),
+        sha384: &hex!(
+            "6617178e941f020d351e2f254e8fd32c602420feb0b8fb9a"
+            "dccebb82461e99c5a678cc31e799176d3860e6110c46523e"
+        ),
+        sha512: &hex!(
+            "e37b6a775dc87dbaa4dfa9f96e5e3ffddebd71f8867289865df5a32d20cdc944"
+            "b6022cac3c4982b10d5eeb55c3e4de15134676fb6de0446065c97440fa8c6a58"
+        ),
+    },
+];
+
+impl Case {
+    fn expected(&self, algorithm: HmacAlgorithm) -> &'static [u8] {
+        match algorithm {
+            HmacAlgorithm::Sha256 => self.sha256,
+            HmacAlgorithm::Sha384 => self.sha384,
+            HmacAlgorithm::Sha512 => self.sha512,
+        }
+    }
+}
+
+const ALGORITHMS: [HmacAlgorithm; 3] = [
+    HmacAlgorithm::Sha256,
+    HmacAlgorithm::Sha384,
+    HmacAlgorithm::Sha512,
+];
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    let mut mac = [0; 32];
+    assert_eq!(
+        Hmac::mac(HmacAlgorithm::Sha256, b"key", b"data", &mut mac),
+        Err(ErrorCode::NoDevice)
+    );
+}
+
+#[test]
+fn rfc_4231() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Hmac::new();
+    kernel.add_driver(&driver);
+
+    for case in &CASES {
+        for algorithm in ALGORITHMS {
+            let expected = case.expected(algorithm);
+            let mut mac = [0; 64];
+            assert_eq!(Hmac::mac(algorithm, case.key, case.data, &mut mac), Ok(()));
+            assert_eq!(mac[..expected.len()], *expected);
+            if expected.len() == algorithm.mac_len() {
+                assert_eq!(
+                    Hmac::verify(algorithm, case.key, case.data, expected),
+                    Ok(true)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn mac_buffer_too_small() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Hmac::new();
+    kernel.add_driver(&driver);
+
+    let mut mac = [0; 64];
+    assert_eq!(
+        Hmac::mac(HmacAlgorithm::Sha384, b"key", b"data", &mut mac[..47]),
+        Err(ErrorCode::Size)
+    );
+}
+
+#[test]
+fn verify() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Hmac::new();
+    kernel.add_driver(&driver);
+
+    let case = &CASES[1];
+    let mut forged = [0; 32];
+    forged.copy_from_slice(case.sha256);
+    forged[31] ^= 1;
+    assert_eq!(
+        Hmac::verify(HmacAlgorithm::Sha256, case.key, case.data, &forged),
+        Ok(false)
+    );
+    // A truncated MAC does not verify.
+    assert_eq!(
+        Hmac::verify(
+            HmacAlgorithm::Sha256,
+            case.key,
+            case.data,
+            &case.sha256[..16]
+        ),
+        Ok(false)
+    );
+    assert_eq!(
+        Hmac::verify(HmacAlgorithm::Sha256, b"Jeff", case.data, case.sha256),
+        Ok(false)
+    );
+}
+
+#[test]
+fn streaming() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Hmac::new();
+    kernel.add_driver(&driver);
+
+    let case = &CASES[6];
+    for algorithm in ALGORITHMS {
+        let mut hmac = Hmac::start(algorithm, case.key).unwrap();
+        for chunk in case.data.chunks(7) {
+            assert_eq!(hmac.update(chunk), Ok(()));
+        }
+        assert_eq!(hmac.verify(case.expected(algorithm)), Ok(true));
+    }
+    assert_eq!(driver.updates(), 3 * 22);
+
+    // Finishing resets the driver, so a second operation starts afresh.
+    let case = &CASES[1];
+    let mut mac = [0; 32];
+    let mut hmac = Hmac::start(HmacAlgorithm::Sha256, case.key).unwrap();
+    assert_eq!(hmac.update(&case.data[..4]), Ok(()));
+    assert_eq!(hmac.update(b""), Ok(()));
+    assert_eq!(hmac.update(&case.data[4..]), Ok(()));
+    assert_eq!(hmac.finish(&mut mac), Ok(()));
+    assert_eq!(mac, case.sha256);
+
+    let mut hmac = Hmac::start(HmacAlgorithm::Sha256, case.key).unwrap();
+    assert_eq!(hmac.update(b"what do ya want for something?"), Ok(()));
+    assert_eq!(hmac.verify(case.sha256), Ok(false));
+}
+
+#[cfg(feature = "rust_crypto")]
+#[test]
+fn mac_traits() {
+    use digest::{KeyInit, Mac};
+    use libtock_sha::ShaAlgorithm;
+    let kernel = fake::Kernel::new();
+    let driver = fake::Hmac::new();
+    kernel.add_driver(&driver);
+    let sha = fake::Sha::new();
+    kernel.add_driver(&sha);
+
+    fn check<M: Mac + KeyInit>(case: &Case, expected: &[u8]) {
+        let mut mac = <M as KeyInit>::new_from_slice(case.key).unwrap();
+        let (head, tail) = case.data.split_at(case.data.len() / 2);
+        mac.update(head);
+        mac.update(tail);
+        assert_eq!(mac.finalize().into_bytes()[..expected.len()], *expected);
+    }
+    let check_all = |cases: &[Case]| {
+        for case in cases {
+            check::<super::HmacSha256<fake::Syscalls>>(case, case.sha256);
+            check::<super::HmacSha384<fake::Syscalls>>(case, case.sha384);
+            check::<super::HmacSha512<fake::Syscalls>>(case, case.sha512);
+        }
+    };
+    // Keys no longer than the block size are used as they are, and longer
+    // ones (the 131-byte keys of the last two cases) are hashed first.
+    check_all(&CASES[..5]);
+    assert_eq!(sha.algorithm(), None);
+    check_all(&CASES[5..]);
+    assert_eq!(sha.algorithm(), Some(ShaAlgorithm::Sha512 as u32));
+
+    let case = &CASES[3];
+    let mut mac = <super::HmacSha256<fake::Syscalls> as KeyInit>::new_from_slice(case.key).unwrap();
+    mac.update(&case.data[..10]);
+    mac.update(&case.data[10..]);
+    assert_eq!(mac.finalize_reset().into_bytes()[..], *case.sha256);
+    mac.update(b"discarded");
+    Mac::reset(&mut mac);
+    mac.update(case.data);
+    assert_eq!(mac.verify_slice(case.sha256), Ok(()));
+
+    let mut mac = <super::HmacSha512<fake::Syscalls> as KeyInit>::new_from_slice(case.key).unwrap();
+    mac.update(case.data);
+    assert!(mac.verify_slice(case.sha384).is_err());
+}
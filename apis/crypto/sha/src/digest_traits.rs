@@ -0,0 +1,95 @@
+//! Implementations of the RustCrypto `digest` traits.
+//!
+//! The traits cannot report errors, so these hashers panic if the driver
+//! returns one. The driver computes one digest at a time, so only one hasher
+//! may be in use at once; use [`Sha`] directly to handle errors.
+
+use crate::{Config, Sha, ShaAlgorithm};
+use core::marker::PhantomData;
+use digest::consts::{U32, U48, U64};
+use digest::{FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser, Reset, Update};
+use libtock_platform::{DefaultConfig, Syscalls};
+
+macro_rules! hasher {
+    ($(#[$attr:meta])* $name:ident, $algorithm:ident, $output_size:ty) => {
+        $(#[$attr])*
+        pub struct $name<S: Syscalls, C: Config = DefaultConfig> {
+            // Whether the driver has been set up for this digest.
+            started: bool,
+            _syscalls: PhantomData<(S, C)>,
+        }
+
+        impl<S: Syscalls, C: Config> $name<S, C> {
+            fn start(&mut self) {
+                if !self.started {
+                    Sha::<S, C>::set_algorithm(ShaAlgorithm::$algorithm)
+                        .expect("Unable to select the SHA algorithm");
+                    self.started = true;
+                }
+            }
+        }
+
+        impl<S: Syscalls, C: Config> Default for $name<S, C> {
+            fn default() -> Self {
+                $name {
+                    started: false,
+                    _syscalls: PhantomData,
+                }
+            }
+        }
+
+        impl<S: Syscalls, C: Config> HashMarker for $name<S, C> {}
+
+        impl<S: Syscalls, C: Config> OutputSizeUser for $name<S, C> {
+            type OutputSize = $output_size;
+        }
+
+        impl<S: Syscalls, C: Config> Update for $name<S, C> {
+            fn update(&mut self, data: &[u8]) {
+                self.start();
+                Sha::<S, C>::update(data).expect("SHA update failed");
+            }
+        }
+
+        impl<S: Syscalls, C: Config> FixedOutput for $name<S, C> {
+            fn finalize_into(mut self, out: &mut Output<Self>) {
+                self.finalize_into_reset(out);
+            }
+        }
+
+        impl<S: Syscalls, C: Config> Reset for $name<S, C> {
+            fn reset(&mut self) {
+                // Selecting the algorithm again discards the digest in
+                // progress.
+                self.started = false;
+            }
+        }
+
+        impl<S: Syscalls, C: Config> FixedOutputReset for $name<S, C> {
+            fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+                self.start();
+                Sha::<S, C>::finish(out).expect("SHA finish failed");
+                self.started = false;
+            }
+        }
+    };
+}
+
+hasher!(
+    /// SHA-256, computed by the SHA driver.
+    Sha256,
+    Sha256,
+    U32
+);
+hasher!(
+    /// SHA-384, computed by the SHA driver.
+    Sha384,
+    Sha384,
+    U48
+);
+hasher!(
+    /// SHA-512, computed by the SHA driver.
+    Sha512,
+    Sha512,
+    U64
+);
@@ -0,0 +1,141 @@
+#![no_std]
+
+use libtock_platform as platform;
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::share;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+#[cfg(feature = "rust_crypto")]
+mod digest_traits;
+#[cfg(feature = "rust_crypto")]
+pub use digest_traits::{Sha256, Sha384, Sha512};
+
+/// The SHA-2 digest driver.
+///
+/// The driver computes one digest at a time: select an algorithm, feed it data
+/// with any number of `update` calls, then call `finish` to retrieve the
+/// digest. `finish` resets the driver, ready for the next digest with the same
+/// algorithm.
+///
+/// # Example
+/// ```ignore
+/// use libtock::sha::{Sha, ShaAlgorithm};
+///
+/// let mut digest = [0; 32];
+/// Sha::set_algorithm(ShaAlgorithm::Sha256)?;
+/// Sha::update(b"Hello, ")?;
+/// Sha::update(b"World!")?;
+/// Sha::finish(&mut digest)?;
+///
+/// // Or, in one go:
+/// Sha::digest(ShaAlgorithm::Sha256, b"Hello, World!", &mut digest)?;
+/// ```
+pub struct Sha<S: Syscalls, C: Config = DefaultConfig>(S, C);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ShaAlgorithm {
+    Sha256 = 0,
+    Sha384 = 1,
+    Sha512 = 2,
+}
+
+impl ShaAlgorithm {
+    /// The length of the digest, in bytes.
+    pub const fn digest_len(self) -> usize {
+        match self {
+            ShaAlgorithm::Sha256 => 32,
+            ShaAlgorithm::Sha384 => 48,
+            ShaAlgorithm::Sha512 => 64,
+        }
+    }
+}
+
+impl<S: Syscalls, C: Config> Sha<S, C> {
+    /// Selects the algorithm used by the following calls.
+    pub fn set_algorithm(algorithm: ShaAlgorithm) -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::SET_ALGORITHM, algorithm as u32, 0).to_result()
+    }
+
+    /// Adds `data` to the digest being computed.
+    pub fn update(data: &[u8]) -> Result<(), ErrorCode> {
+        share::scope::<AllowRo<_, DRIVER_NUM, { allow_ro::DATA }>, _, _>(|allow| {
+            S::allow_ro::<C, DRIVER_NUM, { allow_ro::DATA }>(allow, data)?;
+            S::command(DRIVER_NUM, command::UPDATE, 0, 0).to_result::<(), ErrorCode>()?;
+            S::yield_wait_for_status(DRIVER_NUM, subscribe::DONE)
+        })
+    }
+
+    /// Finishes the digest and writes it to the start of `digest`, which must
+    /// be at least [`ShaAlgorithm::digest_len`] bytes long.
+    pub fn finish(digest: &mut [u8]) -> Result<(), ErrorCode> {
+        share::scope::<AllowRw<_, DRIVER_NUM, { allow_rw::DIGEST }>, _, _>(|allow| {
+            S::allow_rw::<C, DRIVER_NUM, { allow_rw::DIGEST }>(allow, digest)?;
+            S::command(DRIVER_NUM, command::FINISH, 0, 0).to_result::<(), ErrorCode>()?;
+            S::yield_wait_for_status(DRIVER_NUM, subscribe::DONE)
+        })
+    }
+
+    /// Computes the digest of `data` with a single system call, writing it to
+    /// the start of `digest`. Discards any digest in progress.
+    pub fn digest(
+        algorithm: ShaAlgorithm,
+        data: &[u8],
+        digest: &mut [u8],
+    ) -> Result<(), ErrorCode> {
+        Self::set_algorithm(algorithm)?;
+        share::scope::<
+            (
+                AllowRo<_, DRIVER_NUM, { allow_ro::DATA }>,
+                AllowRw<_, DRIVER_NUM, { allow_rw::DIGEST }>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_ro, allow_rw) = handle.split();
+            S::allow_ro::<C, DRIVER_NUM, { allow_ro::DATA }>(allow_ro, data)?;
+            S::allow_rw::<C, DRIVER_NUM, { allow_rw::DIGEST }>(allow_rw, digest)?;
+            S::command(DRIVER_NUM, command::RUN, 0, 0).to_result::<(), ErrorCode>()?;
+            S::yield_wait_for_status(DRIVER_NUM, subscribe::DONE)
+        })
+    }
+}
+
+/// System call configuration trait for `Sha`.
+pub trait Config:
+    platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config
+{
+}
+impl<T: platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config>
+    Config for T
+{
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x40005;
+
+// Command IDs
+mod command {
+    pub const SET_ALGORITHM: u32 = 0;
+    pub const RUN: u32 = 1;
+    pub const UPDATE: u32 = 2;
+    pub const FINISH: u32 = 3;
+}
+
+mod subscribe {
+    pub const DONE: u32 = 0;
+}
+
+mod allow_ro {
+    pub const DATA: u32 = 1;
+}
+
+mod allow_rw {
+    pub const DIGEST: u32 = 2;
+}
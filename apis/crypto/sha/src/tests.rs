@@ -0,0 +1,116 @@
+use super::ShaAlgorithm;
+use hex_literal::hex;
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+type Sha = super::Sha<fake::Syscalls>;
+
+// Test vectors from FIPS 180-2.
+const ABC_SHA256: [u8; 32] =
+    hex!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+const ABC_SHA384: [u8; 48] = hex!(
+    "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded163"
+    "1a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7"
+);
+const ABC_SHA512: [u8; 64] = hex!(
+    "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a"
+    "2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+);
+const TWO_BLOCKS: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+const TWO_BLOCKS_SHA256: [u8; 32] =
+    hex!("248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1");
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert_eq!(
+        Sha::set_algorithm(ShaAlgorithm::Sha256),
+        Err(ErrorCode::NoDevice)
+    );
+}
+
+#[test]
+fn digest() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Sha::new();
+    kernel.add_driver(&driver);
+
+    let mut digest = [0; 64];
+    assert_eq!(
+        Sha::digest(ShaAlgorithm::Sha256, b"abc", &mut digest),
+        Ok(())
+    );
+    assert_eq!(digest[..32], ABC_SHA256);
+    assert_eq!(
+        Sha::digest(ShaAlgorithm::Sha384, b"abc", &mut digest),
+        Ok(())
+    );
+    assert_eq!(digest[..48], ABC_SHA384);
+    assert_eq!(
+        Sha::digest(ShaAlgorithm::Sha512, b"abc", &mut digest),
+        Ok(())
+    );
+    assert_eq!(digest, ABC_SHA512);
+
+    assert_eq!(
+        Sha::digest(ShaAlgorithm::Sha512, b"abc", &mut digest[..63]),
+        Err(ErrorCode::Size)
+    );
+}
+
+#[test]
+fn streaming() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Sha::new();
+    kernel.add_driver(&driver);
+
+    // Updates before an algorithm is selected are rejected.
+    assert_eq!(Sha::update(b"abc"), Err(ErrorCode::Reserve));
+
+    let mut digest = [0; 32];
+    assert_eq!(Sha::set_algorithm(ShaAlgorithm::Sha256), Ok(()));
+    for chunk in TWO_BLOCKS.chunks(5) {
+        assert_eq!(Sha::update(chunk), Ok(()));
+    }
+    assert_eq!(Sha::finish(&mut digest), Ok(()));
+    assert_eq!(digest, TWO_BLOCKS_SHA256);
+    assert_eq!(driver.updates(), 12);
+
+    // Finishing resets the driver for the next digest.
+    assert_eq!(Sha::update(b"ab"), Ok(()));
+    assert_eq!(Sha::update(b""), Ok(()));
+    assert_eq!(Sha::update(b"c"), Ok(()));
+    assert_eq!(Sha::finish(&mut digest), Ok(()));
+    assert_eq!(digest, ABC_SHA256);
+}
+
+#[cfg(feature = "rust_crypto")]
+#[test]
+fn digest_traits() {
+    use digest::Digest;
+    let kernel = fake::Kernel::new();
+    let driver = fake::Sha::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(
+        super::Sha256::<fake::Syscalls>::digest(b"abc")[..],
+        ABC_SHA256
+    );
+    assert_eq!(
+        super::Sha384::<fake::Syscalls>::digest(b"abc")[..],
+        ABC_SHA384
+    );
+    assert_eq!(
+        super::Sha512::<fake::Syscalls>::digest(b"abc")[..],
+        ABC_SHA512
+    );
+
+    let mut hasher = super::Sha256::<fake::Syscalls>::new();
+    hasher.update(&TWO_BLOCKS[..10]);
+    hasher.update(&TWO_BLOCKS[10..]);
+    assert_eq!(hasher.finalize_reset()[..], TWO_BLOCKS_SHA256);
+    hasher.update(b"discarded");
+    Digest::reset(&mut hasher);
+    hasher.update(b"abc");
+    assert_eq!(hasher.finalize()[..], ABC_SHA256);
+}
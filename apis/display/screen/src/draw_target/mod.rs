@@ -0,0 +1,155 @@
+//! `embedded-graphics` support, enabled by the `rust_embedded` feature.
+
+use crate::{Config, PixelFormat, Screen};
+use core::marker::PhantomData;
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{Dimensions, OriginDimensions, Size};
+use embedded_graphics_core::pixelcolor::{IntoStorage, PixelColor, Rgb565, Rgb888};
+use embedded_graphics_core::primitives::{PointsIter, Rectangle};
+use embedded_graphics_core::Pixel;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+/// A color type that can be sent to the screen without conversion.
+pub trait DisplayColor: PixelColor {
+    /// The pixel format the screen must be set to in order to draw this color.
+    const FORMAT: PixelFormat;
+
+    /// Encodes the color in `FORMAT`, filling `buffer`, which is exactly one
+    /// pixel long.
+    fn encode(self, buffer: &mut [u8]);
+}
+
+impl DisplayColor for Rgb565 {
+    const FORMAT: PixelFormat = PixelFormat::Rgb565;
+
+    fn encode(self, buffer: &mut [u8]) {
+        buffer.copy_from_slice(&self.into_storage().to_be_bytes());
+    }
+}
+
+impl DisplayColor for Rgb888 {
+    const FORMAT: PixelFormat = PixelFormat::Rgb888;
+
+    fn encode(self, buffer: &mut [u8]) {
+        buffer.copy_from_slice(&self.into_storage().to_be_bytes()[1..]);
+    }
+}
+
+/// An `embedded_graphics` `DrawTarget` that draws directly to the screen.
+///
+/// `Display` has no framebuffer: every drawing operation is sent to the kernel
+/// immediately. Filled rectangles (including `clear`) are a single fill
+/// operation, and contiguous areas (such as images) are streamed through a
+/// small buffer, but individual pixels each need their own write frame, so
+/// drawing many scattered pixels is slow.
+///
+/// The screen's size is read when the `Display` is created, so set the
+/// rotation and resolution first.
+pub struct Display<S: Syscalls, P: DisplayColor, C: Config = DefaultConfig> {
+    size: Size,
+    _phantom: PhantomData<(S, P, C)>,
+}
+
+impl<S: Syscalls, P: DisplayColor, C: Config> Display<S, P, C> {
+    /// Switches the screen to `P`'s pixel format and returns a `Display` that
+    /// draws to it.
+    pub fn new() -> Result<Self, ErrorCode> {
+        Screen::<S, C>::set_pixel_format(P::FORMAT)?;
+        let (width, height) = Screen::<S, C>::resolution()?;
+        Ok(Self {
+            size: Size::new(width, height),
+            _phantom: PhantomData,
+        })
+    }
+
+    // Sets the write frame to `area`, which must lie within the screen.
+    fn set_write_frame(area: &Rectangle) -> Result<(), ErrorCode> {
+        Screen::<S, C>::set_write_frame(
+            area.top_left.x as u16,
+            area.top_left.y as u16,
+            area.size.width as u16,
+            area.size.height as u16,
+        )
+    }
+}
+
+const PIXEL_BYTES: usize = 4;
+const BUFFER_PIXELS: usize = 32;
+
+impl<S: Syscalls, P: DisplayColor, C: Config> OriginDimensions for Display<S, P, C> {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl<S: Syscalls, P: DisplayColor, C: Config> DrawTarget for Display<S, P, C> {
+    type Color = P;
+    type Error = ErrorCode;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), ErrorCode>
+    where
+        I: IntoIterator<Item = Pixel<P>>,
+    {
+        let bytes = P::FORMAT.bits_per_pixel() / 8;
+        let mut buffer = [0; PIXEL_BYTES];
+        let bounding_box = self.bounding_box();
+        for Pixel(point, color) in pixels {
+            if !bounding_box.contains(point) {
+                continue;
+            }
+            Self::set_write_frame(&Rectangle::new(point, Size::new(1, 1)))?;
+            color.encode(&mut buffer[..bytes]);
+            Screen::<S, C>::write(&buffer[..bytes])?;
+        }
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), ErrorCode>
+    where
+        I: IntoIterator<Item = P>,
+    {
+        // Parts of the area are off-screen, so some colors must be skipped.
+        if self.bounding_box().intersection(area) != *area {
+            return self.draw_iter(area.points().zip(colors).map(|(p, c)| Pixel(p, c)));
+        }
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+        Self::set_write_frame(area)?;
+        let bytes = P::FORMAT.bits_per_pixel() / 8;
+        let mut buffer = [0; PIXEL_BYTES * BUFFER_PIXELS];
+        let mut len = 0;
+        let pixel_count = area.size.width as usize * area.size.height as usize;
+        for color in colors.into_iter().take(pixel_count) {
+            color.encode(&mut buffer[len..len + bytes]);
+            len += bytes;
+            if len + bytes > buffer.len() {
+                Screen::<S, C>::write(&buffer[..len])?;
+                len = 0;
+            }
+        }
+        if len > 0 {
+            Screen::<S, C>::write(&buffer[..len])?;
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: P) -> Result<(), ErrorCode> {
+        let area = self.bounding_box().intersection(area);
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+        Self::set_write_frame(&area)?;
+        let bytes = P::FORMAT.bits_per_pixel() / 8;
+        let mut buffer = [0; PIXEL_BYTES];
+        color.encode(&mut buffer[..bytes]);
+        Screen::<S, C>::fill(&buffer[..bytes])
+    }
+
+    fn clear(&mut self, color: P) -> Result<(), ErrorCode> {
+        self.fill_solid(&self.bounding_box(), color)
+    }
+}
+
+#[cfg(test)]
+mod tests;
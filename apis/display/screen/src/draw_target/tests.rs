@@ -0,0 +1,91 @@
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{OriginDimensions, Point, Size};
+use embedded_graphics_core::pixelcolor::{Rgb565, Rgb888, RgbColor};
+use embedded_graphics_core::primitives::Rectangle;
+use embedded_graphics_core::Pixel;
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+type Display<P> = super::Display<fake::Syscalls, P>;
+
+#[test]
+fn new() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new_with_resolution_modes(&[(8, 4)]);
+    kernel.add_driver(&driver);
+
+    let display = Display::<Rgb888>::new().unwrap();
+    assert_eq!(display.size(), Size::new(8, 4));
+    assert_eq!(driver.pixel_format(), fake::screen::RGB_888);
+
+    driver.set_supported_pixel_formats(&[fake::screen::RGB_565]);
+    assert_eq!(Display::<Rgb888>::new().err(), Some(ErrorCode::Invalid));
+}
+
+#[test]
+fn draw() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new_with_resolution_modes(&[(4, 3)]);
+    kernel.add_driver(&driver);
+    let mut display = Display::<Rgb565>::new().unwrap();
+
+    display.clear(Rgb565::BLUE).unwrap();
+    assert_eq!(driver.pixel(3, 2), 0x001F);
+
+    // The rectangle hangs off the right edge of the screen.
+    display
+        .fill_solid(
+            &Rectangle::new(Point::new(2, 1), Size::new(5, 1)),
+            Rgb565::RED,
+        )
+        .unwrap();
+    display
+        .draw_iter([
+            Pixel(Point::new(0, 0), Rgb565::GREEN),
+            Pixel(Point::new(-1, 0), Rgb565::GREEN),
+            Pixel(Point::new(0, 3), Rgb565::GREEN),
+        ])
+        .unwrap();
+    display
+        .fill_contiguous(
+            &Rectangle::new(Point::new(0, 2), Size::new(2, 1)),
+            [Rgb565::WHITE, Rgb565::BLACK],
+        )
+        .unwrap();
+
+    #[rustfmt::skip]
+    let expected = [
+        0x07E0, 0x001F, 0x001F, 0x001F,
+        0x001F, 0x001F, 0xF800, 0xF800,
+        0xFFFF, 0x0000, 0x001F, 0x001F,
+    ];
+    for (i, &pixel) in expected.iter().enumerate() {
+        assert_eq!(driver.pixel(i as u32 % 4, i as u32 / 4), pixel);
+    }
+}
+
+#[test]
+fn fill_contiguous_large_area() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new_with_resolution_modes(&[(10, 10)]);
+    kernel.add_driver(&driver);
+    let mut display = Display::<Rgb888>::new().unwrap();
+
+    // Needs several writes to send all 100 pixels.
+    let colors = (0..100).map(|i| Rgb888::new(i, 0, 255 - i));
+    display
+        .fill_contiguous(&Rectangle::new(Point::zero(), Size::new(10, 10)), colors)
+        .unwrap();
+    for i in 0..100 {
+        assert_eq!(driver.pixel(i % 10, i / 10), i << 16 | (255 - i));
+    }
+
+    // Only the on-screen part of a partially visible area is drawn.
+    display
+        .fill_contiguous(
+            &Rectangle::new(Point::new(9, 9), Size::new(2, 2)),
+            [Rgb888::WHITE; 4],
+        )
+        .unwrap();
+    assert_eq!(driver.pixel(9, 9), 0xFFFFFF);
+}
@@ -0,0 +1,301 @@
+#![no_std]
+
+use libtock_platform as platform;
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::share;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+#[cfg(feature = "rust_embedded")]
+mod draw_target;
+
+#[cfg(feature = "rust_embedded")]
+pub use draw_target::{Display, DisplayColor};
+
+/// The screen driver.
+///
+/// Drawing happens in two steps: [`Screen::set_write_frame`] selects a
+/// rectangle of the screen, and [`Screen::write`] or [`Screen::fill`] then
+/// supplies the pixels for that rectangle, in row-major order. Pixel data is
+/// encoded in the screen's current [`PixelFormat`].
+///
+/// Every operation that changes the screen's state, and reading the current
+/// resolution, rotation or pixel format, completes asynchronously in the
+/// kernel; the functions here block until it has.
+///
+/// # Example
+/// ```ignore
+/// use libtock::screen::{PixelFormat, Screen};
+///
+/// Screen::set_pixel_format(PixelFormat::Rgb565)?;
+/// // Paint a 10x10 red square in the top-left corner.
+/// Screen::set_write_frame(0, 0, 10, 10)?;
+/// Screen::fill(&0xF800u16.to_be_bytes())?;
+/// ```
+pub struct Screen<S: Syscalls, C: Config = DefaultConfig>(S, C);
+
+/// The pixel formats a screen can support. The discriminants are the values
+/// used by the kernel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// One bit per pixel, eight pixels per byte.
+    Mono = 0,
+    /// 8-bit RGB, with 2 bits of red and 3 bits each of green and blue.
+    Rgb233 = 1,
+    /// 16-bit RGB, sent most significant byte first.
+    Rgb565 = 2,
+    /// 24-bit RGB, sent as red, green, blue.
+    Rgb888 = 3,
+    /// 32-bit RGB with an alpha channel, sent as alpha, red, green, blue.
+    Argb8888 = 4,
+}
+
+impl PixelFormat {
+    /// The number of bits used to encode one pixel.
+    pub fn bits_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Mono => 1,
+            PixelFormat::Rgb233 => 8,
+            PixelFormat::Rgb565 => 16,
+            PixelFormat::Rgb888 => 24,
+            PixelFormat::Argb8888 => 32,
+        }
+    }
+}
+
+impl TryFrom<u32> for PixelFormat {
+    type Error = ErrorCode;
+
+    fn try_from(value: u32) -> Result<Self, ErrorCode> {
+        match value {
+            0 => Ok(PixelFormat::Mono),
+            1 => Ok(PixelFormat::Rgb233),
+            2 => Ok(PixelFormat::Rgb565),
+            3 => Ok(PixelFormat::Rgb888),
+            4 => Ok(PixelFormat::Argb8888),
+            _ => Err(ErrorCode::Invalid),
+        }
+    }
+}
+
+/// The rotation of the screen's contents, clockwise. The discriminants are the
+/// values used by the kernel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rotation {
+    Normal = 0,
+    Rotated90 = 1,
+    Rotated180 = 2,
+    Rotated270 = 3,
+}
+
+impl TryFrom<u32> for Rotation {
+    type Error = ErrorCode;
+
+    fn try_from(value: u32) -> Result<Self, ErrorCode> {
+        match value {
+            0 => Ok(Rotation::Normal),
+            1 => Ok(Rotation::Rotated90),
+            2 => Ok(Rotation::Rotated180),
+            3 => Ok(Rotation::Rotated270),
+            _ => Err(ErrorCode::Invalid),
+        }
+    }
+}
+
+impl<S: Syscalls, C: Config> Screen<S, C> {
+    /// Run a check against the screen capsule to ensure it is present.
+    #[inline(always)]
+    pub fn exists() -> bool {
+        S::command(DRIVER_NUM, command::EXISTS, 0, 0).is_success()
+    }
+
+    /// Turns the screen on or off.
+    pub fn set_power(on: bool) -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::SET_POWER, on as u32, 0).to_result::<(), ErrorCode>()?;
+        S::yield_wait_for_status(DRIVER_NUM, subscribe::DONE)
+    }
+
+    /// Sets the brightness of the screen's backlight. 0 turns the backlight
+    /// off; screens that cannot dim treat every other value as fully on.
+    pub fn set_brightness(brightness: u16) -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::SET_BRIGHTNESS, brightness as u32, 0)
+            .to_result::<(), ErrorCode>()?;
+        S::yield_wait_for_status(DRIVER_NUM, subscribe::DONE)
+    }
+
+    /// Enables or disables color inversion.
+    pub fn set_invert(inverted: bool) -> Result<(), ErrorCode> {
+        let command = match inverted {
+            true => command::INVERT_ON,
+            false => command::INVERT_OFF,
+        };
+        S::command(DRIVER_NUM, command, 0, 0).to_result::<(), ErrorCode>()?;
+        S::yield_wait_for_status(DRIVER_NUM, subscribe::DONE)
+    }
+
+    /// Returns the current width and height of the screen in pixels, taking
+    /// the rotation into account.
+    pub fn resolution() -> Result<(u32, u32), ErrorCode> {
+        Self::get(command::GET_RESOLUTION)
+    }
+
+    /// Returns the resolutions the screen supports, as (width, height) pairs.
+    pub fn resolution_modes() -> impl Iterator<Item = (u32, u32)> {
+        let count: u32 = S::command(DRIVER_NUM, command::RESOLUTION_MODES_COUNT, 0, 0)
+            .to_result::<u32, ErrorCode>()
+            .unwrap_or(0);
+        (0..count).filter_map(|index| {
+            S::command(DRIVER_NUM, command::RESOLUTION_MODE, index, 0)
+                .to_result::<(u32, u32), ErrorCode>()
+                .ok()
+        })
+    }
+
+    /// Changes the screen's resolution to one of its supported
+    /// [resolution modes](Self::resolution_modes).
+    pub fn set_resolution(width: u32, height: u32) -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::SET_RESOLUTION, width, height)
+            .to_result::<(), ErrorCode>()?;
+        S::yield_wait_for_status(DRIVER_NUM, subscribe::DONE)
+    }
+
+    pub fn rotation() -> Result<Rotation, ErrorCode> {
+        Self::get(command::GET_ROTATION)?.0.try_into()
+    }
+
+    pub fn set_rotation(rotation: Rotation) -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::SET_ROTATION, rotation as u32, 0)
+            .to_result::<(), ErrorCode>()?;
+        S::yield_wait_for_status(DRIVER_NUM, subscribe::DONE)
+    }
+
+    pub fn pixel_format() -> Result<PixelFormat, ErrorCode> {
+        Self::get(command::GET_PIXEL_FORMAT)?.0.try_into()
+    }
+
+    /// Returns the pixel formats the screen supports. Formats this crate does
+    /// not know about are skipped.
+    pub fn supported_pixel_formats() -> impl Iterator<Item = PixelFormat> {
+        let count: u32 = S::command(DRIVER_NUM, command::PIXEL_FORMATS_COUNT, 0, 0)
+            .to_result::<u32, ErrorCode>()
+            .unwrap_or(0);
+        (0..count).filter_map(|index| {
+            S::command(DRIVER_NUM, command::PIXEL_FORMAT, index, 0)
+                .to_result::<u32, ErrorCode>()
+                .ok()
+                .and_then(|format| format.try_into().ok())
+        })
+    }
+
+    pub fn set_pixel_format(format: PixelFormat) -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::SET_PIXEL_FORMAT, format as u32, 0)
+            .to_result::<(), ErrorCode>()?;
+        S::yield_wait_for_status(DRIVER_NUM, subscribe::DONE)
+    }
+
+    /// Selects the first format in `preferred` that the screen supports, and
+    /// returns it. Returns `ErrorCode::NoSupport` if the screen supports none
+    /// of them.
+    pub fn negotiate_pixel_format(preferred: &[PixelFormat]) -> Result<PixelFormat, ErrorCode> {
+        let format = *preferred
+            .iter()
+            .find(|&&format| Self::supported_pixel_formats().any(|f| f == format))
+            .ok_or(ErrorCode::NoSupport)?;
+        Self::set_pixel_format(format)?;
+        Ok(format)
+    }
+
+    /// Selects the rectangle that the next [`write`](Self::write) or
+    /// [`fill`](Self::fill) draws to. Subsequent writes continue where the
+    /// previous one stopped, until the write frame is set again.
+    pub fn set_write_frame(x: u16, y: u16, width: u16, height: u16) -> Result<(), ErrorCode> {
+        S::command(
+            DRIVER_NUM,
+            command::SET_WRITE_FRAME,
+            (x as u32) << 16 | y as u32,
+            (width as u32) << 16 | height as u32,
+        )
+        .to_result::<(), ErrorCode>()?;
+        S::yield_wait_for_status(DRIVER_NUM, subscribe::DONE)
+    }
+
+    /// Writes pixel data, encoded in the current pixel format, to the write
+    /// frame.
+    pub fn write(data: &[u8]) -> Result<(), ErrorCode> {
+        Self::write_buffer(command::WRITE, data)
+    }
+
+    /// Fills the whole write frame with a single color. `pixel` holds the
+    /// color, encoded in the current pixel format.
+    pub fn fill(pixel: &[u8]) -> Result<(), ErrorCode> {
+        Self::write_buffer(command::FILL, pixel)
+    }
+
+    fn write_buffer(command_num: u32, data: &[u8]) -> Result<(), ErrorCode> {
+        share::scope::<AllowRo<_, DRIVER_NUM, { allow_ro::WRITE_BUFFER }>, _, _>(|allow| {
+            S::allow_ro::<C, DRIVER_NUM, { allow_ro::WRITE_BUFFER }>(allow, data)?;
+
+            S::command(DRIVER_NUM, command_num, data.len() as u32, 0)
+                .to_result::<(), ErrorCode>()?;
+
+            S::yield_wait_for_status(DRIVER_NUM, subscribe::DONE)
+        })
+    }
+
+    /// Runs a getter command. The kernel queues it behind any other screen
+    /// operation and reports the value in the DONE upcall's second and third
+    /// arguments.
+    fn get(command_num: u32) -> Result<(u32, u32), ErrorCode> {
+        S::command(DRIVER_NUM, command_num, 0, 0).to_result::<(), ErrorCode>()?;
+        match S::yield_wait_for(DRIVER_NUM, subscribe::DONE) {
+            (0, data1, data2) => Ok((data1, data2)),
+            (status, _, _) => Err(status.try_into().unwrap_or(ErrorCode::Fail)),
+        }
+    }
+}
+
+/// System call configuration trait for `Screen`.
+pub trait Config: platform::allow_ro::Config + platform::subscribe::Config {}
+impl<T: platform::allow_ro::Config + platform::subscribe::Config> Config for T {}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x90001;
+
+// Command IDs
+#[allow(unused)]
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const SET_POWER: u32 = 2;
+    pub const SET_BRIGHTNESS: u32 = 3;
+    pub const INVERT_ON: u32 = 4;
+    pub const INVERT_OFF: u32 = 5;
+
+    pub const RESOLUTION_MODES_COUNT: u32 = 11;
+    pub const RESOLUTION_MODE: u32 = 12;
+    pub const PIXEL_FORMATS_COUNT: u32 = 13;
+    pub const PIXEL_FORMAT: u32 = 14;
+
+    pub const GET_ROTATION: u32 = 21;
+    pub const SET_ROTATION: u32 = 22;
+    pub const GET_RESOLUTION: u32 = 23;
+    pub const SET_RESOLUTION: u32 = 24;
+    pub const GET_PIXEL_FORMAT: u32 = 25;
+    pub const SET_PIXEL_FORMAT: u32 = 26;
+
+    pub const SET_WRITE_FRAME: u32 = 100;
+    pub const WRITE: u32 = 200;
+    pub const FILL: u32 = 300;
+}
+
+mod subscribe {
+    pub const DONE: u32 = 0;
+}
+
+mod allow_ro {
+    pub const WRITE_BUFFER: u32 = 0;
+}
@@ -0,0 +1,115 @@
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+use crate::{PixelFormat, Rotation};
+
+type Screen = super::Screen<fake::Syscalls>;
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert!(!Screen::exists());
+}
+
+#[test]
+fn exists() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new();
+    kernel.add_driver(&driver);
+
+    assert!(Screen::exists());
+}
+
+#[test]
+fn power_and_brightness() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(Screen::set_power(true), Ok(()));
+    assert!(driver.is_powered());
+    assert_eq!(Screen::set_brightness(1000), Ok(()));
+    assert_eq!(driver.brightness(), 1000);
+    assert_eq!(Screen::set_invert(true), Ok(()));
+    assert!(driver.is_inverted());
+    assert_eq!(Screen::set_invert(false), Ok(()));
+    assert!(!driver.is_inverted());
+    assert_eq!(Screen::set_power(false), Ok(()));
+    assert!(!driver.is_powered());
+}
+
+#[test]
+fn resolution_and_rotation() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new_with_resolution_modes(&[(240, 320), (120, 160)]);
+    kernel.add_driver(&driver);
+
+    assert!(Screen::resolution_modes().eq([(240, 320), (120, 160)]));
+    assert_eq!(Screen::resolution(), Ok((240, 320)));
+    assert_eq!(Screen::rotation(), Ok(Rotation::Normal));
+
+    assert_eq!(Screen::set_rotation(Rotation::Rotated270), Ok(()));
+    assert_eq!(Screen::rotation(), Ok(Rotation::Rotated270));
+    assert_eq!(Screen::resolution(), Ok((320, 240)));
+
+    assert_eq!(Screen::set_resolution(120, 160), Ok(()));
+    assert_eq!(Screen::resolution(), Ok((160, 120)));
+    assert_eq!(Screen::set_resolution(100, 100), Err(ErrorCode::Invalid));
+}
+
+#[test]
+fn pixel_format_negotiation() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new();
+    kernel.add_driver(&driver);
+    driver.set_supported_pixel_formats(&[fake::screen::RGB_565, fake::screen::RGB_233]);
+
+    assert!(Screen::supported_pixel_formats().eq([PixelFormat::Rgb565, PixelFormat::Rgb233]));
+    assert_eq!(Screen::pixel_format(), Ok(PixelFormat::Rgb565));
+
+    assert_eq!(
+        Screen::negotiate_pixel_format(&[PixelFormat::Argb8888, PixelFormat::Rgb233]),
+        Ok(PixelFormat::Rgb233)
+    );
+    assert_eq!(Screen::pixel_format(), Ok(PixelFormat::Rgb233));
+    assert_eq!(driver.pixel_format(), fake::screen::RGB_233);
+
+    assert_eq!(
+        Screen::negotiate_pixel_format(&[PixelFormat::Mono, PixelFormat::Rgb888]),
+        Err(ErrorCode::NoSupport)
+    );
+    assert_eq!(
+        Screen::set_pixel_format(PixelFormat::Rgb888),
+        Err(ErrorCode::Invalid)
+    );
+}
+
+#[test]
+fn write_and_fill() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new_with_resolution_modes(&[(4, 3)]);
+    kernel.add_driver(&driver);
+
+    // Fill the middle two pixels of each row, then overwrite the middle row.
+    assert_eq!(Screen::set_write_frame(1, 0, 2, 3), Ok(()));
+    assert_eq!(Screen::fill(&[0xF8, 0x00]), Ok(()));
+    assert_eq!(Screen::set_write_frame(0, 1, 4, 1), Ok(()));
+    assert_eq!(Screen::write(&[0x00, 0x01, 0x00, 0x02]), Ok(()));
+    // Writes continue where the previous one stopped.
+    assert_eq!(Screen::write(&[0x00, 0x03, 0x00, 0x04]), Ok(()));
+
+    #[rustfmt::skip]
+    let expected = [
+        0x0000, 0xF800, 0xF800, 0x0000,
+        0x0001, 0x0002, 0x0003, 0x0004,
+        0x0000, 0xF800, 0xF800, 0x0000,
+    ];
+    for (i, &pixel) in expected.iter().enumerate() {
+        assert_eq!(driver.pixel(i as u32 % 4, i as u32 / 4), pixel);
+    }
+
+    assert_eq!(Screen::set_write_frame(3, 0, 2, 1), Err(ErrorCode::Invalid));
+    // Partial pixels are rejected.
+    assert_eq!(Screen::write(&[0xFF]), Err(ErrorCode::Invalid));
+    assert_eq!(Screen::fill(&[]), Err(ErrorCode::Invalid));
+}
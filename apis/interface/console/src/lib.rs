@@ -1,13 +1,11 @@
 #![no_std]
 
-use core::cell::Cell;
 use core::fmt;
 use core::marker::PhantomData;
 use libtock_platform as platform;
 use libtock_platform::allow_ro::AllowRo;
 use libtock_platform::allow_rw::AllowRw;
 use libtock_platform::share;
-use libtock_platform::subscribe::Subscribe;
 use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
 
 /// The console driver.
@@ -39,30 +37,14 @@ impl<S: Syscalls, C: Config> Console<S, C> {
     /// This is an alternative to `fmt::Write::write`
     /// because this can actually return an error code.
     pub fn write(s: &[u8]) -> Result<(), ErrorCode> {
-        let called: Cell<Option<(u32,)>> = Cell::new(None);
-        share::scope::<
-            (
-                AllowRo<_, DRIVER_NUM, { allow_ro::WRITE }>,
-                Subscribe<_, DRIVER_NUM, { subscribe::WRITE }>,
-            ),
-            _,
-            _,
-        >(|handle| {
-            let (allow_ro, subscribe) = handle.split();
-
+        share::scope::<AllowRo<_, DRIVER_NUM, { allow_ro::WRITE }>, _, _>(|allow_ro| {
             S::allow_ro::<C, DRIVER_NUM, { allow_ro::WRITE }>(allow_ro, s)?;
 
-            S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::WRITE }>(subscribe, &called)?;
-
             S::command(DRIVER_NUM, command::WRITE, s.len() as u32, 0)
                 .to_result::<(), ErrorCode>()?;
 
-            loop {
-                S::yield_wait();
-                if let Some((_,)) = called.get() {
-                    return Ok(());
-                }
-            }
+            S::yield_wait_for(DRIVER_NUM, subscribe::WRITE);
+            Ok(())
         })
     }
 
@@ -71,34 +53,20 @@ impl<S: Syscalls, C: Config> Console<S, C> {
     /// No special guarantees about when the read stops.
     /// Returns count of bytes written to `buf`.
     pub fn read(buf: &mut [u8]) -> (usize, Result<(), ErrorCode>) {
-        let called: Cell<Option<(u32, u32)>> = Cell::new(None);
         let mut bytes_received = 0;
-        let r = share::scope::<
-            (
-                AllowRw<_, DRIVER_NUM, { allow_rw::READ }>,
-                Subscribe<_, DRIVER_NUM, { subscribe::READ }>,
-            ),
-            _,
-            _,
-        >(|handle| {
-            let (allow_rw, subscribe) = handle.split();
+        let r = share::scope::<AllowRw<_, DRIVER_NUM, { allow_rw::READ }>, _, _>(|allow_rw| {
             let len = buf.len();
             S::allow_rw::<C, DRIVER_NUM, { allow_rw::READ }>(allow_rw, buf)?;
-            S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::READ }>(subscribe, &called)?;
 
-            // When this fails, `called` is guaranteed unmodified,
-            // because upcalls are never processed until we call `yield`.
+            // When this fails, `bytes_received` is guaranteed unmodified,
+            // because we only wait for the upcall after the command succeeds.
             S::command(DRIVER_NUM, command::READ, len as u32, 0).to_result::<(), ErrorCode>()?;
 
-            loop {
-                S::yield_wait();
-                if let Some((status, bytes_pushed_count)) = called.get() {
-                    bytes_received = bytes_pushed_count as usize;
-                    return match status {
-                        0 => Ok(()),
-                        e_status => Err(e_status.try_into().unwrap_or(ErrorCode::Fail)),
-                    };
-                }
+            let (status, bytes_pushed_count, _) = S::yield_wait_for(DRIVER_NUM, subscribe::READ);
+            bytes_received = bytes_pushed_count as usize;
+            match status {
+                0 => Ok(()),
+                e_status => Err(e_status.try_into().unwrap_or(ErrorCode::Fail)),
             }
         });
         (bytes_received, r)
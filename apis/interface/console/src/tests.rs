@@ -1,7 +1,7 @@
 use super::*;
 use core::fmt::Write;
 use libtock_platform::ErrorCode;
-use libtock_unittest::{command_return, fake, ExpectedSyscall};
+use libtock_unittest::{command_return, fake, ExpectedSyscall, SyscallLogEntry};
 
 type Console = super::Console<fake::Syscalls>;
 
@@ -55,6 +55,40 @@ fn read_bytes_short() {
     assert_eq!(&buf[..count], b"Hello");
 }
 
+#[test]
+fn write_waits_for_upcall() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new();
+    kernel.add_driver(&driver);
+
+    Console::write(b"foo").unwrap();
+    assert_eq!(
+        kernel.take_syscall_log(),
+        [
+            SyscallLogEntry::AllowRo {
+                driver_num: DRIVER_NUM,
+                buffer_num: allow_ro::WRITE,
+                len: 3,
+            },
+            SyscallLogEntry::Command {
+                driver_id: DRIVER_NUM,
+                command_id: command::WRITE,
+                argument0: 3,
+                argument1: 0,
+            },
+            SyscallLogEntry::YieldWaitFor {
+                driver_num: DRIVER_NUM,
+                subscribe_num: subscribe::WRITE,
+            },
+            SyscallLogEntry::AllowRo {
+                driver_num: DRIVER_NUM,
+                buffer_num: allow_ro::WRITE,
+                len: 0,
+            },
+        ]
+    );
+}
+
 #[test]
 fn read_bytes_alot() {
     let kernel = fake::Kernel::new();
@@ -82,11 +116,6 @@ fn failed_print() {
         buffer_num: allow_ro::WRITE,
         return_error: None,
     });
-    kernel.add_expected_syscall(ExpectedSyscall::Subscribe {
-        driver_num: DRIVER_NUM,
-        subscribe_num: subscribe::WRITE,
-        skip_with_error: None,
-    });
     kernel.add_expected_syscall(ExpectedSyscall::Command {
         driver_id: DRIVER_NUM,
         command_id: command::WRITE,
@@ -110,11 +139,6 @@ fn failed_read() {
         buffer_num: allow_rw::READ,
         return_error: None,
     });
-    kernel.add_expected_syscall(ExpectedSyscall::Subscribe {
-        driver_num: DRIVER_NUM,
-        subscribe_num: subscribe::READ,
-        skip_with_error: None,
-    });
     kernel.add_expected_syscall(ExpectedSyscall::Command {
         driver_id: DRIVER_NUM,
         command_id: command::READ,
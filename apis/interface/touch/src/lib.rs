@@ -0,0 +1,316 @@
+#![no_std]
+
+use core::cell::Cell;
+use libtock_platform as platform;
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::share::{self, Handle};
+use libtock_platform::subscribe::{OneId, Subscribe};
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls, Upcall};
+
+/// The touch panel driver.
+///
+/// Single touch events and gestures are delivered to listeners, in the same
+/// way as button events. Multi-touch events carry a variable number of
+/// touches, which the kernel writes into a shared buffer, so they are read
+/// with [`Touch::wait_for_multi_touch`] instead.
+///
+/// # Example
+/// ```ignore
+/// use libtock::touch::{Touch, TouchListener};
+///
+/// let listener = TouchListener(|event| {
+///     // make use of event.x, event.y and event.status
+/// });
+///
+/// share::scope(|subscribe| {
+///     Touch::register_listener(&listener, subscribe)?;
+///     Touch::enable_single_touch()?;
+///     loop {
+///         TockSyscalls::yield_wait();
+///     }
+/// });
+/// ```
+pub struct Touch<S: Syscalls, C: Config = DefaultConfig>(S, C);
+
+/// The state of a touch, as reported in a [`TouchEvent`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TouchStatus {
+    Released,
+    Pressed,
+    Moved,
+    #[default]
+    Unstarted,
+}
+
+impl From<u32> for TouchStatus {
+    fn from(value: u32) -> TouchStatus {
+        match value {
+            0 => TouchStatus::Released,
+            1 => TouchStatus::Pressed,
+            2 => TouchStatus::Moved,
+            _ => TouchStatus::Unstarted,
+        }
+    }
+}
+
+/// A single touch. Panels that cannot measure size or pressure report 0.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TouchEvent {
+    /// Identifies the finger across events of a multi-touch sequence. Always
+    /// 0 for single touch events.
+    pub id: u8,
+    pub status: TouchStatus,
+    pub x: u16,
+    pub y: u16,
+    pub size: u16,
+    pub pressure: u16,
+}
+
+impl TouchEvent {
+    // Decodes a touch from the multi-touch buffer.
+    fn from_bytes(bytes: &[u8]) -> TouchEvent {
+        TouchEvent {
+            id: bytes[0],
+            status: (bytes[1] as u32).into(),
+            x: u16::from_le_bytes([bytes[2], bytes[3]]),
+            y: u16::from_le_bytes([bytes[4], bytes[5]]),
+            size: bytes[6] as u16,
+            pressure: bytes[7] as u16,
+        }
+    }
+}
+
+/// A gesture recognized by the touch panel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gesture {
+    NoGesture,
+    SwipeUp,
+    SwipeDown,
+    SwipeLeft,
+    SwipeRight,
+    ZoomIn,
+    ZoomOut,
+}
+
+impl From<u32> for Gesture {
+    fn from(value: u32) -> Gesture {
+        match value {
+            1 => Gesture::SwipeUp,
+            2 => Gesture::SwipeDown,
+            3 => Gesture::SwipeLeft,
+            4 => Gesture::SwipeRight,
+            5 => Gesture::ZoomIn,
+            6 => Gesture::ZoomOut,
+            _ => Gesture::NoGesture,
+        }
+    }
+}
+
+/// A multi-touch event read by [`Touch::wait_for_multi_touch`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MultiTouch {
+    /// The number of touches stored in the caller's buffer.
+    pub len: usize,
+    /// The number of multi-touch events the kernel dropped since the previous
+    /// event was read, because the process was not ready to receive them.
+    pub dropped_events: u32,
+}
+
+impl<S: Syscalls, C: Config> Touch<S, C> {
+    /// Run a check against the touch capsule to ensure it is present.
+    #[inline(always)]
+    pub fn exists() -> bool {
+        S::command(DRIVER_NUM, command::EXISTS, 0, 0).is_success()
+    }
+
+    /// Returns the maximum number of simultaneous touches the panel can
+    /// report.
+    pub fn max_touches() -> Result<u32, ErrorCode> {
+        S::command(DRIVER_NUM, command::NUMBER_OF_TOUCHES, 0, 0).to_result()
+    }
+
+    /// Enable single touch events.
+    pub fn enable_single_touch() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::ENABLE_SINGLE_TOUCH, 0, 0).to_result()
+    }
+
+    /// Disable single touch events.
+    pub fn disable_single_touch() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::DISABLE_SINGLE_TOUCH, 0, 0).to_result()
+    }
+
+    /// Enable multi-touch events. While multi-touch is enabled, the panel
+    /// reports multi-touch events instead of single touch events.
+    pub fn enable_multi_touch() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::ENABLE_MULTI_TOUCH, 0, 0).to_result()
+    }
+
+    /// Disable multi-touch events.
+    pub fn disable_multi_touch() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::DISABLE_MULTI_TOUCH, 0, 0).to_result()
+    }
+
+    /// Register a single touch listener
+    ///
+    /// There can be only one single listener registered at a time.
+    /// Each time this function is used, it will replace the
+    /// previously registered listener.
+    pub fn register_listener<'share, F: Fn(TouchEvent)>(
+        listener: &'share TouchListener<F>,
+        subscribe: Handle<Subscribe<'share, S, DRIVER_NUM, { subscribe::SINGLE_TOUCH }>>,
+    ) -> Result<(), ErrorCode> {
+        S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::SINGLE_TOUCH }>(subscribe, listener)
+    }
+
+    /// Unregister the single touch listener
+    ///
+    /// This function may be used even if there was no
+    /// previously registered listener.
+    pub fn unregister_listener() {
+        S::unsubscribe(DRIVER_NUM, subscribe::SINGLE_TOUCH)
+    }
+
+    /// Register a gesture listener, replacing any previously registered
+    /// gesture listener.
+    pub fn register_gesture_listener<'share, F: Fn(Gesture)>(
+        listener: &'share GestureListener<F>,
+        subscribe: Handle<Subscribe<'share, S, DRIVER_NUM, { subscribe::GESTURE }>>,
+    ) -> Result<(), ErrorCode> {
+        S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::GESTURE }>(subscribe, listener)
+    }
+
+    /// Unregister the gesture listener
+    pub fn unregister_gesture_listener() {
+        S::unsubscribe(DRIVER_NUM, subscribe::GESTURE)
+    }
+
+    /// Waits for the next multi-touch event and stores its touches in
+    /// `touches`. Touches that do not fit in `touches` (or that exceed
+    /// `MAX_MULTI_TOUCHES`) are discarded. Multi-touch must be enabled with
+    /// [`Touch::enable_multi_touch`] first.
+    ///
+    /// Multi-touch events that arrive while the process is not waiting in this
+    /// function are dropped by the kernel, and counted in
+    /// [`MultiTouch::dropped_events`] of the next event.
+    pub fn wait_for_multi_touch(touches: &mut [TouchEvent]) -> Result<MultiTouch, ErrorCode> {
+        let mut buf = [0; MAX_MULTI_TOUCHES * TOUCH_LEN];
+        let len = touches.len().min(MAX_MULTI_TOUCHES) * TOUCH_LEN;
+        let called: Cell<Option<(u32, u32)>> = Cell::new(None);
+        share::scope::<
+            (
+                AllowRw<_, DRIVER_NUM, { allow_rw::MULTI_TOUCH }>,
+                Subscribe<_, DRIVER_NUM, { subscribe::MULTI_TOUCH }>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_rw, subscribe) = handle.split();
+            // Subscribe first, so an event delivered as soon as the buffer is
+            // shared is not lost.
+            S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::MULTI_TOUCH }>(subscribe, &called)?;
+            S::allow_rw::<C, DRIVER_NUM, { allow_rw::MULTI_TOUCH }>(allow_rw, &mut buf[..len])?;
+            while called.get().is_none() {
+                S::yield_wait();
+            }
+            Ok::<(), ErrorCode>(())
+        })?;
+        // Tell the kernel the buffer has been read, so it can deliver the next
+        // event.
+        S::command(DRIVER_NUM, command::MULTI_TOUCH_ACK, 0, 0).to_result::<(), ErrorCode>()?;
+
+        let (count, dropped_events) = called.get().unwrap_or_default();
+        let count = (count as usize).min(len / TOUCH_LEN);
+        for (touch, bytes) in touches
+            .iter_mut()
+            .zip(buf[..count * TOUCH_LEN].chunks_exact(TOUCH_LEN))
+        {
+            *touch = TouchEvent::from_bytes(bytes);
+        }
+        Ok(MultiTouch {
+            len: count,
+            dropped_events,
+        })
+    }
+}
+
+/// The most touches [`Touch::wait_for_multi_touch`] can read from a single
+/// multi-touch event.
+pub const MAX_MULTI_TOUCHES: usize = 10;
+
+// The size of one touch in the multi-touch buffer.
+const TOUCH_LEN: usize = 8;
+
+/// A wrapper around a closure to be registered and called when
+/// a single touch event occurs.
+///
+/// ```ignore
+/// let listener = TouchListener(|event| {
+///     // make use of the event
+/// });
+/// ```
+pub struct TouchListener<F: Fn(TouchEvent)>(pub F);
+
+impl<F: Fn(TouchEvent)> Upcall<OneId<DRIVER_NUM, { subscribe::SINGLE_TOUCH }>>
+    for TouchListener<F>
+{
+    fn upcall(&self, status: u32, position: u32, pressure_size: u32) {
+        self.0(TouchEvent {
+            id: 0,
+            status: status.into(),
+            x: (position >> 16) as u16,
+            y: position as u16,
+            size: pressure_size as u16,
+            pressure: (pressure_size >> 16) as u16,
+        })
+    }
+}
+
+/// A wrapper around a closure to be registered and called when
+/// a gesture is recognized.
+///
+/// ```ignore
+/// let listener = GestureListener(|gesture| {
+///     // make use of the gesture
+/// });
+/// ```
+pub struct GestureListener<F: Fn(Gesture)>(pub F);
+
+impl<F: Fn(Gesture)> Upcall<OneId<DRIVER_NUM, { subscribe::GESTURE }>> for GestureListener<F> {
+    fn upcall(&self, gesture: u32, _arg1: u32, _arg2: u32) {
+        self.0(gesture.into())
+    }
+}
+
+/// System call configuration trait for `Touch`.
+pub trait Config: platform::allow_rw::Config + platform::subscribe::Config {}
+impl<T: platform::allow_rw::Config + platform::subscribe::Config> Config for T {}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x90002;
+
+// Command IDs
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const ENABLE_SINGLE_TOUCH: u32 = 1;
+    pub const DISABLE_SINGLE_TOUCH: u32 = 2;
+    pub const MULTI_TOUCH_ACK: u32 = 10;
+    pub const ENABLE_MULTI_TOUCH: u32 = 11;
+    pub const DISABLE_MULTI_TOUCH: u32 = 12;
+    pub const NUMBER_OF_TOUCHES: u32 = 100;
+}
+
+mod subscribe {
+    pub const SINGLE_TOUCH: u32 = 0;
+    pub const GESTURE: u32 = 1;
+    pub const MULTI_TOUCH: u32 = 2;
+}
+
+mod allow_rw {
+    pub const MULTI_TOUCH: u32 = 2;
+}
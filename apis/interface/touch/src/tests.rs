@@ -0,0 +1,172 @@
+use core::cell::Cell;
+use libtock_platform::{share, Syscalls};
+use libtock_unittest::fake;
+
+use crate::{Gesture, GestureListener, MultiTouch, TouchEvent, TouchListener, TouchStatus};
+
+type Touch = super::Touch<fake::Syscalls>;
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert!(!Touch::exists());
+}
+
+#[test]
+fn exists() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Touch::new();
+    kernel.add_driver(&driver);
+
+    assert!(Touch::exists());
+    assert_eq!(Touch::max_touches(), Ok(5));
+}
+
+#[test]
+fn enable_disable() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Touch::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(Touch::enable_single_touch(), Ok(()));
+    assert!(driver.is_single_touch_enabled());
+    assert_eq!(Touch::disable_single_touch(), Ok(()));
+    assert!(!driver.is_single_touch_enabled());
+
+    assert_eq!(Touch::enable_multi_touch(), Ok(()));
+    assert!(driver.is_multi_touch_enabled());
+    assert_eq!(Touch::disable_multi_touch(), Ok(()));
+    assert!(!driver.is_multi_touch_enabled());
+}
+
+#[test]
+fn single_touch() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Touch::new();
+    kernel.add_driver(&driver);
+
+    let last_event = Cell::new(None);
+    let count = Cell::new(0);
+    let listener = TouchListener(|event| {
+        last_event.set(Some(event));
+        count.set(count.get() + 1);
+    });
+    share::scope(|subscribe| {
+        assert_eq!(Touch::register_listener(&listener, subscribe), Ok(()));
+        assert_eq!(Touch::enable_single_touch(), Ok(()));
+
+        let swipe = [
+            (fake::touch::PRESSED, 10),
+            (fake::touch::MOVED, 50),
+            (fake::touch::MOVED, 90),
+            (fake::touch::RELEASED, 100),
+        ]
+        .map(|(status, x)| fake::touch::TouchEvent {
+            status,
+            x,
+            y: 200,
+            size: 3,
+            pressure: 40,
+            ..Default::default()
+        });
+        driver.inject_touch_sequence(&swipe);
+        while count.get() < swipe.len() {
+            fake::Syscalls::yield_wait();
+        }
+        assert_eq!(
+            last_event.get(),
+            Some(TouchEvent {
+                id: 0,
+                status: TouchStatus::Released,
+                x: 100,
+                y: 200,
+                size: 3,
+                pressure: 40,
+            })
+        );
+
+        Touch::unregister_listener();
+        driver.inject_touch(Default::default());
+        assert_eq!(
+            fake::Syscalls::yield_no_wait(),
+            libtock_platform::YieldNoWaitReturn::NoUpcall
+        );
+    });
+}
+
+#[test]
+fn gestures() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Touch::new();
+    kernel.add_driver(&driver);
+
+    let gesture = Cell::new(None);
+    let listener = GestureListener(|g| gesture.set(Some(g)));
+    share::scope(|subscribe| {
+        assert_eq!(
+            Touch::register_gesture_listener(&listener, subscribe),
+            Ok(())
+        );
+        driver.inject_gesture(5);
+        fake::Syscalls::yield_wait();
+        assert_eq!(gesture.get(), Some(Gesture::ZoomIn));
+        driver.inject_gesture(4);
+        fake::Syscalls::yield_wait();
+        assert_eq!(gesture.get(), Some(Gesture::SwipeRight));
+    });
+}
+
+#[test]
+fn multi_touch() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Touch::new();
+    kernel.add_driver(&driver);
+    assert_eq!(Touch::enable_multi_touch(), Ok(()));
+
+    let pinch = [(1, 100), (2, 300)].map(|(id, x)| fake::touch::TouchEvent {
+        id,
+        status: fake::touch::PRESSED,
+        x,
+        y: 150,
+        ..Default::default()
+    });
+    driver.inject_multi_touch(&pinch);
+    driver.inject_multi_touch(&pinch[..1]);
+    driver.drop_multi_touch_events(3);
+
+    let mut touches = [TouchEvent::default(); 4];
+    assert_eq!(
+        Touch::wait_for_multi_touch(&mut touches),
+        Ok(MultiTouch {
+            len: 2,
+            dropped_events: 3,
+        })
+    );
+    assert_eq!(
+        touches[1],
+        TouchEvent {
+            id: 2,
+            status: TouchStatus::Pressed,
+            x: 300,
+            y: 150,
+            size: 0,
+            pressure: 0,
+        }
+    );
+
+    driver.inject_multi_touch(&pinch);
+    assert_eq!(
+        Touch::wait_for_multi_touch(&mut touches[..1]),
+        Ok(MultiTouch {
+            len: 1,
+            dropped_events: 0,
+        })
+    );
+    assert_eq!(touches[0].id, 1);
+    // Only one of the two touches fits.
+    assert_eq!(
+        Touch::wait_for_multi_touch(&mut touches[..1]).map(|m| m.len),
+        Ok(1)
+    );
+    assert_eq!(driver.pending_multi_touches(), 0);
+}
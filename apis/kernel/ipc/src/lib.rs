@@ -0,0 +1,234 @@
+#![no_std]
+
+use libtock_platform as platform;
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::share::{self, Handle};
+use libtock_platform::subscribe::OneId;
+use libtock_platform::{DefaultConfig, ErrorCode, Subscribe, Syscalls, Upcall};
+
+/// The IPC driver, which lets processes offer services to each other.
+///
+/// A process acting as a service registers a service listener. A client finds
+/// the service by its package name using `discover`, optionally shares a
+/// buffer with it, and notifies it. The service is then notified with the
+/// client's ID and the buffer the client shared with it, and may notify the
+/// client back once it has handled the request.
+///
+/// Services and clients are identified by their process index, which is the
+/// ID `discover` returns. Because buffer and upcall numbers are part of the
+/// `share` handle types, sharing a buffer with or listening to a particular
+/// process requires its ID at compile time (see `share` and
+/// `register_client_listener`).
+///
+/// # Example
+/// ```ignore
+/// use libtock::ipc::{client_subscribe_num, Ipc, IpcListener, DRIVER_NUM};
+///
+/// // Client side
+/// let service = Ipc::discover(b"org.tockos.examples.rng")?;
+/// let listener = IpcListener(|notification| {
+///     // The service handled our request.
+/// });
+/// let mut buffer = [0; 32];
+/// // The service's ID must be known at compile time; here, it is 2.
+/// const SERVICE: u32 = 2;
+/// const CLIENT_UPCALL: u32 = client_subscribe_num(SERVICE);
+/// share::scope::<
+///     (AllowRw<_, DRIVER_NUM, SERVICE>, Subscribe<_, DRIVER_NUM, CLIENT_UPCALL>),
+///     _,
+///     _,
+/// >(|handle| {
+///     let (allow_rw, subscribe) = handle.split();
+///     Ipc::share::<SERVICE>(allow_rw, &mut buffer)?;
+///     Ipc::register_client_listener::<CLIENT_UPCALL, _>(&listener, subscribe)?;
+///     Ipc::notify_service(service)?;
+///     // yield
+/// });
+/// ```
+pub struct Ipc<S: Syscalls, C: Config = DefaultConfig>(S, C);
+
+impl<S: Syscalls, C: Config> Ipc<S, C> {
+    /// Run a check against the IPC capsule to ensure it is present.
+    pub fn exists() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::EXISTS, 0, 0).to_result()
+    }
+
+    /// Looks up the process whose package name is `package_name`, and returns
+    /// its ID. Returns `ErrorCode::NoDevice` if there is no such process.
+    pub fn discover(package_name: &[u8]) -> Result<u32, ErrorCode> {
+        share::scope::<AllowRo<_, DRIVER_NUM, { allow_ro::SEARCH }>, _, _>(|allow_search| {
+            S::allow_ro::<C, DRIVER_NUM, { allow_ro::SEARCH }>(allow_search, package_name)?;
+            S::command(DRIVER_NUM, command::DISCOVER, 0, 0).to_result()
+        })
+    }
+
+    /// Notifies the service with ID `service`. The service's listener receives
+    /// the buffer this process shared with it, if any.
+    pub fn notify_service(service: u32) -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::NOTIFY_SERVICE, service, 0).to_result()
+    }
+
+    /// Notifies the client with ID `client`, usually in response to the client
+    /// notifying this service. The client's listener receives the buffer this
+    /// process shared with it, if any.
+    pub fn notify_client(client: u32) -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::NOTIFY_CLIENT, client, 0).to_result()
+    }
+
+    /// Shares `buffer` with the process with ID `PROCESS`. The process receives
+    /// the buffer's location when this process notifies it.
+    pub fn share<'share, const PROCESS: u32>(
+        allow_rw: Handle<AllowRw<'share, S, DRIVER_NUM, PROCESS>>,
+        buffer: &'share mut [u8],
+    ) -> Result<(), ErrorCode> {
+        S::allow_rw::<C, DRIVER_NUM, PROCESS>(allow_rw, buffer)
+    }
+
+    /// Registers a listener that is called when a client notifies this
+    /// process, making it available as a service.
+    ///
+    /// There can be only one service listener registered at a time. Each time
+    /// this function is used, it replaces the previously registered listener.
+    pub fn register_service_listener<'share, F: Fn(IpcNotification)>(
+        listener: &'share IpcListener<F>,
+        subscribe: Handle<Subscribe<'share, S, DRIVER_NUM, { subscribe::SERVICE }>>,
+    ) -> Result<(), ErrorCode> {
+        S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::SERVICE }>(subscribe, listener)
+    }
+
+    /// Unregisters the service listener.
+    pub fn unregister_service_listener() {
+        S::unsubscribe(DRIVER_NUM, subscribe::SERVICE)
+    }
+
+    /// Registers a listener that is called when a service notifies this
+    /// process. `SUBSCRIBE_NUM` selects the service, and must be
+    /// `client_subscribe_num(service)`.
+    ///
+    /// There can be one client listener registered per service. Each time this
+    /// function is used, it replaces the listener previously registered for the
+    /// same service.
+    pub fn register_client_listener<'share, const SUBSCRIBE_NUM: u32, F: Fn(IpcNotification)>(
+        listener: &'share IpcListener<F>,
+        subscribe: Handle<Subscribe<'share, S, DRIVER_NUM, SUBSCRIBE_NUM>>,
+    ) -> Result<(), ErrorCode> {
+        if SUBSCRIBE_NUM == subscribe::SERVICE {
+            return Err(ErrorCode::Invalid);
+        }
+        S::subscribe::<_, _, C, DRIVER_NUM, SUBSCRIBE_NUM>(subscribe, listener)
+    }
+
+    /// Unregisters the client listener for the service with ID `service`.
+    pub fn unregister_client_listener(service: u32) {
+        S::unsubscribe(DRIVER_NUM, client_subscribe_num(service))
+    }
+}
+
+/// Returns the subscribe number of the upcall that notifications from the
+/// service with ID `service` are delivered to. Use it as
+/// `register_client_listener`'s `SUBSCRIBE_NUM`.
+pub const fn client_subscribe_num(service: u32) -> u32 {
+    service + 1
+}
+
+/// A notification from another process, passed to an [`IpcListener`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IpcNotification {
+    /// The ID of the process that sent the notification.
+    pub sender: u32,
+
+    /// The length of the buffer the sender shared with this process, or 0 if
+    /// it did not share one.
+    pub buffer_len: u32,
+
+    /// The address of the buffer the sender shared with this process.
+    pub buffer_address: u32,
+}
+
+impl IpcNotification {
+    /// Returns the buffer the sender shared with this process, or `None` if it
+    /// did not share one.
+    ///
+    /// # Safety
+    /// The buffer lives in the sender's memory. The caller must make sure the
+    /// sender keeps the buffer shared, and does not access it, for as long as
+    /// the returned slice is in use. Typically, that is guaranteed by the
+    /// protocol between the client and the service (e.g. the client does not
+    /// touch the buffer until the service notifies it back).
+    pub unsafe fn buffer<'a>(&self) -> Option<&'a mut [u8]> {
+        if self.buffer_address == 0 {
+            return None;
+        }
+        // Safety: The kernel only passes the address and length of a buffer
+        // the sender shared, and our caller guarantees it is not otherwise in
+        // use.
+        Some(unsafe {
+            core::slice::from_raw_parts_mut(
+                self.buffer_address as usize as *mut u8,
+                self.buffer_len as usize,
+            )
+        })
+    }
+}
+
+/// A wrapper around a closure to be registered and called when another
+/// process notifies this process.
+///
+/// ```ignore
+/// let listener = IpcListener(|notification| {
+///     // handle the notification
+/// });
+/// ```
+pub struct IpcListener<F: Fn(IpcNotification)>(pub F);
+
+impl<F: Fn(IpcNotification), const SUBSCRIBE_NUM: u32> Upcall<OneId<DRIVER_NUM, SUBSCRIBE_NUM>>
+    for IpcListener<F>
+{
+    fn upcall(&self, sender: u32, buffer_len: u32, buffer_address: u32) {
+        self.0(IpcNotification {
+            sender,
+            buffer_len,
+            buffer_address,
+        })
+    }
+}
+
+/// System call configuration trait for `Ipc`.
+pub trait Config:
+    platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config
+{
+}
+impl<T: platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config>
+    Config for T
+{
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+/// The IPC driver number. Needed to name the `share` handle types passed to
+/// `Ipc::share` and `Ipc::register_client_listener`.
+pub const DRIVER_NUM: u32 = 0x10000;
+
+// Command IDs
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const DISCOVER: u32 = 1;
+    pub const NOTIFY_SERVICE: u32 = 2;
+    pub const NOTIFY_CLIENT: u32 = 3;
+}
+
+mod subscribe {
+    // Notifications from clients. Notifications from the service with ID n are
+    // delivered to upcall n + 1.
+    pub const SERVICE: u32 = 0;
+}
+
+mod allow_ro {
+    pub const SEARCH: u32 = 0;
+}
@@ -0,0 +1,155 @@
+use core::cell::Cell;
+
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::{share, ErrorCode, Subscribe, Syscalls, YieldNoWaitReturn};
+use libtock_unittest::fake;
+
+use crate::{client_subscribe_num, IpcListener, IpcNotification, DRIVER_NUM};
+
+type Ipc = super::Ipc<fake::Syscalls>;
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert_eq!(Ipc::exists(), Err(ErrorCode::NoDevice));
+}
+
+#[test]
+fn exists() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ipc::<4>::new("test");
+    kernel.add_driver(&driver);
+    assert_eq!(Ipc::exists(), Ok(()));
+}
+
+#[test]
+fn discover() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ipc::<4>::new("test");
+    kernel.add_driver(&driver);
+    driver.add_process("org.tockos.rng");
+    let service = driver.add_process("org.tockos.storage");
+
+    assert_eq!(Ipc::discover(b"org.tockos.storage"), Ok(service));
+    assert_eq!(Ipc::discover(b"test"), Ok(0));
+    assert_eq!(Ipc::discover(b"org.tockos"), Err(ErrorCode::NoDevice));
+}
+
+#[test]
+fn notify() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ipc::<4>::new("test");
+    kernel.add_driver(&driver);
+    let process = driver.add_process("other");
+
+    assert_eq!(Ipc::notify_service(process), Ok(()));
+    assert_eq!(Ipc::notify_client(process), Ok(()));
+    assert_eq!(
+        driver.take_notifications(process),
+        [fake::Notification::Service, fake::Notification::Client]
+    );
+    assert_eq!(Ipc::notify_service(3), Err(ErrorCode::Invalid));
+}
+
+// The process under test acts as a client: it shares a buffer with the service
+// and waits for the service to reply.
+#[test]
+fn client() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ipc::<4>::new("test");
+    kernel.add_driver(&driver);
+    driver.add_process("other");
+    const SERVICE: u32 = 2;
+    assert_eq!(driver.add_process("service"), SERVICE);
+
+    let notification = Cell::new(None);
+    let listener = IpcListener(|n| notification.set(Some(n)));
+    let mut buffer = *b"request";
+    share::scope::<
+        (
+            AllowRw<_, DRIVER_NUM, SERVICE>,
+            Subscribe<_, DRIVER_NUM, { client_subscribe_num(SERVICE) }>,
+        ),
+        _,
+        _,
+    >(|handle| {
+        let (allow_rw, subscribe) = handle.split();
+        assert_eq!(Ipc::share::<SERVICE>(allow_rw, &mut buffer), Ok(()));
+        assert_eq!(
+            Ipc::register_client_listener::<{ client_subscribe_num(SERVICE) }, _>(
+                &listener, subscribe
+            ),
+            Ok(())
+        );
+        assert_eq!(Ipc::notify_service(SERVICE), Ok(()));
+
+        assert_eq!(
+            driver.take_notifications(SERVICE),
+            [fake::Notification::Service]
+        );
+        assert_eq!(driver.shared_buffer(SERVICE), b"request");
+        driver.write_shared_buffer(SERVICE, b"reply");
+        driver.notify_as_service(SERVICE);
+
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(
+            notification.get(),
+            Some(IpcNotification {
+                sender: SERVICE,
+                buffer_len: 0,
+                buffer_address: 0,
+            })
+        );
+    });
+    assert_eq!(&buffer, b"replyst");
+}
+
+// The process under test acts as a service.
+#[test]
+fn service() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ipc::<4>::new("test");
+    kernel.add_driver(&driver);
+    let client = driver.add_process("client");
+    driver.set_buffer(client, b"request");
+
+    let notification = Cell::new(None);
+    let listener = IpcListener(|n| notification.set(Some(n)));
+    share::scope(|subscribe| {
+        assert_eq!(Ipc::register_service_listener(&listener, subscribe), Ok(()));
+        driver.notify_as_client(client);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(
+            notification.get(),
+            Some(IpcNotification {
+                sender: client,
+                buffer_len: 7,
+                buffer_address: driver.buffer_address(client),
+            })
+        );
+        assert_eq!(Ipc::notify_client(client), Ok(()));
+        assert_eq!(
+            driver.take_notifications(client),
+            [fake::Notification::Client]
+        );
+
+        Ipc::unregister_service_listener();
+        driver.notify_as_client(client);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+    });
+}
+
+#[test]
+fn client_listener_rejects_service_upcall() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ipc::<4>::new("test");
+    kernel.add_driver(&driver);
+
+    let listener = IpcListener(|_| {});
+    share::scope(|subscribe| {
+        assert_eq!(
+            Ipc::register_client_listener::<0, _>(&listener, subscribe),
+            Err(ErrorCode::Invalid)
+        );
+    });
+}
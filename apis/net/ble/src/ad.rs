@@ -0,0 +1,239 @@
+use libtock_platform::ErrorCode;
+
+/// The maximum length of legacy advertising data, in bytes.
+pub const MAX_ADV_DATA_LEN: usize = 31;
+
+/// Values for the Flags AD structure, which can be combined with `|`.
+pub mod flags {
+    pub const LE_LIMITED_DISCOVERABLE: u8 = 0x01;
+    pub const LE_GENERAL_DISCOVERABLE: u8 = 0x02;
+    pub const BR_EDR_NOT_SUPPORTED: u8 = 0x04;
+}
+
+/// AD structure types, as assigned by the Bluetooth SIG.
+mod ad_type {
+    pub const FLAGS: u8 = 0x01;
+    pub const INCOMPLETE_UUIDS_16: u8 = 0x02;
+    pub const COMPLETE_UUIDS_16: u8 = 0x03;
+    pub const INCOMPLETE_UUIDS_128: u8 = 0x06;
+    pub const COMPLETE_UUIDS_128: u8 = 0x07;
+    pub const SHORTENED_LOCAL_NAME: u8 = 0x08;
+    pub const COMPLETE_LOCAL_NAME: u8 = 0x09;
+    pub const TX_POWER_LEVEL: u8 = 0x0A;
+    pub const MANUFACTURER_DATA: u8 = 0xFF;
+}
+
+/// Builds an advertisement payload out of AD structures.
+///
+/// ```ignore
+/// let mut data = AdvertisingData::new();
+/// data.add_flags(flags::LE_GENERAL_DISCOVERABLE | flags::BR_EDR_NOT_SUPPORTED)?
+///     .add_complete_local_name(b"TockOS")?
+///     .add_service_uuids16(&[0x180F])?;
+/// ```
+///
+/// Each `add_` function returns `ErrorCode::Size` if the structure does not fit
+/// in the remaining space, in which case the payload is unchanged.
+#[derive(Clone, Debug)]
+pub struct AdvertisingData {
+    buf: [u8; MAX_ADV_DATA_LEN],
+    len: usize,
+}
+
+impl Default for AdvertisingData {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AdvertisingData {
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; MAX_ADV_DATA_LEN],
+            len: 0,
+        }
+    }
+
+    /// Returns the encoded payload.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Removes all AD structures.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Adds a Flags structure. See [`flags`] for the values.
+    pub fn add_flags(&mut self, flags: u8) -> Result<&mut Self, ErrorCode> {
+        self.add_with(ad_type::FLAGS, 1, |buf| buf[0] = flags)
+    }
+
+    pub fn add_complete_local_name(&mut self, name: &[u8]) -> Result<&mut Self, ErrorCode> {
+        self.add(ad_type::COMPLETE_LOCAL_NAME, name)
+    }
+
+    pub fn add_shortened_local_name(&mut self, name: &[u8]) -> Result<&mut Self, ErrorCode> {
+        self.add(ad_type::SHORTENED_LOCAL_NAME, name)
+    }
+
+    /// Adds a Manufacturer Specific Data structure for the given company
+    /// identifier.
+    pub fn add_manufacturer_data(
+        &mut self,
+        company_id: u16,
+        data: &[u8],
+    ) -> Result<&mut Self, ErrorCode> {
+        self.add_with(ad_type::MANUFACTURER_DATA, 2 + data.len(), |buf| {
+            buf[..2].copy_from_slice(&company_id.to_le_bytes());
+            buf[2..].copy_from_slice(data);
+        })
+    }
+
+    /// Adds a Complete List of 16-bit Service UUIDs structure.
+    pub fn add_service_uuids16(&mut self, uuids: &[u16]) -> Result<&mut Self, ErrorCode> {
+        self.add_with(ad_type::COMPLETE_UUIDS_16, 2 * uuids.len(), |buf| {
+            for (chunk, uuid) in buf.chunks_exact_mut(2).zip(uuids) {
+                chunk.copy_from_slice(&uuid.to_le_bytes());
+            }
+        })
+    }
+
+    /// Adds a Complete List of 128-bit Service UUIDs structure. Each UUID is
+    /// given in little-endian byte order, as it is transmitted.
+    pub fn add_service_uuids128(&mut self, uuids: &[[u8; 16]]) -> Result<&mut Self, ErrorCode> {
+        self.add_with(ad_type::COMPLETE_UUIDS_128, 16 * uuids.len(), |buf| {
+            for (chunk, uuid) in buf.chunks_exact_mut(16).zip(uuids) {
+                chunk.copy_from_slice(uuid);
+            }
+        })
+    }
+
+    pub fn add_tx_power_level(&mut self, dbm: i8) -> Result<&mut Self, ErrorCode> {
+        self.add_with(ad_type::TX_POWER_LEVEL, 1, |buf| buf[0] = dbm as u8)
+    }
+
+    /// Adds an AD structure of any type.
+    pub fn add(&mut self, ad_type: u8, data: &[u8]) -> Result<&mut Self, ErrorCode> {
+        self.add_with(ad_type, data.len(), |buf| buf.copy_from_slice(data))
+    }
+
+    // Appends the length and type bytes of a structure with `data_len` bytes
+    // of data, then lets `write` fill in the data.
+    fn add_with(
+        &mut self,
+        ad_type: u8,
+        data_len: usize,
+        write: impl FnOnce(&mut [u8]),
+    ) -> Result<&mut Self, ErrorCode> {
+        let end = self.len + 2 + data_len;
+        if end > MAX_ADV_DATA_LEN {
+            return Err(ErrorCode::Size);
+        }
+        // The length byte covers the type byte and the data.
+        self.buf[self.len] = data_len as u8 + 1;
+        self.buf[self.len + 1] = ad_type;
+        write(&mut self.buf[self.len + 2..end]);
+        self.len = end;
+        Ok(self)
+    }
+}
+
+/// A single AD structure, parsed out of advertising data by [`AdStructures`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AdStructure<'a> {
+    Flags(u8),
+    /// A (complete or incomplete) list of 16-bit service UUIDs, as
+    /// little-endian byte pairs. Use [`AdStructure::uuids16`] to decode them.
+    ServiceUuids16(&'a [u8]),
+    /// A (complete or incomplete) list of 128-bit service UUIDs, in
+    /// little-endian byte order.
+    ServiceUuids128(&'a [u8]),
+    CompleteLocalName(&'a [u8]),
+    ShortenedLocalName(&'a [u8]),
+    TxPowerLevel(i8),
+    ManufacturerData {
+        company_id: u16,
+        data: &'a [u8],
+    },
+    /// A structure of a type not listed above, or one that is malformed.
+    Other {
+        ad_type: u8,
+        data: &'a [u8],
+    },
+}
+
+impl<'a> AdStructure<'a> {
+    /// Returns the 16-bit UUIDs in a `ServiceUuids16` structure. Returns an
+    /// empty iterator for other structures.
+    pub fn uuids16(&self) -> impl Iterator<Item = u16> + 'a {
+        let data: &'a [u8] = match *self {
+            AdStructure::ServiceUuids16(data) => data,
+            _ => &[],
+        };
+        data.chunks_exact(2)
+            .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+    }
+
+    fn parse(ad_type: u8, data: &'a [u8]) -> Self {
+        match (ad_type, data) {
+            (ad_type::FLAGS, &[flags]) => AdStructure::Flags(flags),
+            (ad_type::INCOMPLETE_UUIDS_16 | ad_type::COMPLETE_UUIDS_16, _)
+                if data.len() % 2 == 0 =>
+            {
+                AdStructure::ServiceUuids16(data)
+            }
+            (ad_type::INCOMPLETE_UUIDS_128 | ad_type::COMPLETE_UUIDS_128, _)
+                if data.len() % 16 == 0 =>
+            {
+                AdStructure::ServiceUuids128(data)
+            }
+            (ad_type::COMPLETE_LOCAL_NAME, _) => AdStructure::CompleteLocalName(data),
+            (ad_type::SHORTENED_LOCAL_NAME, _) => AdStructure::ShortenedLocalName(data),
+            (ad_type::TX_POWER_LEVEL, &[dbm]) => AdStructure::TxPowerLevel(dbm as i8),
+            (ad_type::MANUFACTURER_DATA, &[low, high, ref data @ ..]) => {
+                AdStructure::ManufacturerData {
+                    company_id: u16::from_le_bytes([low, high]),
+                    data,
+                }
+            }
+            _ => AdStructure::Other { ad_type, data },
+        }
+    }
+}
+
+/// Iterates over the AD structures in advertising data. Iteration stops at the
+/// first structure whose length runs past the end of the data.
+#[derive(Clone, Debug)]
+pub struct AdStructures<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> AdStructures<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+}
+
+impl<'a> Iterator for AdStructures<'a> {
+    type Item = AdStructure<'a>;
+
+    fn next(&mut self) -> Option<AdStructure<'a>> {
+        loop {
+            let (&len, rest) = self.data.split_first()?;
+            let len = len as usize;
+            // A zero length byte is padding.
+            if len == 0 {
+                self.data = rest;
+                continue;
+            }
+            if len > rest.len() {
+                self.data = &[];
+                return None;
+            }
+            let (structure, rest) = rest.split_at(len);
+            self.data = rest;
+            return Some(AdStructure::parse(structure[0], &structure[1..]));
+        }
+    }
+}
@@ -0,0 +1,313 @@
+//! The BLE advertising driver, which sends BLE advertisements and passively
+//! scans for advertisements sent by other devices.
+
+#![no_std]
+
+use core::cell::Cell;
+use core::marker::PhantomData;
+use libtock_platform as platform;
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::share::{self, Handle};
+use libtock_platform::subscribe::Subscribe;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+mod ad;
+
+pub use ad::{flags, AdStructure, AdStructures, AdvertisingData, MAX_ADV_DATA_LEN};
+
+/// The BLE advertising driver.
+///
+/// # Example
+/// ```ignore
+/// use libtock::ble::{flags, AdvertisingData, Ble, PassiveScanner, PduType, ScanBuffer};
+///
+/// let mut data = AdvertisingData::new();
+/// data.add_flags(flags::LE_GENERAL_DISCOVERABLE | flags::BR_EDR_NOT_SUPPORTED)?
+///     .add_complete_local_name(b"TockOS")?;
+///
+/// Ble::set_tx_power(0)?;
+/// share::scope(|allow_ro| {
+///     Ble::start_advertising(allow_ro, &data, PduType::NonConnectableUndirected, 300)?;
+///     // Advertising continues while data is shared with the kernel.
+///     Ble::stop_advertising()
+/// })?;
+///
+/// // Print the names of the next 10 devices found while scanning.
+/// let mut buffer = ScanBuffer::new();
+/// let mut scanner = PassiveScanner::new(&mut buffer);
+/// Ble::start_passive_scan()?;
+/// for _ in 0..10 {
+///     let report = scanner.receive_report()?;
+///     for structure in report.ad_structures() {
+///         if let AdStructure::CompleteLocalName(name) = structure {
+///             // ...
+///         }
+///     }
+/// }
+/// Ble::stop_scan()?;
+///
+/// // Or block until a device advertises a heart rate service.
+/// Ble::passive_scan(|report| {
+///     !report.ad_structures().any(|s| s.uuids16().any(|uuid| uuid == 0x180D))
+/// })?;
+/// ```
+pub struct Ble<S: Syscalls, C: Config = DefaultConfig>(S, C);
+
+/// The advertising PDU types the driver can send.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum PduType {
+    /// ADV_IND
+    ConnectableUndirected = 0x00,
+    /// ADV_NONCONN_IND
+    NonConnectableUndirected = 0x02,
+    /// ADV_SCAN_IND
+    ScannableUndirected = 0x06,
+}
+
+impl<S: Syscalls, C: Config> Ble<S, C> {
+    /// Run a check against the BLE advertising capsule to ensure it is present.
+    #[inline(always)]
+    pub fn exists() -> bool {
+        S::command(DRIVER_NUM, command::EXISTS, 0, 0).is_success()
+    }
+
+    /// Sets the transmit power used for advertising, in dBm. Returns
+    /// `ErrorCode::Invalid` if the radio does not support that power.
+    #[inline(always)]
+    pub fn set_tx_power(dbm: i8) -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::SET_TX_POWER, dbm as i32 as u32, 0).to_result()
+    }
+
+    /// Starts advertising `data` every `interval_ms` milliseconds. The kernel
+    /// reads `data` every time it advertises, so advertising must be stopped
+    /// before `allow_ro`'s scope ends.
+    pub fn start_advertising<'share>(
+        allow_ro: Handle<AllowRo<'share, S, DRIVER_NUM, { allow_ro::ADV_DATA }>>,
+        data: &'share AdvertisingData,
+        pdu_type: PduType,
+        interval_ms: u32,
+    ) -> Result<(), ErrorCode> {
+        S::allow_ro::<C, DRIVER_NUM, { allow_ro::ADV_DATA }>(allow_ro, data.as_bytes())?;
+        S::command(
+            DRIVER_NUM,
+            command::START_ADVERTISING,
+            pdu_type as u32,
+            interval_ms,
+        )
+        .to_result()
+    }
+
+    /// Stops advertising.
+    #[inline(always)]
+    pub fn stop_advertising() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::STOP, 0, 0).to_result()
+    }
+
+    /// Starts passively scanning for advertisements. Reports are received
+    /// with a [`PassiveScanner`]; advertisements the kernel receives while no
+    /// scanner is waiting are dropped.
+    #[inline(always)]
+    pub fn start_passive_scan() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::PASSIVE_SCAN, 0, 0).to_result()
+    }
+
+    /// Stops passive scanning.
+    #[inline(always)]
+    pub fn stop_scan() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::STOP, 0, 0).to_result()
+    }
+
+    /// Passively scans for advertisements, calling `listener` with each one
+    /// received, until `listener` returns `false`.
+    pub fn passive_scan<F: FnMut(AdvertisementReport) -> bool>(
+        mut listener: F,
+    ) -> Result<(), ErrorCode> {
+        let mut buffer = ScanBuffer::new();
+        let mut scanner = PassiveScanner::<S, C>::new(&mut buffer);
+        Self::start_passive_scan()?;
+        let result = loop {
+            match scanner.receive_report() {
+                Ok(report) if listener(report) => {}
+                Ok(_) => break Ok(()),
+                Err(error) => break Err(error),
+            }
+        };
+        Self::stop_scan()?;
+        result
+    }
+
+    // Shares `buffer` with the kernel and waits until an advertisement is
+    // received into it. Returns the status and length the kernel reported.
+    fn receive_into(buffer: &mut ScanBuffer) -> Result<(u32, u32), ErrorCode> {
+        let called: Cell<Option<(u32, u32)>> = Cell::new(None);
+        share::scope::<
+            (
+                AllowRw<_, DRIVER_NUM, { allow_rw::SCAN_BUFFER }>,
+                Subscribe<_, DRIVER_NUM, { subscribe::SCAN }>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_rw, subscribe) = handle.split();
+            S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::SCAN }>(subscribe, &called)?;
+            S::allow_rw::<C, DRIVER_NUM, { allow_rw::SCAN_BUFFER }>(allow_rw, &mut buffer.0)?;
+            loop {
+                S::yield_wait();
+                if let Some(result) = called.get() {
+                    return Ok(result);
+                }
+            }
+        })
+    }
+}
+
+/// The buffer advertisement reports are received into while scanning.
+pub struct ScanBuffer([u8; SCAN_BUFFER_LEN]);
+
+impl ScanBuffer {
+    pub const fn new() -> Self {
+        Self([0; SCAN_BUFFER_LEN])
+    }
+}
+
+impl Default for ScanBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Receives advertisement reports into a [`ScanBuffer`] while the driver is
+/// scanning (see [`Ble::start_passive_scan`]). Reports the kernel received
+/// with an error, or that cannot be parsed, are dropped.
+///
+/// The buffer is shared with the kernel only while
+/// [`receive_report`](Self::receive_report) waits, and the report is parsed
+/// once the kernel has given it back. Advertisements received while the
+/// process examines a report are therefore lost.
+///
+/// ```ignore
+/// let mut buffer = ScanBuffer::new();
+/// let mut scanner = PassiveScanner::new(&mut buffer);
+/// Ble::start_passive_scan()?;
+/// let report = scanner.receive_report()?;
+/// Ble::stop_scan()?;
+/// ```
+pub struct PassiveScanner<'buf, S: Syscalls, C: Config = DefaultConfig> {
+    buffer: &'buf mut ScanBuffer,
+    s: PhantomData<S>,
+    c: PhantomData<C>,
+}
+
+impl<'buf, S: Syscalls, C: Config> PassiveScanner<'buf, S, C> {
+    /// Creates a new [`PassiveScanner`] that receives reports into `buffer`.
+    pub fn new(buffer: &'buf mut ScanBuffer) -> Self {
+        Self {
+            buffer,
+            s: PhantomData,
+            c: PhantomData,
+        }
+    }
+
+    /// Waits until an advertisement is received, and returns it.
+    pub fn receive_report(&mut self) -> Result<AdvertisementReport<'_>, ErrorCode> {
+        let len = loop {
+            let (status, len) = Ble::<S, C>::receive_into(self.buffer)?;
+            let len = (len as usize).min(SCAN_BUFFER_LEN);
+            if status == 0 && AdvertisementReport::parse(&self.buffer.0[..len]).is_some() {
+                break len;
+            }
+        };
+        Ok(AdvertisementReport::parse(&self.buffer.0[..len]).unwrap())
+    }
+}
+
+/// An advertisement received while scanning.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AdvertisementReport<'a> {
+    /// The advertising PDU type (e.g. 0x02 for ADV_NONCONN_IND).
+    pub pdu_type: u8,
+    /// The advertiser's device address, in the order it was transmitted
+    /// (least significant byte first).
+    pub address: [u8; 6],
+    /// The advertising data.
+    pub data: &'a [u8],
+}
+
+impl<'a> AdvertisementReport<'a> {
+    /// Returns the AD structures in the advertising data.
+    pub fn ad_structures(&self) -> AdStructures<'a> {
+        AdStructures::new(self.data)
+    }
+
+    // Parses a raw advertising PDU: a header byte holding the PDU type, a
+    // length byte, the 6-byte advertiser address, and the advertising data.
+    fn parse(pdu: &'a [u8]) -> Option<Self> {
+        let (&[header, len], rest) = pdu.split_first_chunk::<2>()?;
+        let payload = rest.get(..len as usize)?;
+        let (address, data) = payload.split_first_chunk::<6>()?;
+        Some(Self {
+            pdu_type: header & 0x0F,
+            address: *address,
+            data,
+        })
+    }
+}
+
+/// The size of the buffer advertisement reports are received into: a 2-byte
+/// header followed by the largest legacy advertising payload.
+const SCAN_BUFFER_LEN: usize = 2 + 6 + MAX_ADV_DATA_LEN;
+
+/// System call configuration trait for `Ble`.
+pub trait Config:
+    platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config
+{
+}
+impl<T: platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config>
+    Config for T
+{
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x30000;
+
+// Command IDs
+/// - `0`: Driver existence check.
+/// - `1`: Start advertising the data in the advertising data buffer.
+///   `argument0` is the PDU type and `argument1` the interval in milliseconds.
+/// - `2`: Stop advertising or scanning.
+/// - `3`: Set the transmit power, in dBm.
+/// - `5`: Start passive scanning. Each advertisement received is written to
+///   the scan buffer.
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const START_ADVERTISING: u32 = 1;
+    pub const STOP: u32 = 2;
+    pub const SET_TX_POWER: u32 = 3;
+    pub const PASSIVE_SCAN: u32 = 5;
+}
+
+mod subscribe {
+    /// Advertisement received. Argument 0 is the status, argument 1 the
+    /// length of the report.
+    pub const SCAN: u32 = 0;
+}
+
+/// Ids for read-only allow buffers
+mod allow_ro {
+    /// Advertising data.
+    pub const ADV_DATA: u32 = 0;
+}
+
+/// Ids for read-write allow buffers
+mod allow_rw {
+    /// Scan buffer. Will contain the received advertising PDU.
+    pub const SCAN_BUFFER: u32 = 0;
+}
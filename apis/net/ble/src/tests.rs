@@ -0,0 +1,194 @@
+use core::cell::Cell;
+use libtock_platform::{share, ErrorCode, Syscalls, YieldNoWaitReturn};
+use libtock_unittest::fake;
+
+use crate::{flags, AdStructure, AdStructures, AdvertisingData, PduType, ScanBuffer};
+
+type Ble = super::Ble<fake::Syscalls>;
+type PassiveScanner<'buf> = super::PassiveScanner<'buf, fake::Syscalls>;
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert!(!Ble::exists());
+}
+
+#[test]
+fn exists() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ble::new();
+    kernel.add_driver(&driver);
+
+    assert!(Ble::exists());
+}
+
+#[test]
+fn advertising_data() {
+    let mut data = AdvertisingData::new();
+    data.add_flags(flags::LE_GENERAL_DISCOVERABLE | flags::BR_EDR_NOT_SUPPORTED)
+        .unwrap()
+        .add_complete_local_name(b"Tock")
+        .unwrap()
+        .add_service_uuids16(&[0x180F, 0x180D])
+        .unwrap()
+        .add_manufacturer_data(0x0059, &[1, 2])
+        .unwrap();
+    assert_eq!(
+        data.as_bytes(),
+        [
+            2, 0x01, 0x06, // Flags
+            5, 0x09, b'T', b'o', b'c', b'k', // Name
+            5, 0x03, 0x0F, 0x18, 0x0D, 0x18, // UUIDs
+            5, 0xFF, 0x59, 0x00, 1, 2, // Manufacturer data
+        ]
+    );
+
+    // 21 bytes are used, so 10 remain, enough for 8 bytes of data.
+    assert_eq!(
+        data.add_shortened_local_name(b"too long!").err(),
+        Some(ErrorCode::Size)
+    );
+    assert_eq!(data.as_bytes().len(), 21);
+    data.add_shortened_local_name(b"8 bytes!").unwrap();
+    assert_eq!(data.as_bytes().len(), 31);
+
+    data.clear();
+    assert_eq!(data.as_bytes(), []);
+}
+
+#[test]
+fn parse_ad_structures() {
+    let mut data = AdvertisingData::new();
+    data.add_flags(flags::LE_LIMITED_DISCOVERABLE)
+        .unwrap()
+        .add_tx_power_level(-8)
+        .unwrap()
+        .add_service_uuids128(&[[7; 16]])
+        .unwrap()
+        .add(0x16, &[0xAA])
+        .unwrap();
+    assert!(AdStructures::new(data.as_bytes()).eq([
+        AdStructure::Flags(flags::LE_LIMITED_DISCOVERABLE),
+        AdStructure::TxPowerLevel(-8),
+        AdStructure::ServiceUuids128(&[7; 16]),
+        AdStructure::Other {
+            ad_type: 0x16,
+            data: &[0xAA],
+        },
+    ]));
+
+    let uuids = [3, 0x02, 0x0F, 0x18];
+    let structure = AdStructures::new(&uuids).next().unwrap();
+    assert!(structure.uuids16().eq([0x180F]));
+
+    // Padding is skipped and a truncated structure ends iteration.
+    let truncated = [0, 2, 0x01, 0x06, 5, 0x09, b'a'];
+    assert!(AdStructures::new(&truncated).eq([AdStructure::Flags(0x06)]));
+}
+
+#[test]
+fn tx_power() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ble::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(Ble::set_tx_power(-4), Ok(()));
+    assert_eq!(driver.tx_power(), -4);
+    assert_eq!(Ble::set_tx_power(20), Err(ErrorCode::Invalid));
+}
+
+#[test]
+fn advertise() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ble::new();
+    kernel.add_driver(&driver);
+
+    let mut data = AdvertisingData::new();
+    data.add_complete_local_name(b"Tock").unwrap();
+    share::scope(|allow_ro| {
+        assert_eq!(
+            Ble::start_advertising(allow_ro, &data, PduType::NonConnectableUndirected, 300),
+            Ok(())
+        );
+        assert_eq!(
+            driver.advertisement(),
+            Some(fake::Advertisement {
+                pdu_type: 0x02,
+                interval_ms: 300,
+                data: data.as_bytes().to_vec(),
+            })
+        );
+        assert_eq!(Ble::stop_advertising(), Ok(()));
+    });
+    assert_eq!(driver.advertisement(), None);
+    assert_eq!(Ble::stop_advertising(), Err(ErrorCode::Already));
+}
+
+#[test]
+fn passive_scanner() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ble::new();
+    kernel.add_driver(&driver);
+
+    let mut buffer = ScanBuffer::new();
+    let mut scanner = PassiveScanner::new(&mut buffer);
+    assert_eq!(Ble::start_passive_scan(), Ok(()));
+    assert!(driver.is_scanning());
+
+    // Nothing is delivered until the scanner shares its buffer.
+    driver.inject_scan_result(0x00, [1, 2, 3, 4, 5, 6], &[5, 0x09, b'f', b'i', b'r', b'1']);
+    assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+    assert_eq!(driver.pending_scan_results(), 1);
+
+    // A report that does not fit the buffer is dropped.
+    driver.inject_scan_result(0x02, [0; 6], &[0; 40]);
+    driver.inject_scan_result(0x02, [6, 5, 4, 3, 2, 1], &[3, 0x03, 0x0D, 0x18]);
+
+    let report = scanner.receive_report().unwrap();
+    assert_eq!(report.pdu_type, 0x00);
+    assert_eq!(report.address, [1, 2, 3, 4, 5, 6]);
+    assert!(report
+        .ad_structures()
+        .eq([AdStructure::CompleteLocalName(b"fir1")]));
+
+    let report = scanner.receive_report().unwrap();
+    assert_eq!(report.pdu_type, 0x02);
+    assert_eq!(report.address, [6, 5, 4, 3, 2, 1]);
+    assert_eq!(driver.pending_scan_results(), 0);
+
+    assert_eq!(Ble::stop_scan(), Ok(()));
+    assert!(!driver.is_scanning());
+    assert_eq!(Ble::stop_scan(), Err(ErrorCode::Already));
+}
+
+#[test]
+fn passive_scan() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ble::new();
+    kernel.add_driver(&driver);
+
+    driver.inject_scan_result(0x00, [1, 2, 3, 4, 5, 6], &[5, 0x09, b'f', b'i', b'r', b'1']);
+
+    let reports = Cell::new(0);
+    let result = Ble::passive_scan(|report| {
+        reports.set(reports.get() + 1);
+        match reports.get() {
+            1 => {
+                assert_eq!(report.address, [1, 2, 3, 4, 5, 6]);
+                // Another device advertises while the scan goes on.
+                driver.inject_scan_result(0x02, [6, 5, 4, 3, 2, 1], &[3, 0x03, 0x0D, 0x18]);
+                true
+            }
+            _ => {
+                assert_eq!(report.address, [6, 5, 4, 3, 2, 1]);
+                // Stop once the heart rate service is found.
+                !report
+                    .ad_structures()
+                    .any(|s| s.uuids16().any(|uuid| uuid == 0x180D))
+            }
+        }
+    });
+    assert_eq!(result, Ok(()));
+    assert_eq!(reports.get(), 2);
+    assert!(!driver.is_scanning());
+}
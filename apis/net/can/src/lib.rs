@@ -0,0 +1,455 @@
+//! The CAN driver, which sends and receives frames on a CAN bus.
+
+#![no_std]
+
+use core::cell::Cell;
+use libtock_platform as platform;
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::share;
+use libtock_platform::subscribe::{OneId, Subscribe};
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls, Upcall, YieldNoWaitReturn};
+
+mod rx;
+pub use rx::{Receiver, RxRingBuffer};
+
+/// The CAN driver.
+///
+/// The controller is configured with [`Can::set_bitrate`] and
+/// [`Can::set_mode`] while it is disabled, then joins the bus when
+/// [`Can::enable`] is called. Frames are sent one at a time with
+/// [`Can::send`], and received into an [`RxRingBuffer`] through a
+/// [`Receiver`]. Acceptance filters limit which frames are received.
+///
+/// # Example
+/// ```ignore
+/// use libtock::can::{Can, Filter, Frame, Id, Mode, Receiver, RxRingBuffer};
+///
+/// Can::set_bitrate(500_000)?;
+/// Can::set_mode(Mode::Normal)?;
+/// Can::add_filter(Filter::new(Id::Standard(0x7E0), 0x7FF))?;
+/// Can::enable()?;
+///
+/// Can::send(&Frame::new(Id::Standard(0x7E8), &[0x02, 0x10, 0x01])?)?;
+///
+/// let mut buf = RxRingBuffer::<4>::new();
+/// let mut receiver = Receiver::new(&mut buf);
+/// let request = receiver.receive_frame()?;
+/// ```
+pub struct Can<S: Syscalls, C: Config = DefaultConfig>(S, C);
+
+/// The largest number of data bytes in a frame.
+pub const MAX_DATA_LEN: usize = 8;
+
+/// A CAN identifier.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Id {
+    /// An 11-bit identifier.
+    Standard(u16),
+    /// A 29-bit identifier.
+    Extended(u32),
+}
+
+impl Id {
+    /// The largest standard identifier.
+    pub const MAX_STANDARD: u16 = 0x7FF;
+    /// The largest extended identifier.
+    pub const MAX_EXTENDED: u32 = 0x1FFF_FFFF;
+
+    fn is_valid(self) -> bool {
+        match self {
+            Id::Standard(id) => id <= Self::MAX_STANDARD,
+            Id::Extended(id) => id <= Self::MAX_EXTENDED,
+        }
+    }
+
+    // The identifier as passed to the kernel: the identifier itself, with
+    // `flags::EXTENDED` set for extended identifiers.
+    fn to_raw(self) -> u32 {
+        match self {
+            Id::Standard(id) => id as u32,
+            Id::Extended(id) => id | flags::EXTENDED,
+        }
+    }
+
+    fn from_raw(raw: u32) -> Id {
+        match raw & flags::EXTENDED {
+            0 => Id::Standard((raw & Self::MAX_STANDARD as u32) as u16),
+            _ => Id::Extended(raw & Self::MAX_EXTENDED),
+        }
+    }
+}
+
+/// A CAN frame, laid out as the kernel writes it into an [`RxRingBuffer`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub struct Frame {
+    // The raw identifier, with the `flags` bits, in little-endian order.
+    raw_id: [u8; 4],
+    len: u8,
+    _reserved: [u8; 3],
+    data: [u8; MAX_DATA_LEN],
+}
+
+const EMPTY_FRAME: Frame = Frame {
+    raw_id: [0; 4],
+    len: 0,
+    _reserved: [0; 3],
+    data: [0; MAX_DATA_LEN],
+};
+
+impl Frame {
+    /// Creates a data frame. Returns `ErrorCode::Invalid` if `id` is out of
+    /// range, and `ErrorCode::Size` if `data` is longer than
+    /// [`MAX_DATA_LEN`].
+    pub fn new(id: Id, data: &[u8]) -> Result<Frame, ErrorCode> {
+        if data.len() > MAX_DATA_LEN {
+            return Err(ErrorCode::Size);
+        }
+        let mut frame = Frame::with_id(id, 0, data.len())?;
+        frame.data[..data.len()].copy_from_slice(data);
+        Ok(frame)
+    }
+
+    /// Creates a remote frame, which requests `len` bytes of data from the
+    /// node that sends `id`.
+    pub fn remote(id: Id, len: usize) -> Result<Frame, ErrorCode> {
+        if len > MAX_DATA_LEN {
+            return Err(ErrorCode::Size);
+        }
+        Frame::with_id(id, flags::REMOTE, len)
+    }
+
+    fn with_id(id: Id, flags: u32, len: usize) -> Result<Frame, ErrorCode> {
+        if !id.is_valid() {
+            return Err(ErrorCode::Invalid);
+        }
+        Ok(Frame {
+            raw_id: (id.to_raw() | flags).to_le_bytes(),
+            len: len as u8,
+            ..EMPTY_FRAME
+        })
+    }
+
+    fn raw_id(&self) -> u32 {
+        u32::from_le_bytes(self.raw_id)
+    }
+
+    pub fn id(&self) -> Id {
+        Id::from_raw(self.raw_id())
+    }
+
+    /// Returns whether this is a remote frame.
+    pub fn is_remote(&self) -> bool {
+        self.raw_id() & flags::REMOTE != 0
+    }
+
+    /// Returns the frame's data length code: the number of data bytes, or for
+    /// a remote frame, the number of bytes requested.
+    pub fn len(&self) -> usize {
+        (self.len as usize).min(MAX_DATA_LEN)
+    }
+
+    /// Returns whether the data length code is zero.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the frame's data, which is empty for a remote frame.
+    pub fn data(&self) -> &[u8] {
+        match self.is_remote() {
+            true => &[],
+            false => &self.data[..self.len()],
+        }
+    }
+}
+
+/// An acceptance filter. A frame passes the filter if its identifier is of the
+/// same kind as the filter's, and matches it in every bit set in `mask`.
+///
+/// When no filters are installed, every frame is received.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Filter {
+    pub id: Id,
+    pub mask: u32,
+}
+
+impl Filter {
+    pub fn new(id: Id, mask: u32) -> Filter {
+        Filter { id, mask }
+    }
+
+    /// A filter that passes every frame with an identifier of the same kind as
+    /// `id`.
+    pub fn any(id: Id) -> Filter {
+        Filter { id, mask: 0 }
+    }
+}
+
+/// The controller's operating mode.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    /// Sends and receives frames on the bus.
+    Normal = 0,
+    /// Receives the frames it sends, without putting them on the bus.
+    Loopback = 1,
+    /// Receives frames without sending or acknowledging any.
+    ListenOnly = 2,
+}
+
+/// The controller's error state, which follows from its error counters.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BusState {
+    /// Both error counters are at most 127.
+    ErrorActive = 0,
+    /// An error counter is above 127; the controller no longer signals errors
+    /// it detects.
+    ErrorPassive = 1,
+    /// The transmit error counter passed 255, and the controller has left the
+    /// bus. It rejoins when enabled again.
+    BusOff = 2,
+}
+
+impl From<u32> for BusState {
+    fn from(value: u32) -> BusState {
+        match value {
+            0 => BusState::ErrorActive,
+            1 => BusState::ErrorPassive,
+            _ => BusState::BusOff,
+        }
+    }
+}
+
+/// The controller's transmit and receive error counters.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ErrorCounters {
+    pub transmit: u32,
+    pub receive: u32,
+}
+
+// Configuration
+impl<S: Syscalls, C: Config> Can<S, C> {
+    /// Run a check against the CAN capsule to ensure it is present.
+    ///
+    /// Returns `true` if the driver was present. This does not necessarily mean
+    /// that the driver is working, as it may still fail to allocate grant
+    /// memory.
+    #[inline(always)]
+    pub fn exists() -> bool {
+        S::command(DRIVER_NUM, command::EXISTS, 0, 0).is_success()
+    }
+
+    /// Sets the bitrate, in bits per second. Returns `ErrorCode::Busy` if the
+    /// controller is enabled, and `ErrorCode::Invalid` if the controller cannot
+    /// run at `bitrate`.
+    pub fn set_bitrate(bitrate: u32) -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::SET_BITRATE, bitrate, 0).to_result()
+    }
+
+    /// Sets the operating mode. Returns `ErrorCode::Busy` if the controller is
+    /// enabled.
+    pub fn set_mode(mode: Mode) -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::SET_MODE, mode as u32, 0).to_result()
+    }
+
+    /// Joins the bus. Enabling the controller after it went bus-off resets
+    /// its error counters.
+    pub fn enable() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::ENABLE, 0, 0).to_result()
+    }
+
+    /// Leaves the bus.
+    pub fn disable() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::DISABLE, 0, 0).to_result()
+    }
+
+    /// Installs an acceptance filter, and returns its index. Returns
+    /// `ErrorCode::NoMem` if the controller has no free filters.
+    pub fn add_filter(filter: Filter) -> Result<u32, ErrorCode> {
+        if !filter.id.is_valid() {
+            return Err(ErrorCode::Invalid);
+        }
+        S::command(
+            DRIVER_NUM,
+            command::ADD_FILTER,
+            filter.id.to_raw(),
+            filter.mask,
+        )
+        .to_result()
+    }
+
+    /// Removes the filter at `index`, returned by [`Can::add_filter`].
+    pub fn remove_filter(index: u32) -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::REMOVE_FILTER, index, 0).to_result()
+    }
+
+    /// Removes every filter, so that every frame is received.
+    pub fn clear_filters() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::CLEAR_FILTERS, 0, 0).to_result()
+    }
+}
+
+// Errors
+impl<S: Syscalls, C: Config> Can<S, C> {
+    pub fn error_counters() -> Result<ErrorCounters, ErrorCode> {
+        let (transmit, receive) =
+            S::command(DRIVER_NUM, command::ERROR_COUNTERS, 0, 0).to_result::<(u32, u32), _>()?;
+        Ok(ErrorCounters { transmit, receive })
+    }
+
+    pub fn bus_state() -> Result<BusState, ErrorCode> {
+        S::command(DRIVER_NUM, command::BUS_STATE, 0, 0)
+            .to_result::<u32, _>()
+            .map(BusState::from)
+    }
+
+    /// Registers a listener to be called whenever the controller's
+    /// [`BusState`] changes.
+    pub fn register_error_listener<'share, F: Fn(BusState, ErrorCounters)>(
+        listener: &'share ErrorListener<F>,
+        subscribe: share::Handle<Subscribe<'share, S, DRIVER_NUM, { subscribe::ERROR }>>,
+    ) -> Result<(), ErrorCode> {
+        S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::ERROR }>(subscribe, listener)
+    }
+
+    /// Unregisters the error listener.
+    pub fn unregister_error_listener() {
+        S::unsubscribe(DRIVER_NUM, subscribe::ERROR)
+    }
+}
+
+// Transmission
+impl<S: Syscalls, C: Config> Can<S, C> {
+    /// Sends `frame`, and waits until it has been transmitted. Returns
+    /// `ErrorCode::Off` if the controller is disabled or bus-off, and
+    /// `ErrorCode::Invalid` in listen-only mode.
+    pub fn send(frame: &Frame) -> Result<(), ErrorCode> {
+        share::scope::<AllowRo<_, DRIVER_NUM, { allow_ro::WRITE }>, _, _>(|allow_ro| {
+            Self::start_send(frame, allow_ro)?;
+            S::yield_wait_for_status(DRIVER_NUM, subscribe::TX_DONE)
+        })
+    }
+
+    /// Sends `frame` like [`Can::send`], but stops waiting for it to be
+    /// transmitted once `stop` returns `true`. `stop` is checked after each
+    /// upcall, so it is typically set by another upcall, such as an alarm's.
+    /// Returns `Ok(false)` if waiting stopped before the frame was
+    /// transmitted.
+    pub fn send_until(frame: &Frame, stop: impl Fn() -> bool) -> Result<bool, ErrorCode> {
+        let status: Cell<Option<(u32,)>> = Cell::new(None);
+        share::scope::<
+            (
+                AllowRo<_, DRIVER_NUM, { allow_ro::WRITE }>,
+                Subscribe<_, DRIVER_NUM, { subscribe::TX_DONE }>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_ro, subscribe) = handle.split();
+            S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::TX_DONE }>(subscribe, &status)?;
+            Self::start_send(frame, allow_ro)?;
+            match wait_until::<S>(&status, stop) {
+                None => Ok(false),
+                Some((0,)) => Ok(true),
+                Some((status,)) => Err(status.try_into().unwrap_or(ErrorCode::Fail)),
+            }
+        })
+    }
+
+    fn start_send<'share>(
+        frame: &'share Frame,
+        allow_ro: share::Handle<AllowRo<'share, S, DRIVER_NUM, { allow_ro::WRITE }>>,
+    ) -> Result<(), ErrorCode> {
+        S::allow_ro::<C, DRIVER_NUM, { allow_ro::WRITE }>(allow_ro, frame.data())?;
+        S::command(
+            DRIVER_NUM,
+            command::SEND,
+            frame.raw_id(),
+            frame.len() as u32,
+        )
+        .to_result()
+    }
+}
+
+// Yields until `upcall` is called or `stop` returns `true`, and returns the
+// upcall's arguments if it was called. When `stop` returns `true`, the upcalls
+// that are already pending are handled before giving up, so that an upcall
+// that raced with the one that set `stop` is not lost.
+fn wait_until<S: Syscalls>(
+    upcall: &Cell<Option<(u32,)>>,
+    stop: impl Fn() -> bool,
+) -> Option<(u32,)> {
+    while !stop() {
+        S::yield_wait();
+        if let Some(args) = upcall.get() {
+            return Some(args);
+        }
+    }
+    while upcall.get().is_none() && S::yield_no_wait() == YieldNoWaitReturn::Upcall {}
+    upcall.get()
+}
+
+/// A listener for changes of the controller's error state, called with the new
+/// state and the error counters that caused it.
+pub struct ErrorListener<F: Fn(BusState, ErrorCounters)>(pub F);
+
+impl<F: Fn(BusState, ErrorCounters)> Upcall<OneId<DRIVER_NUM, { subscribe::ERROR }>>
+    for ErrorListener<F>
+{
+    fn upcall(&self, state: u32, transmit: u32, receive: u32) {
+        self.0(state.into(), ErrorCounters { transmit, receive })
+    }
+}
+
+/// System call configuration trait for `Can`.
+pub trait Config:
+    platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config
+{
+}
+impl<T: platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config>
+    Config for T
+{
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x20007;
+
+// Bits of a raw identifier above the identifier itself.
+mod flags {
+    pub const EXTENDED: u32 = 1 << 31;
+    pub const REMOTE: u32 = 1 << 30;
+}
+
+// Command IDs
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const SET_BITRATE: u32 = 1;
+    pub const SET_MODE: u32 = 2;
+    pub const ENABLE: u32 = 3;
+    pub const DISABLE: u32 = 4;
+    pub const SEND: u32 = 5;
+    pub const ADD_FILTER: u32 = 6;
+    pub const REMOVE_FILTER: u32 = 7;
+    pub const CLEAR_FILTERS: u32 = 8;
+    pub const ERROR_COUNTERS: u32 = 9;
+    pub const BUS_STATE: u32 = 10;
+}
+
+mod subscribe {
+    pub const TX_DONE: u32 = 0;
+    pub const RX: u32 = 1;
+    pub const ERROR: u32 = 2;
+}
+
+mod allow_ro {
+    pub const WRITE: u32 = 0;
+}
+
+mod allow_rw {
+    pub const READ: u32 = 0;
+}
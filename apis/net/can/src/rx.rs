@@ -0,0 +1,154 @@
+use core::cell::Cell;
+use core::marker::PhantomData;
+
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::subscribe::Subscribe;
+use libtock_platform::{share, DefaultConfig, ErrorCode, Syscalls};
+
+use crate::{allow_rw, subscribe, wait_until, Can, Config, Frame, DRIVER_NUM, EMPTY_FRAME};
+
+/// The ring buffer that is shared with the kernel using allow-rw syscall, with
+/// the kernel acting as a producer of frames and the process as the consumer.
+///
+/// The `N` parameter specifies the capacity of the buffer in number of frames.
+/// As with `ieee802154::RxRingBuffer`, an empty buffer cannot be told apart
+/// from a full one, so the kernel uses at most `N - 1` slots. Frames that
+/// arrive while the buffer is full or not shared wait in the kernel, which may
+/// drop them if it runs out of room; specify `N` as `F + 1`, where `F` is the
+/// number of frames expected in short succession.
+#[derive(Debug)]
+#[repr(C)]
+pub struct RxRingBuffer<const N: usize> {
+    /// From where the next frame will be read by process.
+    /// Updated by process only.
+    read_index: u8,
+    /// Where the next frame will be written by kernel.
+    /// Updated by kernel only.
+    write_index: u8,
+    _reserved: [u8; 2],
+    /// Slots for received frames.
+    frames: [Frame; N],
+}
+
+impl<const N: usize> Default for RxRingBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> RxRingBuffer<N> {
+    /// Creates a new [RxRingBuffer] that can be used to receive frames into.
+    pub const fn new() -> Self {
+        Self {
+            read_index: 0,
+            write_index: 0,
+            _reserved: [0; 2],
+            frames: [EMPTY_FRAME; N],
+        }
+    }
+
+    fn as_mut_byte_slice(&mut self) -> &mut [u8] {
+        // SAFETY: Self is made up of bytes and byte arrays, and any byte value
+        // is valid for any of them, so casts back and forth cannot break the
+        // type system.
+        unsafe {
+            core::slice::from_raw_parts_mut(
+                self as *mut Self as *mut u8,
+                core::mem::size_of::<Self>(),
+            )
+        }
+    }
+
+    fn has_frame(&self) -> bool {
+        self.read_index != self.write_index
+    }
+
+    fn next_frame(&mut self) -> &Frame {
+        let frame = &self.frames[self.read_index as usize % N];
+        self.read_index = ((self.read_index as usize + 1) % N) as u8;
+        frame
+    }
+}
+
+/// Receives frames from the kernel into an [RxRingBuffer].
+///
+/// The buffer is only shared with the kernel while [Receiver::receive_frame]
+/// waits for a frame, so frames that arrive while the process handles the ones
+/// it has received wait in the kernel.
+pub struct Receiver<'buf, const N: usize, S: Syscalls, C: Config = DefaultConfig> {
+    buf: &'buf mut RxRingBuffer<N>,
+    s: PhantomData<S>,
+    c: PhantomData<C>,
+}
+
+impl<'buf, const N: usize, S: Syscalls, C: Config> Receiver<'buf, N, S, C> {
+    /// Creates a new [Receiver] that can be used to receive frames.
+    pub fn new(buf: &'buf mut RxRingBuffer<N>) -> Self {
+        Self {
+            buf,
+            s: PhantomData,
+            c: PhantomData,
+        }
+    }
+
+    /// Receive one new frame.
+    ///
+    /// Logically pop one frame out of the ring buffer and provide access to
+    /// it. If no frame is ready for reception, wait until one is available.
+    pub fn receive_frame(&mut self) -> Result<&Frame, ErrorCode> {
+        while !self.buf.has_frame() {
+            Can::<S, C>::wait_for_frames(self.buf)?;
+        }
+        Ok(self.buf.next_frame())
+    }
+
+    /// Receive one new frame like [Receiver::receive_frame], but stop waiting
+    /// once `stop` returns `true`. `stop` is checked after each upcall, so it
+    /// is typically set by another upcall, such as an alarm's. Returns
+    /// `Ok(None)` if waiting stopped before a frame arrived.
+    pub fn receive_frame_until(
+        &mut self,
+        stop: impl Fn() -> bool,
+    ) -> Result<Option<&Frame>, ErrorCode> {
+        if !self.buf.has_frame() {
+            Can::<S, C>::wait_for_frames_until(self.buf, &stop)?;
+        }
+        // Frames that arrived are received even if `stop` was set first.
+        match self.buf.has_frame() {
+            true => Ok(Some(self.buf.next_frame())),
+            false => Ok(None),
+        }
+    }
+}
+
+// Reception
+impl<S: Syscalls, C: Config> Can<S, C> {
+    fn wait_for_frames<const N: usize>(buf: &mut RxRingBuffer<N>) -> Result<(), ErrorCode> {
+        share::scope::<AllowRw<_, DRIVER_NUM, { allow_rw::READ }>, _, _>(|allow_rw| {
+            S::allow_rw::<C, DRIVER_NUM, { allow_rw::READ }>(allow_rw, buf.as_mut_byte_slice())?;
+            S::yield_wait_for(DRIVER_NUM, subscribe::RX);
+            Ok(())
+        })
+    }
+
+    fn wait_for_frames_until<const N: usize>(
+        buf: &mut RxRingBuffer<N>,
+        stop: impl Fn() -> bool,
+    ) -> Result<(), ErrorCode> {
+        let received: Cell<Option<(u32,)>> = Cell::new(None);
+        share::scope::<
+            (
+                AllowRw<_, DRIVER_NUM, { allow_rw::READ }>,
+                Subscribe<_, DRIVER_NUM, { subscribe::RX }>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_rw, subscribe) = handle.split();
+            S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::RX }>(subscribe, &received)?;
+            S::allow_rw::<C, DRIVER_NUM, { allow_rw::READ }>(allow_rw, buf.as_mut_byte_slice())?;
+            wait_until::<S>(&received, stop);
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,250 @@
+use core::cell::Cell;
+use libtock_platform::{share, ErrorCode, Syscalls, YieldNoWaitReturn};
+use libtock_unittest::fake::{self, CanFrame};
+
+use super::{BusState, ErrorCounters, ErrorListener, Filter, Frame, Id, Mode, RxRingBuffer};
+
+type Can = super::Can<fake::Syscalls>;
+type Receiver<'buf, const N: usize> = super::Receiver<'buf, N, fake::Syscalls>;
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert!(!Can::exists());
+}
+
+#[test]
+fn exists() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Can::new();
+    kernel.add_driver(&driver);
+
+    assert!(Can::exists());
+}
+
+#[test]
+fn frames() {
+    let frame = Frame::new(Id::Standard(0x123), &[1, 2, 3]).unwrap();
+    assert_eq!(frame.id(), Id::Standard(0x123));
+    assert!(!frame.is_remote());
+    assert_eq!(frame.len(), 3);
+    assert_eq!(frame.data(), [1, 2, 3]);
+
+    let frame = Frame::remote(Id::Extended(0x1234_5678), 8).unwrap();
+    assert_eq!(frame.id(), Id::Extended(0x1234_5678));
+    assert!(frame.is_remote());
+    assert_eq!(frame.len(), 8);
+    assert_eq!(frame.data(), []);
+
+    assert_eq!(
+        Frame::new(Id::Standard(0x800), &[]),
+        Err(ErrorCode::Invalid)
+    );
+    assert_eq!(
+        Frame::new(Id::Extended(0x2000_0000), &[]),
+        Err(ErrorCode::Invalid)
+    );
+    assert_eq!(
+        Frame::new(Id::Standard(0x123), &[0; 9]),
+        Err(ErrorCode::Size)
+    );
+}
+
+#[test]
+fn configure() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Can::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(Can::set_bitrate(1_000_000), Ok(()));
+    assert_eq!(Can::set_bitrate(42), Err(ErrorCode::Invalid));
+    assert_eq!(Can::set_mode(Mode::ListenOnly), Ok(()));
+    assert_eq!(Can::enable(), Ok(()));
+    assert_eq!(driver.bitrate(), 1_000_000);
+    assert_eq!(driver.mode(), Mode::ListenOnly as u32);
+    assert!(driver.is_enabled());
+
+    // The configuration is fixed while the controller is on the bus, and a
+    // listen-only controller cannot send.
+    assert_eq!(Can::set_bitrate(125_000), Err(ErrorCode::Busy));
+    assert_eq!(Can::set_mode(Mode::Normal), Err(ErrorCode::Busy));
+    let frame = Frame::new(Id::Standard(0x123), &[]).unwrap();
+    assert_eq!(Can::send(&frame), Err(ErrorCode::Invalid));
+
+    assert_eq!(Can::disable(), Ok(()));
+    assert_eq!(Can::send(&frame), Err(ErrorCode::Off));
+}
+
+#[test]
+fn send() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Can::new();
+    kernel.add_driver(&driver);
+    Can::enable().unwrap();
+
+    let frame = Frame::new(Id::Extended(0x18DA_F110), &[0x02, 0x10, 0x03]).unwrap();
+    assert_eq!(Can::send(&frame), Ok(()));
+    let frame = Frame::remote(Id::Standard(0x7FF), 2).unwrap();
+    assert_eq!(Can::send(&frame), Ok(()));
+    assert_eq!(
+        driver.take_transmitted_frames(),
+        [
+            CanFrame::extended(0x18DA_F110, &[0x02, 0x10, 0x03]),
+            CanFrame {
+                id: 0x7FF,
+                extended: false,
+                remote: true,
+                data: [0; 2].into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn receive() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Can::new();
+    kernel.add_driver(&driver);
+    Can::enable().unwrap();
+
+    driver.receive_frame(CanFrame::standard(0x100, &[1]));
+    driver.receive_frame(CanFrame::extended(0x100, &[2, 3]));
+    driver.receive_frame(CanFrame::standard(0x101, &[]));
+
+    // Room for two frames, so the third waits in the kernel.
+    let mut buf = RxRingBuffer::<3>::new();
+    let mut receiver = Receiver::new(&mut buf);
+    let frame = receiver.receive_frame().unwrap();
+    assert_eq!(frame.id(), Id::Standard(0x100));
+    assert_eq!(frame.data(), [1]);
+    let frame = receiver.receive_frame().unwrap();
+    assert_eq!(frame.id(), Id::Extended(0x100));
+    assert_eq!(frame.data(), [2, 3]);
+    assert!(driver.has_pending_rx_frames());
+    let frame = receiver.receive_frame().unwrap();
+    assert_eq!(frame.id(), Id::Standard(0x101));
+    assert!(frame.is_empty());
+    assert!(!driver.has_pending_rx_frames());
+}
+
+#[test]
+fn until() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Can::new();
+    kernel.add_driver(&driver);
+    Can::enable().unwrap();
+
+    // Upcalls that are already pending are handled even if `stop` is set.
+    let frame = Frame::new(Id::Standard(0x123), &[1]).unwrap();
+    assert_eq!(Can::send_until(&frame, || true), Ok(true));
+    assert_eq!(driver.take_transmitted_frames().len(), 1);
+
+    let mut buf = RxRingBuffer::<2>::new();
+    let mut receiver = Receiver::new(&mut buf);
+    assert_eq!(receiver.receive_frame_until(|| true), Ok(None));
+    driver.receive_frame(CanFrame::standard(0x321, &[2]));
+    assert_eq!(
+        receiver
+            .receive_frame_until(|| true)
+            .map(|frame| frame.map(Frame::data)),
+        Ok(Some(&[2][..]))
+    );
+}
+
+#[test]
+fn loopback() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Can::new();
+    kernel.add_driver(&driver);
+    Can::set_mode(Mode::Loopback).unwrap();
+    Can::enable().unwrap();
+
+    let sent = Frame::new(Id::Standard(0x42), &[0xCA, 0xFE]).unwrap();
+    Can::send(&sent).unwrap();
+    assert_eq!(driver.take_transmitted_frames(), []);
+
+    let mut buf = RxRingBuffer::<2>::new();
+    let mut receiver = Receiver::new(&mut buf);
+    assert_eq!(receiver.receive_frame(), Ok(&sent));
+}
+
+#[test]
+fn filters() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Can::new();
+    kernel.add_driver(&driver);
+    Can::enable().unwrap();
+
+    assert_eq!(
+        Can::add_filter(Filter::new(Id::Standard(0x7E0), 0x7FF)),
+        Ok(0)
+    );
+    assert_eq!(Can::add_filter(Filter::any(Id::Extended(0))), Ok(1));
+    assert_eq!(
+        Can::add_filter(Filter::any(Id::Standard(0x800))),
+        Err(ErrorCode::Invalid)
+    );
+    assert!(driver.receive_frame(CanFrame::standard(0x7E0, &[])));
+    assert!(!driver.receive_frame(CanFrame::standard(0x7E1, &[])));
+    assert!(driver.receive_frame(CanFrame::extended(0x7E1, &[])));
+
+    assert_eq!(Can::remove_filter(1), Ok(()));
+    assert_eq!(Can::remove_filter(1), Err(ErrorCode::Invalid));
+    assert!(!driver.receive_frame(CanFrame::extended(0x7E1, &[])));
+
+    assert_eq!(Can::clear_filters(), Ok(()));
+    assert!(driver.receive_frame(CanFrame::standard(0x7E1, &[])));
+}
+
+#[test]
+fn errors() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Can::new();
+    kernel.add_driver(&driver);
+    Can::enable().unwrap();
+
+    let state: Cell<Option<(BusState, ErrorCounters)>> = Cell::new(None);
+    let listener = ErrorListener(|bus_state, counters| state.set(Some((bus_state, counters))));
+    share::scope(|subscribe| {
+        Can::register_error_listener(&listener, subscribe).unwrap();
+
+        // Errors below the error-passive limit are not reported.
+        driver.set_error_counters(10, 20);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+        assert_eq!(Can::bus_state(), Ok(BusState::ErrorActive));
+
+        driver.set_error_counters(10, 128);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(
+            state.get(),
+            Some((
+                BusState::ErrorPassive,
+                ErrorCounters {
+                    transmit: 10,
+                    receive: 128
+                }
+            ))
+        );
+
+        driver.set_error_counters(256, 128);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(
+            state.get().map(|(bus_state, _)| bus_state),
+            Some(BusState::BusOff)
+        );
+    });
+    assert_eq!(
+        Can::error_counters(),
+        Ok(ErrorCounters {
+            transmit: 256,
+            receive: 128
+        })
+    );
+    let frame = Frame::new(Id::Standard(0x123), &[]).unwrap();
+    assert_eq!(Can::send(&frame), Err(ErrorCode::Off));
+
+    // Enabling the controller again recovers from bus-off.
+    assert_eq!(Can::enable(), Ok(()));
+    assert_eq!(Can::bus_state(), Ok(BusState::ErrorActive));
+    assert_eq!(Can::send(&frame), Ok(()));
+}
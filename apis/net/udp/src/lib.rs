@@ -0,0 +1,287 @@
+//! The UDP driver, which sends and receives UDP datagrams over the kernel's
+//! IPv6 stack.
+
+#![no_std]
+
+use core::marker::PhantomData;
+use core::net::{Ipv6Addr, SocketAddrV6};
+use libtock_platform as platform;
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::share;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+/// The UDP driver.
+///
+/// A process binds to a local address and port with [`Udp::bind`], which
+/// returns a [`UdpSocket`] that sends datagrams, and receives them through a
+/// [`UdpReceiver`]. The kernel only
+/// supports one binding per process.
+///
+/// # Example
+/// ```ignore
+/// use core::net::{Ipv6Addr, SocketAddrV6};
+/// use libtock::udp::{RxBuffer, Udp};
+///
+/// let mut interfaces = [Ipv6Addr::UNSPECIFIED; 1];
+/// Udp::interfaces(&mut interfaces)?;
+/// let socket = Udp::bind(SocketAddrV6::new(interfaces[0], 1000, 0, 0))?;
+///
+/// let peer = SocketAddrV6::new("fe80::2".parse().unwrap(), 2000, 0, 0);
+/// socket.send_to(b"hello", peer)?;
+///
+/// let mut buf = RxBuffer::<64>::new();
+/// let mut receiver = socket.receiver(&mut buf);
+/// let (payload, sender) = receiver.receive_from()?;
+/// ```
+pub struct Udp<S: Syscalls, C: Config = DefaultConfig>(S, C);
+
+// Existence check and queries
+impl<S: Syscalls, C: Config> Udp<S, C> {
+    /// Run a check against the UDP capsule to ensure it is present.
+    ///
+    /// Returns `true` if the driver was present. This does not necessarily mean
+    /// that the driver is working, as it may still fail to allocate grant
+    /// memory.
+    #[inline(always)]
+    pub fn exists() -> bool {
+        S::command(DRIVER_NUM, command::EXISTS, 0, 0).is_success()
+    }
+
+    /// Copies the IPv6 addresses of the node's network interfaces into
+    /// `interfaces`, and returns the total number of interfaces. If there are
+    /// more interfaces than fit in `interfaces` (or `MAX_INTERFACES`), only the
+    /// first ones are copied.
+    pub fn interfaces(interfaces: &mut [Ipv6Addr]) -> Result<usize, ErrorCode> {
+        let count = interfaces.len().min(MAX_INTERFACES);
+        let mut buf = [0; MAX_INTERFACES * IPV6_ADDR_LEN];
+        let total = share::scope::<AllowRw<_, DRIVER_NUM, { allow_rw::CFG }>, _, _>(|allow_rw| {
+            S::allow_rw::<C, DRIVER_NUM, { allow_rw::CFG }>(allow_rw, &mut buf)?;
+            S::command(DRIVER_NUM, command::INTERFACES, count as u32, 0).to_result::<u32, _>()
+        })?;
+        for (interface, bytes) in interfaces[..count]
+            .iter_mut()
+            .zip(buf.chunks_exact(IPV6_ADDR_LEN))
+        {
+            *interface = Ipv6Addr::from(<[u8; IPV6_ADDR_LEN]>::try_from(bytes).unwrap());
+        }
+        Ok(total as usize)
+    }
+
+    /// Returns the largest payload, in bytes, that can be sent in a single
+    /// datagram.
+    #[inline(always)]
+    pub fn max_payload_len() -> Result<u32, ErrorCode> {
+        S::command(DRIVER_NUM, command::MAX_PAYLOAD_LEN, 0, 0).to_result()
+    }
+
+    /// Binds this process to `local`, which must be the address of one of the
+    /// node's interfaces. Returns `ErrorCode::Busy` if another process is
+    /// already bound to the port, and `ErrorCode::Invalid` if the address is
+    /// not one of the node's addresses.
+    pub fn bind(local: SocketAddrV6) -> Result<UdpSocket<S, C>, ErrorCode> {
+        let mut cfg = [0; 2 * SOCK_ADDR_LEN];
+        encode_sock_addr(&mut cfg[SOCK_ADDR_LEN..], local);
+        share::scope::<AllowRw<_, DRIVER_NUM, { allow_rw::RX_CFG }>, _, _>(|allow_rw| {
+            S::allow_rw::<C, DRIVER_NUM, { allow_rw::RX_CFG }>(allow_rw, &mut cfg)?;
+            S::command(DRIVER_NUM, command::BIND, 0, 0).to_result::<(), ErrorCode>()
+        })?;
+        Ok(UdpSocket {
+            local,
+            s: PhantomData,
+            c: PhantomData,
+        })
+    }
+}
+
+/// A bound UDP socket, returned by [`Udp::bind`].
+pub struct UdpSocket<S: Syscalls, C: Config = DefaultConfig> {
+    local: SocketAddrV6,
+    s: PhantomData<S>,
+    c: PhantomData<C>,
+}
+
+impl<S: Syscalls, C: Config> UdpSocket<S, C> {
+    /// Returns the address and port this socket is bound to.
+    pub fn local_addr(&self) -> SocketAddrV6 {
+        self.local
+    }
+
+    /// Sends `payload` to `dest`, and waits until it has been transmitted.
+    /// Returns `ErrorCode::Size` if `payload` is longer than
+    /// [`Udp::max_payload_len`].
+    pub fn send_to(&self, payload: &[u8], dest: SocketAddrV6) -> Result<(), ErrorCode> {
+        let mut cfg = [0; 2 * SOCK_ADDR_LEN];
+        encode_sock_addr(&mut cfg[..SOCK_ADDR_LEN], self.local);
+        encode_sock_addr(&mut cfg[SOCK_ADDR_LEN..], dest);
+        share::scope::<
+            (
+                AllowRo<_, DRIVER_NUM, { allow_ro::WRITE }>,
+                AllowRw<_, DRIVER_NUM, { allow_rw::CFG }>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_ro, allow_rw) = handle.split();
+            S::allow_ro::<C, DRIVER_NUM, { allow_ro::WRITE }>(allow_ro, payload)?;
+            S::allow_rw::<C, DRIVER_NUM, { allow_rw::CFG }>(allow_rw, &mut cfg)?;
+            S::command(DRIVER_NUM, command::SEND, 0, 0).to_result::<(), ErrorCode>()?;
+            S::yield_wait_for_status(DRIVER_NUM, subscribe::PACKET_TRANSMITTED)
+        })
+    }
+
+    /// Creates a [`UdpReceiver`] that receives the datagrams addressed to
+    /// this socket into `buf`.
+    pub fn receiver<'buf, const N: usize>(
+        &self,
+        buf: &'buf mut RxBuffer<N>,
+    ) -> UdpReceiver<'buf, N, S, C> {
+        encode_sock_addr(&mut buf.cfg[SOCK_ADDR_LEN..], self.local);
+        UdpReceiver {
+            buf,
+            s: PhantomData,
+            c: PhantomData,
+        }
+    }
+}
+
+/// The buffer that is shared with the kernel using allow-rw syscalls to
+/// receive datagrams into.
+///
+/// The `N` parameter specifies the largest payload that can be received.
+/// Longer payloads are truncated.
+pub struct RxBuffer<const N: usize> {
+    /// The receive configuration: the kernel writes the sender's address
+    /// into the first half, and the second half holds the bound address.
+    cfg: [u8; 2 * SOCK_ADDR_LEN],
+    /// The received payload.
+    payload: [u8; N],
+}
+
+impl<const N: usize> Default for RxBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> RxBuffer<N> {
+    /// Creates a new [RxBuffer] that can be used to receive datagrams into.
+    pub const fn new() -> Self {
+        Self {
+            cfg: [0; 2 * SOCK_ADDR_LEN],
+            payload: [0; N],
+        }
+    }
+}
+
+/// Receives the datagrams addressed to a [`UdpSocket`] into an [`RxBuffer`],
+/// returned by [`UdpSocket::receiver`].
+///
+/// The buffer is only shared with the kernel while
+/// [`receive_from`](Self::receive_from) waits, and the datagram is read once
+/// the kernel has given it back. Datagrams that arrive while the process
+/// examines the previous one are dropped.
+pub struct UdpReceiver<'buf, const N: usize, S: Syscalls, C: Config = DefaultConfig> {
+    buf: &'buf mut RxBuffer<N>,
+    s: PhantomData<S>,
+    c: PhantomData<C>,
+}
+
+impl<const N: usize, S: Syscalls, C: Config> UdpReceiver<'_, N, S, C> {
+    /// Waits for a datagram, and returns its payload and the sender's
+    /// address. If the payload does not fit in the buffer, it is truncated.
+    pub fn receive_from(&mut self) -> Result<(&[u8], SocketAddrV6), ErrorCode> {
+        let buf = &mut *self.buf;
+        let (len, _, _) = share::scope::<
+            (
+                AllowRw<_, DRIVER_NUM, { allow_rw::READ }>,
+                AllowRw<_, DRIVER_NUM, { allow_rw::RX_CFG }>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_read, allow_cfg) = handle.split();
+            S::allow_rw::<C, DRIVER_NUM, { allow_rw::READ }>(allow_read, &mut buf.payload)?;
+            S::allow_rw::<C, DRIVER_NUM, { allow_rw::RX_CFG }>(allow_cfg, &mut buf.cfg)?;
+            Ok::<_, ErrorCode>(S::yield_wait_for(DRIVER_NUM, subscribe::PACKET_RECEIVED))
+        })?;
+        let len = (len as usize).min(N);
+        Ok((
+            &self.buf.payload[..len],
+            decode_sock_addr(&self.buf.cfg[..SOCK_ADDR_LEN]),
+        ))
+    }
+}
+
+// The kernel represents an address and port as the 16-byte IPv6 address
+// followed by the port in the host's byte order.
+fn encode_sock_addr(buf: &mut [u8], addr: SocketAddrV6) {
+    buf[..IPV6_ADDR_LEN].copy_from_slice(&addr.ip().octets());
+    buf[IPV6_ADDR_LEN..SOCK_ADDR_LEN].copy_from_slice(&addr.port().to_ne_bytes());
+}
+
+fn decode_sock_addr(buf: &[u8]) -> SocketAddrV6 {
+    let ip: [u8; IPV6_ADDR_LEN] = buf[..IPV6_ADDR_LEN].try_into().unwrap();
+    let port = u16::from_ne_bytes([buf[IPV6_ADDR_LEN], buf[IPV6_ADDR_LEN + 1]]);
+    SocketAddrV6::new(ip.into(), port, 0, 0)
+}
+
+/// The largest number of interfaces [`Udp::interfaces`] returns.
+pub const MAX_INTERFACES: usize = 4;
+
+const IPV6_ADDR_LEN: usize = 16;
+const SOCK_ADDR_LEN: usize = IPV6_ADDR_LEN + 2;
+
+/// System call configuration trait for `Udp`.
+pub trait Config: platform::allow_ro::Config + platform::allow_rw::Config {}
+impl<T: platform::allow_ro::Config + platform::allow_rw::Config> Config for T {}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x30002;
+
+// Command IDs
+/// - `0`: Driver existence check.
+/// - `1`: Copy the addresses of the first `argument0` interfaces into the
+///   configuration buffer, and return the number of interfaces.
+/// - `2`: Send the payload in the write buffer. The configuration buffer holds
+///   the source address (which must be the bound address) followed by the
+///   destination address.
+/// - `3`: Bind to the address in the second half of the receive configuration
+///   buffer.
+/// - `4`: Return the maximum payload length.
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const INTERFACES: u32 = 1;
+    pub const SEND: u32 = 2;
+    pub const BIND: u32 = 3;
+    pub const MAX_PAYLOAD_LEN: u32 = 4;
+}
+
+mod subscribe {
+    /// Datagram is received. Argument 0 is its payload length.
+    pub const PACKET_RECEIVED: u32 = 0;
+    /// Datagram is transmitted. Argument 0 is the transmission status.
+    pub const PACKET_TRANSMITTED: u32 = 1;
+}
+
+/// Ids for read-only allow buffers
+mod allow_ro {
+    /// Write buffer. Contains the payload to be transmitted.
+    pub const WRITE: u32 = 0;
+}
+
+/// Ids for read-write allow buffers
+mod allow_rw {
+    /// Read buffer. Will contain the received payload.
+    pub const READ: u32 = 0;
+    /// Configuration buffer, used for sending and for the interface list.
+    pub const CFG: u32 = 1;
+    /// Receive configuration buffer, used for binding and receiving.
+    pub const RX_CFG: u32 = 2;
+}
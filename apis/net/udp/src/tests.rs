@@ -0,0 +1,113 @@
+use core::net::{Ipv6Addr, SocketAddrV6};
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake::{self, udp::Datagram};
+
+type Udp = super::Udp<fake::Syscalls>;
+
+const LOCAL_IP: Ipv6Addr = Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1);
+const LOCAL: SocketAddrV6 = SocketAddrV6::new(LOCAL_IP, 1000, 0, 0);
+const PEER: SocketAddrV6 =
+    SocketAddrV6::new(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 2), 2000, 0, 0);
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert!(!Udp::exists());
+}
+
+#[test]
+fn exists() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Udp::new();
+    kernel.add_driver(&driver);
+
+    assert!(Udp::exists());
+}
+
+#[test]
+fn interfaces() {
+    let kernel = fake::Kernel::new();
+    let second = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+    let driver = fake::Udp::new_with_interfaces(&[LOCAL_IP, second]);
+    kernel.add_driver(&driver);
+
+    let mut interfaces = [Ipv6Addr::UNSPECIFIED; 3];
+    assert_eq!(Udp::interfaces(&mut interfaces), Ok(2));
+    assert_eq!(interfaces, [LOCAL_IP, second, Ipv6Addr::UNSPECIFIED]);
+
+    let mut interfaces = [Ipv6Addr::UNSPECIFIED; 1];
+    assert_eq!(Udp::interfaces(&mut interfaces), Ok(2));
+    assert_eq!(interfaces, [LOCAL_IP]);
+}
+
+#[test]
+fn max_payload_len() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Udp::new();
+    kernel.add_driver(&driver);
+
+    driver.set_max_payload_len(64);
+    assert_eq!(Udp::max_payload_len(), Ok(64));
+}
+
+#[test]
+fn bind() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Udp::new();
+    kernel.add_driver(&driver);
+
+    let other = SocketAddrV6::new(Ipv6Addr::LOCALHOST, 1000, 0, 0);
+    assert_eq!(Udp::bind(other).err(), Some(ErrorCode::Invalid));
+    assert_eq!(driver.bound(), None);
+
+    let socket = Udp::bind(LOCAL).unwrap();
+    assert_eq!(socket.local_addr(), LOCAL);
+    assert_eq!(driver.bound(), Some(LOCAL));
+}
+
+#[test]
+fn send_to() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Udp::new();
+    kernel.add_driver(&driver);
+    let socket = Udp::bind(LOCAL).unwrap();
+
+    assert_eq!(socket.send_to(b"hello", PEER), Ok(()));
+    assert_eq!(
+        driver.take_sent(),
+        [Datagram {
+            src: LOCAL,
+            dest: PEER,
+            payload: b"hello".to_vec(),
+        }]
+    );
+
+    driver.set_max_payload_len(4);
+    assert_eq!(socket.send_to(b"hello", PEER), Err(ErrorCode::Size));
+    assert_eq!(driver.take_sent(), []);
+}
+
+#[test]
+fn receive_from() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Udp::new();
+    kernel.add_driver(&driver);
+    let socket = Udp::bind(LOCAL).unwrap();
+
+    driver.receive_datagram(Datagram {
+        src: PEER,
+        dest: LOCAL,
+        payload: b"hello".to_vec(),
+    });
+    driver.receive_datagram(Datagram {
+        src: PEER,
+        dest: LOCAL,
+        payload: b"truncated".to_vec(),
+    });
+
+    let mut buf = super::RxBuffer::<8>::new();
+    let mut receiver = socket.receiver(&mut buf);
+    assert_eq!(receiver.receive_from(), Ok((&b"hello"[..], PEER)));
+    assert_eq!(receiver.receive_from(), Ok((&b"truncate"[..], PEER)));
+    assert!(!driver.has_pending_datagrams());
+}
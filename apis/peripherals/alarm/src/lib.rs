@@ -1,9 +1,9 @@
 #![no_std]
 
-use core::cell::Cell;
 use libtock_platform as platform;
 use libtock_platform::share;
-use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+use libtock_platform::subscribe::{OneId, Subscribe};
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls, Upcall};
 
 /// The alarm driver
 ///
@@ -84,21 +84,50 @@ impl<S: Syscalls, C: platform::subscribe::Config> Alarm<S, C> {
         let freq = Self::get_frequency()?;
         let ticks = time.to_ticks(freq);
 
-        let called: Cell<Option<(u32, u32)>> = Cell::new(None);
-        share::scope(|subscribe| {
-            S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::CALLBACK }>(subscribe, &called)?;
+        S::command(DRIVER_NUM, command::SET_RELATIVE, ticks.0, 0)
+            .to_result()
+            .map(|_when: u32| ())?;
 
-            S::command(DRIVER_NUM, command::SET_RELATIVE, ticks.0, 0)
-                .to_result()
-                .map(|_when: u32| ())?;
+        S::yield_wait_for(DRIVER_NUM, subscribe::CALLBACK);
+        Ok(())
+    }
 
-            loop {
-                S::yield_wait();
-                if let Some((_when, _ref)) = called.get() {
-                    return Ok(());
-                }
-            }
-        })
+    /// Sets an alarm that expires after `time`, without waiting for it. The
+    /// registered listener is called when it expires. Replaces any alarm that
+    /// was already set.
+    pub fn set_alarm<T: Convert>(time: T) -> Result<(), ErrorCode> {
+        let freq = Self::get_frequency()?;
+        let ticks = time.to_ticks(freq);
+
+        S::command(DRIVER_NUM, command::SET_RELATIVE, ticks.0, 0)
+            .to_result()
+            .map(|_when: u32| ())
+    }
+
+    /// Cancels the alarm set by [`Alarm::set_alarm`].
+    pub fn stop() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::STOP, 0, 0).to_result()
+    }
+
+    pub fn register_listener<'share, F: Fn(u32)>(
+        listener: &'share AlarmListener<F>,
+        subscribe: share::Handle<Subscribe<'share, S, DRIVER_NUM, { subscribe::CALLBACK }>>,
+    ) -> Result<(), ErrorCode> {
+        S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::CALLBACK }>(subscribe, listener)
+    }
+
+    pub fn unregister_listener() {
+        S::unsubscribe(DRIVER_NUM, subscribe::CALLBACK)
+    }
+}
+
+/// A listener for alarms set by [`Alarm::set_alarm`], called with the tick
+/// count at which the alarm expired.
+pub struct AlarmListener<F: Fn(u32)>(pub F);
+
+impl<F: Fn(u32)> Upcall<OneId<DRIVER_NUM, { subscribe::CALLBACK }>> for AlarmListener<F> {
+    fn upcall(&self, now: u32, _arg1: u32, _arg2: u32) {
+        self.0(now)
     }
 }
 
@@ -1,6 +1,8 @@
+use core::cell::Cell;
+use libtock_platform::{share, Syscalls};
 use libtock_unittest::fake;
 
-use crate::{Hz, Milliseconds, Ticks};
+use crate::{AlarmListener, Hz, Milliseconds, Ticks};
 
 type Alarm = crate::Alarm<fake::Syscalls>;
 
@@ -22,3 +24,20 @@ fn sleep() {
     assert_eq!(Alarm::sleep_for(Ticks(1000)), Ok(()));
     assert_eq!(Alarm::sleep_for(Milliseconds(1000)), Ok(()));
 }
+
+#[test]
+fn listener() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Alarm::new(1000);
+    kernel.add_driver(&driver);
+
+    let expired = Cell::new(None);
+    let listener = AlarmListener(|now| expired.set(Some(now)));
+    share::scope(|subscribe| {
+        assert_eq!(Alarm::register_listener(&listener, subscribe), Ok(()));
+        assert_eq!(Alarm::set_alarm(Milliseconds(20)), Ok(()));
+        fake::Syscalls::yield_wait();
+        assert_eq!(expired.get(), Some(20));
+        assert_eq!(Alarm::stop(), Ok(()));
+    });
+}
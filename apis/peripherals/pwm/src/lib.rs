@@ -0,0 +1,158 @@
+#![no_std]
+
+use core::marker::PhantomData;
+
+use libtock_platform::{ErrorCode, Syscalls};
+
+/// The PWM driver.
+///
+/// Duty cycles are given as a fraction of [`MAX_DUTY_CYCLE`], so 5000 is a 50%
+/// duty cycle.
+///
+/// # Example
+/// ```ignore
+/// use libtock::pwm::{Pwm, MAX_DUTY_CYCLE};
+///
+/// // Dim the LED on PWM pin 0 to 25% brightness.
+/// Pwm::start(0, 1000, MAX_DUTY_CYCLE / 4)?;
+///
+/// // Or, using a pin handle that remembers the frequency:
+/// let mut pin = Pwm::get_pin(0, 1000)?;
+/// pin.set_duty_cycle(MAX_DUTY_CYCLE / 4)?;
+/// ```
+pub struct Pwm<S: Syscalls>(S);
+
+/// The duty cycle that keeps the output high all the time.
+pub const MAX_DUTY_CYCLE: u16 = 10000;
+
+impl<S: Syscalls> Pwm<S> {
+    /// Returns Ok() if the driver was present. This does not necessarily mean
+    /// that the driver is working.
+    pub fn exists() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, EXISTS, 0, 0).to_result()
+    }
+
+    /// Returns the number of PWM pins.
+    pub fn count() -> Result<u32, ErrorCode> {
+        S::command(DRIVER_NUM, PWM_COUNT, 0, 0).to_result()
+    }
+
+    /// Returns the highest frequency, in Hz, that `pin` can output.
+    pub fn max_frequency(pin: u32) -> Result<u32, ErrorCode> {
+        S::command(DRIVER_NUM, PWM_MAX_FREQUENCY, pin, 0).to_result()
+    }
+
+    /// Starts generating a signal on `pin`, or changes the signal if `pin`
+    /// is already running. `duty_cycle` must not exceed [`MAX_DUTY_CYCLE`].
+    pub fn start(pin: u32, frequency_hz: u32, duty_cycle: u16) -> Result<(), ErrorCode> {
+        if pin > u16::MAX as u32 || duty_cycle > MAX_DUTY_CYCLE {
+            return Err(ErrorCode::Invalid);
+        }
+        S::command(
+            DRIVER_NUM,
+            PWM_START,
+            (duty_cycle as u32) << 16 | pin,
+            frequency_hz,
+        )
+        .to_result()
+    }
+
+    /// Stops generating a signal on `pin`.
+    pub fn stop(pin: u32) -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, PWM_STOP, pin, 0).to_result()
+    }
+
+    /// Returns a handle to `pin` that outputs at `frequency_hz`. The pin does
+    /// not start until a duty cycle is set, and stops when the handle is
+    /// dropped.
+    pub fn get_pin(pin: u32, frequency_hz: u32) -> Result<PwmPin<S>, ErrorCode> {
+        if frequency_hz > Self::max_frequency(pin)? {
+            return Err(ErrorCode::Invalid);
+        }
+        Ok(PwmPin {
+            pin_number: pin,
+            frequency_hz,
+            duty_cycle: 0,
+            _syscalls: PhantomData,
+        })
+    }
+}
+
+pub struct PwmPin<S: Syscalls> {
+    pin_number: u32,
+    frequency_hz: u32,
+    duty_cycle: u16,
+    _syscalls: PhantomData<S>,
+}
+
+impl<S: Syscalls> PwmPin<S> {
+    pub fn pin_number(&self) -> u32 {
+        self.pin_number
+    }
+
+    pub fn frequency(&self) -> u32 {
+        self.frequency_hz
+    }
+
+    /// Returns the last duty cycle set on this pin.
+    pub fn duty_cycle(&self) -> u16 {
+        self.duty_cycle
+    }
+
+    /// Starts the pin with the given duty cycle, or changes the duty cycle if
+    /// it is already running.
+    pub fn set_duty_cycle(&mut self, duty_cycle: u16) -> Result<(), ErrorCode> {
+        Pwm::<S>::start(self.pin_number, self.frequency_hz, duty_cycle)?;
+        self.duty_cycle = duty_cycle;
+        Ok(())
+    }
+
+    /// Changes the frequency, keeping the current duty cycle.
+    pub fn set_frequency(&mut self, frequency_hz: u32) -> Result<(), ErrorCode> {
+        Pwm::<S>::start(self.pin_number, frequency_hz, self.duty_cycle)?;
+        self.frequency_hz = frequency_hz;
+        Ok(())
+    }
+
+    pub fn stop(&mut self) -> Result<(), ErrorCode> {
+        Pwm::<S>::stop(self.pin_number)
+    }
+}
+
+impl<S: Syscalls> Drop for PwmPin<S> {
+    fn drop(&mut self) {
+        let _ = Pwm::<S>::stop(self.pin_number);
+    }
+}
+
+#[cfg(feature = "rust_embedded")]
+impl<S: Syscalls> embedded_hal::pwm::ErrorType for PwmPin<S> {
+    type Error = ErrorCode;
+}
+
+#[cfg(feature = "rust_embedded")]
+impl<S: Syscalls> embedded_hal::pwm::SetDutyCycle for PwmPin<S> {
+    fn max_duty_cycle(&self) -> u16 {
+        MAX_DUTY_CYCLE
+    }
+
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+        PwmPin::set_duty_cycle(self, duty)
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x10;
+
+// Command IDs
+const EXISTS: u32 = 0;
+const PWM_START: u32 = 1;
+const PWM_STOP: u32 = 2;
+const PWM_MAX_FREQUENCY: u32 = 3;
+const PWM_COUNT: u32 = 4;
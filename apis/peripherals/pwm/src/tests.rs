@@ -0,0 +1,106 @@
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+use crate::MAX_DUTY_CYCLE;
+
+type Pwm = super::Pwm<fake::Syscalls>;
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert_eq!(Pwm::exists(), Err(ErrorCode::NoDevice));
+}
+
+#[test]
+fn exists() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Pwm::<3>::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(Pwm::exists(), Ok(()));
+    assert_eq!(Pwm::count(), Ok(3));
+}
+
+#[test]
+fn max_frequency() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Pwm::<3>::new();
+    kernel.add_driver(&driver);
+
+    driver.set_max_frequency(20_000);
+    assert_eq!(Pwm::max_frequency(0), Ok(20_000));
+    assert_eq!(Pwm::max_frequency(3), Err(ErrorCode::Invalid));
+}
+
+#[test]
+fn start_stop() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Pwm::<3>::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(Pwm::start(2, 1000, MAX_DUTY_CYCLE / 4), Ok(()));
+    assert_eq!(
+        driver.output(2),
+        Some(fake::PwmOutput {
+            frequency_hz: 1000,
+            duty_cycle: 2500
+        })
+    );
+    assert_eq!(Pwm::stop(2), Ok(()));
+    assert_eq!(driver.output(2), None);
+
+    assert_eq!(
+        Pwm::start(0, 1000, MAX_DUTY_CYCLE + 1),
+        Err(ErrorCode::Invalid)
+    );
+    assert_eq!(Pwm::start(3, 1000, 0), Err(ErrorCode::Invalid));
+    assert_eq!(Pwm::start(0x1_0000, 1000, 0), Err(ErrorCode::Invalid));
+    assert_eq!(driver.output(0), None);
+}
+
+#[test]
+fn pin() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Pwm::<3>::new();
+    kernel.add_driver(&driver);
+    driver.set_max_frequency(20_000);
+
+    assert!(Pwm::get_pin(1, 50_000).is_err());
+    let mut pin = Pwm::get_pin(1, 10_000).unwrap();
+    assert_eq!(pin.pin_number(), 1);
+    // Getting a pin does not start it.
+    assert_eq!(driver.output(1), None);
+
+    assert_eq!(pin.set_duty_cycle(7500), Ok(()));
+    assert_eq!(pin.duty_cycle(), 7500);
+    assert_eq!(pin.set_frequency(15_000), Ok(()));
+    assert_eq!(
+        driver.output(1),
+        Some(fake::PwmOutput {
+            frequency_hz: 15_000,
+            duty_cycle: 7500
+        })
+    );
+    // Failed changes leave the pin's settings alone.
+    assert_eq!(pin.set_frequency(30_000), Err(ErrorCode::Invalid));
+    assert_eq!(pin.frequency(), 15_000);
+
+    drop(pin);
+    assert_eq!(driver.output(1), None);
+}
+
+#[cfg(feature = "rust_embedded")]
+#[test]
+fn set_duty_cycle() {
+    use embedded_hal::pwm::SetDutyCycle;
+    let kernel = fake::Kernel::new();
+    let driver = fake::Pwm::<1>::new();
+    kernel.add_driver(&driver);
+
+    let mut pin = Pwm::get_pin(0, 1000).unwrap();
+    assert_eq!(pin.max_duty_cycle(), MAX_DUTY_CYCLE);
+    pin.set_duty_cycle_percent(40).unwrap();
+    assert_eq!(driver.output(0).unwrap().duty_cycle, 4000);
+    pin.set_duty_cycle_fully_off().unwrap();
+    assert_eq!(driver.output(0).unwrap().duty_cycle, 0);
+}
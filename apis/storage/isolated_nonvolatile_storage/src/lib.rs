@@ -0,0 +1,170 @@
+#![no_std]
+
+use core::marker::PhantomData;
+use libtock_platform as platform;
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::share;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+/// The isolated nonvolatile storage driver.
+///
+/// It gives each process its own region of persistent storage, which other
+/// processes cannot access. The region is byte-addressable: the kernel takes
+/// care of erasing flash pages as needed, so any range can be overwritten
+/// directly.
+///
+/// # Example
+/// ```ignore
+/// use libtock::isolated_nonvolatile_storage::IsolatedNonvolatileStorage;
+///
+/// let mut calibration = [0; 16];
+/// IsolatedNonvolatileStorage::read(0, &mut calibration)?;
+/// calibration[0] += 1;
+/// IsolatedNonvolatileStorage::write(0, &calibration)?;
+/// ```
+pub struct IsolatedNonvolatileStorage<S: Syscalls, C: Config = DefaultConfig>(S, C);
+
+impl<S: Syscalls, C: Config> IsolatedNonvolatileStorage<S, C> {
+    /// Run a check against the isolated nonvolatile storage capsule to ensure
+    /// it is present.
+    #[inline(always)]
+    pub fn exists() -> bool {
+        S::command(DRIVER_NUM, command::DRIVER_CHECK, 0, 0).is_success()
+    }
+
+    /// Returns the size of the process' storage region, in bytes.
+    pub fn size() -> Result<u64, ErrorCode> {
+        S::command(DRIVER_NUM, command::GET_SIZE, 0, 0).to_result::<(), ErrorCode>()?;
+        match S::yield_wait_for(DRIVER_NUM, subscribe::GET_SIZE_DONE) {
+            (0, low, high) => Ok((high as u64) << 32 | low as u64),
+            (status, _, _) => Err(status.try_into().unwrap_or(ErrorCode::Fail)),
+        }
+    }
+
+    /// Fills `buf` with the bytes stored starting at `offset`.
+    pub fn read(offset: u64, buf: &mut [u8]) -> Result<(), ErrorCode> {
+        share::scope::<AllowRw<_, DRIVER_NUM, { allow_rw::READ }>, _, _>(|allow| {
+            S::allow_rw::<C, DRIVER_NUM, { allow_rw::READ }>(allow, buf)?;
+
+            S::command(
+                DRIVER_NUM,
+                command::READ,
+                offset as u32,
+                (offset >> 32) as u32,
+            )
+            .to_result::<(), ErrorCode>()?;
+
+            S::yield_wait_for_status(DRIVER_NUM, subscribe::READ_DONE)
+        })
+    }
+
+    /// Stores `buf` starting at `offset`.
+    pub fn write(offset: u64, buf: &[u8]) -> Result<(), ErrorCode> {
+        share::scope::<AllowRo<_, DRIVER_NUM, { allow_ro::WRITE }>, _, _>(|allow| {
+            S::allow_ro::<C, DRIVER_NUM, { allow_ro::WRITE }>(allow, buf)?;
+
+            S::command(
+                DRIVER_NUM,
+                command::WRITE,
+                offset as u32,
+                (offset >> 32) as u32,
+            )
+            .to_result::<(), ErrorCode>()?;
+
+            S::yield_wait_for_status(DRIVER_NUM, subscribe::WRITE_DONE)
+        })
+    }
+
+    /// Returns a handle to the storage region, which implements the
+    /// `embedded_storage` traits when the `rust_embedded` feature is enabled.
+    pub fn region() -> Result<StorageRegion<S, C>, ErrorCode> {
+        Ok(StorageRegion {
+            capacity: Self::size()?,
+            _syscalls: PhantomData,
+        })
+    }
+}
+
+/// A handle to the process' storage region, returned by
+/// [`IsolatedNonvolatileStorage::region`].
+pub struct StorageRegion<S: Syscalls, C: Config = DefaultConfig> {
+    capacity: u64,
+    _syscalls: PhantomData<(S, C)>,
+}
+
+impl<S: Syscalls, C: Config> StorageRegion<S, C> {
+    /// The size of the region, in bytes.
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    pub fn read(&self, offset: u64, buf: &mut [u8]) -> Result<(), ErrorCode> {
+        IsolatedNonvolatileStorage::<S, C>::read(offset, buf)
+    }
+
+    pub fn write(&mut self, offset: u64, buf: &[u8]) -> Result<(), ErrorCode> {
+        IsolatedNonvolatileStorage::<S, C>::write(offset, buf)
+    }
+}
+
+#[cfg(feature = "rust_embedded")]
+impl<S: Syscalls, C: Config> embedded_storage::ReadStorage for StorageRegion<S, C> {
+    type Error = ErrorCode;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        StorageRegion::read(self, offset as u64, bytes)
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity as usize
+    }
+}
+
+#[cfg(feature = "rust_embedded")]
+impl<S: Syscalls, C: Config> embedded_storage::Storage for StorageRegion<S, C> {
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+        StorageRegion::write(self, offset as u64, bytes)
+    }
+}
+
+/// System call configuration trait for `IsolatedNonvolatileStorage`.
+pub trait Config:
+    platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config
+{
+}
+impl<T: platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config>
+    Config for T
+{
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x50004;
+
+// Command IDs
+mod command {
+    pub const DRIVER_CHECK: u32 = 0;
+    pub const GET_SIZE: u32 = 1;
+    pub const READ: u32 = 2;
+    pub const WRITE: u32 = 3;
+}
+
+mod subscribe {
+    pub const GET_SIZE_DONE: u32 = 0;
+    pub const READ_DONE: u32 = 1;
+    pub const WRITE_DONE: u32 = 2;
+}
+
+mod allow_ro {
+    pub const WRITE: u32 = 0;
+}
+
+mod allow_rw {
+    pub const READ: u32 = 0;
+}
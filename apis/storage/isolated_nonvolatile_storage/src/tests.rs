@@ -0,0 +1,106 @@
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+type IsolatedNonvolatileStorage = super::IsolatedNonvolatileStorage<fake::Syscalls>;
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert!(!IsolatedNonvolatileStorage::exists());
+}
+
+#[test]
+fn exists() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::IsolatedNonvolatileStorage::new(2048);
+    kernel.add_driver(&driver);
+
+    assert!(IsolatedNonvolatileStorage::exists());
+    assert_eq!(IsolatedNonvolatileStorage::size(), Ok(2048));
+}
+
+#[test]
+fn read_write() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::IsolatedNonvolatileStorage::new(2048);
+    kernel.add_driver(&driver);
+
+    let mut buf = [0; 4];
+    assert_eq!(IsolatedNonvolatileStorage::read(100, &mut buf), Ok(()));
+    assert_eq!(buf, [0xFF; 4]);
+
+    assert_eq!(
+        IsolatedNonvolatileStorage::write(100, &[1, 2, 3, 4]),
+        Ok(())
+    );
+    // Overwriting needs an erase, which the driver handles.
+    assert_eq!(
+        IsolatedNonvolatileStorage::write(102, &[0xAA, 0xBB]),
+        Ok(())
+    );
+    assert_eq!(IsolatedNonvolatileStorage::read(100, &mut buf), Ok(()));
+    assert_eq!(buf, [1, 2, 0xAA, 0xBB]);
+    assert_eq!(driver.contents()[100..104], [1, 2, 0xAA, 0xBB]);
+
+    assert_eq!(
+        IsolatedNonvolatileStorage::read(2046, &mut buf),
+        Err(ErrorCode::Invalid)
+    );
+    assert_eq!(
+        IsolatedNonvolatileStorage::write(1 << 32, &buf),
+        Err(ErrorCode::Invalid)
+    );
+}
+
+#[test]
+fn worn_out() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::IsolatedNonvolatileStorage::new_with_page_size(1024, 256);
+    kernel.add_driver(&driver);
+    driver.set_max_erase_cycles(2);
+
+    // The first write only clears bits, the others each need an erase.
+    for value in [1, 2, 4] {
+        assert_eq!(IsolatedNonvolatileStorage::write(0, &[value]), Ok(()));
+    }
+    assert_eq!(driver.erase_count(0), 2);
+    assert_eq!(
+        IsolatedNonvolatileStorage::write(0, &[3]),
+        Err(ErrorCode::Fail)
+    );
+    assert_eq!(IsolatedNonvolatileStorage::write(256, &[3]), Ok(()));
+}
+
+#[test]
+fn region() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::IsolatedNonvolatileStorage::new(1024);
+    kernel.add_driver(&driver);
+
+    let mut region = IsolatedNonvolatileStorage::region().unwrap();
+    assert_eq!(region.capacity(), 1024);
+    assert_eq!(region.write(10, b"calibration"), Ok(()));
+    let mut buf = [0; 11];
+    assert_eq!(region.read(10, &mut buf), Ok(()));
+    assert_eq!(&buf, b"calibration");
+}
+
+#[cfg(feature = "rust_embedded")]
+#[test]
+fn embedded_storage() {
+    use embedded_storage::{ReadStorage, Storage};
+    let kernel = fake::Kernel::new();
+    let driver = fake::IsolatedNonvolatileStorage::new(1024);
+    kernel.add_driver(&driver);
+
+    let mut region = IsolatedNonvolatileStorage::region().unwrap();
+    assert_eq!(ReadStorage::capacity(&region), 1024);
+    Storage::write(&mut region, 1000, b"log entry").unwrap();
+    let mut buf = [0; 9];
+    ReadStorage::read(&mut region, 1000, &mut buf).unwrap();
+    assert_eq!(&buf, b"log entry");
+    assert_eq!(
+        Storage::write(&mut region, 1020, b"log entry"),
+        Err(ErrorCode::Invalid)
+    );
+}
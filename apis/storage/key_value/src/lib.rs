@@ -1,11 +1,9 @@
 #![no_std]
 
-use core::cell::Cell;
 use libtock_platform as platform;
 use libtock_platform::allow_ro::AllowRo;
 use libtock_platform::allow_rw::AllowRw;
 use libtock_platform::share;
-use libtock_platform::subscribe::Subscribe;
 use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
 
 /// The key-value driver.
@@ -22,61 +20,47 @@ impl<S: Syscalls, C: Config> KeyValue<S, C> {
 
     /// Get a key-value object from the `key`.
     pub fn get(key: &[u8], value: &mut [u8]) -> Result<u32, ErrorCode> {
-        let called: Cell<Option<Result<(u32,), ErrorCode>>> = Cell::new(None);
         share::scope::<
             (
                 AllowRo<_, DRIVER_NUM, { allow_ro::KEY }>,
                 AllowRw<_, DRIVER_NUM, { allow_rw::VALUE_READ }>,
-                Subscribe<_, DRIVER_NUM, { subscribe::CALLBACK }>,
             ),
             _,
             _,
         >(|handle| {
-            let (allow_key, allow_value, subscribe) = handle.split();
+            let (allow_key, allow_value) = handle.split();
 
             S::allow_ro::<C, DRIVER_NUM, { allow_ro::KEY }>(allow_key, key)?;
             S::allow_rw::<C, DRIVER_NUM, { allow_rw::VALUE_READ }>(allow_value, value)?;
 
-            S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::CALLBACK }>(subscribe, &called)?;
-
             S::command(DRIVER_NUM, command::GET, 0, 0).to_result::<(), ErrorCode>()?;
 
-            loop {
-                S::yield_wait();
-                if let Some(ret) = called.get() {
-                    return ret.map(|(arg0,)| arg0);
-                }
+            let (status, len, _) = S::yield_wait_for(DRIVER_NUM, subscribe::CALLBACK);
+            match status {
+                0 => Ok(len),
+                _ => Err(status.try_into().unwrap_or(ErrorCode::Fail)),
             }
         })
     }
 
     /// Set a key-value object for the `key`.
     fn insert(command_num: u32, key: &[u8], value: &[u8]) -> Result<(), ErrorCode> {
-        let called: Cell<Option<Result<(), ErrorCode>>> = Cell::new(None);
         share::scope::<
             (
                 AllowRo<_, DRIVER_NUM, { allow_ro::KEY }>,
                 AllowRo<_, DRIVER_NUM, { allow_ro::VALUE_WRITE }>,
-                Subscribe<_, DRIVER_NUM, { subscribe::CALLBACK }>,
             ),
             _,
             _,
         >(|handle| {
-            let (allow_key, allow_value, subscribe) = handle.split();
+            let (allow_key, allow_value) = handle.split();
 
             S::allow_ro::<C, DRIVER_NUM, { allow_ro::KEY }>(allow_key, key)?;
             S::allow_ro::<C, DRIVER_NUM, { allow_ro::VALUE_WRITE }>(allow_value, value)?;
 
-            S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::CALLBACK }>(subscribe, &called)?;
-
             S::command(DRIVER_NUM, command_num, 0, 0).to_result::<(), ErrorCode>()?;
 
-            loop {
-                S::yield_wait();
-                if let Some(ret) = called.get() {
-                    return ret;
-                }
-            }
+            S::yield_wait_for_status(DRIVER_NUM, subscribe::CALLBACK)
         })
     }
 
@@ -97,29 +81,12 @@ impl<S: Syscalls, C: Config> KeyValue<S, C> {
 
     /// Delete a key-value object by `key`.
     pub fn delete(key: &[u8]) -> Result<(), ErrorCode> {
-        let called: Cell<Option<Result<(), ErrorCode>>> = Cell::new(None);
-        share::scope::<
-            (
-                AllowRo<_, DRIVER_NUM, { allow_ro::KEY }>,
-                Subscribe<_, DRIVER_NUM, { subscribe::CALLBACK }>,
-            ),
-            _,
-            _,
-        >(|handle| {
-            let (allow_key, subscribe) = handle.split();
-
+        share::scope::<AllowRo<_, DRIVER_NUM, { allow_ro::KEY }>, _, _>(|allow_key| {
             S::allow_ro::<C, DRIVER_NUM, { allow_ro::KEY }>(allow_key, key)?;
 
-            S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::CALLBACK }>(subscribe, &called)?;
-
             S::command(DRIVER_NUM, command::DELETE, 0, 0).to_result::<(), ErrorCode>()?;
 
-            loop {
-                S::yield_wait();
-                if let Some(ret) = called.get() {
-                    return ret;
-                }
-            }
+            S::yield_wait_for_status(DRIVER_NUM, subscribe::CALLBACK)
         })
     }
 }
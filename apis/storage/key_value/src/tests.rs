@@ -15,11 +15,6 @@ fn _get(kernel: &fake::Kernel) -> Result<u32, ErrorCode> {
         buffer_num: allow_rw::VALUE_READ,
         return_error: None,
     });
-    kernel.add_expected_syscall(ExpectedSyscall::Subscribe {
-        driver_num: DRIVER_NUM,
-        subscribe_num: subscribe::CALLBACK,
-        skip_with_error: None,
-    });
     kernel.add_expected_syscall(ExpectedSyscall::Command {
         driver_id: DRIVER_NUM,
         command_id: command::GET,
@@ -27,6 +22,11 @@ fn _get(kernel: &fake::Kernel) -> Result<u32, ErrorCode> {
         argument1: 0,
         override_return: Some(command_return::success()),
     });
+    kernel.add_expected_syscall(ExpectedSyscall::YieldWaitFor {
+        driver_num: DRIVER_NUM,
+        subscribe_num: subscribe::CALLBACK,
+        override_return: None,
+    });
 
     let mut buf = [0; 3];
     Kv::get("mykey".as_bytes(), &mut buf)
@@ -43,11 +43,6 @@ fn _set(kernel: &fake::Kernel) -> Result<(), ErrorCode> {
         buffer_num: allow_ro::VALUE_WRITE,
         return_error: None,
     });
-    kernel.add_expected_syscall(ExpectedSyscall::Subscribe {
-        driver_num: DRIVER_NUM,
-        subscribe_num: subscribe::CALLBACK,
-        skip_with_error: None,
-    });
     kernel.add_expected_syscall(ExpectedSyscall::Command {
         driver_id: DRIVER_NUM,
         command_id: command::SET,
@@ -55,6 +50,11 @@ fn _set(kernel: &fake::Kernel) -> Result<(), ErrorCode> {
         argument1: 0,
         override_return: Some(command_return::success()),
     });
+    kernel.add_expected_syscall(ExpectedSyscall::YieldWaitFor {
+        driver_num: DRIVER_NUM,
+        subscribe_num: subscribe::CALLBACK,
+        override_return: None,
+    });
 
     Kv::set("mykey".as_bytes(), b"hooray")
 }
@@ -70,11 +70,6 @@ fn _add(kernel: &fake::Kernel) -> Result<(), ErrorCode> {
         buffer_num: allow_ro::VALUE_WRITE,
         return_error: None,
     });
-    kernel.add_expected_syscall(ExpectedSyscall::Subscribe {
-        driver_num: DRIVER_NUM,
-        subscribe_num: subscribe::CALLBACK,
-        skip_with_error: None,
-    });
     kernel.add_expected_syscall(ExpectedSyscall::Command {
         driver_id: DRIVER_NUM,
         command_id: command::ADD,
@@ -82,6 +77,11 @@ fn _add(kernel: &fake::Kernel) -> Result<(), ErrorCode> {
         argument1: 0,
         override_return: Some(command_return::success()),
     });
+    kernel.add_expected_syscall(ExpectedSyscall::YieldWaitFor {
+        driver_num: DRIVER_NUM,
+        subscribe_num: subscribe::CALLBACK,
+        override_return: None,
+    });
 
     Kv::add("mykey".as_bytes(), b"hooray2")
 }
@@ -97,11 +97,6 @@ fn _update(kernel: &fake::Kernel) -> Result<(), ErrorCode> {
         buffer_num: allow_ro::VALUE_WRITE,
         return_error: None,
     });
-    kernel.add_expected_syscall(ExpectedSyscall::Subscribe {
-        driver_num: DRIVER_NUM,
-        subscribe_num: subscribe::CALLBACK,
-        skip_with_error: None,
-    });
     kernel.add_expected_syscall(ExpectedSyscall::Command {
         driver_id: DRIVER_NUM,
         command_id: command::UPDATE,
@@ -109,6 +104,11 @@ fn _update(kernel: &fake::Kernel) -> Result<(), ErrorCode> {
         argument1: 0,
         override_return: Some(command_return::success()),
     });
+    kernel.add_expected_syscall(ExpectedSyscall::YieldWaitFor {
+        driver_num: DRIVER_NUM,
+        subscribe_num: subscribe::CALLBACK,
+        override_return: None,
+    });
 
     Kv::update("mykey".as_bytes(), b"hooray3")
 }
@@ -119,11 +119,6 @@ fn _delete(kernel: &fake::Kernel) -> Result<(), ErrorCode> {
         buffer_num: allow_ro::KEY,
         return_error: None,
     });
-    kernel.add_expected_syscall(ExpectedSyscall::Subscribe {
-        driver_num: DRIVER_NUM,
-        subscribe_num: subscribe::CALLBACK,
-        skip_with_error: None,
-    });
     kernel.add_expected_syscall(ExpectedSyscall::Command {
         driver_id: DRIVER_NUM,
         command_id: command::DELETE,
@@ -131,6 +126,11 @@ fn _delete(kernel: &fake::Kernel) -> Result<(), ErrorCode> {
         argument1: 0,
         override_return: Some(command_return::success()),
     });
+    kernel.add_expected_syscall(ExpectedSyscall::YieldWaitFor {
+        driver_num: DRIVER_NUM,
+        subscribe_num: subscribe::CALLBACK,
+        override_return: None,
+    });
 
     Kv::delete("mykey".as_bytes())
 }
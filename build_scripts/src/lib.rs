@@ -27,6 +27,51 @@ const PLATFORMS: &[(&str, &str, &str, &str, &str)] = &[
     ("nano33ble"          , "0x00050000", "704K"     , "0x20005000", "240K"   ),
 ];
 
+/// Flash and RAM locations to build process binaries for when packaging them
+/// into a single TAB for several locations, keyed by Tock architecture name.
+/// Tockloader installs whichever location fits on the board. Both `make tab`
+/// and the runner's `--multi-location` option use these locations.
+#[rustfmt::skip]
+const TAB_LOCATIONS: &[(&str, &str, &str, &str)] = &[
+    // Arch.     | Target                      | Flash start | RAM start
+    ("cortex-m0" , "thumbv6m-none-eabi"         , "0x10020000", "0x20004000"),
+    ("cortex-m0" , "thumbv6m-none-eabi"         , "0x10028000", "0x2000c000"),
+    ("cortex-m33", "thumbv8m.main-none-eabi"    , "0x10040000", "0x20020000"),
+    ("cortex-m33", "thumbv8m.main-none-eabi"    , "0x10060000", "0x20028000"),
+    ("cortex-m4" , "thumbv7em-none-eabi"        , "0x00030000", "0x20008000"),
+    ("cortex-m4" , "thumbv7em-none-eabi"        , "0x00038000", "0x20010000"),
+    ("cortex-m4" , "thumbv7em-none-eabi"        , "0x00040000", "0x10002000"),
+    ("cortex-m4" , "thumbv7em-none-eabi"        , "0x00048000", "0x1000a000"),
+    ("cortex-m4" , "thumbv7em-none-eabi"        , "0x00040000", "0x20008000"),
+    ("cortex-m4" , "thumbv7em-none-eabi"        , "0x00042000", "0x2000a000"),
+    ("cortex-m4" , "thumbv7em-none-eabi"        , "0x00048000", "0x20010000"),
+    ("cortex-m4" , "thumbv7em-none-eabi"        , "0x00080000", "0x20006000"),
+    ("cortex-m4" , "thumbv7em-none-eabi"        , "0x00088000", "0x2000e000"),
+    ("riscv32imc", "riscv32imc-unknown-none-elf", "0x403b0000", "0x3fca2000"),
+    ("riscv32imc", "riscv32imc-unknown-none-elf", "0x40440000", "0x3fcaa000"),
+];
+
+/// Returns the (flash start, RAM start) locations a process binary for the
+/// given Tock architecture (e.g. `cortex-m4`) should be built for when it is
+/// packaged into a TAB for several locations. The addresses can be passed to
+/// `auto_layout` through `LIBTOCK_LINKER_FLASH` and `LIBTOCK_LINKER_RAM`.
+pub fn tab_locations(
+    architecture: &str,
+) -> impl Iterator<Item = (&'static str, &'static str)> + '_ {
+    TAB_LOCATIONS
+        .iter()
+        .filter(move |&&(arch, _, _, _)| arch == architecture)
+        .map(|&(_, _, flash_start, ram_start)| (flash_start, ram_start))
+}
+
+/// Returns every location a process binary is built for when it is packaged
+/// into a TAB for several locations, as (Tock architecture, Rust target, flash
+/// start, RAM start) tuples.
+pub fn all_tab_locations(
+) -> impl Iterator<Item = (&'static str, &'static str, &'static str, &'static str)> {
+    TAB_LOCATIONS.iter().copied()
+}
+
 /// Helper function to configure cargo to use suitable linker scripts for
 /// linking libtock-rs apps.
 ///
@@ -53,6 +98,13 @@ const PLATFORMS: &[(&str, &str, &str, &str, &str)] = &[
 /// additionally pass `LIBTOCK_TBF_HEADER_SIZE`, `LIBTOCK_LINKER_FLASH_LENGTH`,
 /// and/or `LIBTOCK_LINKER_RAM_LENGTH`. If not specified, this function will
 /// assume some default values for those variables.
+///
+/// If the app is compiled with the `rwpi` relocation model (see
+/// `ram_relocatable`), the RAM start address is ignored: the app's RAM is
+/// linked at address 0 and `libtock_runtime` relocates it to wherever the
+/// kernel places the process' memory. The app is then linked with
+/// `--emit-relocs` so that the runner can check it has no pointers to RAM in
+/// its initialized data, which cannot be relocated.
 pub fn auto_layout() {
     use std::env::var;
     use std::fs::File;
@@ -115,6 +167,18 @@ pub fn auto_layout() {
             PLATFORM_VAR, LINKER_FLASH_VAR, LINKER_RAM_VAR
         ),
     };
+    let ram_start = if ram_relocatable() {
+        let arch = var("CARGO_CFG_TARGET_ARCH").expect("Unable to read CARGO_CFG_TARGET_ARCH");
+        assert!(
+            arch == "arm",
+            "The rwpi relocation model is only supported on ARM, not {}",
+            arch
+        );
+        println!("cargo:rustc-link-arg=--emit-relocs");
+        "0"
+    } else {
+        ram_start
+    };
     let tbf_header_size;
     let tbf_header_size = match get_env_var(TBF_HEADER_SIZE_VAR) {
         None => "0x80",
@@ -185,6 +249,27 @@ pub fn auto_layout() {
     println!("cargo:rustc-link-arg=-zmax-page-size=4096");
 }
 
+/// Returns whether the crate being built is compiled with the `rwpi` (or
+/// `ropi-rwpi`) relocation model, i.e. whether `-C relocation-model=rwpi` is
+/// in its rustflags. Code compiled this way addresses read-write data relative
+/// to the r9 register, so the process' RAM can be placed anywhere. Only ARM
+/// supports this relocation model.
+pub fn ram_relocatable() -> bool {
+    println!("cargo:rerun-if-env-changed=CARGO_ENCODED_RUSTFLAGS");
+    let rustflags = get_env_var("CARGO_ENCODED_RUSTFLAGS").unwrap_or_default();
+    // The flag may be passed as `-Crelocation-model=..` or as
+    // `-C relocation-model=..`, and the last occurrence wins.
+    let relocation_model = rustflags
+        .split('\x1f')
+        .filter_map(|flag| {
+            flag.strip_prefix("-C")
+                .unwrap_or(flag)
+                .strip_prefix("relocation-model=")
+        })
+        .next_back();
+    matches!(relocation_model, Some("rwpi" | "ropi-rwpi"))
+}
+
 // Retrieves an environment variable as a String. Returns None if the variable
 // is not specified and panics if the variable is not valid Unicode.
 fn get_env_var(name: &str) -> Option<String> {
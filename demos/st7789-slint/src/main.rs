@@ -11,12 +11,9 @@ use libtock::alarm::{Alarm, Milliseconds};
 use libtock::console::Console;
 use libtock::gpio::Gpio;
 use libtock::platform::ErrorCode;
-use libtock::runtime::{set_main, stack_size};
+use libtock::runtime::{heap_size, set_main, stack_size};
 use libtock::spi_controller::EmbeddedHalSpi;
 
-use critical_section::RawRestoreState;
-use embedded_alloc::Heap;
-
 use display_interface_spi::SPIInterface;
 use embedded_hal::digital::OutputPin;
 use mipidsi::{models::ST7789, options::ColorInversion, Builder, Display};
@@ -25,34 +22,7 @@ slint::include_modules!();
 
 set_main! {main}
 stack_size! {0x1400}
-
-#[global_allocator]
-static HEAP: Heap = Heap::empty();
-
-struct MyCriticalSection;
-critical_section::set_impl!(MyCriticalSection);
-
-unsafe impl critical_section::Impl for MyCriticalSection {
-    unsafe fn acquire() -> RawRestoreState {
-        // Tock is single threaded, so this can only be preempted by interrupts
-        // The kernel won't schedule anything from our app unless we yield
-        // so as long as we don't yield we won't concurrently run with
-        // other critical sections from our app.
-        // The kernel might schedule itself or other applications, but there
-        // is nothing we can do about that.
-    }
-
-    unsafe fn release(_token: RawRestoreState) {}
-}
-
-// Setup the heap and the global allocator.
-unsafe fn setup_heap() {
-    use core::mem::MaybeUninit;
-
-    const HEAP_SIZE: usize = 1024 * 6;
-    static mut HEAP_MEM: [MaybeUninit<u8>; HEAP_SIZE] = [MaybeUninit::uninit(); HEAP_SIZE];
-    unsafe { HEAP.init(&raw mut HEAP_MEM as usize, HEAP_SIZE) }
-}
+heap_size! {0x1800}
 
 // Display
 const W: i32 = 240;
@@ -70,10 +40,6 @@ impl embedded_hal::delay::DelayNs for Delay {
 fn main() {
     writeln!(Console::writer(), "st7789-slint: example\r").unwrap();
 
-    unsafe {
-        setup_heap();
-    }
-
     // Configure platform for Slint
     let window = slint::platform::software_renderer::MinimalSoftwareWindow::new(
         slint::platform::software_renderer::RepaintBufferType::ReusedBuffer,
@@ -0,0 +1,32 @@
+use crate::crc::{crc16_ccitt, crc32_p4, crc8, H2F, SAE_J1850};
+use crate::{Crc16, SoftwareCrc};
+
+const CHECK: &[u8] = b"123456789";
+
+// The check values of the CRC catalogue.
+#[test]
+fn check_values() {
+    assert_eq!(crc8(0xFF, SAE_J1850, CHECK) ^ 0xFF, 0x4B);
+    assert_eq!(crc8(0, SAE_J1850, CHECK), 0x37);
+    assert_eq!(crc8(0xFF, H2F, CHECK) ^ 0xFF, 0xDF);
+    assert_eq!(crc16_ccitt(0xFFFF, CHECK), 0x29B1);
+    assert_eq!(crc32_p4(0xFFFF_FFFF, CHECK) ^ 0xFFFF_FFFF, 0x1697_D06A);
+}
+
+#[test]
+fn parts() {
+    let data: [u8; 100] = core::array::from_fn(|i| (i * 37) as u8);
+    let (a, b) = data.split_at(33);
+    assert_eq!(
+        crc8(crc8(0, SAE_J1850, a), SAE_J1850, b),
+        ::crc::Crc::<u8>::new(&::crc::CRC_8_GSM_A).checksum(&data)
+    );
+    assert_eq!(
+        crc32_p4(crc32_p4(0xFFFF_FFFF, a), b) ^ 0xFFFF_FFFF,
+        ::crc::Crc::<u32>::new(&::crc::CRC_32_AUTOSAR).checksum(&data)
+    );
+    assert_eq!(
+        SoftwareCrc.crc16(&[a, &[], b]),
+        ::crc::Crc::<u16>::new(&::crc::CRC_16_IBM_3740).checksum(&data)
+    );
+}
@@ -0,0 +1,118 @@
+//! `libtock_e2e` implements the AUTOSAR end-to-end (E2E) protection profiles
+//! 1, 2, 4 and 5, which detect corrupted, lost, repeated and misdirected
+//! messages between a sender and a receiver, whatever the bus in between.
+//!
+//! The sender protects each message with a [`Protector`](profile4::Protector)
+//! before sending it, which writes a CRC and an alive counter into the message
+//! and folds a data ID, which identifies the message, into the CRC. The
+//! receiver passes each message it receives, or the lack of one, to a
+//! [`Checker`](profile4::Checker), which keeps track of the counter and
+//! reports a [`CheckStatus`].
+//!
+//! The profiles differ in the size of their CRC and counter, and so in the
+//! length of the messages they suit:
+//!
+//! | Profile | CRC             | Counter | Data ID                        | Messages         |
+//! |---------|-----------------|---------|--------------------------------|------------------|
+//! | 1       | CRC-8-SAE J1850 | 4 bits  | 16 bits, in the CRC            | up to 30 bytes   |
+//! | 2       | CRC-8H2F        | 4 bits  | 8 bits per counter, in the CRC | up to 256 bytes  |
+//! | 4       | CRC-32P4        | 16 bits | 32 bits, in the header         | up to 4096 bytes |
+//! | 5       | CRC-16-CCITT    | 8 bits  | 16 bits, in the CRC            | up to 4096 bytes |
+//!
+//! The crate needs no driver: CRCs are computed in software. With the
+//! `tock_crc` feature, [`TockCrc`] computes profile 5's CRC with Tock's CRC
+//! driver where it is available. The driver does not compute the other
+//! profiles' CRCs.
+//!
+//! # Example
+//! ```ignore
+//! use libtock_e2e::{profile5, CheckStatus};
+//!
+//! let config = profile5::Config::new(0x1234, 8);
+//! let mut protector = profile5::Protector::new(config);
+//! let mut checker = profile5::Checker::new(config);
+//!
+//! let mut message = [0, 0, 0, speed_low, speed_high, 0, 0, 0];
+//! protector.protect(&mut message)?;
+//! // ... on the receiving side:
+//! if checker.check(Some(&message)) == CheckStatus::Ok { ... }
+//! ```
+
+#![no_std]
+
+mod crc;
+pub mod profile1;
+pub mod profile2;
+pub mod profile4;
+pub mod profile5;
+#[cfg(feature = "tock_crc")]
+mod tock_crc;
+
+pub use crc::{Crc16, SoftwareCrc};
+#[cfg(feature = "tock_crc")]
+pub use tock_crc::TockCrc;
+
+/// The outcome of checking a message, or the lack of one.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CheckStatus {
+    /// The message is valid, and its counter follows the last one's.
+    Ok,
+    /// No message was received since the last check.
+    NoNewData,
+    /// The message is corrupted or not the expected one: its CRC, data ID or
+    /// length is wrong.
+    Error,
+    /// The message is valid, but has the same counter as the last one.
+    Repeated,
+    /// The message is valid, and no more messages than the configured
+    /// `max_delta_counter` were lost since the last one.
+    OkSomeLost,
+    /// The message is valid, but more messages were lost since the last one
+    /// than the configured `max_delta_counter`.
+    WrongSequence,
+}
+
+/// An error from protecting a message.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The message does not have the configured length, or is too short for
+    /// the configured layout.
+    InvalidLength,
+}
+
+// The state of a checker: the counter of the last valid message. It starts
+// one before 0, so that the first message is expected to have counter 0.
+#[derive(Clone, Copy, Debug)]
+struct Sequence {
+    last: u32,
+}
+
+impl Sequence {
+    const fn new(modulus: u32) -> Sequence {
+        Sequence { last: modulus - 1 }
+    }
+
+    // Returns the status of a valid message with `counter`, which counts
+    // modulo `modulus`.
+    fn check(&mut self, counter: u32, modulus: u32, max_delta_counter: u32) -> CheckStatus {
+        let delta = (counter + modulus - self.last) % modulus;
+        self.last = counter;
+        match delta {
+            0 => CheckStatus::Repeated,
+            1 => CheckStatus::Ok,
+            _ if delta <= max_delta_counter => CheckStatus::OkSomeLost,
+            _ => CheckStatus::WrongSequence,
+        }
+    }
+}
+
+#[cfg(test)]
+mod crc_tests;
+#[cfg(test)]
+mod profile1_tests;
+#[cfg(test)]
+mod profile2_tests;
+#[cfg(test)]
+mod profile4_tests;
+#[cfg(test)]
+mod profile5_tests;
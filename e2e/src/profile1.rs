@@ -0,0 +1,178 @@
+//! E2E profile 1, for short messages such as CAN frames.
+//!
+//! The CRC is CRC-8-SAE J1850 with an initial value and final XOR of 0,
+//! computed over the data ID, then over the message without the CRC byte. The
+//! counter is a nibble that counts from 0 to 14; 15 is not a valid counter.
+//! The default layout, variant 1A, puts the CRC in the first byte and the
+//! counter in the low nibble of the second.
+
+use crate::crc::{crc8, SAE_J1850};
+use crate::{CheckStatus, Error, Sequence};
+
+// The counter counts from 0 to 14.
+const COUNTER_MODULUS: u32 = 15;
+
+/// Which bytes of the data ID are included in the CRC.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DataIdMode {
+    /// Both bytes, the low byte first.
+    Both,
+    /// The low byte for even counters, and the high byte for odd counters.
+    Alternating,
+    /// The low byte only. The high byte must be 0.
+    Low,
+    /// The low byte, and a high byte of 0. The low nibble of the high byte is
+    /// sent in the message instead, at `data_id_nibble_offset`, which makes
+    /// 12-bit data IDs possible.
+    Nibble,
+}
+
+/// The configuration of a protector or checker.
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    pub data_id: u16,
+    pub data_id_mode: DataIdMode,
+    /// The length of the messages, in bytes.
+    pub data_length: usize,
+    /// The offset of the CRC, in bits. It must be a multiple of 8.
+    pub crc_offset: usize,
+    /// The offset of the counter, in bits. It must be a multiple of 4.
+    pub counter_offset: usize,
+    /// The offset of the data ID nibble with [`DataIdMode::Nibble`], in bits.
+    /// It must be a multiple of 4.
+    pub data_id_nibble_offset: usize,
+    /// The largest increase of the counter between two valid messages that is
+    /// accepted as [`CheckStatus::OkSomeLost`].
+    pub max_delta_counter: u8,
+}
+
+impl Config {
+    /// Returns a configuration with the layout of variant 1A, both bytes of
+    /// the data ID in the CRC, and no messages allowed to be lost.
+    pub fn new(data_id: u16, data_length: usize) -> Config {
+        Config {
+            data_id,
+            data_id_mode: DataIdMode::Both,
+            data_length,
+            crc_offset: 0,
+            counter_offset: 8,
+            data_id_nibble_offset: 12,
+            max_delta_counter: 1,
+        }
+    }
+
+    // Checks that `data` has the configured length and room for the layout.
+    fn check_length(&self, data: &[u8]) -> Result<(), Error> {
+        let bits = self.data_length * 8;
+        let nibble_fits =
+            self.data_id_mode != DataIdMode::Nibble || self.data_id_nibble_offset + 4 <= bits;
+        match data.len() == self.data_length
+            && self.crc_offset + 8 <= bits
+            && self.counter_offset + 4 <= bits
+            && nibble_fits
+        {
+            true => Ok(()),
+            false => Err(Error::InvalidLength),
+        }
+    }
+
+    fn crc(&self, data: &[u8], counter: u8) -> u8 {
+        let [low, high] = self.data_id.to_le_bytes();
+        let crc = match self.data_id_mode {
+            DataIdMode::Both => crc8(0, SAE_J1850, &[low, high]),
+            DataIdMode::Alternating if counter % 2 == 0 => crc8(0, SAE_J1850, &[low]),
+            DataIdMode::Alternating => crc8(0, SAE_J1850, &[high]),
+            DataIdMode::Low => crc8(0, SAE_J1850, &[low]),
+            DataIdMode::Nibble => crc8(0, SAE_J1850, &[low, 0]),
+        };
+        let crc_byte = self.crc_offset / 8;
+        let crc = crc8(crc, SAE_J1850, &data[..crc_byte]);
+        crc8(crc, SAE_J1850, &data[crc_byte + 1..])
+    }
+}
+
+/// Protects the messages a sender sends.
+#[derive(Clone, Copy, Debug)]
+pub struct Protector {
+    config: Config,
+    counter: u8,
+}
+
+impl Protector {
+    pub fn new(config: Config) -> Protector {
+        Protector { config, counter: 0 }
+    }
+
+    /// Writes the counter, the data ID nibble if configured, and the CRC into
+    /// `data`, and advances the counter.
+    pub fn protect(&mut self, data: &mut [u8]) -> Result<(), Error> {
+        let config = &self.config;
+        config.check_length(data)?;
+        write_nibble(data, config.counter_offset, self.counter);
+        if config.data_id_mode == DataIdMode::Nibble {
+            let nibble = (config.data_id >> 8) as u8 & 0xF;
+            write_nibble(data, config.data_id_nibble_offset, nibble);
+        }
+        data[config.crc_offset / 8] = config.crc(data, self.counter);
+        self.counter = (self.counter + 1) % COUNTER_MODULUS as u8;
+        Ok(())
+    }
+}
+
+/// Checks the messages a receiver receives.
+#[derive(Clone, Copy, Debug)]
+pub struct Checker {
+    config: Config,
+    sequence: Sequence,
+}
+
+impl Checker {
+    pub fn new(config: Config) -> Checker {
+        Checker {
+            config,
+            sequence: Sequence::new(COUNTER_MODULUS),
+        }
+    }
+
+    /// Checks `data`, the message received since the last check, if any.
+    pub fn check(&mut self, data: Option<&[u8]>) -> CheckStatus {
+        let Some(data) = data else {
+            return CheckStatus::NoNewData;
+        };
+        let config = &self.config;
+        if config.check_length(data).is_err() {
+            return CheckStatus::Error;
+        }
+        let counter = read_nibble(data, config.counter_offset);
+        if u32::from(counter) >= COUNTER_MODULUS
+            || (config.data_id_mode == DataIdMode::Nibble
+                && read_nibble(data, config.data_id_nibble_offset)
+                    != (config.data_id >> 8) as u8 & 0xF)
+            || data[config.crc_offset / 8] != config.crc(data, counter)
+        {
+            return CheckStatus::Error;
+        }
+        self.sequence.check(
+            counter.into(),
+            COUNTER_MODULUS,
+            config.max_delta_counter.into(),
+        )
+    }
+}
+
+// Nibbles at offsets that are a multiple of 8 are the low nibble of a byte,
+// and the others the high nibble.
+fn write_nibble(data: &mut [u8], offset: usize, value: u8) {
+    let byte = &mut data[offset / 8];
+    *byte = match offset % 8 {
+        0 => (*byte & 0xF0) | value,
+        _ => (*byte & 0x0F) | (value << 4),
+    };
+}
+
+fn read_nibble(data: &[u8], offset: usize) -> u8 {
+    match offset % 8 {
+        0 => data[offset / 8] & 0x0F,
+        _ => data[offset / 8] >> 4,
+    }
+}
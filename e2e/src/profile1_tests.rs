@@ -0,0 +1,124 @@
+use crate::profile1::{Checker, Config, DataIdMode, Protector};
+use crate::{CheckStatus, Error};
+
+// The CRC of profile 1 over `parts`.
+fn crc(parts: &[&[u8]]) -> u8 {
+    let crc = ::crc::Crc::<u8>::new(&::crc::CRC_8_GSM_A);
+    let mut digest = crc.digest();
+    for part in parts {
+        digest.update(part);
+    }
+    digest.finalize()
+}
+
+#[test]
+fn protect() {
+    let mut protector = Protector::new(Config::new(0x0123, 8));
+    let mut message = [0, 0xA0, 1, 2, 3, 4, 5, 6];
+    assert_eq!(protector.protect(&mut message), Ok(()));
+    assert_eq!(message[1], 0xA0);
+    assert_eq!(message[0], crc(&[&[0x23, 0x01], &message[1..]]));
+    assert_eq!(protector.protect(&mut message), Ok(()));
+    assert_eq!(message[1], 0xA1);
+    assert_eq!(message[0], crc(&[&[0x23, 0x01], &message[1..]]));
+    assert_eq!(protector.protect(&mut [0; 7]), Err(Error::InvalidLength));
+
+    // The counter counts to 14.
+    for _ in 2..15 {
+        protector.protect(&mut message).unwrap();
+    }
+    assert_eq!(message[1], 0xAE);
+    protector.protect(&mut message).unwrap();
+    assert_eq!(message[1], 0xA0);
+}
+
+#[test]
+fn check() {
+    let mut config = Config::new(0x0123, 4);
+    config.max_delta_counter = 2;
+    let mut protector = Protector::new(config);
+    let mut checker = Checker::new(config);
+    let mut messages = [[0, 0, 0xAA, 0xBB]; 16];
+    for message in &mut messages {
+        protector.protect(message).unwrap();
+    }
+
+    assert_eq!(checker.check(None), CheckStatus::NoNewData);
+    assert_eq!(checker.check(Some(&messages[0])), CheckStatus::Ok);
+    assert_eq!(checker.check(Some(&messages[0])), CheckStatus::Repeated);
+    assert_eq!(checker.check(Some(&messages[2])), CheckStatus::OkSomeLost);
+    assert_eq!(
+        checker.check(Some(&messages[5])),
+        CheckStatus::WrongSequence
+    );
+    // The counter wraps from 14 to 0.
+    for message in &messages[6..16] {
+        assert_eq!(checker.check(Some(message)), CheckStatus::Ok);
+    }
+
+    let mut corrupted = messages[1];
+    corrupted[3] ^= 1;
+    assert_eq!(checker.check(Some(&corrupted)), CheckStatus::Error);
+    assert_eq!(checker.check(Some(&messages[1][..3])), CheckStatus::Error);
+    // Messages from another sender are detected by their data ID.
+    let mut other = messages[1];
+    Protector::new(Config::new(0x0124, 4))
+        .protect(&mut other)
+        .unwrap();
+    assert_eq!(checker.check(Some(&other)), CheckStatus::Error);
+    assert_eq!(checker.check(Some(&messages[1])), CheckStatus::Ok);
+
+    // 15 is not a valid counter, even with a valid CRC.
+    let mut invalid = [0, 0x0F, 0xAA, 0xBB];
+    invalid[0] = crc(&[&[0x23, 0x01], &invalid[1..]]);
+    assert_eq!(checker.check(Some(&invalid)), CheckStatus::Error);
+}
+
+#[test]
+fn data_id_modes() {
+    let mut config = Config::new(0x0123, 4);
+    config.data_id_mode = DataIdMode::Alternating;
+    let mut protector = Protector::new(config);
+    let mut message = [0; 4];
+    protector.protect(&mut message).unwrap();
+    assert_eq!(message[0], crc(&[&[0x23], &message[1..]]));
+    protector.protect(&mut message).unwrap();
+    assert_eq!(message[0], crc(&[&[0x01], &message[1..]]));
+
+    config.data_id_mode = DataIdMode::Low;
+    Protector::new(config).protect(&mut message).unwrap();
+    assert_eq!(message[0], crc(&[&[0x23], &message[1..]]));
+
+    // The nibble goes in the high nibble of the counter's byte by default.
+    config.data_id_mode = DataIdMode::Nibble;
+    config.data_id = 0x0A23;
+    Protector::new(config).protect(&mut message).unwrap();
+    assert_eq!(message[1], 0xA0);
+    assert_eq!(message[0], crc(&[&[0x23, 0x00], &message[1..]]));
+    assert_eq!(Checker::new(config).check(Some(&message)), CheckStatus::Ok);
+    message[1] = 0xB0;
+    message[0] = crc(&[&[0x23, 0x00], &message[1..]]);
+    assert_eq!(
+        Checker::new(config).check(Some(&message)),
+        CheckStatus::Error
+    );
+}
+
+#[test]
+fn layout() {
+    // The CRC in the last byte, and the counter in a high nibble.
+    let mut config = Config::new(0x0123, 4);
+    config.crc_offset = 24;
+    config.counter_offset = 4;
+    let mut message = [0x0F, 0xAA, 0xBB, 0];
+    Protector::new(config).protect(&mut message).unwrap();
+    assert_eq!(message[0], 0x0F);
+    assert_eq!(message[3], crc(&[&[0x23, 0x01], &message[..3]]));
+    assert_eq!(Checker::new(config).check(Some(&message)), CheckStatus::Ok);
+
+    config.crc_offset = 32;
+    assert_eq!(
+        Protector::new(config).protect(&mut message),
+        Err(Error::InvalidLength)
+    );
+}
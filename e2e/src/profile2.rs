@@ -0,0 +1,105 @@
+//! E2E profile 2, for messages of up to 256 bytes.
+//!
+//! The CRC is CRC-8H2F, computed over the message after the CRC byte, then
+//! over the data ID. The data ID changes with the counter: it is taken from a
+//! list of 16 data IDs. The counter is a nibble that counts from 0 to 15. The
+//! layout is fixed: the CRC is in the first byte and the counter in the low
+//! nibble of the second.
+
+use crate::crc::{crc8, H2F};
+use crate::{CheckStatus, Error, Sequence};
+
+const COUNTER_MODULUS: u32 = 16;
+
+/// The configuration of a protector or checker.
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// The data ID of the messages with each counter value.
+    pub data_id_list: [u8; 16],
+    /// The length of the messages, in bytes.
+    pub data_length: usize,
+    /// The largest increase of the counter between two valid messages that is
+    /// accepted as [`CheckStatus::OkSomeLost`].
+    pub max_delta_counter: u8,
+}
+
+impl Config {
+    /// Returns a configuration that allows no messages to be lost.
+    pub fn new(data_id_list: [u8; 16], data_length: usize) -> Config {
+        Config {
+            data_id_list,
+            data_length,
+            max_delta_counter: 1,
+        }
+    }
+
+    fn check_length(&self, data: &[u8]) -> Result<(), Error> {
+        match data.len() == self.data_length && self.data_length >= 2 {
+            true => Ok(()),
+            false => Err(Error::InvalidLength),
+        }
+    }
+
+    fn crc(&self, data: &[u8], counter: u8) -> u8 {
+        let data_id = self.data_id_list[counter as usize];
+        let crc = crc8(0xFF, H2F, &data[1..]);
+        crc8(crc, H2F, &[data_id]) ^ 0xFF
+    }
+}
+
+/// Protects the messages a sender sends.
+#[derive(Clone, Copy, Debug)]
+pub struct Protector {
+    config: Config,
+    counter: u8,
+}
+
+impl Protector {
+    pub fn new(config: Config) -> Protector {
+        Protector { config, counter: 0 }
+    }
+
+    /// Writes the counter and the CRC into `data`, and advances the counter.
+    pub fn protect(&mut self, data: &mut [u8]) -> Result<(), Error> {
+        self.config.check_length(data)?;
+        data[1] = (data[1] & 0xF0) | self.counter;
+        data[0] = self.config.crc(data, self.counter);
+        self.counter = (self.counter + 1) % COUNTER_MODULUS as u8;
+        Ok(())
+    }
+}
+
+/// Checks the messages a receiver receives.
+#[derive(Clone, Copy, Debug)]
+pub struct Checker {
+    config: Config,
+    sequence: Sequence,
+}
+
+impl Checker {
+    pub fn new(config: Config) -> Checker {
+        Checker {
+            config,
+            sequence: Sequence::new(COUNTER_MODULUS),
+        }
+    }
+
+    /// Checks `data`, the message received since the last check, if any.
+    pub fn check(&mut self, data: Option<&[u8]>) -> CheckStatus {
+        let Some(data) = data else {
+            return CheckStatus::NoNewData;
+        };
+        if self.config.check_length(data).is_err() {
+            return CheckStatus::Error;
+        }
+        let counter = data[1] & 0x0F;
+        if data[0] != self.config.crc(data, counter) {
+            return CheckStatus::Error;
+        }
+        self.sequence.check(
+            counter.into(),
+            COUNTER_MODULUS,
+            self.config.max_delta_counter.into(),
+        )
+    }
+}
@@ -0,0 +1,61 @@
+use crate::profile2::{Checker, Config, Protector};
+use crate::{CheckStatus, Error};
+
+const DATA_IDS: [u8; 16] = [
+    0x10, 0x21, 0x32, 0x43, 0x54, 0x65, 0x76, 0x87, 0x98, 0xA9, 0xBA, 0xCB, 0xDC, 0xED, 0xFE, 0x0F,
+];
+
+// The CRC of profile 2 over a message and its data ID.
+fn crc(message: &[u8], data_id: u8) -> u8 {
+    let crc = ::crc::Crc::<u8>::new(&::crc::CRC_8_AUTOSAR);
+    let mut digest = crc.digest();
+    digest.update(&message[1..]);
+    digest.update(&[data_id]);
+    digest.finalize()
+}
+
+#[test]
+fn protect() {
+    let mut protector = Protector::new(Config::new(DATA_IDS, 8));
+    let mut message = [0, 0x50, 1, 2, 3, 4, 5, 6];
+    for counter in 0..16 {
+        assert_eq!(protector.protect(&mut message), Ok(()));
+        assert_eq!(message[1], 0x50 | counter);
+        assert_eq!(message[0], crc(&message, DATA_IDS[counter as usize]));
+    }
+    protector.protect(&mut message).unwrap();
+    assert_eq!(message[1], 0x50);
+    assert_eq!(protector.protect(&mut [0; 9]), Err(Error::InvalidLength));
+}
+
+#[test]
+fn check() {
+    let mut config = Config::new(DATA_IDS, 3);
+    config.max_delta_counter = 3;
+    let mut protector = Protector::new(config);
+    let mut checker = Checker::new(config);
+    let mut messages = [[0, 0, 0x42]; 20];
+    for message in &mut messages {
+        protector.protect(message).unwrap();
+    }
+
+    assert_eq!(checker.check(Some(&messages[0])), CheckStatus::Ok);
+    assert_eq!(checker.check(Some(&messages[3])), CheckStatus::OkSomeLost);
+    assert_eq!(checker.check(None), CheckStatus::NoNewData);
+    assert_eq!(checker.check(Some(&messages[3])), CheckStatus::Repeated);
+    assert_eq!(
+        checker.check(Some(&messages[2])),
+        CheckStatus::WrongSequence
+    );
+    // The counter wraps from 15 to 0.
+    for message in &messages[3..20] {
+        assert_eq!(checker.check(Some(message)), CheckStatus::Ok);
+    }
+
+    // The data ID depends on the counter, so a message with its counter
+    // changed fails even with its CRC recomputed for the old counter.
+    let mut replayed = messages[4];
+    replayed[1] = 0x05;
+    assert_eq!(checker.check(Some(&replayed)), CheckStatus::Error);
+    assert_eq!(checker.check(Some(&messages[4][..2])), CheckStatus::Error);
+}
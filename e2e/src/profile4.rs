@@ -0,0 +1,128 @@
+//! E2E profile 4, for long messages of varying length.
+//!
+//! The message carries a 12-byte header, in big-endian byte order: the length
+//! of the message (16 bits), the counter (16 bits), the data ID (32 bits) and
+//! the CRC (32 bits). The CRC is CRC-32P4, computed over the message without
+//! the CRC field.
+
+use crate::crc::crc32_p4;
+use crate::{CheckStatus, Error, Sequence};
+
+/// The length of the header.
+pub const HEADER_LEN: usize = 12;
+
+const COUNTER_MODULUS: u32 = 1 << 16;
+
+/// The configuration of a protector or checker.
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    pub data_id: u32,
+    /// The offset of the header, in bytes.
+    pub offset: usize,
+    /// The shortest message, in bytes.
+    pub min_data_length: usize,
+    /// The longest message, in bytes.
+    pub max_data_length: usize,
+    /// The largest increase of the counter between two valid messages that is
+    /// accepted as [`CheckStatus::OkSomeLost`].
+    pub max_delta_counter: u16,
+}
+
+impl Config {
+    /// Returns a configuration with the header at the start of messages of
+    /// any length, that allows no messages to be lost.
+    pub fn new(data_id: u32) -> Config {
+        Config {
+            data_id,
+            offset: 0,
+            min_data_length: HEADER_LEN,
+            max_data_length: 4096,
+            max_delta_counter: 1,
+        }
+    }
+
+    fn check_length(&self, data: &[u8]) -> Result<(), Error> {
+        match (self.min_data_length..=self.max_data_length).contains(&data.len())
+            && self.offset + HEADER_LEN <= data.len()
+            && u16::try_from(data.len()).is_ok()
+        {
+            true => Ok(()),
+            false => Err(Error::InvalidLength),
+        }
+    }
+
+    fn crc(&self, data: &[u8]) -> u32 {
+        let crc_field = self.offset + 8;
+        let crc = crc32_p4(0xFFFF_FFFF, &data[..crc_field]);
+        crc32_p4(crc, &data[crc_field + 4..]) ^ 0xFFFF_FFFF
+    }
+}
+
+/// Protects the messages a sender sends.
+#[derive(Clone, Copy, Debug)]
+pub struct Protector {
+    config: Config,
+    counter: u16,
+}
+
+impl Protector {
+    pub fn new(config: Config) -> Protector {
+        Protector { config, counter: 0 }
+    }
+
+    /// Writes the header into `data`, and advances the counter.
+    pub fn protect(&mut self, data: &mut [u8]) -> Result<(), Error> {
+        let config = &self.config;
+        config.check_length(data)?;
+        let length = data.len() as u16;
+        let header = &mut data[config.offset..config.offset + HEADER_LEN];
+        header[0..2].copy_from_slice(&length.to_be_bytes());
+        header[2..4].copy_from_slice(&self.counter.to_be_bytes());
+        header[4..8].copy_from_slice(&config.data_id.to_be_bytes());
+        let crc = config.crc(data);
+        data[config.offset + 8..config.offset + 12].copy_from_slice(&crc.to_be_bytes());
+        self.counter = self.counter.wrapping_add(1);
+        Ok(())
+    }
+}
+
+/// Checks the messages a receiver receives.
+#[derive(Clone, Copy, Debug)]
+pub struct Checker {
+    config: Config,
+    sequence: Sequence,
+}
+
+impl Checker {
+    pub fn new(config: Config) -> Checker {
+        Checker {
+            config,
+            sequence: Sequence::new(COUNTER_MODULUS),
+        }
+    }
+
+    /// Checks `data`, the message received since the last check, if any.
+    pub fn check(&mut self, data: Option<&[u8]>) -> CheckStatus {
+        let Some(data) = data else {
+            return CheckStatus::NoNewData;
+        };
+        let config = &self.config;
+        if config.check_length(data).is_err() {
+            return CheckStatus::Error;
+        }
+        let header = &data[config.offset..config.offset + HEADER_LEN];
+        let length = u16::from_be_bytes([header[0], header[1]]);
+        let counter = u16::from_be_bytes([header[2], header[3]]);
+        let data_id = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+        let crc = u32::from_be_bytes([header[8], header[9], header[10], header[11]]);
+        if usize::from(length) != data.len() || data_id != config.data_id || crc != config.crc(data)
+        {
+            return CheckStatus::Error;
+        }
+        self.sequence.check(
+            counter.into(),
+            COUNTER_MODULUS,
+            config.max_delta_counter.into(),
+        )
+    }
+}
@@ -0,0 +1,82 @@
+use crate::profile4::{Checker, Config, Protector};
+use crate::{CheckStatus, Error};
+
+// The CRC of profile 4 over a message with its header at `offset`.
+fn crc(message: &[u8], offset: usize) -> u32 {
+    let crc = ::crc::Crc::<u32>::new(&::crc::CRC_32_AUTOSAR);
+    let mut digest = crc.digest();
+    digest.update(&message[..offset + 8]);
+    digest.update(&message[offset + 12..]);
+    digest.finalize()
+}
+
+#[test]
+fn protect() {
+    let mut protector = Protector::new(Config::new(0x0A0B_0C0D));
+    let mut message = [0; 16];
+    message[12..].copy_from_slice(&[1, 2, 3, 4]);
+    assert_eq!(protector.protect(&mut message), Ok(()));
+    assert_eq!(
+        message[..8],
+        [0x00, 0x10, 0x00, 0x00, 0x0A, 0x0B, 0x0C, 0x0D]
+    );
+    assert_eq!(message[8..12], crc(&message, 0).to_be_bytes());
+    protector.protect(&mut message[..13]).unwrap();
+    assert_eq!(message[..4], [0x00, 0x0D, 0x00, 0x01]);
+
+    assert_eq!(protector.protect(&mut [0; 11]), Err(Error::InvalidLength));
+    assert_eq!(protector.protect(&mut [0; 4097]), Err(Error::InvalidLength));
+}
+
+#[test]
+fn check() {
+    let mut config = Config::new(0x0A0B_0C0D);
+    config.offset = 2;
+    config.max_delta_counter = 100;
+    let mut protector = Protector::new(config);
+    let mut checker = Checker::new(config);
+    let mut message = [0xEE; 20];
+
+    protector.protect(&mut message).unwrap();
+    assert_eq!(message[..2], [0xEE, 0xEE]);
+    assert_eq!(message[10..14], crc(&message, 2).to_be_bytes());
+    assert_eq!(checker.check(Some(&message)), CheckStatus::Ok);
+    assert_eq!(checker.check(Some(&message)), CheckStatus::Repeated);
+    for _ in 0..100 {
+        protector.protect(&mut message).unwrap();
+    }
+    assert_eq!(checker.check(Some(&message)), CheckStatus::OkSomeLost);
+    for _ in 0..101 {
+        protector.protect(&mut message).unwrap();
+    }
+    assert_eq!(checker.check(Some(&message)), CheckStatus::WrongSequence);
+    assert_eq!(checker.check(None), CheckStatus::NoNewData);
+
+    // A message whose length does not match its length field.
+    protector.protect(&mut message[..19]).unwrap();
+    assert_eq!(checker.check(Some(&message)), CheckStatus::Error);
+    let mut other = message;
+    Protector::new(Config {
+        data_id: 0x0A0B_0C0E,
+        ..config
+    })
+    .protect(&mut other)
+    .unwrap();
+    assert_eq!(checker.check(Some(&other)), CheckStatus::Error);
+    protector.protect(&mut message).unwrap();
+    message[19] ^= 0x80;
+    assert_eq!(checker.check(Some(&message)), CheckStatus::Error);
+}
+
+#[test]
+fn counter_wraps() {
+    let config = Config::new(1);
+    let mut protector = Protector::new(config);
+    let mut checker = Checker::new(config);
+    let mut message = [0; 12];
+    for _ in 0..=u16::MAX as u32 + 1 {
+        protector.protect(&mut message).unwrap();
+        assert_eq!(checker.check(Some(&message)), CheckStatus::Ok);
+    }
+    assert_eq!(message[2..4], [0, 0]);
+}
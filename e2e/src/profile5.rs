@@ -0,0 +1,130 @@
+//! E2E profile 5, for messages of fixed length.
+//!
+//! The message carries a 3-byte header: the CRC (16 bits, little-endian) and
+//! the counter (8 bits). The CRC is CRC-16-CCITT, computed over the message
+//! without the CRC field, then over the data ID, low byte first.
+
+use crate::crc::{Crc16, SoftwareCrc};
+use crate::{CheckStatus, Error, Sequence};
+
+/// The length of the header.
+pub const HEADER_LEN: usize = 3;
+
+const COUNTER_MODULUS: u32 = 1 << 8;
+
+/// The configuration of a protector or checker.
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    pub data_id: u16,
+    /// The offset of the header, in bytes.
+    pub offset: usize,
+    /// The length of the messages, in bytes.
+    pub data_length: usize,
+    /// The largest increase of the counter between two valid messages that is
+    /// accepted as [`CheckStatus::OkSomeLost`].
+    pub max_delta_counter: u8,
+}
+
+impl Config {
+    /// Returns a configuration with the header at the start of the message,
+    /// that allows no messages to be lost.
+    pub fn new(data_id: u16, data_length: usize) -> Config {
+        Config {
+            data_id,
+            offset: 0,
+            data_length,
+            max_delta_counter: 1,
+        }
+    }
+
+    fn check_length(&self, data: &[u8]) -> Result<(), Error> {
+        match data.len() == self.data_length && self.offset + HEADER_LEN <= data.len() {
+            true => Ok(()),
+            false => Err(Error::InvalidLength),
+        }
+    }
+
+    fn crc(&self, crc: &impl Crc16, data: &[u8]) -> u16 {
+        let (before, after) = (&data[..self.offset], &data[self.offset + 2..]);
+        crc.crc16(&[before, after, &self.data_id.to_le_bytes()])
+    }
+}
+
+/// Protects the messages a sender sends. The CRC is computed by `C`.
+#[derive(Clone, Copy, Debug)]
+pub struct Protector<C: Crc16 = SoftwareCrc> {
+    config: Config,
+    counter: u8,
+    crc: C,
+}
+
+impl Protector {
+    pub fn new(config: Config) -> Protector {
+        Protector::with_crc(config, SoftwareCrc)
+    }
+}
+
+impl<C: Crc16> Protector<C> {
+    pub fn with_crc(config: Config, crc: C) -> Protector<C> {
+        Protector {
+            config,
+            counter: 0,
+            crc,
+        }
+    }
+
+    /// Writes the header into `data`, and advances the counter.
+    pub fn protect(&mut self, data: &mut [u8]) -> Result<(), Error> {
+        let config = &self.config;
+        config.check_length(data)?;
+        data[config.offset + 2] = self.counter;
+        let crc = config.crc(&self.crc, data);
+        data[config.offset..config.offset + 2].copy_from_slice(&crc.to_le_bytes());
+        self.counter = self.counter.wrapping_add(1);
+        Ok(())
+    }
+}
+
+/// Checks the messages a receiver receives. The CRC is computed by `C`.
+#[derive(Clone, Copy, Debug)]
+pub struct Checker<C: Crc16 = SoftwareCrc> {
+    config: Config,
+    sequence: Sequence,
+    crc: C,
+}
+
+impl Checker {
+    pub fn new(config: Config) -> Checker {
+        Checker::with_crc(config, SoftwareCrc)
+    }
+}
+
+impl<C: Crc16> Checker<C> {
+    pub fn with_crc(config: Config, crc: C) -> Checker<C> {
+        Checker {
+            config,
+            sequence: Sequence::new(COUNTER_MODULUS),
+            crc,
+        }
+    }
+
+    /// Checks `data`, the message received since the last check, if any.
+    pub fn check(&mut self, data: Option<&[u8]>) -> CheckStatus {
+        let Some(data) = data else {
+            return CheckStatus::NoNewData;
+        };
+        let config = &self.config;
+        if config.check_length(data).is_err() {
+            return CheckStatus::Error;
+        }
+        let crc = u16::from_le_bytes([data[config.offset], data[config.offset + 1]]);
+        if crc != config.crc(&self.crc, data) {
+            return CheckStatus::Error;
+        }
+        self.sequence.check(
+            data[config.offset + 2].into(),
+            COUNTER_MODULUS,
+            config.max_delta_counter.into(),
+        )
+    }
+}
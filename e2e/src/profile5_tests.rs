@@ -0,0 +1,106 @@
+use crate::profile5::{Checker, Config, Protector};
+use crate::{CheckStatus, Error};
+
+// The CRC of profile 5 over a message with its header at `offset`.
+fn crc(message: &[u8], offset: usize, data_id: u16) -> u16 {
+    let crc = ::crc::Crc::<u16>::new(&::crc::CRC_16_IBM_3740);
+    let mut digest = crc.digest();
+    digest.update(&message[..offset]);
+    digest.update(&message[offset + 2..]);
+    digest.update(&data_id.to_le_bytes());
+    digest.finalize()
+}
+
+#[test]
+fn protect() {
+    let mut protector = Protector::new(Config::new(0x1234, 8));
+    let mut message = [0, 0, 0, 1, 2, 3, 4, 5];
+    assert_eq!(protector.protect(&mut message), Ok(()));
+    assert_eq!(message[2], 0);
+    assert_eq!(message[..2], crc(&message, 0, 0x1234).to_le_bytes());
+    protector.protect(&mut message).unwrap();
+    assert_eq!(message[2], 1);
+    assert_eq!(protector.protect(&mut [0; 7]), Err(Error::InvalidLength));
+
+    let mut config = Config::new(0x1234, 8);
+    config.offset = 6;
+    assert_eq!(
+        Protector::new(config).protect(&mut message),
+        Err(Error::InvalidLength)
+    );
+    config.offset = 5;
+    Protector::new(config).protect(&mut message).unwrap();
+    assert_eq!(message[5..7], crc(&message, 5, 0x1234).to_le_bytes());
+    assert_eq!(message[7], 0);
+}
+
+#[test]
+fn check() {
+    let mut config = Config::new(0x1234, 4);
+    config.max_delta_counter = 2;
+    let mut protector = Protector::new(config);
+    let mut checker = Checker::new(config);
+    let mut messages = [[0, 0, 0, 0x99]; 260];
+    for message in &mut messages {
+        protector.protect(message).unwrap();
+    }
+
+    assert_eq!(checker.check(Some(&messages[0])), CheckStatus::Ok);
+    assert_eq!(checker.check(Some(&messages[2])), CheckStatus::OkSomeLost);
+    assert_eq!(checker.check(Some(&messages[2])), CheckStatus::Repeated);
+    assert_eq!(
+        checker.check(Some(&messages[6])),
+        CheckStatus::WrongSequence
+    );
+    // The counter wraps from 255 to 0.
+    for message in &messages[7..260] {
+        assert_eq!(checker.check(Some(message)), CheckStatus::Ok);
+    }
+    assert_eq!(checker.check(None), CheckStatus::NoNewData);
+
+    let mut other = messages[0];
+    Protector::new(Config::new(0x1235, 4))
+        .protect(&mut other)
+        .unwrap();
+    assert_eq!(checker.check(Some(&other)), CheckStatus::Error);
+    assert_eq!(checker.check(Some(&messages[0][..3])), CheckStatus::Error);
+}
+
+#[cfg(feature = "tock_crc")]
+#[test]
+fn tock_crc() {
+    use libtock_unittest::fake;
+    type TockCrc<const N: usize> =
+        crate::TockCrc<fake::Syscalls, libtock_platform::DefaultConfig, N>;
+
+    let kernel = fake::Kernel::new();
+    let config = Config::new(0x1234, 8);
+    let mut software = [0, 0, 0, 1, 2, 3, 4, 5];
+    Protector::new(config).protect(&mut software).unwrap();
+
+    // Without the driver, the CRC is computed in software.
+    let mut message = [0, 0, 0, 1, 2, 3, 4, 5];
+    Protector::with_crc(config, TockCrc::<64>::new())
+        .protect(&mut message)
+        .unwrap();
+    assert_eq!(message, software);
+
+    let driver = fake::Crc::new();
+    kernel.add_driver(&driver);
+    let mut message = [0, 0, 0, 1, 2, 3, 4, 5];
+    Protector::with_crc(config, TockCrc::<64>::new())
+        .protect(&mut message)
+        .unwrap();
+    assert_eq!(message, software);
+    let mut checker = Checker::with_crc(config, TockCrc::<64>::new());
+    assert_eq!(checker.check(Some(&message)), CheckStatus::Ok);
+    assert_eq!(driver.computations(), 2);
+
+    // The CRC covers eight bytes, which do not fit in four.
+    let mut message = [0, 0, 0, 1, 2, 3, 4, 5];
+    Protector::with_crc(config, TockCrc::<4>::new())
+        .protect(&mut message)
+        .unwrap();
+    assert_eq!(message, software);
+    assert_eq!(driver.computations(), 2);
+}
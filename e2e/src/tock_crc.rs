@@ -0,0 +1,47 @@
+use core::marker::PhantomData;
+use libtock_crc::{Config, Crc, CrcAlgorithm};
+use libtock_platform::{DefaultConfig, Syscalls};
+
+use crate::crc::{Crc16, SoftwareCrc};
+
+/// Computes CRC-16-CCITT with the CRC driver. The driver needs the data in one
+/// buffer, so the parts are copied into a buffer of `N` bytes first. The CRC
+/// is computed in software instead if the parts do not fit, or if the driver
+/// is not present or fails.
+pub struct TockCrc<S: Syscalls, C: Config = DefaultConfig, const N: usize = 64> {
+    s: PhantomData<S>,
+    c: PhantomData<C>,
+}
+
+impl<S: Syscalls, C: Config, const N: usize> TockCrc<S, C, N> {
+    pub fn new() -> Self {
+        Self {
+            s: PhantomData,
+            c: PhantomData,
+        }
+    }
+}
+
+impl<S: Syscalls, C: Config, const N: usize> Default for TockCrc<S, C, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Syscalls, C: Config, const N: usize> Crc16 for TockCrc<S, C, N> {
+    fn crc16(&self, parts: &[&[u8]]) -> u16 {
+        let mut buf = [0; N];
+        let mut len = 0;
+        for part in parts {
+            let Some(dest) = buf.get_mut(len..len + part.len()) else {
+                return SoftwareCrc.crc16(parts);
+            };
+            dest.copy_from_slice(part);
+            len += part.len();
+        }
+        match Crc::<S, C>::compute(CrcAlgorithm::Crc16Ccitt, &buf[..len]) {
+            Ok(crc) => crc as u16,
+            Err(_) => SoftwareCrc.crc16(parts),
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use crate::waker::{wake_count, waker};
+use core::future::Future;
+use core::marker::PhantomData;
+use core::pin::{pin, Pin};
+use core::task::{Context, Poll};
+use libtock_platform::Syscalls;
+
+/// Runs a fixed number of tasks to completion. Tasks are `Future`s with no
+/// output, pinned by the caller:
+///
+/// ```ignore
+/// let mut task = core::pin::pin!(task());
+/// let mut executor = Executor::<TockSyscalls, 1>::new();
+/// executor.spawn(task.as_mut()).unwrap();
+/// executor.run();
+/// ```
+///
+/// `N` is the maximum number of tasks the executor can hold at once. Tasks
+/// cannot spawn other tasks; to run a dynamic set of futures, combine them
+/// into a single task instead.
+pub struct Executor<'a, S: Syscalls, const N: usize> {
+    tasks: [Option<Pin<&'a mut dyn Future<Output = ()>>>; N],
+    _syscalls: PhantomData<S>,
+}
+
+/// Error returned by [`Executor::spawn`] when the executor already holds `N`
+/// unfinished tasks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpawnError;
+
+impl<'a, S: Syscalls, const N: usize> Executor<'a, S, N> {
+    pub const fn new() -> Self {
+        Self {
+            tasks: [const { None }; N],
+            _syscalls: PhantomData,
+        }
+    }
+
+    /// Adds a task to the executor. The task does not start running until
+    /// [`Executor::run`] is called.
+    pub fn spawn(&mut self, task: Pin<&'a mut dyn Future<Output = ()>>) -> Result<(), SpawnError> {
+        let slot = self
+            .tasks
+            .iter_mut()
+            .find(|slot| slot.is_none())
+            .ok_or(SpawnError)?;
+        *slot = Some(task);
+        Ok(())
+    }
+
+    /// Returns the number of tasks that have been spawned but have not yet
+    /// completed.
+    pub fn task_count(&self) -> usize {
+        self.tasks.iter().filter(|slot| slot.is_some()).count()
+    }
+
+    /// Runs the spawned tasks until all of them have completed, calling
+    /// `Syscalls::yield_wait` whenever none of them can make progress.
+    pub fn run(&mut self) {
+        drive::<S, _>(|cx| {
+            let mut pending = false;
+            for slot in &mut self.tasks {
+                if let Some(task) = slot {
+                    match task.as_mut().poll(cx) {
+                        Poll::Ready(()) => *slot = None,
+                        Poll::Pending => pending = true,
+                    }
+                }
+            }
+            if pending {
+                Poll::Pending
+            } else {
+                Poll::Ready(())
+            }
+        })
+    }
+}
+
+// We can't derive(Default) because S is not Default, and derive(Default)
+// generates a Default implementation that requires S to be Default.
+impl<S: Syscalls, const N: usize> Default for Executor<'_, S, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Runs a single future to completion, calling `Syscalls::yield_wait` whenever
+/// it cannot make progress, and returns its output.
+pub fn block_on<S: Syscalls, F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    drive::<S, _>(|cx| future.as_mut().poll(cx))
+}
+
+// The executor's main loop, shared by `Executor::run` and `block_on`. `poll`
+// should poll every unfinished task.
+fn drive<S: Syscalls, T>(mut poll: impl FnMut(&mut Context) -> Poll<T>) -> T {
+    let waker = waker();
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        let wake_count_before = wake_count();
+        if let Poll::Ready(output) = poll(&mut cx) {
+            return output;
+        }
+        // If a task was woken during this pass, it may be able to make progress
+        // without an upcall, so we must not sleep. Otherwise, only an upcall
+        // can change anything.
+        if wake_count() == wake_count_before {
+            S::yield_wait();
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use core::cell::Cell;
+use core::future::Future;
+use core::pin::{pin, Pin};
+use core::task::{Context, Poll};
+use libtock_platform::{share, DefaultConfig, ErrorCode, Subscribe, Syscalls};
+use libtock_unittest::{fake, SyscallLogEntry};
+
+use crate::{block_on, AsyncUpcall, Executor, SpawnError};
+
+// The fake buttons driver is used as a source of upcalls.
+const BUTTONS: u32 = 0x3;
+const ENABLE_INTERRUPTS: u32 = 1;
+
+// A future that wakes itself and returns Pending the first time it is polled,
+// and completes the second time.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+#[test]
+fn block_on_ready() {
+    let kernel = fake::Kernel::new();
+    assert_eq!(block_on::<fake::Syscalls, _>(async { 5 }), 5);
+    assert_eq!(kernel.take_syscall_log(), []);
+}
+
+#[test]
+fn block_on_local_wake() {
+    let kernel = fake::Kernel::new();
+    // The fake kernel panics if yield_wait is called without a queued upcall,
+    // so this also verifies the executor does not sleep after a wakeup.
+    block_on::<fake::Syscalls, _>(YieldOnce(false));
+    assert_eq!(kernel.take_syscall_log(), []);
+}
+
+#[test]
+fn block_on_upcall() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Buttons::<2>::new();
+    kernel.add_driver(&driver);
+
+    let upcall = AsyncUpcall::new();
+    share::scope::<Subscribe<_, BUTTONS, 0>, _, _>(|subscribe| {
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, BUTTONS, 0>(subscribe, &upcall).unwrap();
+        fake::Syscalls::command(BUTTONS, ENABLE_INTERRUPTS, 1, 0)
+            .to_result::<(), ErrorCode>()
+            .unwrap();
+        driver.set_pressed(1, true).unwrap();
+        kernel.take_syscall_log();
+
+        assert_eq!(block_on::<fake::Syscalls, _>(upcall.wait()), (1, 1, 0));
+        assert_eq!(kernel.take_syscall_log(), [SyscallLogEntry::YieldWait]);
+    });
+}
+
+#[test]
+fn run_tasks() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Buttons::<2>::new();
+    kernel.add_driver(&driver);
+
+    let upcall = AsyncUpcall::new();
+    let received = Cell::new(None);
+    let pressed = Cell::new(false);
+    share::scope::<Subscribe<_, BUTTONS, 0>, _, _>(|subscribe| {
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, BUTTONS, 0>(subscribe, &upcall).unwrap();
+        fake::Syscalls::command(BUTTONS, ENABLE_INTERRUPTS, 0, 0)
+            .to_result::<(), ErrorCode>()
+            .unwrap();
+        kernel.take_syscall_log();
+
+        let mut listener = pin!(async {
+            received.set(Some(upcall.wait().await));
+        });
+        let mut presser = pin!(async {
+            YieldOnce(false).await;
+            driver.set_pressed(0, true).unwrap();
+            pressed.set(true);
+        });
+        let mut executor = Executor::<fake::Syscalls, 2>::new();
+        executor.spawn(listener.as_mut()).unwrap();
+        executor.spawn(presser.as_mut()).unwrap();
+        assert_eq!(executor.task_count(), 2);
+        executor.run();
+        assert_eq!(executor.task_count(), 0);
+        assert_eq!(kernel.take_syscall_log(), [SyscallLogEntry::YieldWait]);
+    });
+    assert!(pressed.get());
+    assert_eq!(received.get(), Some((0, 1, 0)));
+}
+
+#[test]
+fn spawn_full() {
+    let mut first = pin!(async {});
+    let mut second = pin!(async {});
+    let mut executor = Executor::<fake::Syscalls, 1>::new();
+    assert_eq!(executor.spawn(first.as_mut()), Ok(()));
+    assert_eq!(executor.spawn(second.as_mut()), Err(SpawnError));
+    assert_eq!(executor.task_count(), 1);
+}
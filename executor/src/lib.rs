@@ -0,0 +1,55 @@
+//! `libtock_executor` is a small executor for running `async` code in a Tock
+//! process.
+//!
+//! Tock processes are single-threaded, and the only way for a process to learn
+//! that something happened is for the kernel to invoke an upcall during a
+//! Yield system call. `libtock_executor` is built around that model:
+//!
+//! 1. Each pass, the executor polls every task that has not yet completed.
+//! 2. If no task woke another task (or itself) while being polled, then no task
+//!    can make progress until the kernel delivers an upcall, so the executor
+//!    calls `Syscalls::yield_wait`.
+//! 3. Once the upcall has run, the executor starts a new pass.
+//!
+//! Upcalls reach tasks through [`AsyncUpcall`], which implements
+//! `libtock_platform::Upcall` and can therefore be passed to
+//! `Syscalls::subscribe` like any other upcall. Awaiting
+//! [`AsyncUpcall::wait`] suspends a task until the upcall has been invoked.
+//!
+//! The executor does not allocate: tasks are pinned by the caller (usually with
+//! `core::pin::pin!`) and stored by reference in a fixed-size array. Because
+//! every unfinished task is polled on every pass, the executor is intended for
+//! the handful of long-running tasks a typical process has, not for thousands
+//! of short-lived ones.
+//!
+//! Blocking APIs (such as the `_sync` functions and `Syscalls::yield_wait_for`)
+//! block the whole process, including every other task; async code should
+//! avoid them.
+//!
+//! # Example
+//! ```ignore
+//! use core::pin::pin;
+//! use libtock_executor::Executor;
+//!
+//! let mut blink = pin!(blink_task());
+//! let mut echo = pin!(echo_task());
+//! let mut executor = Executor::<TockSyscalls, 2>::new();
+//! executor.spawn(blink.as_mut()).unwrap();
+//! executor.spawn(echo.as_mut()).unwrap();
+//! executor.run();
+//! ```
+
+#![cfg_attr(not(test), no_std)]
+#![warn(unsafe_op_in_unsafe_fn)]
+
+mod executor;
+mod upcall;
+mod waker;
+
+pub use executor::{block_on, Executor, SpawnError};
+pub use upcall::{AsyncUpcall, Wait};
+
+#[cfg(test)]
+mod executor_tests;
+#[cfg(test)]
+mod upcall_tests;
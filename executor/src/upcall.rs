@@ -0,0 +1,86 @@
+use core::cell::Cell;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use libtock_platform::subscribe::AnyId;
+use libtock_platform::Upcall;
+
+/// An `Upcall` that async code can wait on. `AsyncUpcall` is passed to
+/// `Syscalls::subscribe` like any other upcall; when the kernel invokes it, it
+/// stores the upcall's arguments and wakes the task awaiting
+/// [`AsyncUpcall::wait`].
+///
+/// If the upcall is invoked more than once before the task retrieves its
+/// arguments, only the most recent arguments are kept.
+///
+/// # Example
+/// ```ignore
+/// let upcall = AsyncUpcall::new();
+/// share::scope(|subscribe| {
+///     S::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe, &upcall)?;
+///     S::command(DRIVER_NUM, START, 0, 0).to_result::<(), ErrorCode>()?;
+///     let (arg0, _, _) = upcall.wait().await;
+///     // ...
+/// })
+/// ```
+#[derive(Default)]
+pub struct AsyncUpcall {
+    args: Cell<Option<(u32, u32, u32)>>,
+    waker: Cell<Option<Waker>>,
+}
+
+impl AsyncUpcall {
+    pub const fn new() -> Self {
+        Self {
+            args: Cell::new(None),
+            waker: Cell::new(None),
+        }
+    }
+
+    /// Returns a future that resolves to the arguments of the next upcall. If
+    /// the upcall was invoked before `wait` was called and its arguments have
+    /// not been retrieved yet, the future resolves immediately.
+    pub fn wait(&self) -> Wait<'_> {
+        Wait { upcall: self }
+    }
+
+    /// Retrieves the arguments of the most recent upcall, if the upcall was
+    /// invoked since its arguments were last retrieved.
+    pub fn take(&self) -> Option<(u32, u32, u32)> {
+        self.args.take()
+    }
+
+    /// Returns `true` if the upcall was invoked since its arguments were last
+    /// retrieved.
+    pub fn is_pending(&self) -> bool {
+        self.args.get().is_some()
+    }
+}
+
+impl Upcall<AnyId> for AsyncUpcall {
+    fn upcall(&self, arg0: u32, arg1: u32, arg2: u32) {
+        self.args.set(Some((arg0, arg1, arg2)));
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Future returned by [`AsyncUpcall::wait`].
+pub struct Wait<'u> {
+    upcall: &'u AsyncUpcall,
+}
+
+impl Future for Wait<'_> {
+    type Output = (u32, u32, u32);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<(u32, u32, u32)> {
+        match self.upcall.args.take() {
+            Some(args) => Poll::Ready(args),
+            None => {
+                self.upcall.waker.set(Some(cx.waker().clone()));
+                Poll::Pending
+            }
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll};
+use libtock_platform::Upcall;
+
+use crate::waker::{wake_count, waker};
+use crate::AsyncUpcall;
+
+#[test]
+fn take() {
+    let upcall = AsyncUpcall::new();
+    assert!(!upcall.is_pending());
+    assert_eq!(upcall.take(), None);
+
+    upcall.upcall(1, 2, 3);
+    upcall.upcall(4, 5, 6);
+    assert!(upcall.is_pending());
+    assert_eq!(upcall.take(), Some((4, 5, 6)));
+    assert!(!upcall.is_pending());
+    assert_eq!(upcall.take(), None);
+}
+
+#[test]
+fn wait() {
+    let upcall = AsyncUpcall::new();
+    let waker = waker();
+    let mut cx = Context::from_waker(&waker);
+
+    // An upcall that happened before wait() was called is not lost.
+    upcall.upcall(1, 2, 3);
+    assert_eq!(pin!(upcall.wait()).poll(&mut cx), Poll::Ready((1, 2, 3)));
+
+    let mut wait = pin!(upcall.wait());
+    assert_eq!(wait.as_mut().poll(&mut cx), Poll::Pending);
+    let wake_count_before = wake_count();
+    upcall.upcall(4, 5, 6);
+    assert_ne!(wake_count(), wake_count_before);
+    assert_eq!(wait.as_mut().poll(&mut cx), Poll::Ready((4, 5, 6)));
+    assert!(!upcall.is_pending());
+}
@@ -0,0 +1,50 @@
+//! The `Waker` used by the executor.
+//!
+//! Every task is woken through the same `Waker`, which records the wakeup by
+//! advancing a global counter. The executor compares the counter before and
+//! after a pass to decide whether it is safe to go to sleep in Yield. Because
+//! the executor polls every unfinished task on every pass, it does not need to
+//! know *which* task was woken, which lets the `Waker` carry no data at all.
+//! That in turn means a `Waker` can never dangle: a task may hold onto it (for
+//! example inside an `AsyncUpcall`) for as long as it likes.
+//!
+//! Not every Tock target supports atomic read-modify-write operations, so on
+//! those targets the counter is advanced with a plain load and store. That is
+//! sufficient because Tock processes are single-threaded. Hosts (where unit
+//! tests run several executors on different threads) use `fetch_add`; there,
+//! one executor may observe another executor's wakeup, which only results in
+//! an extra pass.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::task::{RawWaker, RawWakerVTable, Waker};
+
+static WAKE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns a value that changes whenever a task is woken.
+pub(crate) fn wake_count() -> usize {
+    WAKE_COUNT.load(Ordering::Relaxed)
+}
+
+/// Returns the `Waker` passed to every task.
+pub(crate) fn waker() -> Waker {
+    // Safety: VTABLE's functions satisfy the RawWakerVTable contract for a null
+    // data pointer: they never dereference it, and are safe to call from any
+    // thread.
+    unsafe { Waker::from_raw(RAW_WAKER) }
+}
+
+const RAW_WAKER: RawWaker = RawWaker::new(core::ptr::null(), &VTABLE);
+const VTABLE: RawWakerVTable = RawWakerVTable::new(clone, wake, wake, drop);
+
+unsafe fn clone(_: *const ()) -> RawWaker {
+    RAW_WAKER
+}
+
+unsafe fn wake(_: *const ()) {
+    #[cfg(target_has_atomic = "ptr")]
+    WAKE_COUNT.fetch_add(1, Ordering::Relaxed);
+    #[cfg(not(target_has_atomic = "ptr"))]
+    WAKE_COUNT.store(wake_count().wrapping_add(1), Ordering::Relaxed);
+}
+
+unsafe fn drop(_: *const ()) {}
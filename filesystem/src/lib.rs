@@ -0,0 +1,416 @@
+//! `libtock_filesystem` is a small filesystem for the process' isolated
+//! nonvolatile storage region. It supports files and directories, and is
+//! designed for the things apps typically keep in storage: configuration
+//! files, which are rewritten as a whole, and logs, which are appended to.
+//!
+//! The filesystem is log-structured: every change is appended to a log that
+//! cycles through the whole region, so writes are spread evenly across the
+//! flash rather than wearing out the pages that hold frequently rewritten
+//! files. It does not allocate, and keeps no state beyond the position of the
+//! end of the log, so every lookup scans the log. It is intended for regions
+//! of a few kilobytes.
+//!
+//! The filesystem survives power loss at any point. Each of these operations
+//! either completes or has no effect:
+//!
+//! - [`FileSystem::write_file`], which replaces the whole contents of a file.
+//! - [`FileSystem::rename`], which also replaces the destination, if any.
+//! - [`FileSystem::create_dir`] and [`FileSystem::remove`].
+//!
+//! [`FileSystem::append`] writes its data in chunks of up to [`DATA_CHUNK`]
+//! bytes, and a power loss can keep some of the chunks but not others. A file
+//! always ends at a chunk boundary, though, so appending one line at a time
+//! never leaves half a line behind.
+//!
+//! Paths are separated by `/`, and are relative to the root directory whether
+//! or not they start with a `/`. Names can be up to [`NAME_MAX`] bytes long.
+//!
+//! # Example
+//! ```ignore
+//! use libtock::filesystem::{Error, FileSystem};
+//!
+//! let mut fs = match FileSystem::mount(4096) {
+//!     Err(Error::NotFormatted) => {
+//!         FileSystem::format(4096)?;
+//!         FileSystem::mount(4096)?
+//!     }
+//!     result => result?,
+//! };
+//! fs.create_dir("/logs")?;
+//! fs.append("/logs/boot", b"booted\n")?;
+//! fs.write_file("/config", &config)?;
+//! ```
+
+#![no_std]
+
+mod log;
+
+use core::marker::PhantomData;
+use libtock_isolated_nonvolatile_storage::{Config, IsolatedNonvolatileStorage};
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+use log::{Position, Record, MIN_SECTOR_SIZE, ROOT};
+
+pub use log::{DATA_CHUNK, NAME_MAX};
+
+/// A filesystem stored in the isolated nonvolatile storage region.
+///
+/// The region is divided into sectors of `sector_size` bytes, which must be
+/// the same every time the filesystem is mounted. Larger sectors use the
+/// region more efficiently, as the last few bytes of each sector are often
+/// left unused, but the filesystem must have at least two sectors and each
+/// file's data is moved a sector at a time. A good choice is a few times the
+/// size of the largest file.
+pub struct FileSystem<S: Syscalls, C: Config = DefaultConfig> {
+    sector_size: u32,
+    sector_count: u32,
+    head: u32,
+    head_seq: u32,
+    head_offset: u32,
+    next_id: u32,
+    // A file whose data is being written but that does not exist yet.
+    pending: Option<u32>,
+    _syscalls: PhantomData<(S, C)>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EntryKind {
+    File,
+    Directory,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Metadata {
+    pub kind: EntryKind,
+    /// The length of the file, in bytes. Always 0 for directories.
+    pub len: u32,
+}
+
+/// A directory entry, as passed to the callback of [`FileSystem::read_dir`].
+pub struct DirEntry {
+    name: [u8; NAME_MAX],
+    name_len: usize,
+    kind: EntryKind,
+}
+
+impl DirEntry {
+    pub fn name(&self) -> &str {
+        core::str::from_utf8(&self.name[..self.name_len]).unwrap_or_default()
+    }
+
+    pub fn kind(&self) -> EntryKind {
+        self.kind
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The storage driver returned an error.
+    Storage(ErrorCode),
+    /// The region does not contain a filesystem with this sector size.
+    NotFormatted,
+    NotFound,
+    AlreadyExists,
+    NotADirectory,
+    IsADirectory,
+    DirectoryNotEmpty,
+    /// The path is empty, names the root directory where that is not
+    /// allowed, contains a `.` or `..` component or a name longer than
+    /// [`NAME_MAX`], or would move a directory inside itself.
+    InvalidPath,
+    /// The live files and directories do not fit in the region.
+    NoSpace,
+}
+
+#[derive(Clone, Copy)]
+struct Entry {
+    id: u32,
+    kind: EntryKind,
+}
+
+impl<S: Syscalls, C: Config> FileSystem<S, C> {
+    /// Erases the whole storage region and creates an empty filesystem.
+    pub fn format(sector_size: u32) -> Result<(), Error> {
+        let fs = Self::new(sector_size)?;
+        for sector in 0..fs.sector_count {
+            fs.erase_sector(sector)?;
+        }
+        fs.write_sector_header(0, 1)
+    }
+
+    /// Mounts the filesystem, finishing any garbage collection that was
+    /// interrupted by a power loss.
+    pub fn mount(sector_size: u32) -> Result<Self, Error> {
+        let mut fs = Self::new(sector_size)?;
+        fs.find_head()?;
+        fs.next_id = fs.max_id()? + 1;
+        fs.collect_if_needed()?;
+        Ok(fs)
+    }
+
+    fn new(sector_size: u32) -> Result<Self, Error> {
+        let sector_count = IsolatedNonvolatileStorage::<S, C>::size()? / sector_size.max(1) as u64;
+        if sector_size < MIN_SECTOR_SIZE || sector_count < 2 {
+            return Err(ErrorCode::Invalid.into());
+        }
+        Ok(FileSystem {
+            sector_size,
+            sector_count: sector_count.min(u32::MAX as u64) as u32,
+            head: 0,
+            head_seq: 0,
+            head_offset: 0,
+            next_id: ROOT + 1,
+            pending: None,
+            _syscalls: PhantomData,
+        })
+    }
+
+    /// Creates a directory. Its parent directory must already exist.
+    pub fn create_dir(&mut self, path: &str) -> Result<(), Error> {
+        let (parent, name) = self.resolve_parent(path)?;
+        if self.find(parent, name.as_bytes())?.is_some() {
+            return Err(Error::AlreadyExists);
+        }
+        let id = self.allocate_id();
+        self.append_record(&Record::bind(
+            id,
+            parent,
+            EntryKind::Directory,
+            name.as_bytes(),
+        ))
+    }
+
+    /// Creates a file containing `data`, or atomically replaces the contents
+    /// of an existing file.
+    pub fn write_file(&mut self, path: &str, data: &[u8]) -> Result<(), Error> {
+        let (parent, name) = self.resolve_parent(path)?;
+        if let Some((_, EntryKind::Directory)) = self.find(parent, name.as_bytes())? {
+            return Err(Error::IsADirectory);
+        }
+        // The data is written to a new file, which then replaces the old one
+        // with a single record.
+        let id = self.allocate_id();
+        self.pending = Some(id);
+        let result = self.append_data(id, 0, data).and_then(|()| {
+            self.append_record(&Record::bind(id, parent, EntryKind::File, name.as_bytes()))
+        });
+        self.pending = None;
+        result
+    }
+
+    /// Appends `data` to a file, creating it if it does not exist.
+    pub fn append(&mut self, path: &str, data: &[u8]) -> Result<(), Error> {
+        let (parent, name) = self.resolve_parent(path)?;
+        let (id, len) = match self.find(parent, name.as_bytes())? {
+            Some((_, EntryKind::Directory)) => return Err(Error::IsADirectory),
+            Some((id, EntryKind::File)) => (id, self.file_len(id)?),
+            None => {
+                let id = self.allocate_id();
+                self.append_record(&Record::bind(id, parent, EntryKind::File, name.as_bytes()))?;
+                (id, 0)
+            }
+        };
+        self.append_data(id, len, data)
+    }
+
+    /// Reads the contents of a file starting at `offset` into `buf`, returning
+    /// the number of bytes read. Returns 0 at the end of the file.
+    pub fn read(&self, path: &str, offset: u32, buf: &mut [u8]) -> Result<usize, Error> {
+        let file = self.resolve_file(path)?;
+        let len = self.file_len(file.id)?;
+        let end = len.min(offset.saturating_add(buf.len() as u32));
+        if offset >= end {
+            return Ok(0);
+        }
+        self.scan(Position::START, |_, record| {
+            if record.is_data() && record.id == file.id {
+                let chunk_start = record.data_offset();
+                let chunk_end = chunk_start + record.data_bytes().len() as u32;
+                let start = chunk_start.max(offset);
+                let stop = chunk_end.min(end);
+                if start < stop {
+                    buf[(start - offset) as usize..(stop - offset) as usize].copy_from_slice(
+                        &record.data_bytes()
+                            [(start - chunk_start) as usize..(stop - chunk_start) as usize],
+                    );
+                }
+            }
+            Ok(true)
+        })?;
+        Ok((end - offset) as usize)
+    }
+
+    pub fn metadata(&self, path: &str) -> Result<Metadata, Error> {
+        let entry = self.resolve(path)?;
+        let len = match entry.kind {
+            EntryKind::File => self.file_len(entry.id)?,
+            EntryKind::Directory => 0,
+        };
+        Ok(Metadata {
+            kind: entry.kind,
+            len,
+        })
+    }
+
+    pub fn exists(&self, path: &str) -> Result<bool, Error> {
+        match self.resolve(path) {
+            Ok(_) => Ok(true),
+            Err(Error::NotFound) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Calls `f` on each entry of a directory.
+    pub fn read_dir<F: FnMut(&DirEntry)>(&self, path: &str, mut f: F) -> Result<(), Error> {
+        let dir = self.resolve(path)?;
+        if dir.kind != EntryKind::Directory {
+            return Err(Error::NotADirectory);
+        }
+        self.scan(Position::START, |position, record| {
+            if record.is_bind() && record.parent() == dir.id && self.is_live(position, record)? {
+                let mut entry = DirEntry {
+                    name: [0; NAME_MAX],
+                    name_len: record.name().len(),
+                    kind: record.entry_kind(),
+                };
+                entry.name[..entry.name_len].copy_from_slice(record.name());
+                f(&entry);
+            }
+            Ok(true)
+        })
+    }
+
+    /// Removes a file or an empty directory.
+    pub fn remove(&mut self, path: &str) -> Result<(), Error> {
+        let entry = self.resolve(path)?;
+        if entry.id == ROOT {
+            return Err(Error::InvalidPath);
+        }
+        if entry.kind == EntryKind::Directory && self.has_children(entry.id)? {
+            return Err(Error::DirectoryNotEmpty);
+        }
+        self.append_record(&Record::delete(entry.id))
+    }
+
+    /// Atomically moves a file or directory to `to`. If `to` already exists, it
+    /// is replaced: it must be of the same kind, and if it is a directory, it
+    /// must be empty.
+    pub fn rename(&mut self, from: &str, to: &str) -> Result<(), Error> {
+        let entry = self.resolve(from)?;
+        if entry.id == ROOT {
+            return Err(Error::InvalidPath);
+        }
+        let (parent, name) = self.resolve_parent(to)?;
+        match self.find(parent, name.as_bytes())? {
+            Some((id, _)) if id == entry.id => return Ok(()),
+            Some((_, kind)) if kind != entry.kind => {
+                return Err(match kind {
+                    EntryKind::File => Error::NotADirectory,
+                    EntryKind::Directory => Error::IsADirectory,
+                })
+            }
+            Some((id, EntryKind::Directory)) if self.has_children(id)? => {
+                return Err(Error::DirectoryNotEmpty)
+            }
+            _ => {}
+        }
+        if entry.kind == EntryKind::Directory {
+            let mut ancestor = parent;
+            while ancestor != ROOT {
+                if ancestor == entry.id {
+                    return Err(Error::InvalidPath);
+                }
+                ancestor = match self.last_binding(ancestor)? {
+                    Some((_, record)) => record.parent(),
+                    None => ROOT,
+                };
+            }
+        }
+        self.append_record(&Record::bind(entry.id, parent, entry.kind, name.as_bytes()))
+    }
+
+    fn allocate_id(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn append_data(&mut self, id: u32, mut offset: u32, data: &[u8]) -> Result<(), Error> {
+        for chunk in data.chunks(DATA_CHUNK) {
+            self.append_record(&Record::data(id, offset, chunk))?;
+            offset += chunk.len() as u32;
+        }
+        Ok(())
+    }
+
+    fn file_len(&self, id: u32) -> Result<u32, Error> {
+        let mut len = 0;
+        self.scan(Position::START, |_, record| {
+            if record.is_data() && record.id == id {
+                len = len.max(record.data_offset() + record.data_bytes().len() as u32);
+            }
+            Ok(true)
+        })?;
+        Ok(len)
+    }
+
+    fn has_children(&self, dir: u32) -> Result<bool, Error> {
+        let mut found = false;
+        self.scan(Position::START, |position, record| {
+            found = record.is_bind() && record.parent() == dir && self.is_live(position, record)?;
+            Ok(!found)
+        })?;
+        Ok(found)
+    }
+
+    fn resolve(&self, path: &str) -> Result<Entry, Error> {
+        let mut entry = Entry {
+            id: ROOT,
+            kind: EntryKind::Directory,
+        };
+        for name in path.split('/').filter(|name| !name.is_empty()) {
+            check_name(name)?;
+            if entry.kind != EntryKind::Directory {
+                return Err(Error::NotADirectory);
+            }
+            let (id, kind) = self
+                .find(entry.id, name.as_bytes())?
+                .ok_or(Error::NotFound)?;
+            entry = Entry { id, kind };
+        }
+        Ok(entry)
+    }
+
+    fn resolve_file(&self, path: &str) -> Result<Entry, Error> {
+        let entry = self.resolve(path)?;
+        match entry.kind {
+            EntryKind::File => Ok(entry),
+            EntryKind::Directory => Err(Error::IsADirectory),
+        }
+    }
+
+    /// Returns the ID of the directory that contains `path`, and the last
+    /// component of `path`.
+    fn resolve_parent<'p>(&self, path: &'p str) -> Result<(u32, &'p str), Error> {
+        let path = path.trim_end_matches('/');
+        let (dir, name) = path.rsplit_once('/').unwrap_or(("", path));
+        if name.is_empty() {
+            return Err(Error::InvalidPath);
+        }
+        check_name(name)?;
+        let dir = self.resolve(dir)?;
+        if dir.kind != EntryKind::Directory {
+            return Err(Error::NotADirectory);
+        }
+        Ok((dir.id, name))
+    }
+}
+
+fn check_name(name: &str) -> Result<(), Error> {
+    if name.len() > NAME_MAX || name == "." || name == ".." {
+        return Err(Error::InvalidPath);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
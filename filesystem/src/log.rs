@@ -0,0 +1,503 @@
+//! The on-storage log.
+//!
+//! The storage region is split into sectors, which are used as a ring. Each
+//! sector in use starts with a header holding a sequence number, which orders
+//! the sectors from oldest to newest; the newest sector is the head of the log.
+//! Records are appended to the head until it is full, and then the log moves on
+//! to the next sector in the ring, which is erased first. As the log goes
+//! around the ring, every sector is erased equally often, and data that never
+//! changes is moved along by garbage collection rather than pinning down the
+//! sectors it was first written to.
+//!
+//! There is always at least one free sector after the head. When the log moves
+//! into the last free sector, the oldest sector is garbage collected: its live
+//! records are copied to the new head, and then its header is cleared. A
+//! sector's live records always fit in an empty sector, so collection cannot
+//! run out of space.
+//!
+//! Every record is written with a single write, and carries a CRC that covers
+//! the sequence number of its sector. A torn write therefore never produces a
+//! valid record, and records left over from a sector's previous use are
+//! ignored. Copying records during collection only duplicates them, which
+//! does not change their meaning, so a collection interrupted by a power loss
+//! is simply finished at the next mount.
+
+use crate::{EntryKind, Error, FileSystem};
+use libtock_isolated_nonvolatile_storage::{Config, IsolatedNonvolatileStorage};
+use libtock_platform::{ErrorCode, Syscalls};
+
+/// The longest name a file or directory can have, in bytes.
+pub const NAME_MAX: usize = 32;
+
+/// The ID of the root directory, which is never written to the log.
+pub(crate) const ROOT: u32 = 0;
+
+/// The most file data stored in a single record.
+pub const DATA_CHUNK: usize = MAX_PAYLOAD - 4;
+
+/// The smallest supported sector size.
+pub(crate) const MIN_SECTOR_SIZE: u32 = SECTOR_HEADER_LEN + RECORD_HEADER_LEN + MAX_PAYLOAD as u32;
+
+const SECTOR_MAGIC: u32 = 0x5346_4b54;
+const SECTOR_HEADER_LEN: u32 = 12;
+const RECORD_HEADER_LEN: u32 = 12;
+const MAX_PAYLOAD: usize = 128;
+const ERASED: u8 = 0xFF;
+
+// Record kinds
+const BIND: u8 = 1;
+const DELETE: u8 = 2;
+const DATA: u8 = 3;
+
+/// The location of a record, in log order.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub(crate) struct Position {
+    seq: u32,
+    offset: u32,
+}
+
+impl Position {
+    /// The position of the oldest record in the log.
+    pub const START: Position = Position { seq: 0, offset: 0 };
+
+    /// The position of the record following `record`, which is at `self`.
+    pub fn after(self, record: &Record) -> Position {
+        Position {
+            seq: self.seq,
+            offset: self.offset + record.size(),
+        }
+    }
+}
+
+/// A log record. There are three kinds:
+///
+/// - BIND gives entry `id` a kind, a parent directory and a name. It creates,
+///   renames or moves the entry, and replaces any other entry that had the
+///   same parent and name.
+/// - DELETE removes entry `id`.
+/// - DATA stores part of the contents of file `id`, at an offset.
+#[derive(Clone, Copy)]
+pub(crate) struct Record {
+    kind: u8,
+    pub id: u32,
+    len: u16,
+    payload: [u8; MAX_PAYLOAD],
+}
+
+impl Record {
+    fn new(kind: u8, id: u32, parts: &[&[u8]]) -> Record {
+        let mut record = Record {
+            kind,
+            id,
+            len: 0,
+            payload: [0; MAX_PAYLOAD],
+        };
+        for part in parts {
+            let start = record.len as usize;
+            record.payload[start..start + part.len()].copy_from_slice(part);
+            record.len += part.len() as u16;
+        }
+        record
+    }
+
+    /// `name` must be at most [`NAME_MAX`] bytes long.
+    pub fn bind(id: u32, parent: u32, kind: EntryKind, name: &[u8]) -> Record {
+        let kind = match kind {
+            EntryKind::File => 0,
+            EntryKind::Directory => 1,
+        };
+        Record::new(BIND, id, &[&parent.to_le_bytes(), &[kind], name])
+    }
+
+    pub fn delete(id: u32) -> Record {
+        Record::new(DELETE, id, &[])
+    }
+
+    /// `data` must be at most [`DATA_CHUNK`] bytes long.
+    pub fn data(id: u32, offset: u32, data: &[u8]) -> Record {
+        Record::new(DATA, id, &[&offset.to_le_bytes(), data])
+    }
+
+    /// The number of bytes the record takes up in the log.
+    pub fn size(&self) -> u32 {
+        RECORD_HEADER_LEN + self.len as u32
+    }
+
+    fn payload(&self) -> &[u8] {
+        &self.payload[..self.len as usize]
+    }
+
+    fn u32_at(&self, index: usize) -> u32 {
+        let bytes = &self.payload[index..index + 4];
+        u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    pub fn is_bind(&self) -> bool {
+        self.kind == BIND
+    }
+
+    pub fn is_delete(&self) -> bool {
+        self.kind == DELETE
+    }
+
+    pub fn is_data(&self) -> bool {
+        self.kind == DATA
+    }
+
+    // BIND fields
+
+    pub fn parent(&self) -> u32 {
+        self.u32_at(0)
+    }
+
+    pub fn entry_kind(&self) -> EntryKind {
+        match self.payload[4] {
+            0 => EntryKind::File,
+            _ => EntryKind::Directory,
+        }
+    }
+
+    pub fn name(&self) -> &[u8] {
+        &self.payload()[5..]
+    }
+
+    /// Returns true if this is a BIND record that gives an entry this parent
+    /// and name.
+    pub fn binds(&self, parent: u32, name: &[u8]) -> bool {
+        self.is_bind() && self.parent() == parent && self.name() == name
+    }
+
+    // DATA fields
+
+    pub fn data_offset(&self) -> u32 {
+        self.u32_at(0)
+    }
+
+    pub fn data_bytes(&self) -> &[u8] {
+        &self.payload()[4..]
+    }
+
+    fn crc(&self, header: &[u8], seq: u32) -> u32 {
+        let crc = crc32(0, &seq.to_le_bytes());
+        crc32(crc32(crc, header), self.payload())
+    }
+
+    /// Encodes the record as it is stored in a sector with sequence number
+    /// `seq`, returning the number of bytes used.
+    fn encode(&self, seq: u32, buffer: &mut [u8]) -> usize {
+        buffer[0] = self.kind;
+        buffer[1] = 0;
+        buffer[2..4].copy_from_slice(&self.len.to_le_bytes());
+        buffer[4..8].copy_from_slice(&self.id.to_le_bytes());
+        let crc = self.crc(&buffer[..8], seq);
+        buffer[8..12].copy_from_slice(&crc.to_le_bytes());
+        let end = self.size() as usize;
+        buffer[RECORD_HEADER_LEN as usize..end].copy_from_slice(self.payload());
+        end
+    }
+
+    /// Decodes a record stored in a sector with sequence number `seq`. Returns
+    /// `None` if `bytes` does not start with a valid record.
+    fn decode(seq: u32, bytes: &[u8]) -> Option<Record> {
+        let header = bytes.get(..RECORD_HEADER_LEN as usize)?;
+        let len = u16::from_le_bytes([header[2], header[3]]);
+        let min_len = match header[0] {
+            BIND => 5,
+            DELETE => 0,
+            DATA => 4,
+            _ => return None,
+        };
+        if len < min_len || len as usize > MAX_PAYLOAD {
+            return None;
+        }
+        let payload =
+            bytes.get(RECORD_HEADER_LEN as usize..(RECORD_HEADER_LEN + len as u32) as usize)?;
+        let record = Record::new(
+            header[0],
+            u32::from_le_bytes([header[4], header[5], header[6], header[7]]),
+            &[payload],
+        );
+        let crc = u32::from_le_bytes([header[8], header[9], header[10], header[11]]);
+        if record.crc(&header[..8], seq) != crc {
+            return None;
+        }
+        Some(record)
+    }
+}
+
+/// CRC-32 (IEEE 802.3), continuing from `crc`.
+fn crc32(crc: u32, bytes: &[u8]) -> u32 {
+    let mut crc = !crc;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                crc >> 1 ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+impl<S: Syscalls, C: Config> FileSystem<S, C> {
+    fn address(&self, sector: u32, offset: u32) -> u64 {
+        sector as u64 * self.sector_size as u64 + offset as u64
+    }
+
+    /// Returns the sequence number of `sector`, or `None` if it is free.
+    pub(crate) fn sector_seq(&self, sector: u32) -> Result<Option<u32>, Error> {
+        let mut header = [0; SECTOR_HEADER_LEN as usize];
+        IsolatedNonvolatileStorage::<S, C>::read(self.address(sector, 0), &mut header)?;
+        let magic = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let seq = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        let crc = u32::from_le_bytes([header[8], header[9], header[10], header[11]]);
+        match magic == SECTOR_MAGIC && crc32(0, &header[..8]) == crc {
+            true => Ok(Some(seq)),
+            false => Ok(None),
+        }
+    }
+
+    pub(crate) fn write_sector_header(&self, sector: u32, seq: u32) -> Result<(), Error> {
+        let mut header = [0; SECTOR_HEADER_LEN as usize];
+        header[..4].copy_from_slice(&SECTOR_MAGIC.to_le_bytes());
+        header[4..8].copy_from_slice(&seq.to_le_bytes());
+        let crc = crc32(0, &header[..8]);
+        header[8..].copy_from_slice(&crc.to_le_bytes());
+        IsolatedNonvolatileStorage::<S, C>::write(self.address(sector, 0), &header)?;
+        Ok(())
+    }
+
+    /// Overwrites `sector` with erased bytes. Records are then written by
+    /// only clearing bits, so the kernel erases each page of the sector once
+    /// each time the log goes around the ring.
+    pub(crate) fn erase_sector(&self, sector: u32) -> Result<(), Error> {
+        let erased = [ERASED; MAX_PAYLOAD];
+        let mut offset = 0;
+        while offset < self.sector_size {
+            let len = (self.sector_size - offset).min(erased.len() as u32);
+            IsolatedNonvolatileStorage::<S, C>::write(
+                self.address(sector, offset),
+                &erased[..len as usize],
+            )?;
+            offset += len;
+        }
+        Ok(())
+    }
+
+    /// Reads the record at `offset` in `sector`, whose sequence number is
+    /// `seq`. Returns `None` if there is no valid record there.
+    fn read_record(&self, sector: u32, seq: u32, offset: u32) -> Result<Option<Record>, Error> {
+        let mut buffer = [0; RECORD_HEADER_LEN as usize + MAX_PAYLOAD];
+        let len = buffer.len().min((self.sector_size - offset) as usize);
+        if len < RECORD_HEADER_LEN as usize {
+            return Ok(None);
+        }
+        IsolatedNonvolatileStorage::<S, C>::read(self.address(sector, offset), &mut buffer[..len])?;
+        Ok(Record::decode(seq, &buffer[..len]))
+    }
+
+    /// Finds the head of the log: the sector with the highest sequence
+    /// number, and the end of its records.
+    pub(crate) fn find_head(&mut self) -> Result<(), Error> {
+        let mut newest = None;
+        for sector in 0..self.sector_count {
+            if let Some(seq) = self.sector_seq(sector)? {
+                if newest.is_none_or(|(_, newest_seq)| seq > newest_seq) {
+                    newest = Some((sector, seq));
+                }
+            }
+        }
+        let (head, head_seq) = newest.ok_or(Error::NotFormatted)?;
+        let mut offset = SECTOR_HEADER_LEN;
+        while let Some(record) = self.read_record(head, head_seq, offset)? {
+            offset += record.size();
+        }
+        self.head = head;
+        self.head_seq = head_seq;
+        self.head_offset = offset;
+        Ok(())
+    }
+
+    /// Calls `f` on each record from `from` to the end of the log, in order,
+    /// for as long as it returns `Ok(true)`.
+    pub(crate) fn scan<F>(&self, from: Position, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(Position, &Record) -> Result<bool, Error>,
+    {
+        // The sectors after the head are the oldest ones (or free).
+        for i in 1..=self.sector_count {
+            let sector = (self.head + i) % self.sector_count;
+            let seq = match self.sector_seq(sector)? {
+                Some(seq) if seq >= from.seq => seq,
+                _ => continue,
+            };
+            let end = match sector == self.head {
+                true => self.head_offset,
+                false => self.sector_size,
+            };
+            let mut position = Position {
+                seq,
+                offset: SECTOR_HEADER_LEN,
+            };
+            if seq == from.seq {
+                position.offset = position.offset.max(from.offset);
+            }
+            while position.offset < end {
+                let record = match self.read_record(sector, seq, position.offset)? {
+                    Some(record) => record,
+                    None => break,
+                };
+                if !f(position, &record)? {
+                    return Ok(());
+                }
+                position = position.after(&record);
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends `record` to the log, moving on to the next sector if the head
+    /// is full.
+    pub(crate) fn append_record(&mut self, record: &Record) -> Result<(), Error> {
+        for _ in 0..self.sector_count {
+            if self.head_offset + record.size() <= self.sector_size {
+                return self.write_record(record);
+            }
+            self.advance()?;
+        }
+        Err(Error::NoSpace)
+    }
+
+    fn write_record(&mut self, record: &Record) -> Result<(), Error> {
+        let mut buffer = [0; RECORD_HEADER_LEN as usize + MAX_PAYLOAD];
+        let len = record.encode(self.head_seq, &mut buffer);
+        IsolatedNonvolatileStorage::<S, C>::write(
+            self.address(self.head, self.head_offset),
+            &buffer[..len],
+        )?;
+        self.head_offset += record.size();
+        Ok(())
+    }
+
+    /// Opens the next sector as the head, collecting the oldest sector if no
+    /// free sector would be left otherwise.
+    fn advance(&mut self) -> Result<(), Error> {
+        let next = (self.head + 1) % self.sector_count;
+        self.erase_sector(next)?;
+        self.write_sector_header(next, self.head_seq + 1)?;
+        self.head = next;
+        self.head_seq += 1;
+        self.head_offset = SECTOR_HEADER_LEN;
+        self.collect_if_needed()
+    }
+
+    /// Collects the sector after the head, unless it is already free.
+    pub(crate) fn collect_if_needed(&mut self) -> Result<(), Error> {
+        let oldest = (self.head + 1) % self.sector_count;
+        let seq = match self.sector_seq(oldest)? {
+            Some(seq) => seq,
+            None => return Ok(()),
+        };
+        let mut offset = SECTOR_HEADER_LEN;
+        while let Some(record) = self.read_record(oldest, seq, offset)? {
+            let position = Position { seq, offset };
+            offset += record.size();
+            if self.is_live(position, &record)? {
+                if self.head_offset + record.size() > self.sector_size {
+                    return Err(Error::NoSpace);
+                }
+                self.write_record(&record)?;
+            }
+        }
+        // Clearing bits never needs an erase, so this cannot wear the sector.
+        IsolatedNonvolatileStorage::<S, C>::write(
+            self.address(oldest, 0),
+            &[0; SECTOR_HEADER_LEN as usize],
+        )?;
+        Ok(())
+    }
+
+    /// Returns true if `record`, at `position`, still affects the contents of
+    /// the filesystem, ignoring any duplicate of it written later.
+    pub(crate) fn is_live(&self, position: Position, record: &Record) -> Result<bool, Error> {
+        let mut live = true;
+        if record.is_bind() {
+            // A binding is undone by a later binding or deletion of the same
+            // entry, or by binding another entry to the same name.
+            self.scan(position.after(record), |_, later| {
+                live = !(later.id == record.id && (later.is_bind() || later.is_delete())
+                    || later.binds(record.parent(), record.name()));
+                Ok(live)
+            })?;
+        } else if record.is_data() {
+            if self.pending != Some(record.id) && !self.is_live_id(record.id)? {
+                return Ok(false);
+            }
+            self.scan(position.after(record), |_, later| {
+                live = !(later.is_data()
+                    && later.id == record.id
+                    && later.data_offset() == record.data_offset());
+                Ok(live)
+            })?;
+        } else {
+            // A deletion outlives the bindings of its entry, all of which come
+            // earlier in the log.
+            live = false;
+        }
+        Ok(live)
+    }
+
+    /// Returns true if entry `id` exists.
+    fn is_live_id(&self, id: u32) -> Result<bool, Error> {
+        match self.last_binding(id)? {
+            Some((position, record)) => self.is_live(position, &record),
+            None => Ok(false),
+        }
+    }
+
+    /// Returns the last BIND record for entry `id`.
+    pub(crate) fn last_binding(&self, id: u32) -> Result<Option<(Position, Record)>, Error> {
+        let mut last = None;
+        self.scan(Position::START, |position, record| {
+            if record.is_bind() && record.id == id {
+                last = Some((position, *record));
+            }
+            Ok(true)
+        })?;
+        Ok(last)
+    }
+
+    /// Returns the entry with this parent and name, if there is one.
+    pub(crate) fn find(&self, parent: u32, name: &[u8]) -> Result<Option<(u32, EntryKind)>, Error> {
+        let mut found: Option<(u32, EntryKind)> = None;
+        self.scan(Position::START, |_, record| {
+            if record.binds(parent, name) {
+                found = Some((record.id, record.entry_kind()));
+            } else if found.is_some_and(|(id, _)| id == record.id)
+                && (record.is_bind() || record.is_delete())
+            {
+                found = None;
+            }
+            Ok(true)
+        })?;
+        Ok(found)
+    }
+
+    /// Returns the highest entry ID used in the log.
+    pub(crate) fn max_id(&self) -> Result<u32, Error> {
+        let mut max = ROOT;
+        self.scan(Position::START, |_, record| {
+            max = max.max(record.id);
+            Ok(true)
+        })?;
+        Ok(max)
+    }
+}
+
+impl From<ErrorCode> for Error {
+    fn from(error: ErrorCode) -> Error {
+        Error::Storage(error)
+    }
+}
@@ -0,0 +1,413 @@
+use super::*;
+use libtock_unittest::fake;
+
+type Fs = FileSystem<fake::Syscalls>;
+
+const SECTOR_SIZE: u32 = 512;
+const SECTOR_COUNT: usize = 4;
+const PAGE_SIZE: usize = 128;
+const REGION_SIZE: usize = SECTOR_SIZE as usize * SECTOR_COUNT;
+
+fn formatted() -> Fs {
+    Fs::format(SECTOR_SIZE).unwrap();
+    Fs::mount(SECTOR_SIZE).unwrap()
+}
+
+// Reads a whole file into `buf`, or returns `None` if it does not exist.
+fn read_file<'b>(fs: &Fs, path: &str, buf: &'b mut [u8]) -> Option<&'b [u8]> {
+    match fs.read(path, 0, buf) {
+        Ok(len) => Some(&buf[..len]),
+        Err(Error::NotFound) => None,
+        Err(error) => panic!("Unable to read {path}: {error:?}"),
+    }
+}
+
+fn assert_file(fs: &Fs, path: &str, expected: Option<&[u8]>) {
+    assert_eq!(read_file(fs, path, &mut [0; 512]), expected, "{path}");
+}
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert_eq!(
+        Fs::mount(SECTOR_SIZE).err(),
+        Some(Error::Storage(ErrorCode::NoDevice))
+    );
+}
+
+#[test]
+fn mount() {
+    let kernel = fake::Kernel::new();
+    let storage = fake::IsolatedNonvolatileStorage::new_with_page_size(REGION_SIZE, PAGE_SIZE);
+    kernel.add_driver(&storage);
+
+    assert_eq!(Fs::mount(SECTOR_SIZE).err(), Some(Error::NotFormatted));
+    // The region must hold at least two sectors, each large enough for a
+    // record.
+    assert_eq!(
+        Fs::format(1024 * 3).err(),
+        Some(Error::Storage(ErrorCode::Invalid))
+    );
+    assert_eq!(
+        Fs::format(64).err(),
+        Some(Error::Storage(ErrorCode::Invalid))
+    );
+    Fs::format(SECTOR_SIZE).unwrap();
+    let fs = Fs::mount(SECTOR_SIZE).unwrap();
+    assert_eq!(
+        fs.metadata("/"),
+        Ok(Metadata {
+            kind: EntryKind::Directory,
+            len: 0
+        })
+    );
+    assert_eq!(fs.read_dir("/", |_| panic!("Root is not empty")), Ok(()));
+}
+
+#[test]
+fn files_and_directories() {
+    let kernel = fake::Kernel::new();
+    let storage = fake::IsolatedNonvolatileStorage::new_with_page_size(REGION_SIZE, PAGE_SIZE);
+    kernel.add_driver(&storage);
+    let mut fs = formatted();
+
+    assert_eq!(fs.create_dir("/logs"), Ok(()));
+    assert_eq!(fs.create_dir("logs/"), Err(Error::AlreadyExists));
+    assert_eq!(fs.create_dir("/logs/old/2024"), Err(Error::NotFound));
+    assert_eq!(fs.write_file("/logs/boot", b"one"), Ok(()));
+    assert_eq!(fs.write_file("/config", &[7; 300]), Ok(()));
+    assert_eq!(fs.write_file("/empty", &[]), Ok(()));
+
+    assert_file(&fs, "/logs/boot", Some(b"one"));
+    assert_file(&fs, "config", Some(&[7; 300]));
+    assert_file(&fs, "/empty", Some(b""));
+    assert_file(&fs, "/missing", None);
+    let mut buf = [0; 4];
+    assert_eq!(fs.read("/config", 298, &mut buf), Ok(2));
+    assert_eq!(fs.read("/config", 400, &mut buf), Ok(0));
+    assert_eq!(
+        fs.metadata("/config"),
+        Ok(Metadata {
+            kind: EntryKind::File,
+            len: 300
+        })
+    );
+    assert_eq!(fs.exists("/logs"), Ok(true));
+    assert_eq!(fs.exists("/logs/boot/x"), Err(Error::NotADirectory));
+
+    // Rewriting a file replaces all of its contents.
+    assert_eq!(fs.write_file("/config", b"short"), Ok(()));
+    assert_file(&fs, "/config", Some(b"short"));
+
+    let mut names = [""; 3];
+    let mut count = 0;
+    let mut dirs = 0;
+    fs.read_dir("/", |entry| {
+        names[count] = match entry.name() {
+            "logs" => "logs",
+            "config" => "config",
+            "empty" => "empty",
+            name => panic!("Unexpected entry {name}"),
+        };
+        count += 1;
+        if entry.kind() == EntryKind::Directory {
+            dirs += 1;
+        }
+    })
+    .unwrap();
+    assert_eq!((count, dirs), (3, 1));
+    assert!(names.contains(&"logs") && names.contains(&"config"));
+
+    assert_eq!(fs.read("/logs", 0, &mut buf), Err(Error::IsADirectory));
+    assert_eq!(fs.write_file("/logs", b""), Err(Error::IsADirectory));
+    assert_eq!(fs.read_dir("/config", |_| {}), Err(Error::NotADirectory));
+    assert_eq!(fs.write_file("/config/x", b""), Err(Error::NotADirectory));
+    assert_eq!(fs.write_file("/", b""), Err(Error::InvalidPath));
+    assert_eq!(fs.write_file("/logs/..", b""), Err(Error::InvalidPath));
+    assert_eq!(
+        fs.write_file("/0123456789abcdef0123456789abcdefX", b""),
+        Err(Error::InvalidPath)
+    );
+    assert_eq!(
+        fs.write_file("/0123456789abcdef0123456789abcdef", b""),
+        Ok(())
+    );
+
+    assert_eq!(fs.remove("/logs"), Err(Error::DirectoryNotEmpty));
+    assert_eq!(fs.remove("/logs/boot"), Ok(()));
+    assert_eq!(fs.remove("/logs"), Ok(()));
+    assert_eq!(fs.remove("/logs"), Err(Error::NotFound));
+    assert_eq!(fs.remove("/"), Err(Error::InvalidPath));
+
+    // Everything survives a remount.
+    let fs = Fs::mount(SECTOR_SIZE).unwrap();
+    assert_file(&fs, "/config", Some(b"short"));
+    assert_file(&fs, "/empty", Some(b""));
+    assert_eq!(fs.exists("/logs"), Ok(false));
+}
+
+#[test]
+fn rename() {
+    let kernel = fake::Kernel::new();
+    let storage = fake::IsolatedNonvolatileStorage::new_with_page_size(REGION_SIZE, PAGE_SIZE);
+    kernel.add_driver(&storage);
+    let mut fs = formatted();
+
+    fs.create_dir("/a").unwrap();
+    fs.create_dir("/a/b").unwrap();
+    fs.write_file("/a/b/file", b"data").unwrap();
+    fs.write_file("/other", b"other").unwrap();
+
+    // Moving a directory moves its contents.
+    assert_eq!(fs.rename("/a/b", "/c"), Ok(()));
+    assert_file(&fs, "/c/file", Some(b"data"));
+    assert_eq!(fs.exists("/a/b"), Ok(false));
+
+    // The destination is replaced.
+    assert_eq!(fs.rename("/other", "/c/file"), Ok(()));
+    assert_file(&fs, "/c/file", Some(b"other"));
+    assert_file(&fs, "/other", None);
+    assert_eq!(fs.rename("/c/file", "/c/file"), Ok(()));
+
+    assert_eq!(fs.rename("/missing", "/x"), Err(Error::NotFound));
+    assert_eq!(fs.rename("/c/file", "/missing/x"), Err(Error::NotFound));
+    assert_eq!(fs.rename("/c/file", "/a"), Err(Error::IsADirectory));
+    assert_eq!(fs.rename("/a", "/c/file"), Err(Error::NotADirectory));
+    assert_eq!(fs.rename("/a", "/c"), Err(Error::DirectoryNotEmpty));
+    assert_eq!(fs.rename("/c", "/c/d"), Err(Error::InvalidPath));
+    assert_eq!(fs.rename("/", "/d"), Err(Error::InvalidPath));
+
+    // An empty directory can be replaced.
+    assert_eq!(fs.rename("/c", "/a"), Ok(()));
+    assert_file(&fs, "/a/file", Some(b"other"));
+    assert_eq!(fs.exists("/c"), Ok(false));
+}
+
+#[test]
+fn append() {
+    let kernel = fake::Kernel::new();
+    let storage = fake::IsolatedNonvolatileStorage::new_with_page_size(REGION_SIZE, PAGE_SIZE);
+    kernel.add_driver(&storage);
+    let mut fs = formatted();
+
+    assert_eq!(fs.append("/log", b"first\n"), Ok(()));
+    assert_eq!(fs.append("/log", &[b'x'; 200]), Ok(()));
+    let mut fs = Fs::mount(SECTOR_SIZE).unwrap();
+    assert_eq!(fs.append("/log", b"third\n"), Ok(()));
+
+    let mut buf = [0; 256];
+    let contents = read_file(&fs, "/log", &mut buf).unwrap();
+    assert_eq!(contents.len(), 212);
+    assert_eq!(contents[..6], *b"first\n");
+    assert_eq!(contents[6..206], [b'x'; 200]);
+    assert_eq!(contents[206..], *b"third\n");
+
+    fs.create_dir("/dir").unwrap();
+    assert_eq!(fs.append("/dir", b""), Err(Error::IsADirectory));
+}
+
+#[test]
+fn garbage_collection_and_wear_levelling() {
+    let kernel = fake::Kernel::new();
+    let storage = fake::IsolatedNonvolatileStorage::new_with_page_size(REGION_SIZE, PAGE_SIZE);
+    kernel.add_driver(&storage);
+    let mut fs = formatted();
+
+    fs.write_file("/static", &[1; 100]).unwrap();
+    for i in 0..200u32 {
+        fs.write_file("/config", &[i as u8; 200]).unwrap();
+        if i % 5 == 0 {
+            fs.append("/log", &i.to_le_bytes()).unwrap();
+        }
+    }
+    assert_file(&fs, "/static", Some(&[1; 100]));
+    assert_file(&fs, "/config", Some(&[199; 200]));
+    let fs = Fs::mount(SECTOR_SIZE).unwrap();
+    let mut buf = [0; 800];
+    let log = read_file(&fs, "/log", &mut buf).unwrap();
+    assert!(log
+        .chunks(4)
+        .map(|entry| u32::from_le_bytes(entry.try_into().unwrap()))
+        .eq((0..200).step_by(5)));
+
+    // The log went around the ring many times, wearing every page about
+    // equally.
+    let erase_counts = (0..REGION_SIZE / PAGE_SIZE).map(|page| storage.erase_count(page));
+    let min = erase_counts.clone().min().unwrap();
+    let max = erase_counts.max().unwrap();
+    assert!(min > 10, "Least worn page erased {min} times");
+    assert!(max - min <= 4, "Erased between {min} and {max} times");
+}
+
+#[test]
+fn no_space() {
+    let kernel = fake::Kernel::new();
+    let storage = fake::IsolatedNonvolatileStorage::new_with_page_size(REGION_SIZE, PAGE_SIZE);
+    kernel.add_driver(&storage);
+    let mut fs = formatted();
+
+    let mut written = 0;
+    let error = loop {
+        let mut path = *b"/file0";
+        path[5] += written;
+        match fs.write_file(core::str::from_utf8(&path).unwrap(), &[written; 200]) {
+            Ok(()) => written += 1,
+            Err(error) => break error,
+        }
+    };
+    assert_eq!(error, Error::NoSpace);
+    assert!(written >= 4, "Only {written} files fit");
+
+    // The filesystem is still intact, and removing files frees up space.
+    let mut fs = Fs::mount(SECTOR_SIZE).unwrap();
+    for i in 0..written {
+        let mut path = *b"/file0";
+        path[5] += i;
+        let path = core::str::from_utf8(&path).unwrap();
+        assert_file(&fs, path, Some(&[i; 200]));
+        fs.remove(path).unwrap();
+    }
+    assert_eq!(fs.write_file("/new", &[9; 200]), Ok(()));
+    assert_file(&fs, "/new", Some(&[9; 200]));
+}
+
+// Leaves dead records in the log, so that the next few operations move the
+// log on to new sectors and collect old ones.
+fn churn(fs: &mut Fs) {
+    for _ in 0..4 {
+        fs.write_file("/junk", &[0; 200]).unwrap();
+    }
+    fs.remove("/junk").unwrap();
+}
+
+// Runs `operation` on a filesystem prepared by `setup`, cutting the power at
+// each of its writes in turn. After each power loss, the filesystem must mount
+// in a state that `check` accepts, and remain usable.
+fn cut_power_at_every_write(
+    setup: fn(&mut Fs),
+    operation: fn(&mut Fs) -> Result<(), Error>,
+    check: fn(&Fs),
+) {
+    let snapshot = {
+        let kernel = fake::Kernel::new();
+        let storage = fake::IsolatedNonvolatileStorage::new_with_page_size(REGION_SIZE, PAGE_SIZE);
+        kernel.add_driver(&storage);
+        setup(&mut formatted());
+        storage.contents()
+    };
+    for writes in 0.. {
+        let kernel = fake::Kernel::new();
+        let storage = fake::IsolatedNonvolatileStorage::new_with_page_size(REGION_SIZE, PAGE_SIZE);
+        storage.set_contents(&snapshot);
+        kernel.add_driver(&storage);
+
+        let mut fs = Fs::mount(SECTOR_SIZE).unwrap();
+        storage.cut_power_after(writes);
+        let result = operation(&mut fs);
+        let powered = storage.is_powered();
+        storage.restore_power();
+
+        let mut fs = Fs::mount(SECTOR_SIZE).unwrap();
+        check(&fs);
+        assert_eq!(fs.write_file("/after", b"reboot"), Ok(()));
+        assert_file(&fs, "/after", Some(b"reboot"));
+        check(&fs);
+
+        if powered {
+            // The operation finished before the power was cut.
+            assert_eq!(result, Ok(()));
+            return;
+        }
+        assert_eq!(result, Err(Error::Storage(ErrorCode::Fail)));
+    }
+}
+
+#[test]
+fn power_loss_during_rewrites() {
+    cut_power_at_every_write(
+        |fs| {
+            fs.write_file("/keep", b"untouched").unwrap();
+            fs.create_dir("/etc").unwrap();
+            fs.write_file("/etc/config", &[0; 200]).unwrap();
+        },
+        // Enough rewrites to collect every sector at least once.
+        |fs| {
+            for version in 1..=12 {
+                fs.write_file("/etc/config", &[version; 200])?;
+            }
+            Ok(())
+        },
+        |fs| {
+            assert_file(fs, "/keep", Some(b"untouched"));
+            let mut buf = [0; 256];
+            let config = read_file(fs, "/etc/config", &mut buf).unwrap();
+            assert_eq!(config.len(), 200);
+            assert!(config.iter().all(|&byte| byte == config[0]));
+        },
+    );
+}
+
+#[test]
+fn power_loss_during_renames() {
+    cut_power_at_every_write(
+        |fs| {
+            fs.write_file("/a", b"old a").unwrap();
+            fs.write_file("/b", b"old b").unwrap();
+            fs.create_dir("/dir").unwrap();
+            churn(fs);
+        },
+        |fs| {
+            fs.rename("/a", "/b")?;
+            fs.rename("/b", "/dir/c")?;
+            fs.remove("/dir/c")?;
+            fs.remove("/dir")
+        },
+        |fs| {
+            let (mut a, mut b, mut c) = ([0; 8], [0; 8], [0; 8]);
+            let state = (
+                read_file(fs, "/a", &mut a),
+                read_file(fs, "/b", &mut b),
+                read_file(fs, "/dir/c", &mut c),
+                fs.exists("/dir").unwrap(),
+            );
+            let old_a: Option<&[u8]> = Some(b"old a");
+            assert!(
+                [
+                    (old_a, Some(&b"old b"[..]), None, true),
+                    (None, old_a, None, true),
+                    (None, None, old_a, true),
+                    (None, None, None, true),
+                    (None, None, None, false),
+                ]
+                .contains(&state),
+                "{state:?}"
+            );
+        },
+    );
+}
+
+#[test]
+fn power_loss_during_appends() {
+    cut_power_at_every_write(
+        |fs| {
+            fs.append("/log", b"boot\n").unwrap();
+            churn(fs);
+        },
+        |fs| {
+            for entry in 0..20u8 {
+                fs.append("/log", &[b'a' + entry % 26; 31])?;
+            }
+            Ok(())
+        },
+        |fs| {
+            // The log holds the first few entries, each of them complete.
+            let mut buf = [0; 640];
+            let log = read_file(fs, "/log", &mut buf).unwrap();
+            assert_eq!(log[..5], *b"boot\n");
+            assert_eq!((log.len() - 5) % 31, 0);
+            for (entry, bytes) in log[5..].chunks(31).enumerate() {
+                assert_eq!(bytes, [b'a' + entry as u8 % 26; 31]);
+            }
+        },
+    );
+}
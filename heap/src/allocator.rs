@@ -0,0 +1,51 @@
+use crate::{Heap, HeapStats, ProcessBreak};
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::UnsafeCell;
+
+/// A [`Heap`] that implements `GlobalAlloc`, growing into the memory above
+/// `B`'s initial break.
+pub struct Allocator<B: ProcessBreak> {
+    heap: UnsafeCell<Heap>,
+    process_break: UnsafeCell<B>,
+}
+
+impl<B: ProcessBreak> Allocator<B> {
+    /// # Safety
+    /// The allocator must only be used by a single thread, and must not be
+    /// reentered, which `B` must not do either. The memory above the initial
+    /// break must only be used by the allocator.
+    pub const unsafe fn new(process_break: B) -> Self {
+        Allocator {
+            heap: UnsafeCell::new(Heap::new()),
+            process_break: UnsafeCell::new(process_break),
+        }
+    }
+
+    /// Returns the current heap usage statistics.
+    pub fn stats(&self) -> HeapStats {
+        // Safety: Only one thread uses the allocator, and it is not in the
+        // middle of an allocation, which never calls back into user code.
+        unsafe { (*self.heap.get()).stats() }
+    }
+}
+
+// Safety: Allocator::new's caller guarantees that only one thread uses it.
+unsafe impl<B: ProcessBreak> Sync for Allocator<B> {}
+
+unsafe impl<B: ProcessBreak> GlobalAlloc for Allocator<B> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // Safety: See the Sync implementation.
+        let (heap, process_break) =
+            unsafe { (&mut *self.heap.get(), &mut *self.process_break.get()) };
+        // Safety: Guaranteed by Allocator::new's caller.
+        unsafe { heap.alloc(layout, process_break) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // Safety: See the Sync implementation.
+        let heap = unsafe { &mut *self.heap.get() };
+        // Safety: GlobalAlloc's contract guarantees ptr was returned by alloc
+        // with the same layout.
+        unsafe { heap.dealloc(ptr, layout) }
+    }
+}
@@ -0,0 +1,247 @@
+use crate::{HeapStats, ProcessBreak};
+use core::alloc::Layout;
+use core::mem::size_of;
+use core::ptr::{null_mut, NonNull};
+use libtock_platform::ErrorCode;
+
+// Free blocks start with this header. BLOCK_SIZE is its size, which is also the
+// granularity of all allocations.
+#[repr(C)]
+struct FreeBlock {
+    size: usize,
+    next: Option<NonNull<FreeBlock>>,
+}
+
+pub(crate) const BLOCK_SIZE: usize = size_of::<FreeBlock>();
+
+// Rounds `value` up to a multiple of `align`, which must be a power of two.
+fn round_up(value: usize, align: usize) -> usize {
+    (value + align - 1) & !(align - 1)
+}
+
+/// A first-fit heap, which grows by moving a [`ProcessBreak`].
+pub struct Heap {
+    // The first free block, or None if there is no free memory.
+    free: Option<NonNull<FreeBlock>>,
+    // The heap occupies [start, end). Both are 0 until the heap is initialized.
+    start: usize,
+    end: usize,
+    stats: HeapStats,
+}
+
+impl Default for Heap {
+    fn default() -> Heap {
+        Heap::new()
+    }
+}
+
+impl Heap {
+    pub const fn new() -> Heap {
+        Heap {
+            free: None,
+            start: 0,
+            end: 0,
+            stats: HeapStats {
+                size: 0,
+                used: 0,
+                peak_used: 0,
+                failed_allocations: 0,
+            },
+        }
+    }
+
+    /// Returns the current heap usage statistics.
+    pub fn stats(&self) -> HeapStats {
+        self.stats
+    }
+
+    // Sets up the heap on the first allocation. Memory between the initial
+    // break and the current break becomes the first free block, and the break
+    // is rounded up to a multiple of BLOCK_SIZE.
+    //
+    // Safety: The memory between the initial break and the current break must
+    // not be used by anything else.
+    unsafe fn init<B: ProcessBreak>(&mut self, process_break: &mut B) -> Result<(), ErrorCode> {
+        let start = round_up(process_break.heap_start(), BLOCK_SIZE);
+        let current_break = process_break.grow(0)?;
+        let end = round_up(current_break.max(start), BLOCK_SIZE);
+        if end > current_break {
+            process_break.grow(end - current_break)?;
+        }
+        self.start = start;
+        self.end = end;
+        if end > start {
+            // Safety: [start, end) is unused memory below the break.
+            unsafe { self.insert_free(start, end - start) };
+        }
+        self.update_size();
+        Ok(())
+    }
+
+    /// Allocates memory for `layout`, moving `process_break` up if the heap
+    /// has no room for it. Returns a null pointer if the break cannot be
+    /// moved.
+    ///
+    /// # Safety
+    /// The memory above the initial break must only be used by this heap, and
+    /// `process_break` must move the same break on every call.
+    pub unsafe fn alloc<B: ProcessBreak>(
+        &mut self,
+        layout: Layout,
+        process_break: &mut B,
+    ) -> *mut u8 {
+        // Safety: Guaranteed by our caller.
+        if self.end == 0 && unsafe { self.init(process_break) }.is_err() {
+            self.stats.failed_allocations += 1;
+            return null_mut();
+        }
+        let size = round_up(layout.size().max(1), BLOCK_SIZE);
+        let align = layout.align().max(BLOCK_SIZE);
+
+        // Safety: The free list only contains unused heap memory.
+        if let Some(ptr) = unsafe { self.take_first_fit(size, align) } {
+            return ptr;
+        }
+
+        // Nothing fits, so grow the heap. If the last free block ends at the
+        // end of the heap, the allocation can start in that block, and the
+        // heap only needs to grow by the remainder.
+        let mut alloc_start = round_up(self.end, align);
+        // Safety: The free list only contains valid free blocks.
+        if let Some((last_start, _)) =
+            unsafe { self.last_free() }.filter(|&(start, size)| start + size == self.end)
+        {
+            alloc_start = round_up(last_start, align);
+        }
+        let increment = alloc_start + size - self.end;
+        if process_break.grow(increment).is_err() {
+            self.stats.failed_allocations += 1;
+            return null_mut();
+        }
+        let new_memory = self.end;
+        self.end += increment;
+        self.update_size();
+        // Safety: The kernel just gave us [new_memory, self.end), which is
+        // unused.
+        unsafe {
+            self.insert_free(new_memory, increment);
+            self.take_first_fit(size, align).unwrap_or(null_mut())
+        }
+    }
+
+    /// Frees memory returned by [`Heap::alloc`].
+    ///
+    /// # Safety
+    /// `ptr` must have been returned by `alloc` with the same layout.
+    pub unsafe fn dealloc(&mut self, ptr: *mut u8, layout: Layout) {
+        let size = round_up(layout.size().max(1), BLOCK_SIZE);
+        self.stats.used -= size;
+        // Safety: Guaranteed by our caller.
+        unsafe { self.insert_free(ptr as usize, size) };
+    }
+
+    // Finds the first free block that can hold an allocation of the given size
+    // and alignment (both multiples of BLOCK_SIZE), removes the allocation from
+    // it, and returns the allocation's address.
+    //
+    // Safety: The free list must only contain unused heap memory.
+    unsafe fn take_first_fit(&mut self, size: usize, align: usize) -> Option<*mut u8> {
+        let mut link: *mut Option<NonNull<FreeBlock>> = &mut self.free;
+        // Safety: link always points at either self.free or the next field of
+        // a free block, and every free block is valid and unused.
+        unsafe {
+            while let Some(block) = *link {
+                let block_start = block.as_ptr() as usize;
+                let block_end = block_start + (*block.as_ptr()).size;
+                let next = (*block.as_ptr()).next;
+                let alloc_start = round_up(block_start, align);
+                let alloc_end = alloc_start + size;
+                if alloc_end > block_end {
+                    link = &mut (*block.as_ptr()).next;
+                    continue;
+                }
+
+                // Split the block into up to three parts: the free space in
+                // front of the allocation, the allocation, and the free space
+                // behind the allocation.
+                let mut rest = next;
+                if alloc_end < block_end {
+                    let back = alloc_end as *mut FreeBlock;
+                    back.write(FreeBlock {
+                        size: block_end - alloc_end,
+                        next,
+                    });
+                    rest = NonNull::new(back);
+                }
+                if alloc_start > block_start {
+                    (*block.as_ptr()).size = alloc_start - block_start;
+                    (*block.as_ptr()).next = rest;
+                } else {
+                    *link = rest;
+                }
+
+                self.stats.used += size;
+                self.stats.peak_used = self.stats.peak_used.max(self.stats.used);
+                return Some(alloc_start as *mut u8);
+            }
+        }
+        None
+    }
+
+    // Returns the start and size of the free block with the highest address.
+    //
+    // Safety: The free list must only contain valid free blocks.
+    unsafe fn last_free(&self) -> Option<(usize, usize)> {
+        let mut last = None;
+        let mut cursor = self.free;
+        while let Some(block) = cursor {
+            // Safety: Guaranteed by our caller.
+            let block_ref = unsafe { block.as_ref() };
+            last = Some((block.as_ptr() as usize, block_ref.size));
+            cursor = block_ref.next;
+        }
+        last
+    }
+
+    // Adds [start, start + size) to the free list, merging it with adjacent
+    // free blocks.
+    //
+    // Safety: The memory must be unused heap memory that is not already in the
+    // free list, and start and size must be multiples of BLOCK_SIZE.
+    unsafe fn insert_free(&mut self, start: usize, size: usize) {
+        let mut prev: Option<NonNull<FreeBlock>> = None;
+        let mut next = self.free;
+        // Safety: Every block in the free list is valid and unused, and the
+        // memory we are inserting is unused and large enough for a FreeBlock.
+        unsafe {
+            while let Some(block) = next {
+                if block.as_ptr() as usize > start {
+                    break;
+                }
+                prev = Some(block);
+                next = (*block.as_ptr()).next;
+            }
+
+            let new = start as *mut FreeBlock;
+            new.write(FreeBlock { size, next });
+            if let Some(next) = next {
+                if start + size == next.as_ptr() as usize {
+                    (*new).size += (*next.as_ptr()).size;
+                    (*new).next = (*next.as_ptr()).next;
+                }
+            }
+            match prev {
+                Some(prev) if prev.as_ptr() as usize + (*prev.as_ptr()).size == start => {
+                    (*prev.as_ptr()).size += (*new).size;
+                    (*prev.as_ptr()).next = (*new).next;
+                }
+                Some(prev) => (*prev.as_ptr()).next = NonNull::new(new),
+                None => self.free = NonNull::new(new),
+            }
+        }
+    }
+
+    fn update_size(&mut self) {
+        self.stats.size = self.end - self.start;
+    }
+}
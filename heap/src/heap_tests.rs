@@ -0,0 +1,188 @@
+use crate::heap::BLOCK_SIZE;
+use crate::{Heap, HeapStats, ProcessBreak};
+use core::alloc::Layout;
+use libtock_platform::ErrorCode;
+
+const ARENA_LEN: usize = 1024;
+
+// The memory the heaps under test grow into.
+#[repr(C, align(256))]
+struct Arena([u8; ARENA_LEN]);
+
+// A process break that moves within an arena, up to `limit` bytes into it.
+struct FakeBreak {
+    heap_start: usize,
+    current: usize,
+    limit: usize,
+    // The increments the heap asked for, excluding queries of the break.
+    grows: Vec<usize>,
+}
+
+impl ProcessBreak for FakeBreak {
+    fn heap_start(&mut self) -> usize {
+        self.heap_start
+    }
+
+    fn grow(&mut self, increment: usize) -> Result<usize, ErrorCode> {
+        if self.current + increment > self.limit {
+            return Err(ErrorCode::NoMem);
+        }
+        if increment > 0 {
+            self.grows.push(increment);
+        }
+        self.current += increment;
+        Ok(self.current - increment)
+    }
+}
+
+// Returns an arena and a break in it with `below_break` bytes of memory already
+// placed below the break.
+fn setup(heap_offset: usize, below_break: usize, limit: usize) -> (Box<Arena>, FakeBreak) {
+    let arena = Box::new(Arena([0; ARENA_LEN]));
+    let start = arena.0.as_ptr() as usize;
+    let process_break = FakeBreak {
+        heap_start: start + heap_offset,
+        current: start + heap_offset + below_break,
+        limit: start + limit,
+        grows: Vec::new(),
+    };
+    (arena, process_break)
+}
+
+fn layout(size: usize, align: usize) -> Layout {
+    Layout::from_size_align(size, align).unwrap()
+}
+
+#[test]
+fn split_and_merge() {
+    let (arena, mut process_break) = setup(0, 8 * BLOCK_SIZE, ARENA_LEN);
+    let start = arena.0.as_ptr() as usize;
+    let mut heap = Heap::new();
+    let block = layout(BLOCK_SIZE, 1);
+
+    // Allocations are split off the front of the memory below the break.
+    let a = unsafe { heap.alloc(block, &mut process_break) };
+    let b = unsafe { heap.alloc(block, &mut process_break) };
+    let c = unsafe { heap.alloc(layout(1, 1), &mut process_break) };
+    assert_eq!(
+        [a as usize, b as usize, c as usize],
+        [start, start + BLOCK_SIZE, start + 2 * BLOCK_SIZE]
+    );
+    assert_eq!(process_break.grows, []);
+    assert_eq!(
+        heap.stats(),
+        HeapStats {
+            size: 8 * BLOCK_SIZE,
+            used: 3 * BLOCK_SIZE,
+            peak_used: 3 * BLOCK_SIZE,
+            failed_allocations: 0,
+        }
+    );
+
+    // A freed block is reused first.
+    unsafe { heap.dealloc(b, block) };
+    assert_eq!(unsafe { heap.alloc(block, &mut process_break) }, b);
+
+    // Freed blocks merge with the free blocks on either side of them, so once
+    // everything is freed the whole heap can be allocated again.
+    unsafe {
+        heap.dealloc(b, block);
+        heap.dealloc(a, block);
+        heap.dealloc(c, layout(1, 1));
+    }
+    assert_eq!(heap.stats().used, 0);
+    let all = layout(8 * BLOCK_SIZE, 1);
+    assert_eq!(unsafe { heap.alloc(all, &mut process_break) }, a);
+    assert_eq!(process_break.grows, []);
+    assert_eq!(heap.stats().peak_used, 8 * BLOCK_SIZE);
+}
+
+#[test]
+fn grow() {
+    // The heap starts out empty, and the break is not block-aligned.
+    let (arena, mut process_break) = setup(0, 1, ARENA_LEN);
+    let start = arena.0.as_ptr() as usize;
+    let mut heap = Heap::new();
+
+    // The first allocation rounds the break up, then grows the heap.
+    let a = unsafe { heap.alloc(layout(3 * BLOCK_SIZE - 1, 1), &mut process_break) };
+    assert_eq!(a as usize, start);
+    assert_eq!(process_break.grows, [BLOCK_SIZE - 1, 2 * BLOCK_SIZE]);
+    assert_eq!(heap.stats().size, 3 * BLOCK_SIZE);
+
+    // When the last free block ends at the end of the heap, the heap only
+    // grows by the part of the allocation that does not fit in it.
+    let b = unsafe { heap.alloc(layout(BLOCK_SIZE, 1), &mut process_break) };
+    assert_eq!(process_break.grows[2..], [BLOCK_SIZE]);
+    unsafe { heap.dealloc(b, layout(BLOCK_SIZE, 1)) };
+    let c = unsafe { heap.alloc(layout(3 * BLOCK_SIZE, 1), &mut process_break) };
+    assert_eq!(c, b);
+    assert_eq!(process_break.grows[3..], [2 * BLOCK_SIZE]);
+    assert_eq!(process_break.current, start + 6 * BLOCK_SIZE);
+    assert_eq!(heap.stats().size, 6 * BLOCK_SIZE);
+}
+
+#[test]
+fn alignment() {
+    // The heap starts one block past a 256-byte boundary.
+    let (arena, mut process_break) = setup(BLOCK_SIZE, 0, ARENA_LEN);
+    let start = arena.0.as_ptr() as usize + BLOCK_SIZE;
+    let mut heap = Heap::new();
+
+    let a = unsafe { heap.alloc(layout(BLOCK_SIZE, 64), &mut process_break) };
+    assert_eq!(a as usize % 64, 0);
+    assert_eq!(a as usize, start - BLOCK_SIZE + 64);
+    let b = unsafe { heap.alloc(layout(1, 256), &mut process_break) };
+    assert_eq!(b as usize % 256, 0);
+    assert_eq!(b as usize, start - BLOCK_SIZE + 256);
+
+    // The gaps in front of aligned allocations stay free, and are used by
+    // later allocations.
+    let c = unsafe { heap.alloc(layout(BLOCK_SIZE, 8), &mut process_break) };
+    assert_eq!(c as usize, start);
+    unsafe {
+        heap.dealloc(a, layout(BLOCK_SIZE, 64));
+        heap.dealloc(b, layout(1, 256));
+        heap.dealloc(c, layout(BLOCK_SIZE, 8));
+    }
+    assert_eq!(heap.stats().used, 0);
+    let size = heap.stats().size;
+    assert_eq!(
+        unsafe { heap.alloc(layout(size, 8), &mut process_break) } as usize,
+        start
+    );
+}
+
+#[test]
+fn out_of_memory() {
+    let (arena, mut process_break) = setup(0, 0, 4 * BLOCK_SIZE);
+    let start = arena.0.as_ptr() as usize;
+    let mut heap = Heap::new();
+
+    let a = unsafe { heap.alloc(layout(2 * BLOCK_SIZE, 1), &mut process_break) };
+    assert_eq!(a as usize, start);
+    // The break cannot move past the limit, so the allocation fails without
+    // changing the heap.
+    let b = unsafe { heap.alloc(layout(3 * BLOCK_SIZE, 1), &mut process_break) };
+    assert!(b.is_null());
+    assert_eq!(
+        heap.stats(),
+        HeapStats {
+            size: 2 * BLOCK_SIZE,
+            used: 2 * BLOCK_SIZE,
+            peak_used: 2 * BLOCK_SIZE,
+            failed_allocations: 1,
+        }
+    );
+
+    // Smaller allocations still succeed.
+    let c = unsafe { heap.alloc(layout(2 * BLOCK_SIZE, 1), &mut process_break) };
+    assert_eq!(c as usize, start + 2 * BLOCK_SIZE);
+
+    // The heap also fails cleanly when the break cannot be read at all.
+    let (_arena, mut process_break) = setup(0, 0, 0);
+    process_break.current += 1;
+    let mut heap = Heap::new();
+    assert!(unsafe { heap.alloc(layout(1, 1), &mut process_break) }.is_null());
+    assert_eq!(heap.stats().failed_allocations, 1);
+}
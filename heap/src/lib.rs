@@ -0,0 +1,61 @@
+//! `libtock_heap` is the memory allocator behind `libtock_runtime`'s global
+//! allocator, kept in its own crate so it can be tested on the host.
+//!
+//! The heap starts at the initial process break (the end of `.bss`) and grows
+//! upwards. Memory the kernel has already placed below the process break is
+//! used first; when that runs out, the allocator asks for more by moving the
+//! break, which a [`ProcessBreak`] does (with Memop, in a Tock process). The
+//! break is never moved back down.
+//!
+//! Free memory is kept in a singly-linked list of blocks, sorted by address,
+//! that is searched first-fit. Adjacent free blocks are merged when memory is
+//! freed. Every block's address and size is a multiple of the size of a free
+//! block's header, so any leftover space in front of or behind an allocation is
+//! always large enough to hold one.
+//!
+//! When the break cannot be moved, the allocator returns a null pointer, and
+//! `alloc::alloc::handle_alloc_error` reports the size of the failed
+//! allocation.
+
+#![cfg_attr(not(test), no_std)]
+#![warn(unsafe_op_in_unsafe_fn)]
+
+mod allocator;
+mod heap;
+
+pub use allocator::Allocator;
+pub use heap::Heap;
+
+use libtock_platform::ErrorCode;
+
+/// The process break, which the heap grows by moving up.
+pub trait ProcessBreak {
+    /// Returns the initial break, where the heap starts.
+    fn heap_start(&mut self) -> usize;
+
+    /// Moves the break up by `increment` bytes and returns the previous break.
+    /// An `increment` of 0 returns the current break.
+    fn grow(&mut self, increment: usize) -> Result<usize, ErrorCode>;
+}
+
+/// Heap usage statistics.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HeapStats {
+    /// The number of bytes between the start of the heap and the current end
+    /// of the heap.
+    pub size: usize,
+
+    /// The number of bytes currently allocated, including padding added to
+    /// satisfy alignment and size requirements.
+    pub used: usize,
+
+    /// The largest value `used` has had.
+    pub peak_used: usize,
+
+    /// The number of allocations that failed because the break could not be
+    /// moved.
+    pub failed_allocations: usize,
+}
+
+#[cfg(test)]
+mod heap_tests;
@@ -0,0 +1,76 @@
+//! Runs `Allocator` as the global allocator, in an arena standing in for the
+//! memory above a Tock process' break.
+
+use libtock_heap::{Allocator, ProcessBreak};
+use libtock_platform::ErrorCode;
+use std::process::Command;
+
+const ARENA_LEN: usize = 1 << 20;
+
+#[repr(C, align(4096))]
+struct Arena([u8; ARENA_LEN]);
+
+static mut ARENA: Arena = Arena([0; ARENA_LEN]);
+
+fn arena_start() -> usize {
+    core::ptr::addr_of_mut!(ARENA) as usize
+}
+
+// The break starts at the start of the arena, and cannot leave it.
+struct ArenaBreak {
+    len: usize,
+}
+
+impl ProcessBreak for ArenaBreak {
+    fn heap_start(&mut self) -> usize {
+        arena_start()
+    }
+
+    fn grow(&mut self, increment: usize) -> Result<usize, ErrorCode> {
+        if self.len + increment > ARENA_LEN {
+            return Err(ErrorCode::NoMem);
+        }
+        self.len += increment;
+        Ok(arena_start() + self.len - increment)
+    }
+}
+
+// Safety: The test harness is disabled, so only the main thread allocates.
+#[global_allocator]
+static ALLOCATOR: Allocator<ArenaBreak> = unsafe { Allocator::new(ArenaBreak { len: 0 }) };
+
+const OUT_OF_MEMORY: &str = "out-of-memory";
+
+fn main() {
+    if std::env::args().nth(1).as_deref() == Some(OUT_OF_MEMORY) {
+        // Reaches handle_alloc_error, which aborts on the host after printing
+        // the size of the allocation (and panics in a Tock process).
+        let buffer = Vec::<u8>::with_capacity(ARENA_LEN);
+        std::hint::black_box(buffer);
+        unreachable!("the allocation succeeded");
+    }
+
+    let boxed = Box::new([7u64; 4]);
+    let mut numbers: Vec<u32> = (0..1000).collect();
+    numbers.retain(|n| n % 3 == 0);
+    assert_eq!(boxed.iter().sum::<u64>(), 28);
+    assert_eq!(numbers.iter().sum::<u32>(), 166_833);
+    let stats = ALLOCATOR.stats();
+    assert!(stats.used > 0 && stats.size <= ARENA_LEN);
+
+    // A failed allocation returns null, which try_reserve reports.
+    let mut buffer = Vec::<u8>::new();
+    assert!(buffer.try_reserve_exact(ARENA_LEN).is_err());
+    assert_eq!(ALLOCATOR.stats().failed_allocations, 1);
+
+    let output = Command::new(std::env::current_exe().unwrap())
+        .arg(OUT_OF_MEMORY)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(&format!("memory allocation of {ARENA_LEN} bytes failed")),
+        "{stderr}"
+    );
+}
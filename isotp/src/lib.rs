@@ -0,0 +1,470 @@
+//! `libtock_isotp` carries messages longer than a CAN frame over the CAN
+//! driver, following ISO 15765-2 (ISO-TP). It is the transport that diagnostic
+//! protocols such as UDS run on.
+//!
+//! A message that fits in one frame is sent as a single frame. A longer
+//! message is sent as a first frame, which carries its length, followed by
+//! consecutive frames. The receiver paces the sender with flow control frames,
+//! which allow a block of consecutive frames to be sent and set the minimum
+//! separation time between them.
+//!
+//! The transport does not allocate: messages are sent from and received into
+//! buffers provided by the caller, and frames are received into a
+//! [`RxRingBuffer`] that the caller provides too. Each step of a transfer is
+//! bounded by a timeout measured with the alarm driver.
+//!
+//! # Example
+//! ```ignore
+//! use libtock::can::{Id, RxRingBuffer};
+//! use libtock::isotp::{IsoTp, IsoTpConfig};
+//!
+//! let mut frames = RxRingBuffer::<8>::new();
+//! let mut isotp = IsoTp::new(
+//!     IsoTpConfig::new(Id::Standard(0x7E8), Id::Standard(0x7E0)),
+//!     &mut frames,
+//! );
+//! let mut request = [0; 256];
+//! let len = isotp.receive(&mut request)?;
+//! isotp.send(&response)?;
+//! ```
+
+#![no_std]
+
+use core::cell::Cell;
+use libtock_alarm::{Alarm, AlarmListener, Milliseconds, Ticks};
+use libtock_can::{Can, Config, Frame, Id, Receiver, RxRingBuffer, MAX_DATA_LEN};
+use libtock_platform::{share, DefaultConfig, ErrorCode, Syscalls};
+
+/// The largest message a first frame can describe without the escape
+/// sequence. Longer messages, up to `u32::MAX` bytes, use the escape sequence.
+pub const MAX_SHORT_MESSAGE_LEN: usize = 0xFFF;
+
+/// How the frames of a connection are addressed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Addressing {
+    /// The CAN identifiers alone identify the connection.
+    Normal,
+    /// The first data byte of each frame holds the target address, which
+    /// leaves one byte less for the message. Frames sent carry `remote`, and
+    /// only received frames that carry `local` are accepted.
+    Extended { local: u8, remote: u8 },
+}
+
+/// The configuration of a connection.
+#[derive(Clone, Copy)]
+pub struct IsoTpConfig {
+    /// The identifier of the frames sent.
+    pub tx_id: Id,
+    /// The identifier of the frames received.
+    pub rx_id: Id,
+    pub addressing: Addressing,
+    /// The number of consecutive frames the peer may send before waiting for
+    /// another flow control frame, or 0 for no limit.
+    pub block_size: u8,
+    /// The minimum separation time between consecutive frames requested from
+    /// the peer, in the STmin encoding: 0 to 127 milliseconds, or 0xF1 to 0xF9
+    /// for 100 to 900 microseconds.
+    pub st_min: u8,
+    /// N_As: how long a frame may take to be transmitted. This also bounds
+    /// the transmission of flow control frames (N_Ar).
+    pub n_as: Milliseconds,
+    /// N_Bs: how long to wait for a flow control frame while sending.
+    pub n_bs: Milliseconds,
+    /// N_Cr: how long to wait for a consecutive frame while receiving.
+    pub n_cr: Milliseconds,
+    /// The number of flow control frames asking to wait that are accepted in
+    /// a row before giving up.
+    pub max_wait_frames: u8,
+    /// If set, frames are padded to 8 bytes with this value.
+    pub padding: Option<u8>,
+}
+
+impl IsoTpConfig {
+    /// Returns a configuration with normal addressing, no flow control limits,
+    /// timeouts of one second and no padding.
+    pub fn new(tx_id: Id, rx_id: Id) -> IsoTpConfig {
+        IsoTpConfig {
+            tx_id,
+            rx_id,
+            addressing: Addressing::Normal,
+            block_size: 0,
+            st_min: 0,
+            n_as: Milliseconds(1000),
+            n_bs: Milliseconds(1000),
+            n_cr: Milliseconds(1000),
+            max_wait_frames: 10,
+            padding: None,
+        }
+    }
+}
+
+/// The timer that expired.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Timeout {
+    /// N_As: a frame was not transmitted in time.
+    As,
+    /// N_Bs: no flow control frame arrived in time.
+    Bs,
+    /// N_Cr: no consecutive frame arrived in time.
+    Cr,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The CAN or alarm driver returned an error.
+    Driver(ErrorCode),
+    Timeout(Timeout),
+    /// The message is empty, or longer than `u32::MAX` bytes.
+    InvalidLength,
+    /// The message does not fit in the receive buffer, or the peer reported
+    /// that it does not fit in its own.
+    Overflow,
+    /// A consecutive frame arrived out of sequence.
+    WrongSequenceNumber,
+    /// A flow control frame had an unknown flow status.
+    InvalidFlowStatus,
+    /// The peer asked to wait more than `max_wait_frames` times in a row.
+    WaitLimit,
+}
+
+impl From<ErrorCode> for Error {
+    fn from(error: ErrorCode) -> Error {
+        Error::Driver(error)
+    }
+}
+
+/// One end of an ISO-TP connection.
+///
+/// Transfers are half-duplex: [`IsoTp::send`] and [`IsoTp::receive`] each
+/// block until their transfer is complete, and frames that are not part of the
+/// transfer in progress are discarded.
+pub struct IsoTp<'buf, const N: usize, S: Syscalls, C: Config = DefaultConfig> {
+    config: IsoTpConfig,
+    receiver: Receiver<'buf, N, S, C>,
+}
+
+impl<'buf, const N: usize, S: Syscalls, C: Config> IsoTp<'buf, N, S, C> {
+    /// Creates a connection that receives frames into `frames`. The CAN
+    /// controller must already be configured and enabled, and its filters
+    /// must pass frames with the configuration's `rx_id`.
+    pub fn new(config: IsoTpConfig, frames: &'buf mut RxRingBuffer<N>) -> Self {
+        IsoTp {
+            config,
+            receiver: Receiver::new(frames),
+        }
+    }
+
+    pub fn config(&self) -> &IsoTpConfig {
+        &self.config
+    }
+
+    /// Sends `message`, waiting for the peer's flow control as needed.
+    pub fn send(&mut self, message: &[u8]) -> Result<(), Error> {
+        if message.is_empty() || u32::try_from(message.len()).is_err() {
+            return Err(Error::InvalidLength);
+        }
+        let room = MAX_DATA_LEN - self.address_len();
+        let mut pdu = [0; MAX_DATA_LEN];
+
+        if message.len() < room {
+            pdu[0] = pci::SINGLE | message.len() as u8;
+            pdu[1..=message.len()].copy_from_slice(message);
+            return self.send_pdu(&pdu[..=message.len()]);
+        }
+
+        let header_len = match message.len() {
+            len if len <= MAX_SHORT_MESSAGE_LEN => {
+                pdu[..2].copy_from_slice(&(len as u16).to_be_bytes());
+                2
+            }
+            len => {
+                pdu[2..6].copy_from_slice(&(len as u32).to_be_bytes());
+                6
+            }
+        };
+        pdu[0] |= pci::FIRST;
+        let mut sent = room - header_len;
+        pdu[header_len..room].copy_from_slice(&message[..sent]);
+        self.send_pdu(&pdu[..room])?;
+
+        let mut sequence_number = 1;
+        while sent < message.len() {
+            let (block_size, st_min) = self.wait_for_flow_control()?;
+            let mut block_len = 0;
+            while sent < message.len() && (block_size == 0 || block_len < block_size as usize) {
+                if block_len > 0 {
+                    Self::separation_time(st_min)?;
+                }
+                let len = (message.len() - sent).min(room - 1);
+                pdu[0] = pci::CONSECUTIVE | sequence_number;
+                pdu[1..=len].copy_from_slice(&message[sent..sent + len]);
+                self.send_pdu(&pdu[..=len])?;
+                sent += len;
+                sequence_number = (sequence_number + 1) % 16;
+                block_len += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Waits for a message and receives it into `buf`, returning its length.
+    /// Returns [`Error::Overflow`] if the message is longer than `buf`.
+    pub fn receive(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let mut pdu = self.receive_pdu()?;
+        loop {
+            match self.receive_message(&pdu, buf)? {
+                Reception::Complete(len) => return Ok(len),
+                Reception::Restart(next) => pdu = next,
+                Reception::Ignored => pdu = self.receive_pdu()?,
+            }
+        }
+    }
+
+    // Receives the message that starts with `first`.
+    fn receive_message(&mut self, first: &Pdu, buf: &mut [u8]) -> Result<Reception, Error> {
+        let bytes = first.bytes();
+        match first.kind() {
+            pci::SINGLE => {
+                let len = (bytes[0] & 0xF) as usize;
+                if len == 0 || len >= bytes.len() {
+                    return Ok(Reception::Ignored);
+                }
+                let dest = buf.get_mut(..len).ok_or(Error::Overflow)?;
+                dest.copy_from_slice(&bytes[1..=len]);
+                return Ok(Reception::Complete(len));
+            }
+            pci::FIRST if bytes.len() >= 2 => {}
+            _ => return Ok(Reception::Ignored),
+        }
+
+        let (len, header_len) = match u16::from_be_bytes([bytes[0], bytes[1]]) & 0xFFF {
+            0 if bytes.len() >= 6 => {
+                let len = u32::from_be_bytes([bytes[2], bytes[3], bytes[4], bytes[5]]);
+                (len as usize, 6)
+            }
+            len => (len as usize, 2),
+        };
+        // A message that fits in a single frame must be sent as one.
+        if len < MAX_DATA_LEN - self.address_len() {
+            return Ok(Reception::Ignored);
+        }
+        if len > buf.len() {
+            self.send_flow_control(flow_status::OVERFLOW)?;
+            return Err(Error::Overflow);
+        }
+        let mut received = bytes.len() - header_len;
+        buf[..received].copy_from_slice(&bytes[header_len..]);
+        self.send_flow_control(flow_status::CONTINUE)?;
+
+        let mut sequence_number = 1;
+        let mut block_len = 0;
+        while received < len {
+            let pdu = self
+                .receive_pdu_within(self.config.n_cr)?
+                .ok_or(Error::Timeout(Timeout::Cr))?;
+            let bytes = pdu.bytes();
+            match pdu.kind() {
+                // A new message from the peer abandons this one.
+                pci::SINGLE | pci::FIRST => return Ok(Reception::Restart(pdu)),
+                pci::CONSECUTIVE => {}
+                _ => continue,
+            }
+            if bytes[0] & 0xF != sequence_number {
+                return Err(Error::WrongSequenceNumber);
+            }
+            let chunk = (len - received).min(bytes.len() - 1);
+            buf[received..received + chunk].copy_from_slice(&bytes[1..=chunk]);
+            received += chunk;
+            sequence_number = (sequence_number + 1) % 16;
+            block_len += 1;
+            if block_len == self.config.block_size as usize && received < len {
+                self.send_flow_control(flow_status::CONTINUE)?;
+                block_len = 0;
+            }
+        }
+        Ok(Reception::Complete(len))
+    }
+
+    // Waits for a flow control frame that lets the next block be sent, and
+    // returns its block size and separation time.
+    fn wait_for_flow_control(&mut self) -> Result<(u8, u8), Error> {
+        let mut wait_frames = 0;
+        loop {
+            let pdu = self
+                .receive_pdu_within(self.config.n_bs)?
+                .ok_or(Error::Timeout(Timeout::Bs))?;
+            let bytes = pdu.bytes();
+            if pdu.kind() != pci::FLOW_CONTROL || bytes.len() < 3 {
+                continue;
+            }
+            match bytes[0] & 0xF {
+                flow_status::CONTINUE => return Ok((bytes[1], bytes[2])),
+                flow_status::WAIT if wait_frames < self.config.max_wait_frames => wait_frames += 1,
+                flow_status::WAIT => return Err(Error::WaitLimit),
+                flow_status::OVERFLOW => return Err(Error::Overflow),
+                _ => return Err(Error::InvalidFlowStatus),
+            }
+        }
+    }
+
+    fn send_flow_control(&self, status: u8) -> Result<(), Error> {
+        self.send_pdu(&[
+            pci::FLOW_CONTROL | status,
+            self.config.block_size,
+            self.config.st_min,
+        ])
+    }
+
+    // Waits as long as `st_min`, in the STmin encoding, asks.
+    fn separation_time(st_min: u8) -> Result<(), Error> {
+        match st_min {
+            0 => Ok(()),
+            1..=0x7F => Ok(Alarm::<S, C>::sleep_for(Milliseconds(st_min as u32))?),
+            0xF1..=0xF9 => {
+                let micros = (st_min - 0xF0) as u64 * 100;
+                let frequency = Alarm::<S, C>::get_frequency()?.0 as u64;
+                let ticks = (micros * frequency).div_ceil(1_000_000);
+                Ok(Alarm::<S, C>::sleep_for(Ticks(ticks as u32))?)
+            }
+            // Reserved values are treated as the longest separation time.
+            _ => Ok(Alarm::<S, C>::sleep_for(Milliseconds(0x7F))?),
+        }
+    }
+
+    fn address_len(&self) -> usize {
+        match self.config.addressing {
+            Addressing::Normal => 0,
+            Addressing::Extended { .. } => 1,
+        }
+    }
+
+    // Sends one frame, which carries `pdu` after the target address, if any.
+    fn send_pdu(&self, pdu: &[u8]) -> Result<(), Error> {
+        let mut data = [self.config.padding.unwrap_or(0); MAX_DATA_LEN];
+        let mut len = self.address_len() + pdu.len();
+        if let Addressing::Extended { remote, .. } = self.config.addressing {
+            data[0] = remote;
+        }
+        data[self.address_len()..len].copy_from_slice(pdu);
+        if self.config.padding.is_some() {
+            len = MAX_DATA_LEN;
+        }
+        let frame = Frame::new(self.config.tx_id, &data[..len])?;
+        let transmitted = with_timeout::<S, C, _>(self.config.n_as, |expired| {
+            Ok(Can::<S, C>::send_until(&frame, expired)?)
+        })?;
+        match transmitted {
+            true => Ok(()),
+            false => Err(Error::Timeout(Timeout::As)),
+        }
+    }
+
+    // Waits for a frame that belongs to this connection.
+    fn receive_pdu(&mut self) -> Result<Pdu, Error> {
+        loop {
+            let frame = self.receiver.receive_frame()?;
+            if let Some(pdu) = Pdu::from_frame(&self.config, frame) {
+                return Ok(pdu);
+            }
+        }
+    }
+
+    // Waits for a frame that belongs to this connection, for up to `timeout`.
+    fn receive_pdu_within(&mut self, timeout: Milliseconds) -> Result<Option<Pdu>, Error> {
+        let (config, receiver) = (&self.config, &mut self.receiver);
+        with_timeout::<S, C, _>(timeout, |expired| loop {
+            match receiver.receive_frame_until(expired)? {
+                None => return Ok(None),
+                Some(frame) => {
+                    if let Some(pdu) = Pdu::from_frame(config, frame) {
+                        return Ok(Some(pdu));
+                    }
+                }
+            }
+        })
+    }
+}
+
+// The outcome of receiving a message.
+enum Reception {
+    Complete(usize),
+    // Another message interrupted this one, starting with this frame.
+    Restart(Pdu),
+    // The first frame did not start a valid message.
+    Ignored,
+}
+
+// Runs `f` with an alarm set to expire after `timeout`. `f` is passed a
+// function that returns whether the alarm has expired.
+fn with_timeout<S: Syscalls, C: Config, R>(
+    timeout: Milliseconds,
+    f: impl FnOnce(&dyn Fn() -> bool) -> Result<R, Error>,
+) -> Result<R, Error> {
+    let expired = Cell::new(false);
+    let listener = AlarmListener(|_| expired.set(true));
+    share::scope(|subscribe| {
+        Alarm::<S, C>::register_listener(&listener, subscribe)?;
+        Alarm::<S, C>::set_alarm(timeout)?;
+        let result = f(&|| expired.get());
+        let _ = Alarm::<S, C>::stop();
+        result
+    })
+}
+
+// The part of a frame that belongs to ISO-TP: the data after the target
+// address, if any.
+struct Pdu {
+    data: [u8; MAX_DATA_LEN],
+    len: usize,
+}
+
+impl Pdu {
+    // Returns the frame's PDU, if the frame belongs to the connection.
+    fn from_frame(config: &IsoTpConfig, frame: &Frame) -> Option<Pdu> {
+        if frame.id() != config.rx_id || frame.is_remote() {
+            return None;
+        }
+        let bytes = match config.addressing {
+            Addressing::Normal => frame.data(),
+            Addressing::Extended { local, .. } => match frame.data().split_first() {
+                Some((&address, rest)) if address == local => rest,
+                _ => return None,
+            },
+        };
+        if bytes.is_empty() {
+            return None;
+        }
+        let mut data = [0; MAX_DATA_LEN];
+        data[..bytes.len()].copy_from_slice(bytes);
+        Some(Pdu {
+            data,
+            len: bytes.len(),
+        })
+    }
+
+    fn bytes(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+
+    fn kind(&self) -> u8 {
+        self.data[0] & 0xF0
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// Protocol control information: the frame type, in the high nibble of the
+// first byte.
+mod pci {
+    pub const SINGLE: u8 = 0x00;
+    pub const FIRST: u8 = 0x10;
+    pub const CONSECUTIVE: u8 = 0x20;
+    pub const FLOW_CONTROL: u8 = 0x30;
+}
+
+mod flow_status {
+    pub const CONTINUE: u8 = 0;
+    pub const WAIT: u8 = 1;
+    pub const OVERFLOW: u8 = 2;
+}
@@ -0,0 +1,257 @@
+use libtock_can::{Id, RxRingBuffer};
+use libtock_unittest::fake::{self, CanFrame};
+
+use super::{Addressing, Error, IsoTpConfig, Timeout};
+
+type Can = libtock_can::Can<fake::Syscalls>;
+type IsoTp<'buf, const N: usize> = super::IsoTp<'buf, N, fake::Syscalls>;
+
+const TX_ID: u32 = 0x7E8;
+const RX_ID: u32 = 0x7E0;
+
+fn config() -> IsoTpConfig {
+    IsoTpConfig::new(Id::Standard(TX_ID as u16), Id::Standard(RX_ID as u16))
+}
+
+// Runs `test` with fake CAN and alarm drivers and an enabled controller.
+fn with_can(test: impl FnOnce(&fake::Can)) {
+    let kernel = fake::Kernel::new();
+    let alarm = fake::Alarm::new(1000);
+    kernel.add_driver(&alarm);
+    let can = fake::Can::new();
+    kernel.add_driver(&can);
+    Can::enable().unwrap();
+    test(&can);
+}
+
+// A frame from the peer.
+fn incoming(data: &[u8]) -> CanFrame {
+    CanFrame::standard(RX_ID, data)
+}
+
+// A frame to the peer.
+fn outgoing(data: &[u8]) -> CanFrame {
+    CanFrame::standard(TX_ID, data)
+}
+
+fn message<const N: usize>() -> [u8; N] {
+    core::array::from_fn(|i| i as u8)
+}
+
+#[test]
+fn single_frame() {
+    with_can(|can| {
+        let mut frames = RxRingBuffer::<4>::new();
+        let mut isotp = IsoTp::new(config(), &mut frames);
+
+        assert_eq!(isotp.send(&[1, 2, 3]), Ok(()));
+        assert_eq!(isotp.send(&message::<7>()), Ok(()));
+        assert_eq!(isotp.send(&[]), Err(Error::InvalidLength));
+        assert_eq!(
+            can.take_transmitted_frames(),
+            [
+                outgoing(&[0x03, 1, 2, 3]),
+                outgoing(&[0x07, 0, 1, 2, 3, 4, 5, 6])
+            ]
+        );
+
+        // Frames for other connections are ignored.
+        can.receive_frame(CanFrame::standard(0x123, &[0x01, 0xAA]));
+        can.receive_frame(incoming(&[0x03, 9, 8, 7]));
+        let mut buf = [0; 8];
+        assert_eq!(isotp.receive(&mut buf), Ok(3));
+        assert_eq!(buf[..3], [9, 8, 7]);
+
+        can.receive_frame(incoming(&[0x03, 9, 8, 7]));
+        assert_eq!(isotp.receive(&mut buf[..2]), Err(Error::Overflow));
+    });
+}
+
+#[test]
+fn send_segmented() {
+    with_can(|can| {
+        let mut frames = RxRingBuffer::<4>::new();
+        let mut isotp = IsoTp::new(config(), &mut frames);
+
+        can.receive_frame(incoming(&[0x30, 0, 0]));
+        assert_eq!(isotp.send(&message::<20>()), Ok(()));
+        assert_eq!(
+            can.take_transmitted_frames(),
+            [
+                outgoing(&[0x10, 20, 0, 1, 2, 3, 4, 5]),
+                outgoing(&[0x21, 6, 7, 8, 9, 10, 11, 12]),
+                outgoing(&[0x22, 13, 14, 15, 16, 17, 18, 19]),
+            ]
+        );
+    });
+}
+
+#[test]
+fn send_flow_control() {
+    with_can(|can| {
+        let mut frames = RxRingBuffer::<4>::new();
+        let mut isotp = IsoTp::new(config(), &mut frames);
+
+        // Blocks of one frame, after a request to wait, with separation times
+        // in milliseconds and microseconds.
+        can.receive_frame(incoming(&[0x31, 0, 0]));
+        can.receive_frame(incoming(&[0x30, 1, 5]));
+        can.receive_frame(incoming(&[0x30, 1, 0xF5]));
+        can.receive_frame(incoming(&[0x30, 0, 0xF5]));
+        assert_eq!(isotp.send(&message::<30>()), Ok(()));
+        let sent = can.take_transmitted_frames();
+        assert_eq!(sent.len(), 5);
+        assert_eq!(sent[3], outgoing(&[0x23, 20, 21, 22, 23, 24, 25, 26]));
+        assert_eq!(sent[4], outgoing(&[0x24, 27, 28, 29]));
+        assert!(!can.has_pending_rx_frames());
+
+        can.receive_frame(incoming(&[0x32, 0, 0]));
+        assert_eq!(isotp.send(&message::<30>()), Err(Error::Overflow));
+        can.receive_frame(incoming(&[0x37, 0, 0]));
+        assert_eq!(isotp.send(&message::<30>()), Err(Error::InvalidFlowStatus));
+    });
+}
+
+#[test]
+fn send_timeouts() {
+    with_can(|can| {
+        let mut frames = RxRingBuffer::<4>::new();
+        let mut config = config();
+        config.max_wait_frames = 1;
+        let mut isotp = IsoTp::new(config, &mut frames);
+
+        assert_eq!(
+            isotp.send(&message::<30>()),
+            Err(Error::Timeout(Timeout::Bs))
+        );
+        can.receive_frame(incoming(&[0x31, 0, 0]));
+        can.receive_frame(incoming(&[0x31, 0, 0]));
+        assert_eq!(isotp.send(&message::<30>()), Err(Error::WaitLimit));
+        can.take_transmitted_frames();
+
+        can.set_acknowledged(false);
+        assert_eq!(isotp.send(&[1]), Err(Error::Timeout(Timeout::As)));
+        assert_eq!(can.take_transmitted_frames(), []);
+    });
+}
+
+#[test]
+fn receive_segmented() {
+    with_can(|can| {
+        let mut frames = RxRingBuffer::<4>::new();
+        let mut config = config();
+        config.block_size = 2;
+        config.st_min = 10;
+        let mut isotp = IsoTp::new(config, &mut frames);
+
+        can.receive_frame(incoming(&[0x10, 30, 0, 1, 2, 3, 4, 5]));
+        can.receive_frame(incoming(&[0x21, 6, 7, 8, 9, 10, 11, 12]));
+        can.receive_frame(incoming(&[0x22, 13, 14, 15, 16, 17, 18, 19]));
+        can.receive_frame(incoming(&[0x23, 20, 21, 22, 23, 24, 25, 26]));
+        can.receive_frame(incoming(&[0x24, 27, 28, 29, 0xCC, 0xCC, 0xCC, 0xCC]));
+        let mut buf = [0; 64];
+        assert_eq!(isotp.receive(&mut buf), Ok(30));
+        assert_eq!(buf[..30], message::<30>());
+        assert_eq!(
+            can.take_transmitted_frames(),
+            [outgoing(&[0x30, 2, 10]), outgoing(&[0x30, 2, 10])]
+        );
+    });
+}
+
+#[test]
+fn receive_errors() {
+    with_can(|can| {
+        let mut frames = RxRingBuffer::<4>::new();
+        let mut isotp = IsoTp::new(config(), &mut frames);
+        let mut buf = [0; 64];
+
+        can.receive_frame(incoming(&[0x10, 30, 0, 1, 2, 3, 4, 5]));
+        assert_eq!(isotp.receive(&mut buf), Err(Error::Timeout(Timeout::Cr)));
+
+        can.receive_frame(incoming(&[0x10, 30, 0, 1, 2, 3, 4, 5]));
+        can.receive_frame(incoming(&[0x22, 6, 7, 8, 9, 10, 11, 12]));
+        assert_eq!(isotp.receive(&mut buf), Err(Error::WrongSequenceNumber));
+
+        can.receive_frame(incoming(&[0x10, 30, 0, 1, 2, 3, 4, 5]));
+        assert_eq!(isotp.receive(&mut buf[..29]), Err(Error::Overflow));
+
+        assert_eq!(
+            can.take_transmitted_frames(),
+            [
+                outgoing(&[0x30, 0, 0]),
+                outgoing(&[0x30, 0, 0]),
+                outgoing(&[0x32, 0, 0])
+            ]
+        );
+
+        // A new message from the peer abandons the one in progress, and
+        // frames that cannot start a message are ignored.
+        can.receive_frame(incoming(&[0x21, 6, 7, 8, 9, 10, 11, 12]));
+        can.receive_frame(incoming(&[0x10, 30, 0, 1, 2, 3, 4, 5]));
+        can.receive_frame(incoming(&[0x02, 0xAB, 0xCD]));
+        assert_eq!(isotp.receive(&mut buf), Ok(2));
+        assert_eq!(buf[..2], [0xAB, 0xCD]);
+    });
+}
+
+#[test]
+fn extended_addressing() {
+    with_can(|can| {
+        let mut frames = RxRingBuffer::<4>::new();
+        let mut config = config();
+        config.addressing = Addressing::Extended {
+            local: 0xF1,
+            remote: 0x10,
+        };
+        config.padding = Some(0xCC);
+        let mut isotp = IsoTp::new(config, &mut frames);
+
+        // Seven bytes no longer fit in a single frame. The peer's flow
+        // control frame is only accepted if it is addressed to this node.
+        can.receive_frame(incoming(&[0xF2, 0x32, 0, 0]));
+        can.receive_frame(incoming(&[0xF1, 0x30, 0, 0]));
+        assert_eq!(isotp.send(&message::<7>()), Ok(()));
+        assert_eq!(
+            can.take_transmitted_frames(),
+            [
+                outgoing(&[0x10, 0x10, 7, 0, 1, 2, 3, 4]),
+                outgoing(&[0x10, 0x21, 5, 6, 0xCC, 0xCC, 0xCC, 0xCC]),
+            ]
+        );
+
+        can.receive_frame(incoming(&[0xF1, 0x10, 8, 0, 1, 2, 3, 4]));
+        can.receive_frame(incoming(&[0xF1, 0x21, 5, 6, 7]));
+        let mut buf = [0; 8];
+        assert_eq!(isotp.receive(&mut buf), Ok(8));
+        assert_eq!(buf, message::<8>());
+        assert_eq!(
+            can.take_transmitted_frames(),
+            [outgoing(&[0x10, 0x30, 0, 0, 0xCC, 0xCC, 0xCC, 0xCC])]
+        );
+    });
+}
+
+#[test]
+fn long_messages() {
+    with_can(|can| {
+        let mut frames = RxRingBuffer::<4>::new();
+        let mut isotp = IsoTp::new(config(), &mut frames);
+        let message = message::<5000>();
+
+        // Messages longer than 4095 bytes use the escape sequence.
+        can.receive_frame(incoming(&[0x30, 0, 0]));
+        assert_eq!(isotp.send(&message), Ok(()));
+        let sent = can.take_transmitted_frames();
+        assert_eq!(sent.len(), 715);
+        assert_eq!(sent[0], outgoing(&[0x10, 0, 0, 0, 0x13, 0x88, 0, 1]));
+        assert_eq!(sent[714].data[1..], message[4993..]);
+
+        for frame in sent {
+            can.receive_frame(incoming(&frame.data));
+        }
+        let mut buf = [0; 5000];
+        assert_eq!(isotp.receive(&mut buf), Ok(5000));
+        assert_eq!(buf, message);
+    });
+}
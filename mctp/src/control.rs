@@ -0,0 +1,161 @@
+//! MCTP control messages (message type 0), with which the bus owner assigns
+//! EIDs and discovers endpoints.
+//!
+//! A control message body starts with a byte holding the request flag and an
+//! instance ID, which matches responses to requests, followed by the command
+//! code. Responses then carry a completion code.
+
+use crate::{MessageType, BROADCAST_EID, NULL_EID};
+
+/// Command codes.
+pub mod command {
+    pub const SET_ENDPOINT_ID: u8 = 0x01;
+    pub const GET_ENDPOINT_ID: u8 = 0x02;
+    pub const GET_ENDPOINT_UUID: u8 = 0x03;
+    pub const GET_VERSION_SUPPORT: u8 = 0x04;
+    pub const GET_MESSAGE_TYPE_SUPPORT: u8 = 0x05;
+}
+
+/// Completion codes.
+pub mod completion_code {
+    pub const SUCCESS: u8 = 0x00;
+    pub const ERROR: u8 = 0x01;
+    pub const ERROR_INVALID_DATA: u8 = 0x02;
+    pub const ERROR_INVALID_LENGTH: u8 = 0x03;
+    pub const ERROR_NOT_READY: u8 = 0x04;
+    pub const ERROR_UNSUPPORTED_CMD: u8 = 0x05;
+    /// Get MCTP Version Support: the message type is not supported.
+    pub const MESSAGE_TYPE_NOT_SUPPORTED: u8 = 0x80;
+}
+
+/// The length of the longest response.
+pub const MAX_RESPONSE_LEN: usize = 3 + MAX_DATA_LEN;
+
+// The longest response data: Get Message Type Support with every type.
+const MAX_DATA_LEN: usize = 1 + 128;
+
+// The request flag and instance ID of the first byte.
+const REQUEST: u8 = 1 << 7;
+const INSTANCE_ID: u8 = 0x1F;
+
+// The operations of Set Endpoint ID.
+const SET_EID: u8 = 0;
+const FORCE_EID: u8 = 1;
+const RESET_EID: u8 = 2;
+
+// The version of the base specification and the control protocol: 1.3.1.
+const VERSION: [u8; 4] = [0xF1, 0xF3, 0xF1, 0x00];
+
+/// Answers the control requests an endpoint receives, and keeps its EID.
+///
+/// The responder supports Set Endpoint ID, Get Endpoint ID, Get Endpoint UUID,
+/// Get MCTP Version Support and Get Message Type Support.
+#[derive(Clone, Copy, Debug)]
+pub struct Responder<'a> {
+    eid: u8,
+    static_eid: Option<u8>,
+    message_types: &'a [MessageType],
+    uuid: Option<[u8; 16]>,
+}
+
+impl<'a> Responder<'a> {
+    /// Creates a responder for an endpoint with `static_eid`, or with the null
+    /// EID until the bus owner assigns it one. `message_types` are the types
+    /// the endpoint supports besides control messages.
+    pub fn new(
+        static_eid: Option<u8>,
+        message_types: &'a [MessageType],
+        uuid: Option<[u8; 16]>,
+    ) -> Responder<'a> {
+        Responder {
+            eid: static_eid.unwrap_or(NULL_EID),
+            static_eid,
+            message_types,
+            uuid,
+        }
+    }
+
+    /// The EID of the endpoint.
+    pub fn eid(&self) -> u8 {
+        self.eid
+    }
+
+    /// Handles the body of a control message, writing the body of the
+    /// response into `response`. Returns the length of the response, or
+    /// `None` if the message is not a request, is too short to be one, or the
+    /// response does not fit in `response`.
+    pub fn handle(&mut self, request: &[u8], response: &mut [u8]) -> Option<usize> {
+        use completion_code::*;
+
+        let (&[instance, command], request) = request.split_first_chunk()?;
+        if instance & REQUEST == 0 {
+            return None;
+        }
+        let mut data = [0; MAX_DATA_LEN];
+        let (completion_code, data_len) = match command {
+            command::SET_ENDPOINT_ID => match self.set_endpoint_id(request) {
+                Ok(()) => {
+                    // Accepted, with no EID pool.
+                    data[..3].copy_from_slice(&[0x00, self.eid, 0]);
+                    (SUCCESS, 3)
+                }
+                Err(completion_code) => (completion_code, 0),
+            },
+            command::GET_ENDPOINT_ID => {
+                // A simple endpoint, with a dynamic EID, or a static one that
+                // is in use or not.
+                let eid_type = match self.static_eid {
+                    None => 0b00,
+                    Some(eid) if eid == self.eid => 0b10,
+                    Some(_) => 0b11,
+                };
+                data[..3].copy_from_slice(&[self.eid, eid_type, 0]);
+                (SUCCESS, 3)
+            }
+            command::GET_ENDPOINT_UUID => match self.uuid {
+                Some(uuid) => {
+                    data[..16].copy_from_slice(&uuid);
+                    (SUCCESS, 16)
+                }
+                None => (ERROR_UNSUPPORTED_CMD, 0),
+            },
+            command::GET_VERSION_SUPPORT => match request.first() {
+                // The base specification, or the control protocol.
+                Some(0xFF | 0x00) => {
+                    data[0] = 1;
+                    data[1..5].copy_from_slice(&VERSION);
+                    (SUCCESS, 5)
+                }
+                Some(_) => (MESSAGE_TYPE_NOT_SUPPORTED, 0),
+                None => (ERROR_INVALID_LENGTH, 0),
+            },
+            command::GET_MESSAGE_TYPE_SUPPORT => {
+                let types = &self.message_types[..self.message_types.len().min(MAX_DATA_LEN - 1)];
+                data[0] = types.len() as u8;
+                for (byte, message_type) in data[1..].iter_mut().zip(types) {
+                    *byte = message_type.0;
+                }
+                (SUCCESS, 1 + types.len())
+            }
+            _ => (ERROR_UNSUPPORTED_CMD, 0),
+        };
+
+        let response = response.get_mut(..3 + data_len)?;
+        response[..3].copy_from_slice(&[instance & INSTANCE_ID, command, completion_code]);
+        response[3..].copy_from_slice(&data[..data_len]);
+        Some(3 + data_len)
+    }
+
+    fn set_endpoint_id(&mut self, request: &[u8]) -> Result<(), u8> {
+        let Some(&[operation, eid]) = request.first_chunk() else {
+            return Err(completion_code::ERROR_INVALID_LENGTH);
+        };
+        match (operation & 0b11, self.static_eid) {
+            (SET_EID | FORCE_EID, _) if eid != NULL_EID && eid != BROADCAST_EID => self.eid = eid,
+            (RESET_EID, Some(static_eid)) => self.eid = static_eid,
+            // The discovered flag is only defined for PCIe.
+            _ => return Err(completion_code::ERROR_INVALID_DATA),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use crate::control::{command, completion_code::*, Responder, MAX_RESPONSE_LEN};
+use crate::MessageType;
+
+const UUID: [u8; 16] = [
+    0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0xF0, 0x0F, 0xED, 0xCB, 0xA9, 0x87, 0x65, 0x43, 0x21,
+];
+
+// Handles `request`, returning the response.
+fn handle(responder: &mut Responder, request: &[u8]) -> Option<Vec<u8>> {
+    let mut response = [0; MAX_RESPONSE_LEN];
+    let len = responder.handle(request, &mut response)?;
+    Some(response[..len].to_vec())
+}
+
+#[test]
+fn set_endpoint_id() {
+    let mut responder = Responder::new(None, &[], None);
+    assert_eq!(responder.eid(), 0);
+
+    // The instance ID is echoed, without the request flag.
+    assert_eq!(
+        handle(
+            &mut responder,
+            &[0x81, command::SET_ENDPOINT_ID, 0x00, 0x0A]
+        ),
+        Some(vec![0x01, 0x01, SUCCESS, 0x00, 0x0A, 0x00])
+    );
+    assert_eq!(responder.eid(), 0x0A);
+    assert_eq!(
+        handle(
+            &mut responder,
+            &[0x82, command::SET_ENDPOINT_ID, 0x01, 0x0B]
+        ),
+        Some(vec![0x02, 0x01, SUCCESS, 0x00, 0x0B, 0x00])
+    );
+
+    // The null and broadcast EIDs cannot be assigned, a dynamic EID cannot be
+    // reset, and the discovered flag is not supported.
+    for data in [[0x00, 0x00], [0x00, 0xFF], [0x02, 0x00], [0x03, 0x00]] {
+        let request = [0x83, command::SET_ENDPOINT_ID, data[0], data[1]];
+        assert_eq!(
+            handle(&mut responder, &request),
+            Some(vec![0x03, 0x01, ERROR_INVALID_DATA])
+        );
+    }
+    assert_eq!(
+        handle(&mut responder, &[0x84, command::SET_ENDPOINT_ID, 0x00]),
+        Some(vec![0x04, 0x01, ERROR_INVALID_LENGTH])
+    );
+    assert_eq!(responder.eid(), 0x0B);
+
+    // A static EID can be overridden, and reset.
+    let mut responder = Responder::new(Some(0x20), &[], None);
+    assert_eq!(responder.eid(), 0x20);
+    handle(
+        &mut responder,
+        &[0x80, command::SET_ENDPOINT_ID, 0x00, 0x30],
+    )
+    .unwrap();
+    assert_eq!(responder.eid(), 0x30);
+    assert_eq!(
+        handle(
+            &mut responder,
+            &[0x80, command::SET_ENDPOINT_ID, 0x02, 0x00]
+        ),
+        Some(vec![0x00, 0x01, SUCCESS, 0x00, 0x20, 0x00])
+    );
+}
+
+#[test]
+fn get_endpoint_id() {
+    let request = [0x80, command::GET_ENDPOINT_ID];
+    let mut responder = Responder::new(None, &[], None);
+    assert_eq!(
+        handle(&mut responder, &request),
+        Some(vec![0x00, 0x02, SUCCESS, 0x00, 0x00, 0x00])
+    );
+
+    let mut responder = Responder::new(Some(0x20), &[], None);
+    assert_eq!(
+        handle(&mut responder, &request),
+        Some(vec![0x00, 0x02, SUCCESS, 0x20, 0x02, 0x00])
+    );
+    handle(
+        &mut responder,
+        &[0x80, command::SET_ENDPOINT_ID, 0x00, 0x30],
+    )
+    .unwrap();
+    assert_eq!(
+        handle(&mut responder, &request),
+        Some(vec![0x00, 0x02, SUCCESS, 0x30, 0x03, 0x00])
+    );
+}
+
+#[test]
+fn discovery() {
+    let types = [MessageType::PLDM, MessageType::SPDM];
+    let mut responder = Responder::new(None, &types, Some(UUID));
+    let mut expected = vec![0x00, 0x03, SUCCESS];
+    expected.extend_from_slice(&UUID);
+    assert_eq!(
+        handle(&mut responder, &[0x80, command::GET_ENDPOINT_UUID]),
+        Some(expected)
+    );
+    assert_eq!(
+        handle(&mut responder, &[0x80, command::GET_MESSAGE_TYPE_SUPPORT]),
+        Some(vec![0x00, 0x05, SUCCESS, 0x02, 0x01, 0x05])
+    );
+    for message_type in [0xFF, 0x00] {
+        assert_eq!(
+            handle(
+                &mut responder,
+                &[0x80, command::GET_VERSION_SUPPORT, message_type]
+            ),
+            Some(vec![0x00, 0x04, SUCCESS, 0x01, 0xF1, 0xF3, 0xF1, 0x00])
+        );
+    }
+    assert_eq!(
+        handle(&mut responder, &[0x80, command::GET_VERSION_SUPPORT, 0x01]),
+        Some(vec![0x00, 0x04, MESSAGE_TYPE_NOT_SUPPORTED])
+    );
+    assert_eq!(
+        handle(&mut responder, &[0x80, command::GET_VERSION_SUPPORT]),
+        Some(vec![0x00, 0x04, ERROR_INVALID_LENGTH])
+    );
+
+    let mut responder = Responder::new(None, &[], None);
+    assert_eq!(
+        handle(&mut responder, &[0x80, command::GET_ENDPOINT_UUID]),
+        Some(vec![0x00, 0x03, ERROR_UNSUPPORTED_CMD])
+    );
+    assert_eq!(
+        handle(&mut responder, &[0x80, command::GET_MESSAGE_TYPE_SUPPORT]),
+        Some(vec![0x00, 0x05, SUCCESS, 0x00])
+    );
+}
+
+#[test]
+fn not_requests() {
+    let mut responder = Responder::new(None, &[], Some(UUID));
+    assert_eq!(
+        handle(&mut responder, &[0x80, 0x7F]),
+        Some(vec![0x00, 0x7F, ERROR_UNSUPPORTED_CMD])
+    );
+    // Responses and truncated messages are not answered.
+    assert_eq!(
+        handle(&mut responder, &[0x00, command::GET_ENDPOINT_ID]),
+        None
+    );
+    assert_eq!(handle(&mut responder, &[0x80]), None);
+    // Neither are requests whose response does not fit.
+    let mut response = [0; 8];
+    assert_eq!(
+        responder.handle(&[0x80, command::GET_ENDPOINT_UUID], &mut response),
+        None
+    );
+}
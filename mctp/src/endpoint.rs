@@ -0,0 +1,283 @@
+use core::marker::PhantomData;
+use libtock_i2c_master_slave::{Config, I2CMasterSlave};
+use libtock_platform::{DefaultConfig, Syscalls};
+
+use crate::control::{self, Responder};
+use crate::smbus::{self, BASELINE_MTU, MAX_MTU};
+use crate::{
+    Error, Fragmenter, Header, Message, MessageType, Reassembler, BROADCAST_EID, NULL_EID,
+};
+
+/// A route to the endpoints with EIDs `first_eid..=last_eid`, which are
+/// reached through the device at `address`: the endpoints themselves, or a
+/// bridge to them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Route {
+    pub first_eid: u8,
+    pub last_eid: u8,
+    pub address: u8,
+}
+
+impl Route {
+    /// Returns a route to the endpoint with `eid`.
+    pub const fn new(eid: u8, address: u8) -> Route {
+        Route {
+            first_eid: eid,
+            last_eid: eid,
+            address,
+        }
+    }
+
+    /// Returns a route to the endpoints with EIDs `first_eid..=last_eid`.
+    pub const fn range(first_eid: u8, last_eid: u8, address: u8) -> Route {
+        Route {
+            first_eid,
+            last_eid,
+            address,
+        }
+    }
+
+    fn contains(&self, eid: u8) -> bool {
+        (self.first_eid..=self.last_eid).contains(&eid)
+    }
+}
+
+/// The configuration of an endpoint.
+#[derive(Clone, Copy, Debug)]
+pub struct EndpointConfig<'a> {
+    /// The address of the endpoint.
+    pub address: u8,
+    /// The transmission unit of the packets sent, from `BASELINE_MTU` to
+    /// `MAX_MTU`. It is clamped to that range.
+    pub mtu: usize,
+    /// The routes to other endpoints, searched in order.
+    pub routes: &'a [Route],
+    /// The address messages to EIDs without a route are sent to, usually the
+    /// bus owner's.
+    pub default_route: Option<u8>,
+    /// The EID of the endpoint if it has a static one. Otherwise, the endpoint
+    /// uses the null EID until the bus owner assigns it one.
+    pub static_eid: Option<u8>,
+    /// The message types the endpoint supports besides control messages, which
+    /// it reports to the bus owner.
+    pub message_types: &'a [MessageType],
+    pub uuid: Option<[u8; 16]>,
+}
+
+impl EndpointConfig<'_> {
+    /// Returns a configuration for an endpoint at `address` with the baseline
+    /// MTU, no routes and no static EID.
+    pub fn new(address: u8) -> Self {
+        EndpointConfig {
+            address,
+            mtu: BASELINE_MTU,
+            routes: &[],
+            default_route: None,
+            static_eid: None,
+            message_types: &[],
+            uuid: None,
+        }
+    }
+}
+
+/// An MCTP endpoint on an SMBus/I2C bus.
+///
+/// Transfers are half-duplex: [`Endpoint::receive`] and [`Endpoint::request`]
+/// block until a message arrives, and answer the control requests that arrive
+/// meanwhile.
+pub struct Endpoint<'a, S: Syscalls, C: Config = DefaultConfig> {
+    config: EndpointConfig<'a>,
+    control: Responder<'a>,
+    reassembler: Reassembler,
+    next_tag: u8,
+    s: PhantomData<S>,
+    c: PhantomData<C>,
+}
+
+impl<'a, S: Syscalls, C: Config> Endpoint<'a, S, C> {
+    pub fn new(config: EndpointConfig<'a>) -> Self {
+        Self {
+            control: Responder::new(config.static_eid, config.message_types, config.uuid),
+            config: EndpointConfig {
+                mtu: config.mtu.clamp(BASELINE_MTU, MAX_MTU),
+                ..config
+            },
+            reassembler: Reassembler::new(),
+            next_tag: 0,
+            s: PhantomData,
+            c: PhantomData,
+        }
+    }
+
+    pub fn config(&self) -> &EndpointConfig<'a> {
+        &self.config
+    }
+
+    /// The EID of the endpoint: its static EID, the EID the bus owner
+    /// assigned it, or the null EID.
+    pub fn eid(&self) -> u8 {
+        self.control.eid()
+    }
+
+    /// Returns the address of the route to `eid`.
+    pub fn route(&self, eid: u8) -> Result<u8, Error> {
+        self.config
+            .routes
+            .iter()
+            .find(|route| route.contains(eid))
+            .map(|route| route.address)
+            .or(self.config.default_route)
+            .ok_or(Error::NoRoute)
+    }
+
+    /// Sends a request to `dest` with a new tag, which is returned.
+    pub fn send_request(
+        &mut self,
+        dest: u8,
+        message_type: MessageType,
+        body: &[u8],
+    ) -> Result<u8, Error> {
+        let address = self.route(dest)?;
+        let tag = self.next_tag;
+        self.next_tag = (tag + 1) % 8;
+        self.send(address, dest, true, tag, message_type, body)?;
+        Ok(tag)
+    }
+
+    /// Sends the response to `request`, to the address it came from.
+    pub fn respond(&mut self, request: &Message, body: &[u8]) -> Result<(), Error> {
+        self.send(
+            request.address,
+            request.source,
+            false,
+            request.tag,
+            request.message_type,
+            body,
+        )
+    }
+
+    /// Sends a request to `dest` and waits for its response, whose body is
+    /// received into `buffer`. Other messages that arrive meanwhile are
+    /// dropped, and so are invalid packets, unless they belong to the
+    /// response: `Error::Overflow` is returned if the response does not fit in
+    /// `buffer`, and `Error::LostPacket` if one of its packets was lost.
+    ///
+    /// There is no timeout: if the response never arrives, `request` waits
+    /// forever.
+    pub fn request(
+        &mut self,
+        dest: u8,
+        message_type: MessageType,
+        body: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<Message, Error> {
+        let tag = self.send_request(dest, message_type, body)?;
+        self.receive_matching(buffer, Some((dest, tag)))
+    }
+
+    /// Waits for a message to this endpoint, whose body is received into
+    /// `buffer`. Control requests are answered instead of being returned.
+    ///
+    /// Packets that cannot be part of a message are dropped, and the error
+    /// returned. The messages after them are received by the next call.
+    pub fn receive(&mut self, buffer: &mut [u8]) -> Result<Message, Error> {
+        self.receive_matching(buffer, None)
+    }
+
+    // Receives messages as `receive` does. If `response` holds the
+    // destination and tag of a request, only its response is returned: other
+    // messages are dropped, and so are the packets that cannot be part of a
+    // message unless they belong to the response.
+    fn receive_matching(
+        &mut self,
+        buffer: &mut [u8],
+        response: Option<(u8, u8)>,
+    ) -> Result<Message, Error> {
+        let is_response = |source: u8, tag_owner: bool, tag: u8| match response {
+            None => true,
+            Some(response) => !tag_owner && (source, tag) == response,
+        };
+        I2CMasterSlave::<S, C>::i2c_master_slave_set_slave_address(self.config.address)?;
+        let mut frame = [0; smbus::MAX_FRAME_LEN];
+        loop {
+            let (len, result) =
+                I2CMasterSlave::<S, C>::i2c_master_slave_write_recv_sync(&mut frame);
+            result?;
+            match self.receive_packet(&frame[..len], buffer) {
+                Ok(None) => {}
+                Ok(Some(message)) => {
+                    if is_response(message.source, message.tag_owner, message.tag) {
+                        return Ok(message);
+                    }
+                }
+                Err(error @ Error::Driver(_)) => return Err(error),
+                Err(error) if response.is_none() => return Err(error),
+                Err(error @ (Error::Overflow | Error::LostPacket)) => {
+                    let header = smbus::decode(&frame[..len], self.config.address)
+                        .and_then(|(_, packet)| Header::parse(packet))?;
+                    if is_response(header.source, header.tag_owner, header.tag) {
+                        return Err(error);
+                    }
+                }
+                Err(_) => {}
+            }
+        }
+    }
+
+    // Adds the packet in `frame` to the message being received into
+    // `buffer`, and returns the message once it is complete, unless it is a
+    // control request, which is answered instead.
+    fn receive_packet(
+        &mut self,
+        frame: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<Option<Message>, Error> {
+        let (address, packet) = smbus::decode(frame, self.config.address)?;
+        let dest = Header::parse(packet)?.dest;
+        if dest != self.eid() && dest != NULL_EID && dest != BROADCAST_EID {
+            return Err(Error::WrongDestination);
+        }
+        let Some(message) = self.reassembler.receive(address, packet, buffer)? else {
+            return Ok(None);
+        };
+        if message.message_type == MessageType::CONTROL {
+            let mut response = [0; control::MAX_RESPONSE_LEN];
+            if let Some(len) = self.control.handle(&buffer[..message.len], &mut response) {
+                self.respond(&message, &response[..len])?;
+                return Ok(None);
+            }
+        }
+        Ok(Some(message))
+    }
+
+    fn send(
+        &mut self,
+        address: u8,
+        dest: u8,
+        tag_owner: bool,
+        tag: u8,
+        message_type: MessageType,
+        body: &[u8],
+    ) -> Result<(), Error> {
+        let header = Header {
+            dest,
+            source: self.eid(),
+            som: false,
+            eom: false,
+            seq: 0,
+            tag_owner,
+            tag,
+        };
+        let mut fragmenter = Fragmenter::new(header, message_type, body, self.config.mtu);
+        let mut frame = [0; smbus::MAX_FRAME_LEN];
+        while let Some(packet_len) = fragmenter.next_packet(&mut frame[smbus::HEADER_LEN..]) {
+            let len = smbus::encode(&mut frame, packet_len, address, self.config.address);
+            I2CMasterSlave::<S, C>::i2c_master_slave_write_sync(
+                address.into(),
+                &frame,
+                len as u16,
+            )?;
+        }
+        Ok(())
+    }
+}
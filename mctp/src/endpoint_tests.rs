@@ -0,0 +1,383 @@
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+use crate::smbus::{self, MAX_FRAME_LEN};
+use crate::{EndpointConfig, Error, Fragmenter, Header, Message, MessageType, Route, NULL_EID};
+
+type Endpoint<'a> = crate::Endpoint<'a, fake::Syscalls>;
+
+const ADDRESS: u8 = 0x34;
+const OWNER: u8 = 0x10;
+const OWNER_EID: u8 = 0x08;
+const PEER: u8 = 0x1D;
+const PEER_EID: u8 = 0x0A;
+const ROUTES: [Route; 1] = [Route::new(PEER_EID, PEER)];
+
+fn config() -> EndpointConfig<'static> {
+    EndpointConfig {
+        routes: &ROUTES,
+        default_route: Some(OWNER),
+        ..EndpointConfig::new(ADDRESS)
+    }
+}
+
+fn header(dest: u8, source: u8, tag_owner: bool, tag: u8) -> Header {
+    Header {
+        dest,
+        source,
+        som: false,
+        eom: false,
+        seq: 0,
+        tag_owner,
+        tag,
+    }
+}
+
+// The frames of a message written from `source` to `dest`, with the baseline
+// MTU.
+fn frames(
+    source: u8,
+    dest: u8,
+    header: Header,
+    message_type: MessageType,
+    body: &[u8],
+) -> Vec<Vec<u8>> {
+    let mut fragmenter = Fragmenter::new(header, message_type, body, smbus::BASELINE_MTU);
+    let mut frames = Vec::new();
+    let mut frame = [0; MAX_FRAME_LEN];
+    while let Some(packet_len) = fragmenter.next_packet(&mut frame[smbus::HEADER_LEN..]) {
+        let len = smbus::encode(&mut frame, packet_len, dest, source);
+        frames.push(frame[..len].to_vec());
+    }
+    frames
+}
+
+// Writes the frames of a message from `source` to the endpoint.
+fn write_message(
+    i2c: &fake::I2CMasterSlave,
+    source: u8,
+    header: Header,
+    message_type: MessageType,
+    body: &[u8],
+) {
+    for frame in frames(source, ADDRESS, header, message_type, body) {
+        i2c.write_to_slave(&frame);
+    }
+}
+
+// Runs `test` with a fake I2C master-slave driver.
+fn with_i2c(test: impl FnOnce(&fake::I2CMasterSlave)) {
+    let kernel = fake::Kernel::new();
+    let i2c = fake::I2CMasterSlave::new();
+    kernel.add_driver(&i2c);
+    test(&i2c);
+}
+
+#[test]
+fn send_request() {
+    with_i2c(|i2c| {
+        let mut endpoint = Endpoint::new(EndpointConfig { mtu: 8, ..config() });
+        assert_eq!(endpoint.config().mtu, smbus::BASELINE_MTU);
+
+        // The message is split into two packets, sent to the route's address.
+        let body = [0x42; 100];
+        assert_eq!(
+            endpoint.send_request(PEER_EID, MessageType::PLDM, &body),
+            Ok(0)
+        );
+        let expected = frames(
+            ADDRESS,
+            PEER,
+            header(PEER_EID, NULL_EID, true, 0),
+            MessageType::PLDM,
+            &body,
+        );
+        assert_eq!(expected.len(), 2);
+        assert_eq!(
+            i2c.take_master_writes(),
+            expected
+                .into_iter()
+                .map(|frame| (PEER, frame))
+                .collect::<Vec<_>>()
+        );
+
+        // EIDs without a route are reached through the default route.
+        assert_eq!(endpoint.send_request(0x50, MessageType::SPDM, &[]), Ok(1));
+        assert_eq!(
+            i2c.take_master_writes(),
+            [(
+                OWNER,
+                frames(
+                    ADDRESS,
+                    OWNER,
+                    header(0x50, NULL_EID, true, 1),
+                    MessageType::SPDM,
+                    &[]
+                )
+                .remove(0)
+            )]
+        );
+
+        // Tags wrap.
+        for tag in 2..8 {
+            assert_eq!(endpoint.send_request(0x50, MessageType::SPDM, &[]), Ok(tag));
+        }
+        assert_eq!(endpoint.send_request(0x50, MessageType::SPDM, &[]), Ok(0));
+
+        i2c.set_acknowledged(false);
+        assert_eq!(
+            endpoint.send_request(PEER_EID, MessageType::PLDM, &[]),
+            Err(Error::Driver(ErrorCode::NoAck))
+        );
+
+        let mut endpoint = Endpoint::new(EndpointConfig::new(ADDRESS));
+        assert_eq!(
+            endpoint.send_request(PEER_EID, MessageType::PLDM, &[]),
+            Err(Error::NoRoute)
+        );
+    });
+}
+
+#[test]
+fn eid_assignment() {
+    with_i2c(|i2c| {
+        let mut endpoint = Endpoint::new(config());
+        assert_eq!(endpoint.eid(), NULL_EID);
+
+        // The bus owner assigns an EID, then sends a request to it.
+        write_message(
+            i2c,
+            OWNER,
+            header(NULL_EID, OWNER_EID, true, 3),
+            MessageType::CONTROL,
+            &[0x80, 0x01, 0x00, 0x0B],
+        );
+        write_message(
+            i2c,
+            OWNER,
+            header(0x0B, OWNER_EID, true, 4),
+            MessageType::PLDM,
+            &[1, 2, 3],
+        );
+        let mut buffer = [0; 16];
+        let message = endpoint.receive(&mut buffer).unwrap();
+        assert_eq!(
+            message,
+            Message {
+                source: OWNER_EID,
+                address: OWNER,
+                message_type: MessageType::PLDM,
+                integrity_check: false,
+                tag_owner: true,
+                tag: 4,
+                len: 3,
+            }
+        );
+        assert_eq!(buffer[..3], [1, 2, 3]);
+        assert_eq!(i2c.slave_address(), Some(ADDRESS));
+        assert_eq!(endpoint.eid(), 0x0B);
+
+        // The response to Set Endpoint ID comes from the new EID.
+        assert_eq!(
+            i2c.take_master_writes(),
+            [(
+                OWNER,
+                frames(
+                    ADDRESS,
+                    OWNER,
+                    header(OWNER_EID, 0x0B, false, 3),
+                    MessageType::CONTROL,
+                    &[0x00, 0x01, 0x00, 0x00, 0x0B, 0x00],
+                )
+                .remove(0)
+            )]
+        );
+
+        assert_eq!(endpoint.respond(&message, &[4]), Ok(()));
+        assert_eq!(
+            i2c.take_master_writes(),
+            [(
+                OWNER,
+                frames(
+                    ADDRESS,
+                    OWNER,
+                    header(OWNER_EID, 0x0B, false, 4),
+                    MessageType::PLDM,
+                    &[4],
+                )
+                .remove(0)
+            )]
+        );
+    });
+}
+
+#[test]
+fn request() {
+    with_i2c(|i2c| {
+        let mut endpoint = Endpoint::new(EndpointConfig {
+            static_eid: Some(0x0B),
+            ..config()
+        });
+
+        // A response with another tag, a corrupted frame and a packet to
+        // another EID are dropped, and the response is reassembled from two
+        // packets.
+        let body: Vec<u8> = (0..100).collect();
+        write_message(
+            i2c,
+            PEER,
+            header(0x0B, PEER_EID, false, 1),
+            MessageType::PLDM,
+            &[0xEE],
+        );
+        let mut corrupted = frames(
+            PEER,
+            ADDRESS,
+            header(0x0B, PEER_EID, false, 0),
+            MessageType::PLDM,
+            &[0xEE],
+        )
+        .remove(0);
+        corrupted[4] ^= 1;
+        i2c.write_to_slave(&corrupted);
+        write_message(
+            i2c,
+            PEER,
+            header(0x0C, PEER_EID, false, 0),
+            MessageType::PLDM,
+            &[0xEE],
+        );
+        write_message(
+            i2c,
+            PEER,
+            header(0x0B, PEER_EID, false, 0),
+            MessageType::PLDM,
+            &body,
+        );
+        let mut buffer = [0; 128];
+        let message = endpoint
+            .request(PEER_EID, MessageType::PLDM, &[1, 2], &mut buffer)
+            .unwrap();
+        assert_eq!(
+            (message.source, message.tag, message.len),
+            (PEER_EID, 0, 100)
+        );
+        assert_eq!(buffer[..100], body[..]);
+        assert!(!i2c.has_pending_writes());
+        assert_eq!(
+            i2c.take_master_writes(),
+            [(
+                PEER,
+                frames(
+                    ADDRESS,
+                    PEER,
+                    header(PEER_EID, 0x0B, true, 0),
+                    MessageType::PLDM,
+                    &[1, 2],
+                )
+                .remove(0)
+            )]
+        );
+    });
+}
+
+#[test]
+fn request_errors() {
+    with_i2c(|i2c| {
+        let mut endpoint = Endpoint::new(EndpointConfig {
+            static_eid: Some(0x0B),
+            ..config()
+        });
+        let mut buffer = [0; 4];
+
+        // A message that does not fit is dropped unless it is the response.
+        write_message(
+            i2c,
+            PEER,
+            header(0x0B, PEER_EID, true, 0),
+            MessageType::PLDM,
+            &[0; 5],
+        );
+        write_message(
+            i2c,
+            PEER,
+            header(0x0B, PEER_EID, false, 0),
+            MessageType::PLDM,
+            &[0; 5],
+        );
+        assert_eq!(
+            endpoint.request(PEER_EID, MessageType::PLDM, &[], &mut buffer),
+            Err(Error::Overflow)
+        );
+        assert!(!i2c.has_pending_writes());
+
+        // The response's first packet is lost.
+        let body: Vec<u8> = (0..100).collect();
+        let mut response = frames(
+            PEER,
+            ADDRESS,
+            header(0x0B, PEER_EID, false, 1),
+            MessageType::PLDM,
+            &body,
+        );
+        i2c.write_to_slave(&response.remove(1));
+        let mut buffer = [0; 128];
+        assert_eq!(
+            endpoint.request(PEER_EID, MessageType::PLDM, &[], &mut buffer),
+            Err(Error::LostPacket)
+        );
+        assert!(!i2c.has_pending_writes());
+    });
+}
+
+#[test]
+fn receive_errors() {
+    with_i2c(|i2c| {
+        let mut endpoint = Endpoint::new(EndpointConfig {
+            static_eid: Some(0x0B),
+            ..config()
+        });
+        let mut buffer = [0; 4];
+
+        write_message(
+            i2c,
+            PEER,
+            header(0x0C, PEER_EID, true, 0),
+            MessageType::PLDM,
+            &[],
+        );
+        assert_eq!(endpoint.receive(&mut buffer), Err(Error::WrongDestination));
+
+        let mut frame = frames(
+            PEER,
+            ADDRESS,
+            header(0x0B, PEER_EID, true, 0),
+            MessageType::PLDM,
+            &[],
+        )
+        .remove(0);
+        frame[4] ^= 1;
+        i2c.write_to_slave(&frame);
+        assert_eq!(endpoint.receive(&mut buffer), Err(Error::InvalidPacket));
+
+        write_message(
+            i2c,
+            PEER,
+            header(0x0B, PEER_EID, true, 0),
+            MessageType::PLDM,
+            &[0; 5],
+        );
+        assert_eq!(endpoint.receive(&mut buffer), Err(Error::Overflow));
+
+        // Messages to the broadcast EID are received.
+        write_message(
+            i2c,
+            PEER,
+            header(0xFF, PEER_EID, true, 0),
+            MessageType::PLDM,
+            &[7],
+        );
+        assert_eq!(endpoint.receive(&mut buffer).map(|m| m.len), Ok(1));
+        assert!(i2c.take_master_writes().is_empty());
+    });
+}
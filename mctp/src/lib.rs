@@ -0,0 +1,87 @@
+//! `libtock_mctp` implements MCTP endpoints, following the DMTF Management
+//! Component Transport Protocol base specification (DSP0236) and its SMBus/I2C
+//! transport binding (DSP0237).
+//!
+//! MCTP carries messages of several types, such as PLDM, SPDM or NVMe-MI,
+//! between endpoints identified by endpoint IDs (EIDs). A message is split into
+//! packets of at most the transmission unit (MTU), each with a 4-byte header
+//! that carries the source and destination EIDs, start-of-message (SOM) and
+//! end-of-message (EOM) flags, a sequence number and a message tag. The tag,
+//! with the tag owner flag, matches responses to requests.
+//!
+//! An [`Endpoint`] sends packets as SMBus block writes with the I2C
+//! master-slave driver, to the address of the route to their destination EID,
+//! and receives packets written to its own address in slave mode. It answers
+//! the control requests the bus owner sends, such as Set Endpoint ID, with a
+//! [`control::Responder`], and keeps the EID the bus owner assigns.
+//!
+//! The stack does not allocate: messages are sent from and received into
+//! buffers provided by the caller.
+//!
+//! # Example
+//! ```ignore
+//! use libtock::mctp::{Endpoint, EndpointConfig, MessageType, Route};
+//!
+//! let routes = [Route::new(0x0A, 0x1D)];
+//! let mut endpoint = Endpoint::new(EndpointConfig {
+//!     routes: &routes,
+//!     message_types: &[MessageType::PLDM],
+//!     ..EndpointConfig::new(0x34)
+//! });
+//! let mut response = [0; 128];
+//! let message = endpoint.request(0x0A, MessageType::PLDM, &request, &mut response)?;
+//! let body = &response[..message.len];
+//! ```
+
+#![cfg_attr(not(test), no_std)]
+
+pub mod control;
+mod endpoint;
+mod packet;
+pub mod smbus;
+
+pub use endpoint::{Endpoint, EndpointConfig, Route};
+pub use packet::{Fragmenter, Header, Message, MessageType, Reassembler, HEADER_LEN};
+
+use libtock_platform::ErrorCode;
+
+/// The null EID, which addresses the endpoint at the physical address a
+/// packet is sent to. Endpoints use it before the bus owner assigns them an
+/// EID.
+pub const NULL_EID: u8 = 0x00;
+
+/// The broadcast EID, which addresses all endpoints.
+pub const BROADCAST_EID: u8 = 0xFF;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The I2C master-slave driver returned an error.
+    Driver(ErrorCode),
+    /// A packet is malformed: it is too short, or its header version, SMBus
+    /// command code, byte count or PEC is wrong.
+    InvalidPacket,
+    /// A packet was dropped because a packet before it was lost: it continues
+    /// no message being received, or arrived out of sequence.
+    LostPacket,
+    /// The message does not fit in the receive buffer.
+    Overflow,
+    /// A packet is addressed to another EID.
+    WrongDestination,
+    /// No route leads to the destination EID.
+    NoRoute,
+}
+
+impl From<ErrorCode> for Error {
+    fn from(error: ErrorCode) -> Error {
+        Error::Driver(error)
+    }
+}
+
+#[cfg(test)]
+mod control_tests;
+#[cfg(test)]
+mod endpoint_tests;
+#[cfg(test)]
+mod packet_tests;
+#[cfg(test)]
+mod smbus_tests;
@@ -0,0 +1,233 @@
+//! MCTP packets: their header, and the fragmentation of messages into packets
+//! and their reassembly.
+
+use crate::Error;
+
+/// The length of the packet header.
+pub const HEADER_LEN: usize = 4;
+
+const HEADER_VERSION: u8 = 0b0001;
+
+// Flags of the last header byte, and the message type byte.
+const SOM: u8 = 1 << 7;
+const EOM: u8 = 1 << 6;
+const TAG_OWNER: u8 = 1 << 3;
+const INTEGRITY_CHECK: u8 = 1 << 7;
+
+/// The type of a message, carried in the first byte of its first packet.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MessageType(pub u8);
+
+impl MessageType {
+    pub const CONTROL: MessageType = MessageType(0x00);
+    pub const PLDM: MessageType = MessageType(0x01);
+    pub const NCSI: MessageType = MessageType(0x02);
+    pub const ETHERNET: MessageType = MessageType(0x03);
+    pub const NVME_MI: MessageType = MessageType(0x04);
+    pub const SPDM: MessageType = MessageType(0x05);
+    pub const SECURED: MessageType = MessageType(0x06);
+    pub const VENDOR_PCI: MessageType = MessageType(0x7E);
+    pub const VENDOR_IANA: MessageType = MessageType(0x7F);
+}
+
+/// The header of a packet.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Header {
+    pub dest: u8,
+    pub source: u8,
+    /// Start of message: the packet is the first of its message.
+    pub som: bool,
+    /// End of message: the packet is the last of its message.
+    pub eom: bool,
+    /// The sequence number, which counts the packets of a message modulo 4.
+    pub seq: u8,
+    /// Set on requests, whose tag the sender chose, and clear on responses,
+    /// which carry the tag of their request.
+    pub tag_owner: bool,
+    /// The message tag, from 0 to 7.
+    pub tag: u8,
+}
+
+impl Header {
+    /// Parses the header at the start of `packet`.
+    pub fn parse(packet: &[u8]) -> Result<Header, Error> {
+        let Some(&[version, dest, source, flags]) = packet.first_chunk() else {
+            return Err(Error::InvalidPacket);
+        };
+        if version & 0x0F != HEADER_VERSION {
+            return Err(Error::InvalidPacket);
+        }
+        Ok(Header {
+            dest,
+            source,
+            som: flags & SOM != 0,
+            eom: flags & EOM != 0,
+            seq: (flags >> 4) & 0b11,
+            tag_owner: flags & TAG_OWNER != 0,
+            tag: flags & 0b111,
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut flags = (self.seq & 0b11) << 4 | self.tag & 0b111;
+        if self.som {
+            flags |= SOM;
+        }
+        if self.eom {
+            flags |= EOM;
+        }
+        if self.tag_owner {
+            flags |= TAG_OWNER;
+        }
+        [HEADER_VERSION, self.dest, self.source, flags]
+    }
+}
+
+/// Splits a message into packets.
+///
+/// The message is its type byte followed by its body. Every packet but the
+/// last carries `mtu` bytes of it.
+pub struct Fragmenter<'m> {
+    header: Header,
+    message_type: MessageType,
+    body: &'m [u8],
+    mtu: usize,
+    // The number of bytes of the message already in packets.
+    sent: usize,
+}
+
+impl<'m> Fragmenter<'m> {
+    /// Creates a fragmenter for a message with the addresses and tag of
+    /// `header`, whose flags and sequence number are set for each packet.
+    /// `mtu` must not be 0.
+    pub fn new(header: Header, message_type: MessageType, body: &'m [u8], mtu: usize) -> Self {
+        Fragmenter {
+            header: Header { seq: 0, ..header },
+            message_type,
+            body,
+            mtu,
+            sent: 0,
+        }
+    }
+
+    /// Writes the next packet into `packet`, which must have room for the
+    /// header and `mtu` bytes. Returns the length of the packet, or `None`
+    /// once the whole message was written.
+    pub fn next_packet(&mut self, packet: &mut [u8]) -> Option<usize> {
+        let message_len = 1 + self.body.len();
+        if self.sent == message_len {
+            return None;
+        }
+        let payload_len = (message_len - self.sent).min(self.mtu);
+        self.header.som = self.sent == 0;
+        self.header.eom = self.sent + payload_len == message_len;
+        packet[..HEADER_LEN].copy_from_slice(&self.header.to_bytes());
+
+        let mut payload = &mut packet[HEADER_LEN..HEADER_LEN + payload_len];
+        let body_start = match self.sent {
+            0 => {
+                payload[0] = self.message_type.0;
+                payload = &mut payload[1..];
+                0
+            }
+            sent => sent - 1,
+        };
+        payload.copy_from_slice(&self.body[body_start..body_start + payload.len()]);
+
+        self.sent += payload_len;
+        self.header.seq = (self.header.seq + 1) % 4;
+        Some(HEADER_LEN + payload_len)
+    }
+}
+
+/// A message received.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Message {
+    /// The EID of the sender.
+    pub source: u8,
+    /// The physical address of the sender, on the bus the message arrived on.
+    pub address: u8,
+    pub message_type: MessageType,
+    /// Whether the message carries an integrity check, as defined by its type.
+    pub integrity_check: bool,
+    pub tag_owner: bool,
+    pub tag: u8,
+    /// The length of the body, which follows the type byte.
+    pub len: usize,
+}
+
+/// Reassembles messages from their packets.
+///
+/// One message is reassembled at a time: a packet that starts a message
+/// abandons the message being reassembled, and packets that continue another
+/// message are dropped.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Reassembler {
+    message: Option<Message>,
+    next_seq: u8,
+}
+
+impl Reassembler {
+    pub const fn new() -> Reassembler {
+        Reassembler {
+            message: None,
+            next_seq: 0,
+        }
+    }
+
+    /// Adds `packet`, received from `address`, to the message being
+    /// reassembled, whose body is written into `buffer`. Returns the message
+    /// once its last packet was added. `buffer` must be the same for all the
+    /// packets of a message.
+    pub fn receive(
+        &mut self,
+        address: u8,
+        packet: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<Option<Message>, Error> {
+        let header = Header::parse(packet)?;
+        let mut payload = &packet[HEADER_LEN..];
+        if header.som {
+            self.message = None;
+            let Some((&type_byte, body)) = payload.split_first() else {
+                return Err(Error::InvalidPacket);
+            };
+            self.message = Some(Message {
+                source: header.source,
+                address,
+                message_type: MessageType(type_byte & !INTEGRITY_CHECK),
+                integrity_check: type_byte & INTEGRITY_CHECK != 0,
+                tag_owner: header.tag_owner,
+                tag: header.tag,
+                len: 0,
+            });
+            self.next_seq = header.seq;
+            payload = body;
+        }
+
+        let Some(message) = &mut self.message else {
+            return Err(Error::LostPacket);
+        };
+        if (header.source, header.tag_owner, header.tag)
+            != (message.source, message.tag_owner, message.tag)
+        {
+            return Err(Error::LostPacket);
+        }
+        if header.seq != self.next_seq {
+            self.message = None;
+            return Err(Error::LostPacket);
+        }
+        let Some(destination) = buffer.get_mut(message.len..message.len + payload.len()) else {
+            self.message = None;
+            return Err(Error::Overflow);
+        };
+        destination.copy_from_slice(payload);
+        message.len += payload.len();
+        self.next_seq = (header.seq + 1) % 4;
+
+        match header.eom {
+            true => Ok(self.message.take()),
+            false => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{Error, Fragmenter, Header, Message, MessageType, Reassembler, HEADER_LEN};
+
+fn header() -> Header {
+    Header {
+        dest: 0x0A,
+        source: 0x08,
+        som: false,
+        eom: false,
+        seq: 0,
+        tag_owner: true,
+        tag: 5,
+    }
+}
+
+// Splits a message into packets with `mtu`.
+fn fragment(message_type: MessageType, body: &[u8], mtu: usize) -> Vec<Vec<u8>> {
+    let mut fragmenter = Fragmenter::new(header(), message_type, body, mtu);
+    let mut packets = Vec::new();
+    let mut packet = [0; 64];
+    while let Some(len) = fragmenter.next_packet(&mut packet) {
+        packets.push(packet[..len].to_vec());
+    }
+    packets
+}
+
+#[test]
+fn header_bytes() {
+    let header = Header {
+        som: true,
+        seq: 2,
+        ..header()
+    };
+    assert_eq!(header.to_bytes(), [0x01, 0x0A, 0x08, 0xAD]);
+    assert_eq!(Header::parse(&[0x01, 0x0A, 0x08, 0xAD, 0xFF]), Ok(header));
+    // The reserved bits are ignored.
+    assert_eq!(Header::parse(&[0xF1, 0x0A, 0x08, 0xAD]), Ok(header));
+    let eom = Header::parse(&[0x01, 0x0A, 0x08, 0x40]).unwrap();
+    assert!(!eom.som && eom.eom && !eom.tag_owner);
+    assert_eq!((eom.seq, eom.tag), (0, 0));
+
+    assert_eq!(
+        Header::parse(&[0x02, 0x0A, 0x08, 0xAD]),
+        Err(Error::InvalidPacket)
+    );
+    assert_eq!(
+        Header::parse(&[0x01, 0x0A, 0x08]),
+        Err(Error::InvalidPacket)
+    );
+}
+
+#[test]
+fn fragmentation() {
+    let body: Vec<u8> = (1..=10).collect();
+    let packets = fragment(MessageType::PLDM, &body, 4);
+    assert_eq!(
+        packets,
+        [
+            vec![0x01, 0x0A, 0x08, 0x8D, 0x01, 1, 2, 3],
+            vec![0x01, 0x0A, 0x08, 0x1D, 4, 5, 6, 7],
+            vec![0x01, 0x0A, 0x08, 0x6D, 8, 9, 10],
+        ]
+    );
+
+    // The type byte fills the packet of an empty message.
+    assert_eq!(
+        fragment(MessageType::SPDM, &[], 4),
+        [vec![0x01, 0x0A, 0x08, 0xCD, 0x05]]
+    );
+    // The sequence number wraps.
+    let packets = fragment(MessageType::PLDM, &[0; 20], 4);
+    assert_eq!(packets.len(), 6);
+    assert_eq!(packets[4][3], 0x0D);
+    assert_eq!(packets[5][3], 0x5D);
+}
+
+#[test]
+fn reassembly() {
+    let body: Vec<u8> = (1..=20).collect();
+    let mut reassembler = Reassembler::new();
+    let mut buffer = [0; 32];
+    let packets = fragment(MessageType::NVME_MI, &body, 4);
+    for packet in &packets[..5] {
+        assert_eq!(reassembler.receive(0x1D, packet, &mut buffer), Ok(None));
+    }
+    let expected = Message {
+        source: 0x08,
+        address: 0x1D,
+        message_type: MessageType::NVME_MI,
+        integrity_check: false,
+        tag_owner: true,
+        tag: 5,
+        len: 20,
+    };
+    assert_eq!(
+        reassembler.receive(0x1D, &packets[5], &mut buffer),
+        Ok(Some(expected))
+    );
+    assert_eq!(buffer[..20], body[..]);
+
+    // The integrity check flag is split from the type.
+    let mut packet = fragment(MessageType::SPDM, &[7], 4).remove(0);
+    packet[HEADER_LEN] |= 0x80;
+    let message = reassembler.receive(0x1D, &packet, &mut buffer).unwrap();
+    assert_eq!(
+        message.map(|message| (message.message_type, message.integrity_check)),
+        Some((MessageType::SPDM, true))
+    );
+
+    // A first packet without a type byte.
+    assert_eq!(
+        reassembler.receive(0x1D, &packet[..HEADER_LEN], &mut buffer),
+        Err(Error::InvalidPacket)
+    );
+}
+
+#[test]
+fn lost_packets() {
+    let mut reassembler = Reassembler::new();
+    let mut buffer = [0; 32];
+    let packets = fragment(MessageType::PLDM, &[0x42; 10], 4);
+
+    // A packet that continues no message.
+    assert_eq!(
+        reassembler.receive(0x1D, &packets[1], &mut buffer),
+        Err(Error::LostPacket)
+    );
+
+    // A packet out of sequence abandons the message.
+    reassembler.receive(0x1D, &packets[0], &mut buffer).unwrap();
+    assert_eq!(
+        reassembler.receive(0x1D, &packets[2], &mut buffer),
+        Err(Error::LostPacket)
+    );
+    assert_eq!(
+        reassembler.receive(0x1D, &packets[1], &mut buffer),
+        Err(Error::LostPacket)
+    );
+
+    // Packets of another message are dropped, and the message continues.
+    reassembler.receive(0x1D, &packets[0], &mut buffer).unwrap();
+    let mut other = packets[1].clone();
+    other[3] ^= 0x01;
+    assert_eq!(
+        reassembler.receive(0x1D, &other, &mut buffer),
+        Err(Error::LostPacket)
+    );
+    reassembler.receive(0x1D, &packets[1], &mut buffer).unwrap();
+
+    // A first packet restarts reassembly.
+    let restart = fragment(MessageType::PLDM, &[1, 2], 4);
+    let message = reassembler.receive(0x1E, &restart[0], &mut buffer);
+    assert_eq!(
+        message
+            .unwrap()
+            .map(|message| (message.address, message.len)),
+        Some((0x1E, 2))
+    );
+    assert_eq!(buffer[..2], [1, 2]);
+}
+
+#[test]
+fn overflow() {
+    let mut reassembler = Reassembler::new();
+    let mut buffer = [0; 6];
+    let packets = fragment(MessageType::PLDM, &[0; 10], 4);
+    reassembler.receive(0x1D, &packets[0], &mut buffer).unwrap();
+    assert_eq!(
+        reassembler.receive(0x1D, &packets[1], &mut buffer),
+        Err(Error::Overflow)
+    );
+    assert_eq!(
+        reassembler.receive(0x1D, &packets[2], &mut buffer),
+        Err(Error::LostPacket)
+    );
+}
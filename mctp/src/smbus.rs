@@ -0,0 +1,73 @@
+//! The SMBus/I2C transport binding (DSP0237).
+//!
+//! A packet is sent as an SMBus block write to the destination's address: the
+//! command code 0x0F, a byte count, the source's address, the packet, and a
+//! packet error code (PEC). The I2C driver sends the destination address
+//! itself, so frames start with the command code. Addresses are 7-bit.
+
+use crate::{Error, HEADER_LEN as PACKET_HEADER_LEN};
+
+/// The SMBus command code of MCTP.
+pub const COMMAND_CODE: u8 = 0x0F;
+
+/// The length of the frame before the packet: the command code, the byte
+/// count and the source address.
+pub const HEADER_LEN: usize = 3;
+
+/// The MTU all endpoints support.
+pub const BASELINE_MTU: usize = 64;
+
+/// The largest MTU: the byte count is at most 255, and counts the source
+/// address and the packet header too.
+pub const MAX_MTU: usize = 255 - 1 - PACKET_HEADER_LEN;
+
+/// The length of the longest frame.
+pub const MAX_FRAME_LEN: usize = HEADER_LEN + PACKET_HEADER_LEN + MAX_MTU + 1;
+
+/// Computes the PEC, a CRC-8 with polynomial 0x07, over `parts`.
+pub fn pec(parts: &[&[u8]]) -> u8 {
+    let mut crc = 0u8;
+    for &byte in parts.iter().flat_map(|part| part.iter()) {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = match crc & 0x80 {
+                0 => crc << 1,
+                _ => (crc << 1) ^ 0x07,
+            };
+        }
+    }
+    crc
+}
+
+/// Frames the packet of `packet_len` bytes at `frame[HEADER_LEN..]` for a
+/// write from `source` to `dest`. Returns the length of the frame.
+///
+/// # Panics
+/// If the packet is longer than `MAX_MTU` allows, or `frame` has no room for
+/// the PEC.
+pub fn encode(frame: &mut [u8], packet_len: usize, dest: u8, source: u8) -> usize {
+    assert!(packet_len <= PACKET_HEADER_LEN + MAX_MTU);
+    let len = HEADER_LEN + packet_len;
+    frame[0] = COMMAND_CODE;
+    frame[1] = (1 + packet_len) as u8;
+    frame[2] = source << 1 | 1;
+    frame[len] = pec(&[&[dest << 1], &frame[..len]]);
+    len + 1
+}
+
+/// Parses a frame written to `address`. Returns the address of the sender and
+/// the packet.
+pub fn decode(frame: &[u8], address: u8) -> Result<(u8, &[u8]), Error> {
+    let Some((&pec_byte, frame)) = frame.split_last() else {
+        return Err(Error::InvalidPacket);
+    };
+    if frame.len() < HEADER_LEN + PACKET_HEADER_LEN
+        || frame[0] != COMMAND_CODE
+        || usize::from(frame[1]) != frame.len() - 2
+        || frame[2] & 1 == 0
+        || pec(&[&[address << 1], frame]) != pec_byte
+    {
+        return Err(Error::InvalidPacket);
+    }
+    Ok((frame[2] >> 1, &frame[HEADER_LEN..]))
+}
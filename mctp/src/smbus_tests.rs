@@ -0,0 +1,55 @@
+use crate::smbus::{decode, encode, pec, HEADER_LEN, MAX_FRAME_LEN, MAX_MTU};
+use crate::Error;
+
+// The PEC computed by the `crc` crate.
+fn expected_pec(bytes: &[u8]) -> u8 {
+    ::crc::Crc::<u8>::new(&::crc::CRC_8_SMBUS).checksum(bytes)
+}
+
+#[test]
+fn pec_check_value() {
+    assert_eq!(pec(&[b"123456789"]), 0xF4);
+    assert_eq!(pec(&[b"1234", b"", b"56789"]), 0xF4);
+    assert_eq!(pec(&[]), 0);
+}
+
+#[test]
+fn encode_decode() {
+    let packet = [0x01, 0x0A, 0x08, 0xC8, 0x01, 0x02];
+    let mut frame = [0; 16];
+    frame[HEADER_LEN..HEADER_LEN + packet.len()].copy_from_slice(&packet);
+    assert_eq!(encode(&mut frame, packet.len(), 0x1D, 0x34), 10);
+    assert_eq!(
+        frame[..9],
+        [0x0F, 0x07, 0x69, 0x01, 0x0A, 0x08, 0xC8, 0x01, 0x02]
+    );
+    // The PEC covers the destination address, which the driver sends.
+    let mut covered = vec![0x1D << 1];
+    covered.extend_from_slice(&frame[..9]);
+    assert_eq!(frame[9], expected_pec(&covered));
+
+    assert_eq!(decode(&frame[..10], 0x1D), Ok((0x34, &packet[..])));
+    assert_eq!(decode(&frame[..10], 0x1E), Err(Error::InvalidPacket));
+    assert_eq!(decode(&frame[..9], 0x1D), Err(Error::InvalidPacket));
+    assert_eq!(decode(&[], 0x1D), Err(Error::InvalidPacket));
+
+    // A wrong command code or byte count, or a write address, is rejected
+    // even with a valid PEC.
+    for (index, value) in [(0, 0x0E), (1, 0x08), (2, 0x68)] {
+        let mut invalid = frame;
+        invalid[index] = value;
+        invalid[9] = pec(&[&[0x1D << 1], &invalid[..9]]);
+        assert_eq!(decode(&invalid[..10], 0x1D), Err(Error::InvalidPacket));
+    }
+}
+
+#[test]
+fn longest_frame() {
+    let mut frame = [0xAB; MAX_FRAME_LEN];
+    frame[HEADER_LEN] = 0x01;
+    let packet_len = 4 + MAX_MTU;
+    assert_eq!(encode(&mut frame, packet_len, 0x1D, 0x34), MAX_FRAME_LEN);
+    assert_eq!(frame[1], 0xFF);
+    let (address, packet) = decode(&frame, 0x1D).unwrap();
+    assert_eq!((address, packet.len()), (0x34, packet_len));
+}
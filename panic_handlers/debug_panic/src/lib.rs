@@ -14,6 +14,32 @@ fn panic_handler(info: &core::panic::PanicInfo) -> ! {
     let mut writer = Console::<TockSyscalls>::writer();
     // If this printing fails, we can't panic harder, and we can't print it either.
     let _ = writeln!(writer, "{}", info);
+    // Out-of-memory errors are reported as panics, so heap usage is often the
+    // most useful context we can add.
+    #[cfg(feature = "alloc")]
+    {
+        let stats = libtock_runtime::heap::stats();
+        let _ = writeln!(
+            writer,
+            "heap: {} of {} bytes used (peak {}), {} failed allocations",
+            stats.used, stats.size, stats.peak_used, stats.failed_allocations
+        );
+    }
+    #[cfg(feature = "stack_paint")]
+    {
+        use libtock_runtime::stack;
+        let _ = writeln!(
+            writer,
+            "stack: {} of {} bytes used, canary {}",
+            stack::stack_high_water_mark(),
+            stack::stack_size(),
+            if stack::stack_canary_intact() {
+                "intact"
+            } else {
+                "overwritten"
+            }
+        );
+    }
     // Exit with a non-zero exit code to indicate failure.
     TockSyscalls::exit_terminate(ErrorCode::Fail as u32);
 }
@@ -299,3 +299,11 @@ impl embedded_hal::spi::Error for ErrorCode {
         ErrorKind::Other
     }
 }
+
+#[cfg(feature = "rust_embedded")]
+impl embedded_hal::pwm::Error for ErrorCode {
+    fn kind(&self) -> embedded_hal::pwm::ErrorKind {
+        use embedded_hal::pwm::ErrorKind;
+        ErrorKind::Other
+    }
+}
@@ -56,6 +56,7 @@ use crate::Register;
 //
 //   unsafe fn yield1([Reg; 1]) -> [Reg; 4];
 //   unsafe fn yield2([Reg; 2]) -> [Reg; 4];
+//   unsafe fn yield3([Reg; 3]) -> [Reg; 4];
 //   unsafe fn syscall1<const CLASS: usize>([Reg; 1]) -> [Reg; 4];
 //   unsafe fn syscall2<const CLASS: usize>([Reg; 2]) -> [Reg; 4];
 //   unsafe fn syscall4<const CLASS: usize>([Reg; 4]) -> [Reg; 4];
@@ -104,6 +105,29 @@ pub unsafe trait RawSyscalls: Sized {
     /// It has the same safety invariants as the underlying system call.
     unsafe fn yield2(_: [Register; 2]);
 
+    // yield3 can only be used to call `yield-wait-for`. `yield-wait-for` does
+    // not invoke an upcall; instead, it returns the arguments of the upcall it
+    // waited for in r0-r2. r3 is not used by `yield-wait-for`, so yield3 only
+    // returns 3 values.
+    //
+    // yield3 should:
+    //     1. Call syscall class 0
+    //     2. Pass in r0, r1, and r2 as inlateout registers and return their
+    //        values.
+    //     3. Mark all caller-saved registers as lateout clobbers.
+    //     4. NOT provide any of the following options:
+    //            pure             (yield has side effects)
+    //            nomem            (the kernel can write into allowed buffers)
+    //            readonly         (the kernel can write into allowed buffers)
+    //            preserves_flags  (conservatively matches yield1 and yield2)
+    //            noreturn         (yield is expected to return)
+    //            nostack          (conservatively matches yield1 and yield2)
+    /// `yield3` should only be called by `libtock_platform`.
+    /// # Safety
+    /// yield3 may only be used for yield operations that return 3 values in
+    /// r0-r2. It has the same safety invariants as the underlying system call.
+    unsafe fn yield3(_: [Register; 3]) -> [Register; 3];
+
     // syscall1 is only used to invoke Memop operations. Because there are no
     // Memop commands that set r2 or r3, raw_syscall1 only needs to return r0
     // and r1.
@@ -20,6 +20,19 @@ pub trait Syscalls: RawSyscalls + Sized {
     /// callback, then returns.
     fn yield_wait();
 
+    /// Puts the process to sleep until the upcall with the given driver and
+    /// subscribe numbers is scheduled, then returns that upcall's arguments.
+    /// Unlike `yield_wait`, `yield_wait_for` does not invoke any upcall
+    /// functions (including a function subscribed to the specified upcall),
+    /// and other upcalls remain queued. Requires Tock 2.2 or newer.
+    fn yield_wait_for(driver_num: u32, subscribe_num: u32) -> (u32, u32, u32);
+
+    /// Waits for the upcall with the given driver and subscribe numbers, as
+    /// `yield_wait_for` does, for drivers that report the status of an
+    /// operation in the upcall's first argument: 0 for success, or an error
+    /// code.
+    fn yield_wait_for_status(driver_num: u32, subscribe_num: u32) -> Result<(), ErrorCode>;
+
     // -------------------------------------------------------------------------
     // Subscribe
     // -------------------------------------------------------------------------
@@ -112,7 +125,34 @@ pub trait Syscalls: RawSyscalls + Sized {
     /// Tells the kernel the initial program break, to support debugging.
     fn memop_debug_heap_start(initial_break: *const u8) -> Result<(), ErrorCode>;
 
-    // TODO: Add remaining memop() methods (3-9).
+    /// Gets the address immediately after the end of this application's RAM
+    /// allocation.
+    fn memop_app_ram_end() -> Result<*const u8, ErrorCode>;
+
+    /// Gets the address of the start of this application's flash region. This
+    /// is where the application's TBF header is located.
+    fn memop_flash_start() -> Result<*const u8, ErrorCode>;
+
+    /// Gets the address immediately after the end of this application's flash
+    /// region.
+    fn memop_flash_end() -> Result<*const u8, ErrorCode>;
+
+    /// Gets the address of the lowest address of the grant region for this
+    /// application. The application must not use memory above this address.
+    fn memop_grant_start() -> Result<*const u8, ErrorCode>;
+
+    /// Gets the number of writeable flash regions defined in this
+    /// application's TBF header.
+    fn memop_flash_region_count() -> Result<u32, ErrorCode>;
+
+    /// Gets the start address of the writeable flash region with the given
+    /// index. Returns `ErrorCode::Invalid` if there is no such region.
+    fn memop_flash_region_start(region: u32) -> Result<*const u8, ErrorCode>;
+
+    /// Gets the address immediately after the end of the writeable flash
+    /// region with the given index. Returns `ErrorCode::Invalid` if there is no
+    /// such region.
+    fn memop_flash_region_end(region: u32) -> Result<*const u8, ErrorCode>;
 
     // -------------------------------------------------------------------------
     // Exit
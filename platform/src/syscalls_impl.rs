@@ -35,6 +35,28 @@ impl<S: RawSyscalls> Syscalls for S {
         }
     }
 
+    fn yield_wait_for(driver_num: u32, subscribe_num: u32) -> (u32, u32, u32) {
+        // Safety: yield-wait-for returns the upcall's arguments in r0-r2,
+        // which satisfies yield3's requirement. The yield-wait-for system call
+        // does not invoke an upcall function, and cannot trigger undefined
+        // behavior on its own in any other way.
+        let [r0, r1, r2] = unsafe {
+            Self::yield3([
+                yield_id::WAIT_FOR.into(),
+                driver_num.into(),
+                subscribe_num.into(),
+            ])
+        };
+        (r0.as_u32(), r1.as_u32(), r2.as_u32())
+    }
+
+    fn yield_wait_for_status(driver_num: u32, subscribe_num: u32) -> Result<(), ErrorCode> {
+        match Self::yield_wait_for(driver_num, subscribe_num) {
+            (0, _, _) => Ok(()),
+            (status, _, _) => Err(status.try_into().unwrap_or(ErrorCode::Fail)),
+        }
+    }
+
     // -------------------------------------------------------------------------
     // Subscribe
     // -------------------------------------------------------------------------
@@ -421,25 +443,57 @@ impl<S: RawSyscalls> Syscalls for S {
     fn memop_app_ram_start() -> Result<*const u8, ErrorCode> {
         // Safety: syscall1's documentation indicates it can be used to call Memop operations
         // that only accept a memop operation number.
-        let [r0, r1] = unsafe { Self::syscall1::<{ syscall_class::MEMOP }>([2u32.into()]) };
-        let return_variant: ReturnVariant = r0.as_u32().into();
-        // TRD 104 guarantees that memop 2 returns either Success
-        // or Failure. We check the return variant by comparing against Failure
-        // for 1 reason:
-        //
-        //   1. On RISC-V with compressed instructions, it generates smaller
-        //      code. FAILURE has value 0, which can be loaded into a
-        //      register with a single compressed instruction, whereas
-        //      loading SUCCESS_U32 uses an uncompressed instruction.
-        if return_variant == return_variant::FAILURE {
-            // Safety: TRD 104 guarantees that if r0 is Failure,
-            // then r1 will contain a valid error code. ErrorCode is
-            // designed to be safely transmuted directly from a kernel error
-            // code.
-            Err(unsafe { core::mem::transmute::<u32, ErrorCode>(r1.as_u32()) })
-        } else {
-            Ok(r1.into())
-        }
+        let registers = unsafe { Self::syscall1::<{ syscall_class::MEMOP }>([2u32.into()]) };
+        memop_success_u32(registers).map(Into::into)
+    }
+
+    fn memop_app_ram_end() -> Result<*const u8, ErrorCode> {
+        // Safety: syscall1's documentation indicates it can be used to call Memop operations
+        // that only accept a memop operation number.
+        let registers = unsafe { Self::syscall1::<{ syscall_class::MEMOP }>([3u32.into()]) };
+        memop_success_u32(registers).map(Into::into)
+    }
+
+    fn memop_flash_start() -> Result<*const u8, ErrorCode> {
+        // Safety: syscall1's documentation indicates it can be used to call Memop operations
+        // that only accept a memop operation number.
+        let registers = unsafe { Self::syscall1::<{ syscall_class::MEMOP }>([4u32.into()]) };
+        memop_success_u32(registers).map(Into::into)
+    }
+
+    fn memop_flash_end() -> Result<*const u8, ErrorCode> {
+        // Safety: syscall1's documentation indicates it can be used to call Memop operations
+        // that only accept a memop operation number.
+        let registers = unsafe { Self::syscall1::<{ syscall_class::MEMOP }>([5u32.into()]) };
+        memop_success_u32(registers).map(Into::into)
+    }
+
+    fn memop_grant_start() -> Result<*const u8, ErrorCode> {
+        // Safety: syscall1's documentation indicates it can be used to call Memop operations
+        // that only accept a memop operation number.
+        let registers = unsafe { Self::syscall1::<{ syscall_class::MEMOP }>([6u32.into()]) };
+        memop_success_u32(registers).map(Into::into)
+    }
+
+    fn memop_flash_region_count() -> Result<u32, ErrorCode> {
+        // Safety: syscall1's documentation indicates it can be used to call Memop operations
+        // that only accept a memop operation number.
+        let registers = unsafe { Self::syscall1::<{ syscall_class::MEMOP }>([7u32.into()]) };
+        memop_success_u32(registers).map(Register::as_u32)
+    }
+
+    fn memop_flash_region_start(region: u32) -> Result<*const u8, ErrorCode> {
+        // Safety: syscall2's documentation indicates it can be used to call Memop.
+        let registers =
+            unsafe { Self::syscall2::<{ syscall_class::MEMOP }>([8u32.into(), region.into()]) };
+        memop_success_u32(registers).map(Into::into)
+    }
+
+    fn memop_flash_region_end(region: u32) -> Result<*const u8, ErrorCode> {
+        // Safety: syscall2's documentation indicates it can be used to call Memop.
+        let registers =
+            unsafe { Self::syscall2::<{ syscall_class::MEMOP }>([9u32.into(), region.into()]) };
+        memop_success_u32(registers).map(Into::into)
     }
 
     fn memop_debug_stack_start(stack_top: *const u8) -> Result<(), ErrorCode> {
@@ -530,3 +584,21 @@ impl<S: RawSyscalls> Syscalls for S {
         }
     }
 }
+
+// Interprets the return value of a Memop operation that returns either Success
+// with U32 or Failure, returning r1 on success. Memop operations 2 through 9 all
+// return this way, so they share this function to keep code size small.
+fn memop_success_u32([r0, r1]: [Register; 2]) -> Result<Register, ErrorCode> {
+    let return_variant: ReturnVariant = r0.as_u32().into();
+    // Comparing against FAILURE (0) rather than SUCCESS_U32 generates smaller
+    // code on RISC-V with compressed instructions.
+    if return_variant == return_variant::FAILURE {
+        // Safety: TRD 104 guarantees that if r0 is Failure,
+        // then r1 will contain a valid error code. ErrorCode is
+        // designed to be safely transmuted directly from a kernel error
+        // code.
+        Err(unsafe { core::mem::transmute::<u32, ErrorCode>(r1.as_u32()) })
+    } else {
+        Ok(r1)
+    }
+}
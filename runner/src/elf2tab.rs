@@ -1,4 +1,4 @@
-use super::Cli;
+use super::{multi_location, Cli};
 use std::fs::{metadata, remove_file};
 use std::io::ErrorKind;
 use std::path::PathBuf;
@@ -28,18 +28,18 @@ fn get_platform_architecture(platform: &str) -> Option<&'static str> {
 }
 
 // Converts the ELF file specified on the command line into TBF and TAB files,
-// and returns the paths to those files.
+// and returns the paths to those files. With --multi-location, the TAB also
+// contains a TBF for each location the process binary is rebuilt for.
 pub fn convert_elf(cli: &Cli, platform: &str) -> OutFiles {
-    let package_name = cli.elf.file_stem().expect("ELF must be a file");
-    let mut tab_path = cli.elf.clone();
+    let package_name = cli.elf().file_stem().expect("ELF must be a file");
+    let mut tab_path = cli.elf().to_path_buf();
     tab_path.set_extension("tab");
     if cli.verbose {
         println!("Package name: {:?}", package_name);
         println!("TAB path: {}", tab_path.display());
     }
-    let stack_size = read_stack_size(cli);
-    let elf = cli.elf.as_os_str();
-    let mut tbf_path = cli.elf.clone();
+    let elf = cli.elf().as_os_str();
+    let mut tbf_path = cli.elf().to_path_buf();
     tbf_path.set_extension("tbf");
     let architecture =
         get_platform_architecture(platform).expect("Failed to determine ELF's architecture");
@@ -59,6 +59,8 @@ pub fn convert_elf(cli: &Cli, platform: &str) -> OutFiles {
         }
     }
 
+    check_ram_relocations(cli);
+
     let mut command = Command::new("elf2tab");
     #[rustfmt::skip]
     command.args([
@@ -69,9 +71,14 @@ pub fn convert_elf(cli: &Cli, platform: &str) -> OutFiles {
         "--kernel-minor".as_ref(), "0".as_ref(),
         "-n".as_ref(), package_name,
         "-o".as_ref(), tab_path.as_os_str(),
-        "--stack".as_ref(), stack_size.as_ref(),
-        format!("{},{}", elf.to_str().unwrap(), architecture).as_ref(),
     ]);
+    command.args(size_args(cli));
+    command.arg(format!("{},{}", elf.to_str().unwrap(), architecture));
+    if cli.multi_location {
+        for (location_elf, name) in multi_location::build_locations(cli, architecture) {
+            command.arg(format!("{},{}", location_elf.to_str().unwrap(), name));
+        }
+    }
     if cli.verbose {
         command.arg("-v");
         println!("elf2tab command: {:?}", command);
@@ -110,10 +117,20 @@ pub struct OutFiles {
     pub tbf_path: PathBuf,
 }
 
+// Returns the elf2tab arguments that give the kernel the stack size and the heap
+// size (if heap_size! reserved one) the process binary needs.
+pub fn size_args(cli: &Cli) -> Vec<String> {
+    let mut args = vec!["--stack".to_string(), read_stack_size(cli)];
+    if let Some(heap_size) = read_heap_size(cli) {
+        args.extend(["--app-heap".to_string(), heap_size]);
+    }
+    args
+}
+
 // Reads the stack size, and returns it as a String for use on elf2tab's command
 // line.
 fn read_stack_size(cli: &Cli) -> String {
-    let file = elf::File::open_path(&cli.elf).expect("Unable to open ELF");
+    let file = elf::File::open_path(cli.elf()).expect("Unable to open ELF");
     for section in file.sections {
         // This section name comes from runtime/libtock_layout.ld, and it
         // matches the size (and location) of the process binary's stack.
@@ -126,5 +143,100 @@ fn read_stack_size(cli: &Cli) -> String {
         }
     }
 
-    panic!("Unable to find the .stack section in {}", cli.elf.display());
+    panic!(
+        "Unable to find the .stack section in {}",
+        cli.elf().display()
+    );
+}
+
+// Reads the heap size reserved with heap_size!, if any, and returns it as a
+// String for use on elf2tab's command line.
+fn read_heap_size(cli: &Cli) -> Option<String> {
+    let file = elf::File::open_path(cli.elf()).expect("Unable to open ELF");
+    // This section name comes from runtime/libtock_layout.ld. It is empty if
+    // the process binary does not use heap_size!.
+    let section = file.sections.iter().find(|s| s.shdr.name == ".heap")?;
+    if section.shdr.size == 0 {
+        return None;
+    }
+    let heap_size = section.shdr.size.to_string();
+    if cli.verbose {
+        println!("Found .heap section, size: {}", heap_size);
+    }
+    Some(heap_size)
+}
+
+// ARM relocation types of pointer-sized words (R_ARM_ABS32 and R_ARM_TARGET1).
+const POINTER_RELOCATIONS: [u32; 2] = [2, 38];
+
+// Checks that a RAM-relocatable (rwpi) process binary has no pointers to RAM in
+// its initialized data. libtock_runtime relocates RAM at startup, but it does
+// not rewrite pointers stored in memory, so such pointers would still point to
+// where RAM was linked (address 0). libtock_build_scripts links these binaries
+// with --emit-relocs so that this function can find those pointers.
+fn check_ram_relocations(cli: &Cli) {
+    let file = elf::File::open_path(cli.elf()).expect("Unable to open ELF");
+    // These section names come from runtime/libtock_layout.ld, which places
+    // .stack at the start of RAM. The linker drops the sections that are empty.
+    let find_section = |name| file.sections.iter().find(|s| s.shdr.name == name);
+    match find_section(".stack") {
+        // Statically-linked process binary.
+        Some(stack) if stack.shdr.addr != 0 => return,
+        None => return,
+        _ => {}
+    }
+    let ram_end = [".stack", ".data", ".bss", ".heap"]
+        .into_iter()
+        .filter_map(find_section)
+        .map(|s| s.shdr.addr + s.shdr.size)
+        .max()
+        .unwrap_or(0);
+    let ram = 0..ram_end;
+
+    let mut relocation_sections = file
+        .sections
+        .iter()
+        .filter(|s| s.shdr.shtype == elf::types::SHT_REL)
+        .peekable();
+    assert!(
+        relocation_sections.peek().is_some(),
+        "{} is RAM-relocatable but has no relocation sections; link it with --emit-relocs",
+        cli.elf().display()
+    );
+    let mut ram_pointers = vec![];
+    for relocation_section in relocation_sections {
+        // Each Elf32_Rel entry is an r_offset word followed by an r_info word.
+        for entry in relocation_section.data.chunks_exact(8) {
+            let word = |i: usize| u32::from_le_bytes(entry[i..i + 4].try_into().unwrap());
+            let (location, info) = (word(0) as u64, word(4));
+            if !POINTER_RELOCATIONS.contains(&(info & 0xff)) {
+                continue;
+            }
+            let Some(section) = file.sections.iter().find(|s| {
+                s.shdr.flags.0 & elf::types::SHF_ALLOC.0 != 0
+                    && s.shdr.shtype != elf::types::SHT_NOBITS
+                    && (s.shdr.addr..s.shdr.addr + s.shdr.size).contains(&location)
+            }) else {
+                continue;
+            };
+            let offset = (location - section.shdr.addr) as usize;
+            let pointer = u32::from_le_bytes(section.data[offset..offset + 4].try_into().unwrap());
+            if ram.contains(&(pointer as u64)) {
+                ram_pointers.push(format!(
+                    "{} at {:#x} points to {:#x}",
+                    section.shdr.name, location, pointer
+                ));
+            }
+        }
+    }
+    assert!(
+        ram_pointers.is_empty(),
+        "{} is RAM-relocatable but its initialized data contains pointers to RAM, which \
+         cannot be relocated:\n{}",
+        cli.elf().display(),
+        ram_pointers.join("\n")
+    );
+    if cli.verbose {
+        println!("Checked the RAM-relocatable ELF for pointers to RAM");
+    }
 }
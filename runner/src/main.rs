@@ -1,11 +1,12 @@
 mod elf2tab;
+mod multi_location;
 mod output_processor;
 mod qemu;
 mod tockloader;
 
 use clap::{Parser, ValueEnum};
 use std::env::{var, VarError};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Converts ELF binaries into Tock Binary Format binaries and runs them on a
 /// Tock system.
@@ -17,8 +18,29 @@ pub struct Cli {
     deploy: Option<Deploy>,
 
     /// The executable to convert into Tock Binary Format and run.
-    #[clap(action)]
-    elf: PathBuf,
+    #[clap(action, required_unless_present = "tab-locations")]
+    elf: Option<PathBuf>,
+
+    /// Features to enable when rebuilding the process binary for
+    /// --multi-location. Should match the features the ELF was built with.
+    #[clap(action, long)]
+    features: Option<String>,
+
+    /// Also build the process binary for every flash and RAM location listed
+    /// for its architecture in libtock_build_scripts, and package all of them
+    /// into the TAB. Tockloader installs whichever location fits the board.
+    #[clap(action, long)]
+    multi_location: bool,
+
+    /// Print the stack and heap size arguments the runner passes to elf2tab
+    /// for the ELF, and exit. `make tab` uses them for its own elf2tab call.
+    #[clap(action, long)]
+    elf2tab_size_args: bool,
+
+    /// Print the flash and RAM locations `make tab` builds process binaries
+    /// for, one per line, and exit.
+    #[clap(action, long)]
+    tab_locations: bool,
 
     /// Whether to output verbose debugging information to the console.
     #[clap(long, short, action)]
@@ -31,8 +53,30 @@ pub enum Deploy {
     Tockloader,
 }
 
+impl Cli {
+    fn elf(&self) -> &Path {
+        self.elf.as_deref().expect("No ELF specified")
+    }
+}
+
 fn main() {
     let cli = Cli::parse();
+    if cli.tab_locations {
+        // make tab turns each line into a fixed-target call.
+        for (architecture, target, flash_start, ram_start) in
+            libtock_build_scripts::all_tab_locations()
+        {
+            println!(
+                "F={},R={},T={},A={}",
+                flash_start, ram_start, target, architecture
+            );
+        }
+        return;
+    }
+    if cli.elf2tab_size_args {
+        println!("{}", elf2tab::size_args(&cli).join(" "));
+        return;
+    }
     let platform = match var("LIBTOCK_PLATFORM") {
         Err(VarError::NotPresent) => {
             panic!("LIBTOCK_PLATFORM must be specified to deploy")
@@ -0,0 +1,82 @@
+use super::Cli;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Rebuilds the process binary specified on the command line once for every
+// location libtock_build_scripts lists for `architecture`, and returns the
+// path to each resulting ELF along with the name elf2tab should give its TBF
+// file inside the TAB.
+//
+// Cargo does not tell the runner how the ELF was built, so this infers the
+// target, profile, and example or binary name from the ELF's path
+// (<target dir>/<target>/<profile>/[examples/]<name>). Features have to be
+// passed to the runner separately, using --features.
+pub fn build_locations(cli: &Cli, architecture: &str) -> Vec<(PathBuf, String)> {
+    let name = cli.elf().file_name().expect("ELF must be a file");
+    let mut profile_dir = parent(cli.elf());
+    let is_example = profile_dir.file_name() == Some(OsStr::new("examples"));
+    if is_example {
+        profile_dir = parent(profile_dir);
+    }
+    let profile = match profile_dir.file_name().and_then(OsStr::to_str) {
+        Some("debug") => "dev",
+        Some(profile) => profile,
+        None => panic!("Unable to determine profile of {}", cli.elf().display()),
+    };
+    let target_dir = parent(profile_dir);
+    let target = target_dir.file_name().expect("Unable to determine target");
+    let locations_dir = parent(target_dir).join("locations");
+
+    let mut elfs = vec![];
+    for (flash_start, ram_start) in libtock_build_scripts::tab_locations(architecture) {
+        let location = format!("{}.{}.{}", architecture, flash_start, ram_start);
+        // Each location gets its own target directory so the builds do not
+        // overwrite each other (or the ELF the runner was given).
+        let location_dir = locations_dir.join(&location);
+        let mut command = Command::new(std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into()));
+        command
+            .arg("build")
+            .arg(if is_example { "--example" } else { "--bin" })
+            .arg(name)
+            .arg("--target")
+            .arg(target)
+            .arg("--profile")
+            .arg(profile)
+            .arg("--target-dir")
+            .arg(&location_dir);
+        if let Some(features) = &cli.features {
+            command.args(["--features", features]);
+        }
+        // auto_layout refuses to use both a platform and explicit addresses.
+        command
+            .env_remove("LIBTOCK_PLATFORM")
+            .env("LIBTOCK_LINKER_FLASH", flash_start)
+            .env("LIBTOCK_LINKER_RAM", ram_start);
+        if cli.verbose {
+            println!("Building for {}: {:?}", location, command);
+        }
+        let status = command.status().expect("failed to spawn cargo");
+        assert!(
+            status.success(),
+            "Build for {} failed. {}",
+            location,
+            status
+        );
+
+        let mut elf = location_dir
+            .join(target)
+            .join(profile_dir.file_name().unwrap());
+        if is_example {
+            elf.push("examples");
+        }
+        elf.push(name);
+        elfs.push((elf, location));
+    }
+    elfs
+}
+
+fn parent(path: &Path) -> &Path {
+    path.parent()
+        .unwrap_or_else(|| panic!("Unexpected ELF path: {}", path.display()))
+}
@@ -0,0 +1,7 @@
+fn main() {
+    // Selects the startup code for RAM-relocatable (rwpi) process binaries.
+    println!("cargo:rustc-check-cfg=cfg(libtock_rwpi)");
+    if libtock_build_scripts::ram_relocatable() {
+        println!("cargo:rustc-cfg=libtock_rwpi");
+    }
+}
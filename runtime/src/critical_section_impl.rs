@@ -0,0 +1,45 @@
+//! An implementation of the `critical-section` crate's `Impl` trait, enabled by
+//! the `critical-section` feature.
+//!
+//! Tock processes are single-threaded, and the kernel only interrupts a
+//! process' code to run upcalls during Yield system calls that may invoke
+//! upcalls (Yield-NoWait and Yield-Wait). Therefore, a critical section only
+//! needs to ensure the process does not make those calls. Instead of silently
+//! running upcalls inside a critical section, `TockSyscalls` panics if the
+//! process tries to. Yield-WaitFor does not invoke upcalls, and is allowed.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+// True while the process is inside a critical section. We only use loads and
+// stores, as not every Tock target supports atomic read-modify-write
+// operations; that is sufficient because nothing can run between them.
+static IN_CRITICAL_SECTION: AtomicBool = AtomicBool::new(false);
+
+struct TockCriticalSection;
+critical_section::set_impl!(TockCriticalSection);
+
+// Safety: While IN_CRITICAL_SECTION is set, the process cannot yield in a way
+// that invokes upcalls, so no other code can run until the critical section is
+// released. Nested critical sections restore the outer section's state.
+unsafe impl critical_section::Impl for TockCriticalSection {
+    unsafe fn acquire() -> critical_section::RawRestoreState {
+        let was_in_critical_section = IN_CRITICAL_SECTION.load(Ordering::Relaxed);
+        IN_CRITICAL_SECTION.store(true, Ordering::Relaxed);
+        was_in_critical_section
+    }
+
+    unsafe fn release(was_in_critical_section: critical_section::RawRestoreState) {
+        IN_CRITICAL_SECTION.store(was_in_critical_section, Ordering::Relaxed);
+    }
+}
+
+/// Panics if the process is inside a critical section. Called before yields
+/// that may invoke upcalls.
+pub(crate) fn check_yield() {
+    if IN_CRITICAL_SECTION.load(Ordering::Relaxed) {
+        // Leave the critical section so the panic handler is free to yield
+        // (e.g. to print the panic message).
+        IN_CRITICAL_SECTION.store(false, Ordering::Relaxed);
+        panic!("yield called inside a critical section");
+    }
+}
@@ -0,0 +1,64 @@
+//! A global memory allocator for Tock process binaries, enabled by the `alloc`
+//! feature.
+//!
+//! The allocator is `libtock_heap`'s first-fit heap. It starts at the initial
+//! process break (the end of `.bss`) and grows upwards, moving the break with
+//! Memop once the memory the kernel has already placed below the break runs
+//! out. The break is never moved back down.
+//!
+//! When the kernel refuses to move the break, the allocator returns a null
+//! pointer, and `alloc::alloc::handle_alloc_error` panics with a message naming
+//! the size of the failed allocation. That message is reported by the process
+//! binary's panic handler like any other panic.
+
+use crate::TockSyscalls;
+use libtock_heap::{Allocator, ProcessBreak};
+use libtock_platform::{ErrorCode, Syscalls};
+
+pub use libtock_heap::HeapStats;
+
+/// Process binaries may use `heap_size!` to reserve memory for the heap. It
+/// takes a single argument, the number of bytes to reserve. Example:
+/// ```
+/// heap_size!{0x1000}
+/// ```
+/// The kernel allocates the reserved memory to the process when it is loaded,
+/// so growing the heap within the reservation cannot fail. Reserving memory is
+/// optional; the allocator grows the heap on demand either way.
+// heap_size works the same way as stack_size: it puts a buffer in the
+// .heap_buffer section, which the linker script places directly after .bss.
+// This lets the linker verify the heap fits in RAM. The runner, and `make tab`
+// through the runner's --elf2tab-size-args option, pass the size of that
+// section to elf2tab so the kernel allocates enough memory for the process.
+#[macro_export]
+macro_rules! heap_size {
+    {$size:expr} => {
+        #[no_mangle]
+        #[link_section = ".heap_buffer"]
+        pub static mut HEAP_MEMORY: [u8; $size] = [0; $size];
+    }
+}
+
+/// Returns the current heap usage statistics.
+pub fn stats() -> HeapStats {
+    ALLOCATOR.stats()
+}
+
+// Safety: Tock processes are single-threaded. Upcalls only run during Yield,
+// which the allocator never calls, so the allocator cannot be reentered. The
+// memory above the initial break is only used by the heap.
+#[global_allocator]
+static ALLOCATOR: Allocator<TockBreak> = unsafe { Allocator::new(TockBreak) };
+
+struct TockBreak;
+
+impl ProcessBreak for TockBreak {
+    fn heap_start(&mut self) -> usize {
+        crate::startup::get_heap_start() as usize
+    }
+
+    fn grow(&mut self, increment: usize) -> Result<usize, ErrorCode> {
+        let increment = u32::try_from(increment).map_err(|_| ErrorCode::NoMem)?;
+        TockSyscalls::memop_increment_brk(increment).map(|old_break| old_break as usize)
+    }
+}
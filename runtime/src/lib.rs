@@ -21,6 +21,12 @@
 #![no_std]
 #![warn(unsafe_op_in_unsafe_fn)]
 
+#[cfg(feature = "critical-section")]
+mod critical_section_impl;
+#[cfg(feature = "alloc")]
+pub mod heap;
+#[cfg(feature = "stack_paint")]
+pub mod stack;
 pub mod startup;
 
 /// TockSyscalls implements `libtock_platform::Syscalls`.
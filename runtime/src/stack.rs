@@ -0,0 +1,87 @@
+//! Stack usage measurement, enabled by the `stack_paint` feature.
+//!
+//! At startup, `rust_start` fills the unused part of the stack with a known
+//! pattern ("paints" it). Stack frames overwrite the pattern, so the lowest
+//! word that no longer holds the pattern marks the deepest point the stack has
+//! reached. That makes it possible to choose the size passed to `stack_size!`
+//! by measurement instead of guesswork.
+//!
+//! The lowest few words of the stack form a canary. If the canary has been
+//! overwritten, the stack was (or very nearly was) exhausted. The canary is
+//! checked when `main` returns, and, if the `stack_guard` feature is enabled,
+//! on every Yield system call. Because the stack is usually shallow when the
+//! process yields, the guard lets a panic handler report the overflow instead
+//! of the process running off the end of its stack and faulting.
+
+use crate::startup::rt_header;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+const PAINT: u32 = 0xC0DE_57AC;
+const CANARY_WORDS: usize = 4;
+
+fn stack_bottom() -> *mut u32 {
+    rt_header().stack_bottom() as *mut u32
+}
+
+/// Returns the size of the stack, in bytes.
+pub fn stack_size() -> usize {
+    rt_header().stack_top() as usize - stack_bottom() as usize
+}
+
+/// Returns the largest number of bytes of stack the process has used so far.
+pub fn stack_high_water_mark() -> usize {
+    let mut word = stack_bottom();
+    let top = rt_header().stack_top() as *mut u32;
+    // Safety: [stack_bottom, stack_top) is the process' stack, which is valid
+    // and aligned for u32. Words below the current stack pointer are not in use
+    // by any stack frame, so reading them does not race with anything.
+    unsafe {
+        while word < top && word.read_volatile() == PAINT {
+            word = word.add(1);
+        }
+    }
+    top as usize - word as usize
+}
+
+/// Returns `false` if the canary at the bottom of the stack has been
+/// overwritten, which indicates that the stack overflowed.
+pub fn stack_canary_intact() -> bool {
+    // Safety: The canary is at the bottom of the stack, which is valid and
+    // aligned for u32.
+    (0..CANARY_WORDS).all(|i| unsafe { stack_bottom().add(i).read_volatile() } == PAINT)
+}
+
+// Set once check_canary has reported an overflow.
+static OVERFLOW_REPORTED: AtomicBool = AtomicBool::new(false);
+
+/// Panics if the canary has been overwritten.
+pub(crate) fn check_canary() {
+    // Only report the overflow once, so that checks made while the panic
+    // handler runs do not panic again.
+    if !stack_canary_intact() && !OVERFLOW_REPORTED.load(Ordering::Relaxed) {
+        OVERFLOW_REPORTED.store(true, Ordering::Relaxed);
+        panic!("stack overflow: {} byte stack exhausted", stack_size());
+    }
+}
+
+/// Paints the stack below the current stack pointer. Called at the start of
+/// `rust_start`, when the stack is nearly empty.
+pub(crate) fn paint() {
+    let stack_pointer: usize;
+    // Safety: Reading the stack pointer has no side effects.
+    unsafe {
+        #[cfg(target_arch = "arm")]
+        core::arch::asm!("mov {}, sp", lateout(reg) stack_pointer, options(nomem, nostack, preserves_flags));
+        #[cfg(target_arch = "riscv32")]
+        core::arch::asm!("mv {}, sp", lateout(reg) stack_pointer, options(nomem, nostack, preserves_flags));
+    }
+    let mut word = stack_bottom();
+    // Safety: Everything between the bottom of the stack and the stack pointer
+    // is not part of any stack frame.
+    unsafe {
+        while (word as usize) < stack_pointer {
+            word.write_volatile(PAINT);
+            word = word.add(1);
+        }
+    }
+}
@@ -11,7 +11,18 @@
  *     Start of .data in ram       |     20
  *     Size of .bss                |     24
  *     Start of .bss in ram        |     28
+ *     Bottom of the stack         |     32
+ *
+ * In RAM-relocatable (rwpi) builds, LIBTOCK_RWPI is 1 and RAM is linked at
+ * address 0, so the RAM addresses in rt_header are offsets from the start of
+ * the process' memory region. relocate_ram turns such an offset into an
+ * address; it does nothing in statically-linked builds.
  */
+.macro relocate_ram reg
+.if LIBTOCK_RWPI
+	adds \reg, \reg, r6
+.endif
+.endm
 
 /* start is the entry point -- the first code executed by the kernel. The kernel
  * passes arguments through 4 registers:
@@ -23,8 +34,8 @@
  *     r2  Size of the process' allocated memory region (including grant region)
  *     r3  Process break provided by the kernel.
  *
- * We currently only use the value in r0. It is copied into r5 early on because
- * r0 is needed to invoke system calls.
+ * r0 is copied into r5 early on because r0 is needed to invoke system calls.
+ * r1-r3 are saved in r6-r8 and passed on to rust_start, along with r0.
  *
  * To be compatible with ARMv6 Thumb-1, we the cmp and beq instructions
  * instead of cbz in two places. This increases the code size with 4 bytes,
@@ -39,6 +50,9 @@ start:
 	 * address of `start`, which is stored in rt_header. */
 	mov r4, pc        /* r4 = address of .start + 4 (Thumb bit unset) */
 	mov r5, r0        /* Save rt_header; we use r0 for syscalls */
+	mov r6, r1        /* Save the memory start for rust_start */
+	mov r7, r2        /* Save the memory size for rust_start */
+	mov r8, r3        /* Save the kernel's process break for rust_start */
 	ldr r0, [r5, #0]  /* r0 = rt_header.start */
 	adds r0, #4       /* r0 = rt_header.start + 4 */
 	cmp r0, r4        /* Skip error handling if pc correct */
@@ -54,13 +68,20 @@ start:
 	svc 6        /* Execute `exit` */
 
 .Lset_brk:
+.if LIBTOCK_RWPI
+	/* Code compiled with the rwpi relocation model addresses read-write data
+	 * relative to the static base in r9, which is the start of RAM. */
+	mov r9, r6
+.endif
 	/* memop(): set brk to rt_header's initial break value */
 	movs r0, #0       /* operation: set break */
 	ldr r1, [r5, #4]  /* rt_header`s initial process break */
+	relocate_ram r1
 	svc 5             /* call `memop` */
 
 	/* Set the stack pointer */
 	ldr r0, [r5, #8]  /* r0 = rt_header._stack_top */
+	relocate_ram r0
 	mov sp, r0
 
 	/* Copy .data into place */
@@ -69,6 +90,7 @@ start:
 	beq .Lzero_bss         
 	ldr r1, [r5, #16]          /* src = rt_header.data_flash_start */
 	ldr r2, [r5, #20]          /* dest = rt_header.data_ram_start */
+	relocate_ram r2
 .Ldata_loop_body:
 	ldr r3, [r1]               /* r3 = *src */
 	str r3, [r2]               /* *(dest) = r3 */
@@ -83,6 +105,7 @@ start:
 	cmp r0, #0                 /* Jump to call_rust_start if remaining == 0 */
 	beq .Lcall_rust_start  
 	ldr r1, [r5, #28]          /* dest = rt_header.bss_start */
+	relocate_ram r1
 	movs r2, #0                /* r2 = 0 */
 .Lbss_loop_body:
 	strb r2, [r1]              /* *(dest) = r2 = 0 */
@@ -92,4 +115,9 @@ start:
 	bne .Lbss_loop_body        /* Iterate again if remaining != 0 */
 
 .Lcall_rust_start:
+	/* Pass the kernel's arguments on to rust_start */
+	mov r0, r5
+	mov r1, r6
+	mov r2, r7
+	mov r3, r8
 	bl rust_start
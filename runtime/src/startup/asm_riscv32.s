@@ -11,6 +11,7 @@
  *     Start of .data in ram       |     20
  *     Size of .bss                |     24
  *     Start of .bss in ram        |     28
+ *     Bottom of the stack         |     32
  */
 
 /* start is the entry point -- the first code executed by the kernel. The kernel
@@ -23,8 +24,8 @@
  *     a2  Size of the process' allocated memory region (including grant region)
  *     a3  Process break provided by the kernel.
  *
- * We currently only use the value in a0. It is copied into a5 early on because
- * a0-a4 are needed to invoke system calls.
+ * a0 is copied into a5 early on because a0-a4 are needed to invoke system
+ * calls. a1-a3 are saved in s2-s4 and passed on to rust_start, along with a0.
  */
 .section .start, "ax"
 .globl start
@@ -34,6 +35,9 @@ start:
 	 * address of `start`, which is stored in rt_header. */
 	auipc s0, 0            /* s0 = pc */
 	mv a5, a0;             /* Save rt_header so syscalls don't overwrite it */
+	mv s2, a1              /* Save the memory start for rust_start */
+	mv s3, a2              /* Save the memory size for rust_start */
+	mv s4, a3              /* Save the kernel's process break for rust_start */
 	lw s1, 0(a5)           /* s1 = rt_header.start */
 	beq s0, s1, .Lset_brk  /* Skip error handling code if pc is correct */
 	/* If the beq on the previous line did not jump, then the binary is not at
@@ -82,5 +86,10 @@ start:
 	bnez a0, .Lbss_loop_body    /* Iterate again if remaining != 0 */
 
 .Lcall_rust_start:
+	/* Pass the kernel's arguments on to rust_start */
+	mv a0, a5
+	mv a1, s2
+	mv a2, s3
+	mv a3, s4
 	/* Note: rust_start must be a diverging function (i.e. return `!`) */
 	jal rust_start
@@ -5,8 +5,10 @@ use libtock_platform::{Syscalls, Termination};
 
 // Include the correct `start` symbol (the program entry point) for the
 // architecture.
-#[cfg(target_arch = "arm")]
-core::arch::global_asm!(include_str!("asm_arm.s"));
+#[cfg(all(target_arch = "arm", not(libtock_rwpi)))]
+core::arch::global_asm!(".set LIBTOCK_RWPI, 0", include_str!("asm_arm.s"));
+#[cfg(all(target_arch = "arm", libtock_rwpi))]
+core::arch::global_asm!(".set LIBTOCK_RWPI, 1", include_str!("asm_arm.s"));
 #[cfg(target_arch = "riscv32")]
 core::arch::global_asm!(include_str!("asm_riscv32.s"));
 
@@ -38,6 +40,28 @@ macro_rules! set_main {
     }
 }
 
+/// `set_async_main!` is the `async` counterpart of `set_main!`. The process
+/// binary's `main` function must have the signature `FnOnce() -> F`, where `F`
+/// is a `Future` whose output implements `libtock_platform::Termination`
+/// (typically, `main` is an `async fn`). `main` is run to completion by
+/// `libtock_executor::block_on`.
+///
+/// # Example
+/// ```
+/// libtock_runtime::set_async_main!{main};
+///
+/// async fn main() -> () { /* Omitted */ }
+/// ```
+#[macro_export]
+macro_rules! set_async_main {
+    {$name:ident} => {
+        #[no_mangle]
+        fn libtock_unsafe_main() -> ! {
+            $crate::startup::handle_async_main($name())
+        }
+    }
+}
+
 /// Executables must specify their stack size by using the `stack_size!` macro.
 /// It takes a single argument, the desired stack size in bytes. Example:
 /// ```
@@ -59,13 +83,24 @@ macro_rules! stack_size {
 /// This is public for the sake of making `set_main!` usable in other crates.
 /// It doesn't have another function.
 pub fn handle_main_return<T: Termination>(result: T) -> ! {
+    #[cfg(feature = "stack_paint")]
+    crate::stack::check_canary();
     Termination::complete::<TockSyscalls>(result)
 }
 
+/// This is public for the sake of making `set_async_main!` usable in other
+/// crates. It doesn't have another function.
+pub fn handle_async_main<F: core::future::Future>(main: F) -> !
+where
+    F::Output: Termination,
+{
+    handle_main_return(libtock_executor::block_on::<TockSyscalls, F>(main))
+}
+
 // The runtime header, which is generated by the linker script and placed at the
 // beginning of the app binary.
 #[repr(C)]
-struct RtHeader {
+pub(crate) struct RtHeader {
     start: usize,
     initial_break: *mut (),
     stack_top: *mut (),
@@ -74,23 +109,109 @@ struct RtHeader {
     data_ram_start: *mut u8,
     bss_size: usize,
     bss_start: *mut u8,
+    stack_bottom: *mut u8,
+}
+
+impl RtHeader {
+    pub(crate) fn initial_break(&self) -> *mut () {
+        relocate_ram(self.initial_break)
+    }
+
+    pub(crate) fn stack_top(&self) -> *mut () {
+        relocate_ram(self.stack_top)
+    }
+
+    pub(crate) fn stack_bottom(&self) -> *mut u8 {
+        relocate_ram(self.stack_bottom)
+    }
+}
+
+// Returns the runtime header. The header is reached through the code start the
+// kernel passed to start rather than through the rt_header symbol, because code
+// compiled with the rwpi relocation model addresses external statics relative
+// to the RAM base.
+pub(crate) fn rt_header() -> &'static RtHeader {
+    // Safety: start checked that the process binary was loaded at the address
+    // it was linked for, so code_start points to rt_header, which is defined
+    // in the linker script, valid for its type, and not modified anywhere.
+    unsafe { &*(process_memory().code_start as *const RtHeader) }
+}
+
+// Converts a RAM address from the runtime header into a runtime address. In
+// RAM-relocatable (rwpi) builds RAM is linked at address 0, so the header holds
+// offsets from the start of the process' memory region.
+fn relocate_ram<T>(address: *mut T) -> *mut T {
+    #[cfg(libtock_rwpi)]
+    return address.wrapping_byte_add(process_memory().memory_start as usize);
+    #[cfg(not(libtock_rwpi))]
+    address
+}
+
+/// The process' location in memory, as reported by the kernel when it started
+/// the process.
+#[derive(Clone, Copy, Debug)]
+pub struct ProcessMemory {
+    /// The start of the process binary's code, where the kernel started
+    /// executing the process.
+    pub code_start: *const u8,
+    /// The start of the process' memory region.
+    pub memory_start: *mut u8,
+    /// The size of the process' memory region, including the grant region.
+    pub memory_len: usize,
+    /// The process break the kernel set before starting the process.
+    pub initial_kernel_break: *mut u8,
+}
+
+// Written once by rust_start, before main is called.
+static mut PROCESS_MEMORY: ProcessMemory = ProcessMemory {
+    code_start: core::ptr::null(),
+    memory_start: core::ptr::null_mut(),
+    memory_len: 0,
+    initial_kernel_break: core::ptr::null_mut(),
+};
+
+/// Returns the process' location in memory, as reported by the kernel. The
+/// process binary's code is statically linked, so `code_start` always matches
+/// the address it was linked for. `memory_start` only differs from the address
+/// RAM was linked for in RAM-relocatable builds; see `doc/Startup.md` for more
+/// information.
+pub fn process_memory() -> ProcessMemory {
+    // Safety: PROCESS_MEMORY is only written by rust_start, before any other
+    // Rust code runs.
+    unsafe { core::ptr::addr_of!(PROCESS_MEMORY).read() }
 }
 
 // rust_start is the first Rust code to execute in the process. It is called
-// from start, which is written directly in assembly.
+// from start, which is written directly in assembly, and receives the
+// arguments the kernel passed to start.
 #[no_mangle]
-extern "C" fn rust_start() -> ! {
+extern "C" fn rust_start(
+    code_start: *const u8,
+    memory_start: *mut u8,
+    memory_len: usize,
+    initial_kernel_break: *mut u8,
+) -> ! {
     extern "Rust" {
         fn libtock_unsafe_main() -> !;
-        static rt_header: RtHeader;
     }
 
-    #[cfg(not(feature = "no_debug_memop"))]
-    // Safety: rt_header is defined in the linker script, valid for its type,
-    // and not modified anywhere
+    // Safety: Nothing else has accessed PROCESS_MEMORY yet.
     unsafe {
-        let _ = TockSyscalls::memop_debug_stack_start(rt_header.stack_top as *const u8);
-        let _ = TockSyscalls::memop_debug_heap_start(rt_header.initial_break as *const u8);
+        core::ptr::addr_of_mut!(PROCESS_MEMORY).write(ProcessMemory {
+            code_start,
+            memory_start,
+            memory_len,
+            initial_kernel_break,
+        });
+    }
+
+    #[cfg(feature = "stack_paint")]
+    crate::stack::paint();
+
+    #[cfg(not(feature = "no_debug_memop"))]
+    {
+        let _ = TockSyscalls::memop_debug_stack_start(rt_header().stack_top() as *const u8);
+        let _ = TockSyscalls::memop_debug_heap_start(rt_header().initial_break() as *const u8);
     }
 
     // Safety: libtock_unsafe_main is defined by the set_main! macro, and its
@@ -103,10 +224,5 @@ extern "C" fn rust_start() -> ! {
 /// Function which an allocator can call to learn the initial
 /// start of the heap region
 pub fn get_heap_start() -> *mut () {
-    extern "Rust" {
-        static rt_header: RtHeader;
-    }
-    // Safety: rt_header is defined in the linker script, valid for its type,
-    // and not modified anywhere
-    unsafe { rt_header.initial_break }
+    rt_header().initial_break()
 }
@@ -3,6 +3,14 @@ use libtock_platform::{RawSyscalls, Register};
 
 unsafe impl RawSyscalls for crate::TockSyscalls {
     unsafe fn yield1([Register(r0)]: [Register; 1]) {
+        #[cfg(feature = "stack_guard")]
+        crate::stack::check_canary();
+
+        // Yield may invoke upcalls, which must not run inside a critical
+        // section.
+        #[cfg(feature = "critical-section")]
+        crate::critical_section_impl::check_yield();
+
         // Safety: This matches the invariants required by the documentation on
         // RawSyscalls::yield1
         // the use of `clobber_abi` allows us this to run on both Thumb-1 and Thumb-2
@@ -23,6 +31,13 @@ unsafe impl RawSyscalls for crate::TockSyscalls {
     }
 
     unsafe fn yield2([Register(r0), Register(r1)]: [Register; 2]) {
+        #[cfg(feature = "stack_guard")]
+        crate::stack::check_canary();
+
+        // See yield1.
+        #[cfg(feature = "critical-section")]
+        crate::critical_section_impl::check_yield();
+
         // Safety: This matches the invariants required by the documentation on
         // RawSyscalls::yield2
         // the use of `clobber_abi` allows us this to run on both Thumb-1 and Thumb-2
@@ -43,6 +58,34 @@ unsafe impl RawSyscalls for crate::TockSyscalls {
         }
     }
 
+    unsafe fn yield3(
+        [Register(mut r0), Register(mut r1), Register(mut r2)]: [Register; 3],
+    ) -> [Register; 3] {
+        #[cfg(feature = "stack_guard")]
+        crate::stack::check_canary();
+
+        // Safety: This matches the invariants required by the documentation on
+        // RawSyscalls::yield3
+        // the use of `clobber_abi` allows us this to run on both Thumb-1 and Thumb-2
+        unsafe {
+            asm!("svc 0",
+                 inlateout("r0") r0, // a1
+                 inlateout("r1") r1, // a2
+                 inlateout("r2") r2, // a3
+                 // r4-r8 are callee-saved.
+                 // r9 is platform-specific. We don't use it in libtock_runtime,
+                 // so it is either unused or used as a callee-saved register.
+                 // r10 and r11 are callee-saved.
+
+                 // r13 is the stack pointer and must be restored by the callee.
+                 // r15 is the program counter.
+
+                 clobber_abi("C"), // a4, ip (r12), lr (r14)
+            );
+        }
+        [Register(r0), Register(r1), Register(r2)]
+    }
+
     unsafe fn syscall1<const SYSCALL_CLASS_NUMBER: usize>(
         [Register(mut r0)]: [Register; 1],
     ) -> [Register; 2] {
@@ -6,6 +6,14 @@ unsafe impl RawSyscalls for crate::TockSyscalls {
     // floating-point registers, as it does not mark them clobbered.
     #[cfg(not(any(target_feature = "d", target_feature = "f")))]
     unsafe fn yield1([Register(r0)]: [Register; 1]) {
+        #[cfg(feature = "stack_guard")]
+        crate::stack::check_canary();
+
+        // Yield may invoke upcalls, which must not run inside a critical
+        // section.
+        #[cfg(feature = "critical-section")]
+        crate::critical_section_impl::check_yield();
+
         // Safety: This matches the invariants required by the documentation on
         // RawSyscalls::yield1
         unsafe {
@@ -39,6 +47,13 @@ unsafe impl RawSyscalls for crate::TockSyscalls {
     // floating-point registers, as it does not mark them clobbered.
     #[cfg(not(any(target_feature = "d", target_feature = "f")))]
     unsafe fn yield2([Register(r0), Register(r1)]: [Register; 2]) {
+        #[cfg(feature = "stack_guard")]
+        crate::stack::check_canary();
+
+        // See yield1.
+        #[cfg(feature = "critical-section")]
+        crate::critical_section_impl::check_yield();
+
         // Safety: This matches the invariants required by the documentation on
         // RawSyscalls::yield2
         unsafe {
@@ -68,6 +83,45 @@ unsafe impl RawSyscalls for crate::TockSyscalls {
         }
     }
 
+    // This yield implementation is currently limited to RISC-V versions without
+    // floating-point registers, as it does not mark them clobbered.
+    #[cfg(not(any(target_feature = "d", target_feature = "f")))]
+    unsafe fn yield3(
+        [Register(mut r0), Register(mut r1), Register(mut r2)]: [Register; 3],
+    ) -> [Register; 3] {
+        #[cfg(feature = "stack_guard")]
+        crate::stack::check_canary();
+
+        // Safety: This matches the invariants required by the documentation on
+        // RawSyscalls::yield3
+        unsafe {
+            asm!("ecall",
+                 // x0 is the zero register.
+                 lateout("x1") _, // Return address
+                 // x2-x4 are stack, global, and thread pointers. sp is
+                 // callee-saved.
+                 lateout("x5") _, // t0
+                 lateout("x6") _, // t1
+                 lateout("x7") _, // t2
+                 // x8 and x9 are s0 and s1 and are callee-saved.
+                 inlateout("x10") r0,     // a0
+                 inlateout("x11") r1,     // a1
+                 inlateout("x12") r2,     // a2
+                 lateout("x13") _,        // a3
+                 inlateout("x14") 0 => _, // a4
+                 lateout("x15") _,        // a5
+                 lateout("x16") _,        // a6
+                 lateout("x17") _,        // a7
+                 // x18-27 are s2-s11 and are callee-saved
+                 lateout("x28") _, // t3
+                 lateout("x29") _, // t4
+                 lateout("x30") _, // t5
+                 lateout("x31") _, // t6
+            );
+        }
+        [Register(r0), Register(r1), Register(r2)]
+    }
+
     unsafe fn syscall1<const CLASS: usize>([Register(mut r0)]: [Register; 1]) -> [Register; 2] {
         let r1;
         // Safety: This matches the invariants required by the documentation on
@@ -14,6 +14,15 @@ pub mod adc {
     pub type Adc = adc::Adc<super::runtime::TockSyscalls>;
     pub use adc::ADCListener;
 }
+pub mod aes {
+    use libtock_aes as aes;
+    pub type Aes = aes::Aes<super::runtime::TockSyscalls>;
+    pub use aes::{AesMode, Direction, BLOCK_LEN, CCM_NONCE_LEN, KEY_LEN};
+    #[cfg(feature = "rust_crypto")]
+    pub type Aes128 = aes::Aes128<super::runtime::TockSyscalls>;
+    #[cfg(feature = "rust_crypto")]
+    pub type Aes128Ctr = aes::Aes128Ctr<super::runtime::TockSyscalls>;
+}
 
 pub mod air_quality {
     use libtock_air_quality as air_quality;
@@ -24,13 +33,22 @@ pub mod air_quality {
 pub mod alarm {
     use libtock_alarm as alarm;
     pub type Alarm = alarm::Alarm<super::runtime::TockSyscalls>;
-    pub use alarm::{Convert, Hz, Milliseconds, Ticks};
+    pub use alarm::{AlarmListener, Convert, Hz, Milliseconds, Ticks};
 }
 pub mod ambient_light {
     use libtock_ambient_light as ambient_light;
     pub type AmbientLight = ambient_light::AmbientLight<super::runtime::TockSyscalls>;
     pub use ambient_light::IntensityListener;
 }
+pub mod ble {
+    use libtock_ble as ble;
+    pub type Ble = ble::Ble<super::runtime::TockSyscalls>;
+    pub use ble::{
+        flags, AdStructure, AdStructures, AdvertisementReport, AdvertisingData, PduType,
+        ScanBuffer, MAX_ADV_DATA_LEN,
+    };
+    pub type PassiveScanner<'buf> = ble::PassiveScanner<'buf, super::runtime::TockSyscalls>;
+}
 pub mod buttons {
     use libtock_buttons as buttons;
     pub type Buttons = buttons::Buttons<super::runtime::TockSyscalls>;
@@ -41,11 +59,48 @@ pub mod buzzer {
     pub type Buzzer = buzzer::Buzzer<super::runtime::TockSyscalls>;
     pub use buzzer::Note;
 }
+pub mod can {
+    use libtock_can as can;
+    pub type Can = can::Can<super::runtime::TockSyscalls>;
+    pub type Receiver<'buf, const N: usize> = can::Receiver<'buf, N, super::runtime::TockSyscalls>;
+    pub use can::{
+        BusState, ErrorCounters, ErrorListener, Filter, Frame, Id, Mode, RxRingBuffer, MAX_DATA_LEN,
+    };
+}
 pub mod console {
     use libtock_console as console;
     pub type Console = console::Console<super::runtime::TockSyscalls>;
     pub use console::ConsoleWriter;
 }
+pub mod crc {
+    use libtock_crc as crc;
+    pub type Crc = crc::Crc<super::runtime::TockSyscalls>;
+    pub use crc::CrcAlgorithm;
+}
+pub mod e2e {
+    pub use e2e::{profile1, profile2, profile4, profile5, CheckStatus, Crc16, Error, SoftwareCrc};
+    use libtock_e2e as e2e;
+    pub type TockCrc = e2e::TockCrc<super::runtime::TockSyscalls>;
+}
+pub mod ecdsa_p256 {
+    use libtock_ecdsa_p256 as ecdsa_p256;
+    pub type EcdsaP256 = ecdsa_p256::EcdsaP256<super::runtime::TockSyscalls>;
+    pub use ecdsa_p256::{VerifyListener, HASH_LEN, PUBLIC_KEY_LEN, SIGNATURE_LEN};
+}
+pub mod executor {
+    use libtock_executor as executor;
+    pub type Executor<'a, const N: usize> = executor::Executor<'a, super::runtime::TockSyscalls, N>;
+    pub use executor::{AsyncUpcall, SpawnError, Wait};
+
+    pub fn block_on<F: core::future::Future>(future: F) -> F::Output {
+        executor::block_on::<super::runtime::TockSyscalls, F>(future)
+    }
+}
+pub mod filesystem {
+    use libtock_filesystem as filesystem;
+    pub type FileSystem = filesystem::FileSystem<super::runtime::TockSyscalls>;
+    pub use filesystem::{DirEntry, EntryKind, Error, Metadata, DATA_CHUNK, NAME_MAX};
+}
 pub mod gpio {
     use libtock_gpio as gpio;
     pub type Gpio = gpio::Gpio<super::runtime::TockSyscalls>;
@@ -54,6 +109,18 @@ pub mod gpio {
         PullDown, PullNone, PullUp,
     };
 }
+pub mod hmac {
+    use libtock_hmac as hmac;
+    pub type Hmac = hmac::Hmac<super::runtime::TockSyscalls>;
+    pub type HmacOperation<'k> = hmac::HmacOperation<'k, super::runtime::TockSyscalls>;
+    pub use hmac::{HmacAlgorithm, MAX_MAC_LEN};
+    #[cfg(feature = "rust_crypto")]
+    pub type HmacSha256 = hmac::HmacSha256<super::runtime::TockSyscalls>;
+    #[cfg(feature = "rust_crypto")]
+    pub type HmacSha384 = hmac::HmacSha384<super::runtime::TockSyscalls>;
+    #[cfg(feature = "rust_crypto")]
+    pub type HmacSha512 = hmac::HmacSha512<super::runtime::TockSyscalls>;
+}
 pub mod i2c_master {
     use libtock_i2c_master as i2c_master;
     pub type I2CMaster = i2c_master::I2CMaster<super::runtime::TockSyscalls>;
@@ -69,6 +136,23 @@ pub mod ieee802154 {
     pub type RxSingleBufferOperator<'buf, const N: usize> =
         ieee802154::RxSingleBufferOperator<'buf, N, super::runtime::TockSyscalls>;
 }
+pub mod ipc {
+    use libtock_ipc as ipc;
+    pub type Ipc = ipc::Ipc<super::runtime::TockSyscalls>;
+    pub use ipc::{client_subscribe_num, IpcListener, IpcNotification, DRIVER_NUM};
+}
+pub mod isolated_nonvolatile_storage {
+    use libtock_isolated_nonvolatile_storage as isolated_nonvolatile_storage;
+    pub type IsolatedNonvolatileStorage =
+        isolated_nonvolatile_storage::IsolatedNonvolatileStorage<super::runtime::TockSyscalls>;
+    pub type StorageRegion =
+        isolated_nonvolatile_storage::StorageRegion<super::runtime::TockSyscalls>;
+}
+pub mod isotp {
+    use libtock_isotp as isotp;
+    pub type IsoTp<'buf, const N: usize> = isotp::IsoTp<'buf, N, super::runtime::TockSyscalls>;
+    pub use isotp::{Addressing, Error, IsoTpConfig, Timeout, MAX_SHORT_MESSAGE_LEN};
+}
 pub mod leds {
     use libtock_leds as leds;
     pub type Leds = leds::Leds<super::runtime::TockSyscalls>;
@@ -78,6 +162,14 @@ pub mod low_level_debug {
     pub type LowLevelDebug = lldb::LowLevelDebug<super::runtime::TockSyscalls>;
     pub use lldb::AlertCode;
 }
+pub mod mctp {
+    use libtock_mctp as mctp;
+    pub type Endpoint<'a> = mctp::Endpoint<'a, super::runtime::TockSyscalls>;
+    pub use mctp::{
+        control, smbus, EndpointConfig, Error, Fragmenter, Header, Message, MessageType,
+        Reassembler, Route, BROADCAST_EID, HEADER_LEN, NULL_EID,
+    };
+}
 pub mod ninedof {
     use libtock_ninedof as ninedof;
     pub type NineDof = ninedof::NineDof<super::runtime::TockSyscalls>;
@@ -87,11 +179,37 @@ pub mod proximity {
     use libtock_proximity as proximity;
     pub type Proximity = proximity::Proximity<super::runtime::TockSyscalls>;
 }
+pub mod pwm {
+    use libtock_pwm as pwm;
+    pub type Pwm = pwm::Pwm<super::runtime::TockSyscalls>;
+    pub type PwmPin = pwm::PwmPin<super::runtime::TockSyscalls>;
+    pub use pwm::MAX_DUTY_CYCLE;
+}
 pub mod rng {
     use libtock_rng as rng;
     pub type Rng = rng::Rng<super::runtime::TockSyscalls>;
     pub use rng::RngListener;
 }
+pub mod screen {
+    use libtock_screen as screen;
+    pub type Screen = screen::Screen<super::runtime::TockSyscalls>;
+    pub use screen::{PixelFormat, Rotation};
+    #[cfg(feature = "rust_embedded")]
+    pub type Display<P> = screen::Display<super::runtime::TockSyscalls, P>;
+    #[cfg(feature = "rust_embedded")]
+    pub use screen::DisplayColor;
+}
+pub mod sha {
+    use libtock_sha as sha;
+    pub type Sha = sha::Sha<super::runtime::TockSyscalls>;
+    pub use sha::ShaAlgorithm;
+    #[cfg(feature = "rust_crypto")]
+    pub type Sha256 = sha::Sha256<super::runtime::TockSyscalls>;
+    #[cfg(feature = "rust_crypto")]
+    pub type Sha384 = sha::Sha384<super::runtime::TockSyscalls>;
+    #[cfg(feature = "rust_crypto")]
+    pub type Sha512 = sha::Sha512<super::runtime::TockSyscalls>;
+}
 pub mod sound_pressure {
     use libtock_sound_pressure as sound_pressure;
     pub type SoundPressure = sound_pressure::SoundPressure<super::runtime::TockSyscalls>;
@@ -108,6 +226,30 @@ pub mod temperature {
     pub type Temperature = temperature::Temperature<super::runtime::TockSyscalls>;
     pub use temperature::TemperatureListener;
 }
+pub mod touch {
+    use libtock_touch as touch;
+    pub type Touch = touch::Touch<super::runtime::TockSyscalls>;
+    pub use touch::{
+        Gesture, GestureListener, MultiTouch, TouchEvent, TouchListener, TouchStatus,
+        MAX_MULTI_TOUCHES,
+    };
+}
+pub mod udp {
+    use libtock_udp as udp;
+    pub type Udp = udp::Udp<super::runtime::TockSyscalls>;
+    pub type UdpSocket = udp::UdpSocket<super::runtime::TockSyscalls>;
+    pub type UdpReceiver<'buf, const N: usize> =
+        udp::UdpReceiver<'buf, N, super::runtime::TockSyscalls>;
+    pub use udp::{RxBuffer, MAX_INTERFACES};
+}
+pub mod uds {
+    use libtock_uds as uds;
+    pub type Server<'a> = uds::Server<'a, super::runtime::TockSyscalls>;
+    pub use uds::{
+        Access, DataIdentifier, Handler, Nrc, Routine, SecurityLevel, ServerConfig, Service,
+        Session, Sessions, MAX_SEED_LEN,
+    };
+}
 pub mod key_value {
     use libtock_key_value as key_value;
     pub type KeyValue = key_value::KeyValue<super::runtime::TockSyscalls>;
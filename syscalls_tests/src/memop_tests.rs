@@ -113,6 +113,142 @@ fn app_ram_start_test() {
     );
 }
 
+#[test]
+fn app_ram_end_test() {
+    let kernel = fake::Kernel::new();
+    kernel.add_expected_syscall(ExpectedSyscall::Memop {
+        memop_num: 3,
+        argument0: 0.into(),
+        return_error: None,
+    });
+    assert_eq!(
+        fake::Syscalls::memop_app_ram_end(),
+        Ok(fake::MemoryMap::default().app_ram_end as *const u8)
+    );
+    assert_eq!(
+        kernel.take_syscall_log(),
+        [SyscallLogEntry::Memop {
+            memop_num: 3,
+            argument0: 0.into(),
+        }]
+    );
+}
+
+#[test]
+fn memory_map_test() {
+    let kernel = fake::Kernel::new();
+    kernel.set_memory_map(fake::MemoryMap {
+        app_ram_start: 0x20000000,
+        app_ram_end: 0x20004000,
+        flash_start: 0x30000,
+        flash_end: 0x38000,
+        grant_start: 0x20003000,
+        writeable_flash_regions: vec![0x36000..0x37000, 0x37000..0x37800],
+    });
+    assert_eq!(
+        fake::Syscalls::memop_app_ram_start(),
+        Ok(0x20000000 as *const u8)
+    );
+    assert_eq!(
+        fake::Syscalls::memop_app_ram_end(),
+        Ok(0x20004000 as *const u8)
+    );
+    assert_eq!(
+        fake::Syscalls::memop_flash_start(),
+        Ok(0x30000 as *const u8)
+    );
+    assert_eq!(fake::Syscalls::memop_flash_end(), Ok(0x38000 as *const u8));
+    assert_eq!(
+        fake::Syscalls::memop_grant_start(),
+        Ok(0x20003000 as *const u8)
+    );
+    assert_eq!(fake::Syscalls::memop_flash_region_count(), Ok(2));
+    assert_eq!(
+        fake::Syscalls::memop_flash_region_start(1),
+        Ok(0x37000 as *const u8)
+    );
+    assert_eq!(
+        fake::Syscalls::memop_flash_region_end(1),
+        Ok(0x37800 as *const u8)
+    );
+    assert_eq!(
+        fake::Syscalls::memop_flash_region_start(2),
+        Err(ErrorCode::Invalid)
+    );
+    assert_eq!(
+        fake::Syscalls::memop_flash_region_end(2),
+        Err(ErrorCode::Invalid)
+    );
+    assert_eq!(
+        kernel.take_syscall_log(),
+        [
+            SyscallLogEntry::Memop {
+                memop_num: 2,
+                argument0: 0.into(),
+            },
+            SyscallLogEntry::Memop {
+                memop_num: 3,
+                argument0: 0.into(),
+            },
+            SyscallLogEntry::Memop {
+                memop_num: 4,
+                argument0: 0.into(),
+            },
+            SyscallLogEntry::Memop {
+                memop_num: 5,
+                argument0: 0.into(),
+            },
+            SyscallLogEntry::Memop {
+                memop_num: 6,
+                argument0: 0.into(),
+            },
+            SyscallLogEntry::Memop {
+                memop_num: 7,
+                argument0: 0.into(),
+            },
+            SyscallLogEntry::Memop {
+                memop_num: 8,
+                argument0: 1.into(),
+            },
+            SyscallLogEntry::Memop {
+                memop_num: 9,
+                argument0: 1.into(),
+            },
+            SyscallLogEntry::Memop {
+                memop_num: 8,
+                argument0: 2.into(),
+            },
+            SyscallLogEntry::Memop {
+                memop_num: 9,
+                argument0: 2.into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn flash_region_error_test() {
+    let kernel = fake::Kernel::new();
+    kernel.add_expected_syscall(ExpectedSyscall::Memop {
+        memop_num: 7,
+        argument0: 0.into(),
+        return_error: Some(ErrorCode::NoSupport),
+    });
+    assert_eq!(
+        fake::Syscalls::memop_flash_region_count(),
+        Err(ErrorCode::NoSupport)
+    );
+    kernel.add_expected_syscall(ExpectedSyscall::Memop {
+        memop_num: 8,
+        argument0: 0.into(),
+        return_error: Some(ErrorCode::Fail),
+    });
+    assert_eq!(
+        fake::Syscalls::memop_flash_region_start(0),
+        Err(ErrorCode::Fail)
+    );
+}
+
 #[test]
 fn debug_stack_start_test() {
     let kernel = fake::Kernel::new();
@@ -1,7 +1,7 @@
 //! Tests for implementations of Yield system calls in
 //! `libtock_platform::Syscalls`.
 
-use libtock_platform::{Syscalls, YieldNoWaitReturn};
+use libtock_platform::{ErrorCode, Syscalls, YieldNoWaitReturn};
 use libtock_unittest::{fake, ExpectedSyscall, SyscallLogEntry};
 
 // Tests yield_no_wait with an upcall executed.
@@ -36,3 +36,40 @@ fn wait() {
     fake::Syscalls::yield_wait();
     assert_eq!(kernel.take_syscall_log(), [SyscallLogEntry::YieldWait]);
 }
+
+// Tests yield_wait_for.
+#[test]
+fn wait_for() {
+    let kernel = fake::Kernel::new();
+    kernel.add_expected_syscall(ExpectedSyscall::YieldWaitFor {
+        driver_num: 1,
+        subscribe_num: 2,
+        override_return: Some((3, 4, 5)),
+    });
+    assert_eq!(fake::Syscalls::yield_wait_for(1, 2), (3, 4, 5));
+    assert_eq!(
+        kernel.take_syscall_log(),
+        [SyscallLogEntry::YieldWaitFor {
+            driver_num: 1,
+            subscribe_num: 2
+        }]
+    );
+}
+
+// Tests yield_wait_for_status.
+#[test]
+fn wait_for_status() {
+    let kernel = fake::Kernel::new();
+    for (status, expected) in [
+        (0, Ok(())),
+        (ErrorCode::Busy as u32, Err(ErrorCode::Busy)),
+        (0xFFFF, Err(ErrorCode::Fail)),
+    ] {
+        kernel.add_expected_syscall(ExpectedSyscall::YieldWaitFor {
+            driver_num: 1,
+            subscribe_num: 2,
+            override_return: Some((status, 4, 5)),
+        });
+        assert_eq!(fake::Syscalls::yield_wait_for_status(1, 2), expected);
+    }
+}
@@ -0,0 +1,670 @@
+//! `libtock_uds` is a server for Unified Diagnostic Services (UDS, ISO
+//! 14229-1), the protocol testers use to diagnose and configure ECUs. It runs
+//! over ISO-TP, and implements the services most applications need:
+//!
+//! - DiagnosticSessionControl (0x10). Sessions other than the default one end
+//!   when no request arrives for S3.
+//! - ECUReset (0x11), which restarts the process with `exit_restart` once the
+//!   response is sent.
+//! - SecurityAccess (0x27), with seeds and keys computed by the application.
+//! - ReadDataByIdentifier (0x22) and WriteDataByIdentifier (0x2E), with the
+//!   values stored in the key-value driver.
+//! - RoutineControl (0x31).
+//! - TesterPresent (0x3E).
+//!
+//! The application describes what it serves in tables of
+//! [`DataIdentifier`]s, [`Routine`]s, [`SecurityLevel`]s and [`Service`]s,
+//! the latter for services the server does not implement itself. Each entry
+//! states in which sessions it may be used and which security level it needs.
+//! Requests that cannot be served are answered with the negative response
+//! code that ISO 14229-1 specifies.
+//!
+//! # Example
+//! ```ignore
+//! use libtock::uds::{Access, DataIdentifier, Server, ServerConfig, Sessions};
+//!
+//! let data_identifiers = [DataIdentifier {
+//!     id: 0xF190,
+//!     key: b"vin",
+//!     len: 17,
+//!     read: Access::ALWAYS,
+//!     write: Access::new(Sessions::EXTENDED).secured(1),
+//! }];
+//! let mut config = ServerConfig::new();
+//! config.data_identifiers = &data_identifiers;
+//! let mut server = Server::new(config);
+//! let (mut request, mut response) = ([0; 64], [0; 64]);
+//! loop {
+//!     let _ = server.serve(&mut isotp, &mut request, &mut response);
+//! }
+//! ```
+
+#![no_std]
+
+use core::marker::PhantomData;
+use libtock_alarm::{Alarm, Convert, Milliseconds};
+use libtock_isotp::IsoTp;
+use libtock_key_value::KeyValue;
+use libtock_platform::{DefaultConfig, Syscalls};
+
+/// The longest seed a [`SecurityLevel`] may provide.
+pub const MAX_SEED_LEN: usize = 32;
+
+/// A diagnostic session.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum Session {
+    Default = 0x01,
+    Programming = 0x02,
+    Extended = 0x03,
+}
+
+impl Session {
+    fn from_sub_function(sub_function: u8) -> Option<Session> {
+        match sub_function {
+            0x01 => Some(Session::Default),
+            0x02 => Some(Session::Programming),
+            0x03 => Some(Session::Extended),
+            _ => None,
+        }
+    }
+}
+
+/// A set of [`Session`]s.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Sessions(u8);
+
+impl Sessions {
+    pub const NONE: Sessions = Sessions(0);
+    pub const DEFAULT: Sessions = Sessions(1 << (Session::Default as u8 - 1));
+    pub const PROGRAMMING: Sessions = Sessions(1 << (Session::Programming as u8 - 1));
+    pub const EXTENDED: Sessions = Sessions(1 << (Session::Extended as u8 - 1));
+    /// Every session but the default one.
+    pub const NON_DEFAULT: Sessions = Sessions::PROGRAMMING.union(Sessions::EXTENDED);
+    pub const ALL: Sessions = Sessions::DEFAULT.union(Sessions::NON_DEFAULT);
+
+    /// Returns the sessions that are in either set.
+    pub const fn union(self, other: Sessions) -> Sessions {
+        Sessions(self.0 | other.0)
+    }
+
+    pub const fn contains(self, session: Session) -> bool {
+        self.0 & (1 << (session as u8 - 1)) != 0
+    }
+}
+
+/// When an entry of the server's tables may be used.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Access {
+    /// The sessions in which the entry may be used.
+    pub sessions: Sessions,
+    /// The security level that must be unlocked with SecurityAccess, if any.
+    pub security_level: Option<u8>,
+}
+
+impl Access {
+    /// The entry may never be used.
+    pub const DENIED: Access = Access::new(Sessions::NONE);
+    /// The entry may be used in any session, without security access.
+    pub const ALWAYS: Access = Access::new(Sessions::ALL);
+
+    /// The entry may be used in `sessions`, without security access.
+    pub const fn new(sessions: Sessions) -> Access {
+        Access {
+            sessions,
+            security_level: None,
+        }
+    }
+
+    /// Returns this access with security level `level` required too.
+    pub const fn secured(self, level: u8) -> Access {
+        Access {
+            sessions: self.sessions,
+            security_level: Some(level),
+        }
+    }
+}
+
+/// A negative response code, which tells the tester why a request failed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum Nrc {
+    GeneralReject = 0x10,
+    ServiceNotSupported = 0x11,
+    SubFunctionNotSupported = 0x12,
+    IncorrectMessageLengthOrInvalidFormat = 0x13,
+    ResponseTooLong = 0x14,
+    BusyRepeatRequest = 0x21,
+    ConditionsNotCorrect = 0x22,
+    RequestSequenceError = 0x24,
+    RequestOutOfRange = 0x31,
+    SecurityAccessDenied = 0x33,
+    InvalidKey = 0x35,
+    ExceededNumberOfAttempts = 0x36,
+    RequiredTimeDelayNotExpired = 0x37,
+    GeneralProgrammingFailure = 0x72,
+    SubFunctionNotSupportedInActiveSession = 0x7E,
+    ServiceNotSupportedInActiveSession = 0x7F,
+}
+
+/// Handles a request on behalf of the server. It is passed the request's
+/// parameters and a buffer for the response's, and returns the length of the
+/// response's parameters.
+pub type Handler<'a> = &'a dyn Fn(&[u8], &mut [u8]) -> Result<usize, Nrc>;
+
+/// A data identifier (DID), which ReadDataByIdentifier and
+/// WriteDataByIdentifier access. Its value is stored in the key-value driver.
+#[derive(Clone, Copy, Debug)]
+pub struct DataIdentifier<'a> {
+    pub id: u16,
+    /// The key the value is stored under.
+    pub key: &'a [u8],
+    /// The length of the value. Writes must provide exactly this many bytes.
+    pub len: usize,
+    pub read: Access,
+    pub write: Access,
+}
+
+/// A routine, which RoutineControl starts, stops and requests the results
+/// of. Handlers are passed the routine control option record, and write the
+/// routine status record. Missing handlers are reported as sub-functions that
+/// are not supported.
+#[derive(Clone, Copy)]
+pub struct Routine<'a> {
+    pub id: u16,
+    pub access: Access,
+    pub start: Option<Handler<'a>>,
+    pub stop: Option<Handler<'a>>,
+    pub results: Option<Handler<'a>>,
+}
+
+/// A security level that SecurityAccess unlocks. Level `n` is requested with
+/// sub-functions `2n - 1` (requestSeed) and `2n` (sendKey).
+#[derive(Clone, Copy)]
+pub struct SecurityLevel<'a> {
+    pub level: u8,
+    /// The sessions in which the level may be unlocked.
+    pub sessions: Sessions,
+    /// Writes a new seed into the buffer, which holds [`MAX_SEED_LEN`] bytes,
+    /// and returns its length.
+    pub seed: &'a dyn Fn(&mut [u8]) -> Result<usize, Nrc>,
+    /// Returns whether the key, the second argument, matches the seed.
+    pub check_key: &'a dyn Fn(&[u8], &[u8]) -> bool,
+}
+
+/// A service implemented by the application. Services implemented by the
+/// server take precedence.
+#[derive(Clone, Copy)]
+pub struct Service<'a> {
+    pub sid: u8,
+    pub access: Access,
+    /// Handles the request. The parameters exclude the service identifier.
+    pub handler: Handler<'a>,
+}
+
+/// The configuration of a server.
+#[derive(Clone, Copy)]
+pub struct ServerConfig<'a> {
+    pub data_identifiers: &'a [DataIdentifier<'a>],
+    pub routines: &'a [Routine<'a>],
+    pub security_levels: &'a [SecurityLevel<'a>],
+    pub services: &'a [Service<'a>],
+    /// P2: how long the server takes to respond, which is reported to the
+    /// tester when a session starts.
+    pub p2: Milliseconds,
+    /// P2*: how long the server takes to respond after asking the tester to
+    /// wait, which is reported with P2.
+    pub p2_star: Milliseconds,
+    /// S3: how long a session other than the default one lasts without
+    /// requests.
+    pub s3: Milliseconds,
+    /// The number of invalid keys after which SecurityAccess refuses to give
+    /// seeds for `security_delay`.
+    pub max_attempts: u8,
+    pub security_delay: Milliseconds,
+}
+
+impl<'a> ServerConfig<'a> {
+    /// Returns a configuration with empty tables, the default timings of ISO
+    /// 14229-2 and a delay of ten seconds after three invalid keys.
+    pub fn new() -> ServerConfig<'a> {
+        ServerConfig {
+            data_identifiers: &[],
+            routines: &[],
+            security_levels: &[],
+            services: &[],
+            p2: Milliseconds(50),
+            p2_star: Milliseconds(5000),
+            s3: Milliseconds(5000),
+            max_attempts: 3,
+            security_delay: Milliseconds(10000),
+        }
+    }
+}
+
+impl Default for ServerConfig<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A UDS server. It keeps the state of the diagnostic session: the active
+/// session, the unlocked security level and the progress of SecurityAccess.
+pub struct Server<'a, S: Syscalls, C: Config = DefaultConfig> {
+    config: ServerConfig<'a>,
+    session: Session,
+    unlocked: Option<u8>,
+    // The tick count at the last request.
+    last_request: u32,
+    // The level the seed was given for, until a key is sent.
+    seed_level: Option<u8>,
+    seed: [u8; MAX_SEED_LEN],
+    seed_len: usize,
+    attempts: u8,
+    // The tick count at which too many invalid keys were sent.
+    delay_start: Option<u32>,
+    // The reset type of the ECUReset being handled.
+    reset: Option<u8>,
+    s: PhantomData<S>,
+    c: PhantomData<C>,
+}
+
+impl<'a, S: Syscalls, C: Config> Server<'a, S, C> {
+    /// Creates a server in the default session.
+    pub fn new(config: ServerConfig<'a>) -> Self {
+        Self {
+            config,
+            session: Session::Default,
+            unlocked: None,
+            last_request: 0,
+            seed_level: None,
+            seed: [0; MAX_SEED_LEN],
+            seed_len: 0,
+            attempts: 0,
+            delay_start: None,
+            reset: None,
+            s: PhantomData,
+            c: PhantomData,
+        }
+    }
+
+    /// The active session.
+    pub fn session(&self) -> Session {
+        self.session
+    }
+
+    /// The unlocked security level, if any.
+    pub fn security_level(&self) -> Option<u8> {
+        self.unlocked
+    }
+
+    /// Receives a request over `isotp`, handles it and sends the response, if
+    /// any. Requests longer than `request` are dropped. Restarts the process
+    /// after responding to an ECUReset.
+    pub fn serve<const N: usize>(
+        &mut self,
+        isotp: &mut IsoTp<'_, N, S, C>,
+        request: &mut [u8],
+        response: &mut [u8],
+    ) -> Result<(), libtock_isotp::Error> {
+        let len = isotp.receive(request)?;
+        let sent = match self.handle(&request[..len], response) {
+            Some(len) => isotp.send(&response[..len]),
+            None => Ok(()),
+        };
+        self.restart_if_requested();
+        sent
+    }
+
+    /// Handles `request` and writes the response into `response`, returning
+    /// its length, or `None` if no response is to be sent. `response` must
+    /// hold at least the three bytes of a negative response.
+    ///
+    /// This lets the server be used over transports other than ISO-TP, in
+    /// which case [`Server::restart_if_requested`] must be called once the
+    /// response is sent.
+    pub fn handle(&mut self, request: &[u8], response: &mut [u8]) -> Option<usize> {
+        let (&sid, parameters) = request.split_first()?;
+        self.reset = None;
+        if self.session != Session::Default && Self::has_passed(self.last_request, self.config.s3) {
+            self.enter(Session::Default);
+        }
+        self.last_request = Self::now();
+
+        let suppress = has_sub_function(sid)
+            && parameters
+                .first()
+                .is_some_and(|parameter| parameter & SUPPRESS_POSITIVE_RESPONSE != 0);
+        match self.dispatch(sid, parameters, &mut response[1..]) {
+            Ok(_) if suppress => None,
+            Ok(len) => {
+                response[0] = sid + POSITIVE_RESPONSE_OFFSET;
+                Some(1 + len)
+            }
+            Err(nrc) => {
+                response[..3].copy_from_slice(&[NEGATIVE_RESPONSE, sid, nrc as u8]);
+                Some(3)
+            }
+        }
+    }
+
+    /// Restarts the process if the last request handled was an ECUReset. The
+    /// reset type is the completion code.
+    pub fn restart_if_requested(&self) {
+        if let Some(reset) = self.reset {
+            S::exit_restart(reset as u32);
+        }
+    }
+
+    fn dispatch(&mut self, sid: u8, request: &[u8], response: &mut [u8]) -> Result<usize, Nrc> {
+        match sid {
+            sid::DIAGNOSTIC_SESSION_CONTROL => self.session_control(request, response),
+            sid::ECU_RESET => self.ecu_reset(request, response),
+            sid::READ_DATA_BY_IDENTIFIER => self.read_data(request, response),
+            sid::SECURITY_ACCESS => self.security_access(request, response),
+            sid::WRITE_DATA_BY_IDENTIFIER => self.write_data(request, response),
+            sid::ROUTINE_CONTROL => self.routine_control(request, response),
+            sid::TESTER_PRESENT => tester_present(request, response),
+            _ => {
+                let service = self
+                    .config
+                    .services
+                    .iter()
+                    .find(|service| service.sid == sid)
+                    .ok_or(Nrc::ServiceNotSupported)?;
+                self.check(&service.access, Nrc::ServiceNotSupportedInActiveSession)?;
+                (service.handler)(request, response)
+            }
+        }
+    }
+
+    fn session_control(&mut self, request: &[u8], response: &mut [u8]) -> Result<usize, Nrc> {
+        let sub_function = sub_function(request)?;
+        let session =
+            Session::from_sub_function(sub_function).ok_or(Nrc::SubFunctionNotSupported)?;
+        if request.len() != 1 {
+            return Err(Nrc::IncorrectMessageLengthOrInvalidFormat);
+        }
+        self.enter(session);
+        // P2* is reported in units of 10 ms.
+        let [p2_high, p2_low] = u16::try_from(self.config.p2.0)
+            .unwrap_or(u16::MAX)
+            .to_be_bytes();
+        let [p2_star_high, p2_star_low] = u16::try_from(self.config.p2_star.0 / 10)
+            .unwrap_or(u16::MAX)
+            .to_be_bytes();
+        put(
+            response,
+            &[sub_function, p2_high, p2_low, p2_star_high, p2_star_low],
+        )
+    }
+
+    fn ecu_reset(&mut self, request: &[u8], response: &mut [u8]) -> Result<usize, Nrc> {
+        let sub_function = sub_function(request)?;
+        if !matches!(
+            sub_function,
+            reset::HARD_RESET | reset::KEY_OFF_ON_RESET | reset::SOFT_RESET
+        ) {
+            return Err(Nrc::SubFunctionNotSupported);
+        }
+        if request.len() != 1 {
+            return Err(Nrc::IncorrectMessageLengthOrInvalidFormat);
+        }
+        self.reset = Some(sub_function);
+        put(response, &[sub_function])
+    }
+
+    fn read_data(&mut self, request: &[u8], response: &mut [u8]) -> Result<usize, Nrc> {
+        if request.is_empty() || request.len() % 2 != 0 {
+            return Err(Nrc::IncorrectMessageLengthOrInvalidFormat);
+        }
+        // Identifiers that are not supported in the active session are left
+        // out of the response, which is negative only if none are.
+        let mut len = 0;
+        for id in request.chunks_exact(2) {
+            let Some(data) = self.data_identifier([id[0], id[1]]) else {
+                continue;
+            };
+            if !data.read.sessions.contains(self.session) {
+                continue;
+            }
+            self.check(&data.read, Nrc::RequestOutOfRange)?;
+            let record = response
+                .get_mut(len..len + 2 + data.len)
+                .ok_or(Nrc::ResponseTooLong)?;
+            record[..2].copy_from_slice(id);
+            let value_len = KeyValue::<S, C>::get(data.key, &mut record[2..])
+                .map_err(|_| Nrc::ConditionsNotCorrect)?;
+            len += 2 + data.len.min(value_len as usize);
+        }
+        match len {
+            0 => Err(Nrc::RequestOutOfRange),
+            _ => Ok(len),
+        }
+    }
+
+    fn write_data(&mut self, request: &[u8], response: &mut [u8]) -> Result<usize, Nrc> {
+        if request.len() < 3 {
+            return Err(Nrc::IncorrectMessageLengthOrInvalidFormat);
+        }
+        let (id, value) = request.split_at(2);
+        let data = self
+            .data_identifier([id[0], id[1]])
+            .ok_or(Nrc::RequestOutOfRange)?;
+        self.check(&data.write, Nrc::RequestOutOfRange)?;
+        if value.len() != data.len {
+            return Err(Nrc::IncorrectMessageLengthOrInvalidFormat);
+        }
+        KeyValue::<S, C>::set(data.key, value).map_err(|_| Nrc::GeneralProgrammingFailure)?;
+        put(response, id)
+    }
+
+    fn security_access(&mut self, request: &[u8], response: &mut [u8]) -> Result<usize, Nrc> {
+        if self.session == Session::Default {
+            return Err(Nrc::ServiceNotSupportedInActiveSession);
+        }
+        let sub_function = sub_function(request)?;
+        let level = self
+            .config
+            .security_levels
+            .iter()
+            .find(|level| sub_function != 0 && level.level == sub_function.div_ceil(2))
+            .ok_or(Nrc::SubFunctionNotSupported)?;
+        if !level.sessions.contains(self.session) {
+            return Err(Nrc::SubFunctionNotSupportedInActiveSession);
+        }
+
+        if sub_function % 2 == 1 {
+            // requestSeed
+            if request.len() != 1 {
+                return Err(Nrc::IncorrectMessageLengthOrInvalidFormat);
+            }
+            if let Some(start) = self.delay_start {
+                if !Self::has_passed(start, self.config.security_delay) {
+                    return Err(Nrc::RequiredTimeDelayNotExpired);
+                }
+                self.delay_start = None;
+            }
+            let mut seed = [0; MAX_SEED_LEN];
+            let seed_len = (level.seed)(&mut seed)?;
+            if seed_len > MAX_SEED_LEN {
+                return Err(Nrc::GeneralReject);
+            }
+            // An unlocked level is reported with a seed of zeros.
+            if self.unlocked == Some(level.level) {
+                seed = [0; MAX_SEED_LEN];
+            } else {
+                self.seed_level = Some(level.level);
+                self.seed = seed;
+                self.seed_len = seed_len;
+            }
+            let len = put(response, &[sub_function])?;
+            Ok(len + put(&mut response[len..], &seed[..seed_len])?)
+        } else {
+            // sendKey
+            let key = &request[1..];
+            if key.is_empty() {
+                return Err(Nrc::IncorrectMessageLengthOrInvalidFormat);
+            }
+            // Each seed may be used for one key only.
+            if self.seed_level.take() != Some(level.level) {
+                return Err(Nrc::RequestSequenceError);
+            }
+            if !(level.check_key)(&self.seed[..self.seed_len], key) {
+                self.attempts = self.attempts.saturating_add(1);
+                if self.attempts < self.config.max_attempts {
+                    return Err(Nrc::InvalidKey);
+                }
+                self.attempts = 0;
+                self.delay_start = Some(Self::now());
+                return Err(Nrc::ExceededNumberOfAttempts);
+            }
+            self.attempts = 0;
+            self.unlocked = Some(level.level);
+            put(response, &[sub_function])
+        }
+    }
+
+    fn routine_control(&mut self, request: &[u8], response: &mut [u8]) -> Result<usize, Nrc> {
+        if request.len() < 3 {
+            return Err(Nrc::IncorrectMessageLengthOrInvalidFormat);
+        }
+        let sub_function = request[0] & !SUPPRESS_POSITIVE_RESPONSE;
+        let (id, options) = (&request[1..3], &request[3..]);
+        let routine = self
+            .config
+            .routines
+            .iter()
+            .find(|routine| routine.id.to_be_bytes() == id)
+            .ok_or(Nrc::RequestOutOfRange)?;
+        self.check(&routine.access, Nrc::RequestOutOfRange)?;
+        let handler = match sub_function {
+            routine::START => routine.start,
+            routine::STOP => routine.stop,
+            routine::REQUEST_RESULTS => routine.results,
+            _ => None,
+        }
+        .ok_or(Nrc::SubFunctionNotSupported)?;
+        let len = put(response, &[sub_function, id[0], id[1]])?;
+        Ok(len + handler(options, &mut response[len..])?)
+    }
+
+    fn data_identifier(&self, id: [u8; 2]) -> Option<&'a DataIdentifier<'a>> {
+        let id = u16::from_be_bytes(id);
+        self.config
+            .data_identifiers
+            .iter()
+            .find(|data| data.id == id)
+    }
+
+    // Checks that `access` is granted, failing with `not_in_session` if it is
+    // not granted in the active session.
+    fn check(&self, access: &Access, not_in_session: Nrc) -> Result<(), Nrc> {
+        if !access.sessions.contains(self.session) {
+            return Err(not_in_session);
+        }
+        match access.security_level {
+            Some(level) if self.unlocked != Some(level) => Err(Nrc::SecurityAccessDenied),
+            _ => Ok(()),
+        }
+    }
+
+    fn enter(&mut self, session: Session) {
+        // Changing sessions locks the server again.
+        if session != self.session {
+            self.unlocked = None;
+            self.seed_level = None;
+        }
+        self.session = session;
+    }
+
+    // Without an alarm driver, time stands still: sessions do not end and
+    // security delays do not pass.
+    fn now() -> u32 {
+        Alarm::<S, C>::get_ticks().unwrap_or(0)
+    }
+
+    // Returns whether `time` has passed since the tick count `start`.
+    fn has_passed(start: u32, time: Milliseconds) -> bool {
+        match Alarm::<S, C>::get_frequency() {
+            Ok(frequency) => Self::now().wrapping_sub(start) >= time.to_ticks(frequency).0,
+            Err(_) => false,
+        }
+    }
+}
+
+fn tester_present(request: &[u8], response: &mut [u8]) -> Result<usize, Nrc> {
+    if sub_function(request)? != 0 {
+        return Err(Nrc::SubFunctionNotSupported);
+    }
+    if request.len() != 1 {
+        return Err(Nrc::IncorrectMessageLengthOrInvalidFormat);
+    }
+    put(response, &[0])
+}
+
+fn has_sub_function(sid: u8) -> bool {
+    matches!(
+        sid,
+        sid::DIAGNOSTIC_SESSION_CONTROL
+            | sid::ECU_RESET
+            | sid::SECURITY_ACCESS
+            | sid::ROUTINE_CONTROL
+            | sid::TESTER_PRESENT
+    )
+}
+
+// Returns the sub-function of a request, without the bit that suppresses the
+// positive response.
+fn sub_function(request: &[u8]) -> Result<u8, Nrc> {
+    match request.first() {
+        Some(parameter) => Ok(parameter & !SUPPRESS_POSITIVE_RESPONSE),
+        None => Err(Nrc::IncorrectMessageLengthOrInvalidFormat),
+    }
+}
+
+// Writes `bytes` at the start of `response`, returning their length.
+fn put(response: &mut [u8], bytes: &[u8]) -> Result<usize, Nrc> {
+    response
+        .get_mut(..bytes.len())
+        .ok_or(Nrc::ResponseTooLong)?
+        .copy_from_slice(bytes);
+    Ok(bytes.len())
+}
+
+/// System call configuration trait for `Server`.
+pub trait Config: libtock_can::Config + libtock_key_value::Config {}
+impl<T: libtock_can::Config + libtock_key_value::Config> Config for T {}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Service identifiers and sub-functions
+// -----------------------------------------------------------------------------
+
+const POSITIVE_RESPONSE_OFFSET: u8 = 0x40;
+const NEGATIVE_RESPONSE: u8 = 0x7F;
+const SUPPRESS_POSITIVE_RESPONSE: u8 = 0x80;
+
+mod sid {
+    pub const DIAGNOSTIC_SESSION_CONTROL: u8 = 0x10;
+    pub const ECU_RESET: u8 = 0x11;
+    pub const READ_DATA_BY_IDENTIFIER: u8 = 0x22;
+    pub const SECURITY_ACCESS: u8 = 0x27;
+    pub const WRITE_DATA_BY_IDENTIFIER: u8 = 0x2E;
+    pub const ROUTINE_CONTROL: u8 = 0x31;
+    pub const TESTER_PRESENT: u8 = 0x3E;
+}
+
+mod reset {
+    pub const HARD_RESET: u8 = 0x01;
+    pub const KEY_OFF_ON_RESET: u8 = 0x02;
+    pub const SOFT_RESET: u8 = 0x03;
+}
+
+mod routine {
+    pub const START: u8 = 0x01;
+    pub const STOP: u8 = 0x02;
+    pub const REQUEST_RESULTS: u8 = 0x03;
+}
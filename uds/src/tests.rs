@@ -0,0 +1,500 @@
+use core::cell::Cell;
+use libtock_alarm::Milliseconds;
+use libtock_can::{Id, RxRingBuffer};
+use libtock_isotp::IsoTpConfig;
+use libtock_unittest::fake::{self, CanFrame};
+
+use super::{
+    Access, DataIdentifier, Nrc, Routine, SecurityLevel, ServerConfig, Service, Session, Sessions,
+};
+
+type Alarm = libtock_alarm::Alarm<fake::Syscalls>;
+type IsoTp<'buf, const N: usize> = libtock_isotp::IsoTp<'buf, N, fake::Syscalls>;
+type Server<'a> = super::Server<'a, fake::Syscalls>;
+
+// Runs `test` with fake alarm and key-value drivers.
+fn with_drivers(test: impl FnOnce()) {
+    let kernel = fake::Kernel::new();
+    let alarm = fake::Alarm::new(1000);
+    kernel.add_driver(&alarm);
+    let key_value = fake::KeyValue::new();
+    kernel.add_driver(&key_value);
+    test();
+}
+
+// Handles `request` and returns the response, if any.
+fn request<'r>(
+    server: &mut Server,
+    request: &[u8],
+    response: &'r mut [u8; 64],
+) -> Option<&'r [u8]> {
+    server.handle(request, response).map(|len| &response[..len])
+}
+
+fn negative(sid: u8, nrc: Nrc) -> [u8; 3] {
+    [0x7F, sid, nrc as u8]
+}
+
+// Gives seeds that count up from 0x1001, counting in `seeds`.
+fn next_seed(seeds: &Cell<u8>, seed: &mut [u8]) -> Result<usize, Nrc> {
+    seeds.set(seeds.get() + 1);
+    seed[..2].copy_from_slice(&[0x10, seeds.get()]);
+    Ok(2)
+}
+
+// Level 1 takes the seed's bytes inverted as key, and level 3 has no seeds.
+fn security_levels<'a>(
+    seed: &'a dyn Fn(&mut [u8]) -> Result<usize, Nrc>,
+) -> [SecurityLevel<'a>; 2] {
+    [
+        SecurityLevel {
+            level: 1,
+            sessions: Sessions::NON_DEFAULT,
+            seed,
+            check_key: &|seed, key| seed.iter().zip(key).all(|(s, k)| *s == !*k),
+        },
+        SecurityLevel {
+            level: 3,
+            sessions: Sessions::PROGRAMMING,
+            seed: &|_| Err(Nrc::ConditionsNotCorrect),
+            check_key: &|_, _| true,
+        },
+    ]
+}
+
+#[test]
+fn sessions() {
+    with_drivers(|| {
+        let mut server = Server::new(ServerConfig::new());
+        let mut response = [0; 64];
+        assert_eq!(server.session(), Session::Default);
+
+        assert_eq!(
+            request(&mut server, &[0x10, 0x03], &mut response),
+            Some(&[0x50, 0x03, 0x00, 0x32, 0x01, 0xF4][..])
+        );
+        assert_eq!(server.session(), Session::Extended);
+        assert_eq!(
+            request(&mut server, &[0x3E, 0x00], &mut response),
+            Some(&[0x7E, 0x00][..])
+        );
+        // The positive response can be suppressed, but not negative ones.
+        assert_eq!(request(&mut server, &[0x3E, 0x80], &mut response), None);
+        assert_eq!(request(&mut server, &[0x10, 0x82], &mut response), None);
+        assert_eq!(server.session(), Session::Programming);
+
+        assert_eq!(
+            request(&mut server, &[0x10, 0x04], &mut response),
+            Some(&negative(0x10, Nrc::SubFunctionNotSupported)[..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x10, 0x01, 0x00], &mut response),
+            Some(&negative(0x10, Nrc::IncorrectMessageLengthOrInvalidFormat)[..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x3E, 0x81], &mut response),
+            Some(&negative(0x3E, Nrc::SubFunctionNotSupported)[..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x3E], &mut response),
+            Some(&negative(0x3E, Nrc::IncorrectMessageLengthOrInvalidFormat)[..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x19, 0x02, 0xFF], &mut response),
+            Some(&negative(0x19, Nrc::ServiceNotSupported)[..])
+        );
+        assert_eq!(request(&mut server, &[], &mut response), None);
+    });
+}
+
+#[test]
+fn session_timeout() {
+    with_drivers(|| {
+        let mut server = Server::new(ServerConfig::new());
+        let mut response = [0; 64];
+
+        // TesterPresent keeps the session going, until requests stop for S3.
+        request(&mut server, &[0x10, 0x03], &mut response);
+        Alarm::sleep_for(Milliseconds(4000)).unwrap();
+        request(&mut server, &[0x3E, 0x80], &mut response);
+        Alarm::sleep_for(Milliseconds(4000)).unwrap();
+        request(&mut server, &[0x3E, 0x80], &mut response);
+        assert_eq!(server.session(), Session::Extended);
+        Alarm::sleep_for(Milliseconds(5000)).unwrap();
+        request(&mut server, &[0x3E, 0x80], &mut response);
+        assert_eq!(server.session(), Session::Default);
+    });
+}
+
+#[test]
+fn security_access() {
+    with_drivers(|| {
+        let seeds = Cell::new(0);
+        let seed = |seed: &mut [u8]| next_seed(&seeds, seed);
+        let security_levels = security_levels(&seed);
+        let mut config = ServerConfig::new();
+        config.security_levels = &security_levels;
+        let mut server = Server::new(config);
+        let mut response = [0; 64];
+
+        assert_eq!(
+            request(&mut server, &[0x27, 0x01], &mut response),
+            Some(&negative(0x27, Nrc::ServiceNotSupportedInActiveSession)[..])
+        );
+        request(&mut server, &[0x10, 0x03], &mut response);
+        assert_eq!(
+            request(&mut server, &[0x27, 0x03], &mut response),
+            Some(&negative(0x27, Nrc::SubFunctionNotSupported)[..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x27, 0x05], &mut response),
+            Some(&negative(0x27, Nrc::SubFunctionNotSupportedInActiveSession)[..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x27, 0x02, 0xEF, 0xFE], &mut response),
+            Some(&negative(0x27, Nrc::RequestSequenceError)[..])
+        );
+
+        // A seed may be used for one key only.
+        assert_eq!(
+            request(&mut server, &[0x27, 0x01], &mut response),
+            Some(&[0x67, 0x01, 0x10, 0x01][..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x27, 0x02, 0xEF, 0xFF], &mut response),
+            Some(&negative(0x27, Nrc::InvalidKey)[..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x27, 0x02, 0xEF, 0xFE], &mut response),
+            Some(&negative(0x27, Nrc::RequestSequenceError)[..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x27, 0x01], &mut response),
+            Some(&[0x67, 0x01, 0x10, 0x02][..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x27, 0x02], &mut response),
+            Some(&negative(0x27, Nrc::IncorrectMessageLengthOrInvalidFormat)[..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x27, 0x02, 0xEF, 0xFD], &mut response),
+            Some(&[0x67, 0x02][..])
+        );
+        assert_eq!(server.security_level(), Some(1));
+
+        // An unlocked level's seed is zero, and changing sessions locks it.
+        assert_eq!(
+            request(&mut server, &[0x27, 0x01], &mut response),
+            Some(&[0x67, 0x01, 0x00, 0x00][..])
+        );
+        request(&mut server, &[0x10, 0x03], &mut response);
+        assert_eq!(server.security_level(), Some(1));
+        request(&mut server, &[0x10, 0x02], &mut response);
+        assert_eq!(server.security_level(), None);
+
+        // Errors from the seed function are reported.
+        assert_eq!(
+            request(&mut server, &[0x27, 0x05], &mut response),
+            Some(&negative(0x27, Nrc::ConditionsNotCorrect)[..])
+        );
+    });
+}
+
+#[test]
+fn security_delay() {
+    with_drivers(|| {
+        let seeds = Cell::new(0);
+        let seed = |seed: &mut [u8]| next_seed(&seeds, seed);
+        let security_levels = security_levels(&seed);
+        let mut config = ServerConfig::new();
+        config.security_levels = &security_levels;
+        config.max_attempts = 2;
+        config.s3 = Milliseconds(60000);
+        let mut server = Server::new(config);
+        let mut response = [0; 64];
+
+        request(&mut server, &[0x10, 0x03], &mut response);
+        request(&mut server, &[0x27, 0x01], &mut response);
+        assert_eq!(
+            request(&mut server, &[0x27, 0x02, 0x00, 0x00], &mut response),
+            Some(&negative(0x27, Nrc::InvalidKey)[..])
+        );
+        request(&mut server, &[0x27, 0x01], &mut response);
+        assert_eq!(
+            request(&mut server, &[0x27, 0x02, 0x00, 0x00], &mut response),
+            Some(&negative(0x27, Nrc::ExceededNumberOfAttempts)[..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x27, 0x01], &mut response),
+            Some(&negative(0x27, Nrc::RequiredTimeDelayNotExpired)[..])
+        );
+
+        Alarm::sleep_for(Milliseconds(10000)).unwrap();
+        assert_eq!(
+            request(&mut server, &[0x27, 0x01], &mut response),
+            Some(&[0x67, 0x01, 0x10, 0x03][..])
+        );
+    });
+}
+
+#[test]
+fn data_identifiers() {
+    with_drivers(|| {
+        let seeds = Cell::new(0);
+        let seed = |seed: &mut [u8]| next_seed(&seeds, seed);
+        let security_levels = security_levels(&seed);
+        let data_identifiers = [
+            DataIdentifier {
+                id: 0xF190,
+                key: b"vin",
+                len: 4,
+                read: Access::ALWAYS,
+                write: Access::new(Sessions::EXTENDED),
+            },
+            DataIdentifier {
+                id: 0xF18C,
+                key: b"serial",
+                len: 2,
+                read: Access::new(Sessions::NON_DEFAULT),
+                write: Access::new(Sessions::EXTENDED).secured(1),
+            },
+        ];
+        let mut config = ServerConfig::new();
+        config.data_identifiers = &data_identifiers;
+        config.security_levels = &security_levels;
+        let mut server = Server::new(config);
+        let mut response = [0; 64];
+
+        assert_eq!(
+            request(&mut server, &[0x2E, 0xF1, 0x90, 1, 2, 3, 4], &mut response),
+            Some(&negative(0x2E, Nrc::RequestOutOfRange)[..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x22, 0xF1, 0x90], &mut response),
+            Some(&negative(0x22, Nrc::ConditionsNotCorrect)[..])
+        );
+
+        request(&mut server, &[0x10, 0x03], &mut response);
+        assert_eq!(
+            request(&mut server, &[0x2E, 0xF1, 0x90, 1, 2, 3], &mut response),
+            Some(&negative(0x2E, Nrc::IncorrectMessageLengthOrInvalidFormat)[..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x2E, 0xF1, 0x90, 1, 2, 3, 4], &mut response),
+            Some(&[0x6E, 0xF1, 0x90][..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x2E, 0xF1, 0x8C, 5, 6], &mut response),
+            Some(&negative(0x2E, Nrc::SecurityAccessDenied)[..])
+        );
+        request(&mut server, &[0x27, 0x01], &mut response);
+        request(&mut server, &[0x27, 0x02, 0xEF, 0xFE], &mut response);
+        assert_eq!(
+            request(&mut server, &[0x2E, 0xF1, 0x8C, 5, 6], &mut response),
+            Some(&[0x6E, 0xF1, 0x8C][..])
+        );
+
+        // Several identifiers can be read at once, and unknown ones are left
+        // out unless none are known.
+        assert_eq!(
+            request(
+                &mut server,
+                &[0x22, 0xF1, 0x90, 0x12, 0x34, 0xF1, 0x8C],
+                &mut response
+            ),
+            Some(&[0x62, 0xF1, 0x90, 1, 2, 3, 4, 0xF1, 0x8C, 5, 6][..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x22, 0x12, 0x34], &mut response),
+            Some(&negative(0x22, Nrc::RequestOutOfRange)[..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x22, 0xF1], &mut response),
+            Some(&negative(0x22, Nrc::IncorrectMessageLengthOrInvalidFormat)[..])
+        );
+        let mut short = [0; 8];
+        assert_eq!(
+            server.handle(&[0x22, 0xF1, 0x90, 0xF1, 0x8C], &mut short),
+            Some(3)
+        );
+        assert_eq!(short[..3], negative(0x22, Nrc::ResponseTooLong));
+
+        request(&mut server, &[0x10, 0x01], &mut response);
+        assert_eq!(
+            request(&mut server, &[0x22, 0xF1, 0x8C], &mut response),
+            Some(&negative(0x22, Nrc::RequestOutOfRange)[..])
+        );
+    });
+}
+
+#[test]
+fn routines() {
+    with_drivers(|| {
+        let running = Cell::new(false);
+        let routines = [
+            Routine {
+                id: 0xFF00,
+                access: Access::ALWAYS,
+                start: Some(&|options, status| {
+                    running.set(true);
+                    status[..options.len()].copy_from_slice(options);
+                    Ok(options.len())
+                }),
+                stop: None,
+                results: Some(&|_, status| match running.get() {
+                    true => {
+                        status[0] = 0x42;
+                        Ok(1)
+                    }
+                    false => Err(Nrc::RequestSequenceError),
+                }),
+            },
+            Routine {
+                id: 0xFF01,
+                access: Access::new(Sessions::PROGRAMMING),
+                start: Some(&|_, _| Ok(0)),
+                stop: None,
+                results: None,
+            },
+        ];
+        let mut config = ServerConfig::new();
+        config.routines = &routines;
+        let mut server = Server::new(config);
+        let mut response = [0; 64];
+
+        assert_eq!(
+            request(&mut server, &[0x31, 0x03, 0xFF, 0x00], &mut response),
+            Some(&negative(0x31, Nrc::RequestSequenceError)[..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x31, 0x01, 0xFF, 0x00, 7, 8], &mut response),
+            Some(&[0x71, 0x01, 0xFF, 0x00, 7, 8][..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x31, 0x03, 0xFF, 0x00], &mut response),
+            Some(&[0x71, 0x03, 0xFF, 0x00, 0x42][..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x31, 0x02, 0xFF, 0x00], &mut response),
+            Some(&negative(0x31, Nrc::SubFunctionNotSupported)[..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x31, 0x01, 0xFF, 0x01], &mut response),
+            Some(&negative(0x31, Nrc::RequestOutOfRange)[..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x31, 0x01, 0xFF, 0x02], &mut response),
+            Some(&negative(0x31, Nrc::RequestOutOfRange)[..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x31, 0x01, 0xFF], &mut response),
+            Some(&negative(0x31, Nrc::IncorrectMessageLengthOrInvalidFormat)[..])
+        );
+        request(&mut server, &[0x10, 0x02], &mut response);
+        assert_eq!(
+            request(&mut server, &[0x31, 0x81, 0xFF, 0x01], &mut response),
+            None
+        );
+    });
+}
+
+#[test]
+fn services() {
+    with_drivers(|| {
+        let services = [Service {
+            sid: 0x85,
+            access: Access::new(Sessions::EXTENDED),
+            handler: &|request, response| match request {
+                [setting @ (0x01 | 0x02)] => {
+                    response[0] = *setting;
+                    Ok(1)
+                }
+                _ => Err(Nrc::SubFunctionNotSupported),
+            },
+        }];
+        let mut config = ServerConfig::new();
+        config.services = &services;
+        let mut server = Server::new(config);
+        let mut response = [0; 64];
+
+        assert_eq!(
+            request(&mut server, &[0x85, 0x02], &mut response),
+            Some(&negative(0x85, Nrc::ServiceNotSupportedInActiveSession)[..])
+        );
+        request(&mut server, &[0x10, 0x03], &mut response);
+        assert_eq!(
+            request(&mut server, &[0x85, 0x02], &mut response),
+            Some(&[0xC5, 0x02][..])
+        );
+        assert_eq!(
+            request(&mut server, &[0x85, 0x03], &mut response),
+            Some(&negative(0x85, Nrc::SubFunctionNotSupported)[..])
+        );
+    });
+}
+
+#[test]
+fn ecu_reset() {
+    with_drivers(|| {
+        let mut server = Server::new(ServerConfig::new());
+        let mut response = [0; 64];
+        assert_eq!(
+            request(&mut server, &[0x11, 0x04], &mut response),
+            Some(&negative(0x11, Nrc::SubFunctionNotSupported)[..])
+        );
+        // Only a reset that was requested restarts the process.
+        server.restart_if_requested();
+    });
+
+    #[cfg(not(miri))]
+    {
+        let exit = libtock_unittest::exit_test("tests::ecu_reset", || {
+            with_drivers(|| {
+                let mut server = Server::new(ServerConfig::new());
+                let mut response = [0; 64];
+                assert_eq!(
+                    request(&mut server, &[0x11, 0x03], &mut response),
+                    Some(&[0x51, 0x03][..])
+                );
+                server.restart_if_requested();
+            })
+        });
+        assert_eq!(exit, libtock_unittest::ExitCall::Restart(3));
+    }
+}
+
+#[test]
+fn serve() {
+    let kernel = fake::Kernel::new();
+    let alarm = fake::Alarm::new(1000);
+    kernel.add_driver(&alarm);
+    let can = fake::Can::new();
+    kernel.add_driver(&can);
+    libtock_can::Can::<fake::Syscalls>::enable().unwrap();
+
+    let mut frames = RxRingBuffer::<4>::new();
+    let mut isotp = IsoTp::new(
+        IsoTpConfig::new(Id::Standard(0x7E8), Id::Standard(0x7E0)),
+        &mut frames,
+    );
+    let mut server = Server::new(ServerConfig::new());
+    let (mut request, mut response) = ([0; 64], [0; 64]);
+
+    can.receive_frame(CanFrame::standard(0x7E0, &[0x02, 0x10, 0x03]));
+    assert_eq!(
+        server.serve(&mut isotp, &mut request, &mut response),
+        Ok(())
+    );
+    can.receive_frame(CanFrame::standard(0x7E0, &[0x02, 0x3E, 0x80]));
+    assert_eq!(
+        server.serve(&mut isotp, &mut request, &mut response),
+        Ok(())
+    );
+    assert_eq!(
+        can.take_transmitted_frames(),
+        [CanFrame::standard(
+            0x7E8,
+            &[0x06, 0x50, 0x03, 0x00, 0x32, 0x01, 0xF4]
+        )]
+    );
+}
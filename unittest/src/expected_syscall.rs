@@ -21,6 +21,17 @@ pub enum ExpectedSyscall {
         skip_upcall: bool,
     },
 
+    YieldWaitFor {
+        // Matched values: yield-wait-for must be called with the specified
+        // driver_num and subscribe_num.
+        driver_num: u32,
+        subscribe_num: u32,
+
+        /// If not `None`, yield-wait-for will return the specified values
+        /// instead of consuming a queued upcall.
+        override_return: Option<(u32, u32, u32)>,
+    },
+
     // -------------------------------------------------------------------------
     // Subscribe
     // -------------------------------------------------------------------------
@@ -0,0 +1,312 @@
+//! Fake implementation of the AES API.
+//!
+//! `Aes` performs real AES-128 ECB, CBC, CTR and CCM operations on the host, so
+//! tests can compare against known vectors. `crypts` counts the buffers the
+//! process has encrypted or decrypted.
+
+use aes::cipher::generic_array::{ArrayLength, GenericArray};
+use aes::cipher::{
+    BlockDecrypt, BlockDecryptMut, BlockEncrypt, BlockEncryptMut, KeyInit, KeyIvInit, StreamCipher,
+};
+use ccm::aead::AeadInPlace;
+use ccm::consts::{U10, U12, U13, U14, U16, U4, U6, U8};
+use core::cell::{Cell, RefCell};
+use libtock_platform::{CommandReturn, ErrorCode};
+
+use crate::{command_return, DriverInfo, DriverShareRef, RoAllowBuffer, RwAllowBuffer};
+
+pub struct Aes {
+    // The selected algorithm, and whether it encrypts.
+    algorithm: Cell<Option<(u32, bool)>>,
+    aad_len: Cell<usize>,
+    mic_len: Cell<usize>,
+    // The operation in progress, set up by the SETUP command.
+    cipher: RefCell<Option<Cipher>>,
+    crypts: Cell<usize>,
+
+    key_buffer: RefCell<RoAllowBuffer>,
+    iv_buffer: RefCell<RoAllowBuffer>,
+    data_buffer: RefCell<RwAllowBuffer>,
+
+    share_ref: DriverShareRef,
+}
+
+enum Cipher {
+    EcbEncrypt(aes::Aes128),
+    EcbDecrypt(aes::Aes128),
+    CbcEncrypt(cbc::Encryptor<aes::Aes128>),
+    CbcDecrypt(cbc::Decryptor<aes::Aes128>),
+    Ctr(ctr::Ctr128BE<aes::Aes128>),
+    Ccm {
+        key: [u8; KEY_LEN],
+        nonce: [u8; CCM_NONCE_LEN],
+        encrypting: bool,
+    },
+}
+
+impl Cipher {
+    fn new(algorithm: u32, encrypting: bool, key: &[u8], iv: &[u8]) -> Result<Cipher, ErrorCode> {
+        let key: &[u8; KEY_LEN] = key.try_into().map_err(|_| ErrorCode::Size)?;
+        let block_iv = || -> Result<&[u8; BLOCK_LEN], ErrorCode> {
+            iv.try_into().map_err(|_| ErrorCode::Size)
+        };
+        Ok(match (algorithm, encrypting) {
+            (ECB, true) => Cipher::EcbEncrypt(aes::Aes128::new(key.into())),
+            (ECB, false) => Cipher::EcbDecrypt(aes::Aes128::new(key.into())),
+            (CBC, true) => Cipher::CbcEncrypt(cbc::Encryptor::new(key.into(), block_iv()?.into())),
+            (CBC, false) => Cipher::CbcDecrypt(cbc::Decryptor::new(key.into(), block_iv()?.into())),
+            (CTR, _) => Cipher::Ctr(ctr::Ctr128BE::new(key.into(), block_iv()?.into())),
+            _ => Cipher::Ccm {
+                key: *key,
+                nonce: iv.try_into().map_err(|_| ErrorCode::Size)?,
+                encrypting,
+            },
+        })
+    }
+
+    // Processes `data` in place, returning whether the CCM MIC matched.
+    fn crypt(
+        &mut self,
+        data: &mut [u8],
+        aad_len: usize,
+        mic_len: usize,
+    ) -> Result<bool, ErrorCode> {
+        match self {
+            Cipher::EcbEncrypt(cipher) => blocks(data)?.for_each(|b| cipher.encrypt_block(b)),
+            Cipher::EcbDecrypt(cipher) => blocks(data)?.for_each(|b| cipher.decrypt_block(b)),
+            Cipher::CbcEncrypt(cipher) => blocks(data)?.for_each(|b| cipher.encrypt_block_mut(b)),
+            Cipher::CbcDecrypt(cipher) => blocks(data)?.for_each(|b| cipher.decrypt_block_mut(b)),
+            Cipher::Ctr(cipher) => blocks(data)?.for_each(|b| cipher.apply_keystream(b)),
+            Cipher::Ccm {
+                key,
+                nonce,
+                encrypting,
+            } => {
+                if aad_len + mic_len > data.len() {
+                    return Err(ErrorCode::Size);
+                }
+                return Ok(match mic_len {
+                    4 => ccm::<U4>(key, nonce, *encrypting, aad_len, data),
+                    6 => ccm::<U6>(key, nonce, *encrypting, aad_len, data),
+                    8 => ccm::<U8>(key, nonce, *encrypting, aad_len, data),
+                    10 => ccm::<U10>(key, nonce, *encrypting, aad_len, data),
+                    12 => ccm::<U12>(key, nonce, *encrypting, aad_len, data),
+                    14 => ccm::<U14>(key, nonce, *encrypting, aad_len, data),
+                    _ => ccm::<U16>(key, nonce, *encrypting, aad_len, data),
+                });
+            }
+        }
+        Ok(true)
+    }
+}
+
+// Splits `data` into blocks, which all modes but CCM require it to be made of,
+// as the kernel does.
+fn blocks(data: &mut [u8]) -> Result<impl Iterator<Item = &mut aes::Block>, ErrorCode> {
+    match data.len() % BLOCK_LEN {
+        0 => Ok(data.chunks_mut(BLOCK_LEN).map(GenericArray::from_mut_slice)),
+        _ => Err(ErrorCode::Size),
+    }
+}
+
+// Runs CCM with a MIC of `M` bytes over `data`, laid out as additional data,
+// message, MIC. Returns whether the MIC matched.
+fn ccm<M: ArrayLength<u8> + ccm::TagSize>(
+    key: &[u8; KEY_LEN],
+    nonce: &[u8; CCM_NONCE_LEN],
+    encrypting: bool,
+    aad_len: usize,
+    data: &mut [u8],
+) -> bool {
+    let cipher = ccm::Ccm::<aes::Aes128, M, U13>::new(key.into());
+    let (aad, rest) = data.split_at_mut(aad_len);
+    let (message, mic) = rest.split_at_mut(rest.len() - M::to_usize());
+    if encrypting {
+        let tag = cipher
+            .encrypt_in_place_detached(nonce.into(), aad, message)
+            .expect("CCM message too long");
+        mic.copy_from_slice(&tag);
+        true
+    } else {
+        cipher
+            .decrypt_in_place_detached(nonce.into(), aad, message, GenericArray::from_slice(mic))
+            .is_ok()
+    }
+}
+
+impl Aes {
+    pub fn new() -> std::rc::Rc<Aes> {
+        std::rc::Rc::new(Aes {
+            algorithm: Cell::new(None),
+            aad_len: Cell::new(0),
+            mic_len: Cell::new(16),
+            cipher: RefCell::new(None),
+            crypts: Cell::new(0),
+            key_buffer: Default::default(),
+            iv_buffer: Default::default(),
+            data_buffer: Default::default(),
+            share_ref: Default::default(),
+        })
+    }
+
+    /// Returns the algorithm selected by the process, if any.
+    pub fn algorithm(&self) -> Option<u32> {
+        self.algorithm.get().map(|(algorithm, _)| algorithm)
+    }
+
+    /// Returns whether an operation is in progress.
+    pub fn is_busy(&self) -> bool {
+        self.cipher.borrow().is_some()
+    }
+
+    /// Returns the number of buffers encrypted or decrypted.
+    pub fn crypts(&self) -> usize {
+        self.crypts.get()
+    }
+
+    fn crypt(&self) -> Result<bool, ErrorCode> {
+        let mut cipher = self.cipher.borrow_mut();
+        let cipher = cipher.as_mut().ok_or(ErrorCode::Reserve)?;
+        let authentic = cipher.crypt(
+            &mut self.data_buffer.borrow_mut(),
+            self.aad_len.get(),
+            self.mic_len.get(),
+        )?;
+        self.crypts.set(self.crypts.get() + 1);
+        Ok(authentic)
+    }
+}
+
+impl crate::fake::SyscallDriver for Aes {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(1)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn command(&self, command_number: u32, argument0: u32, argument1: u32) -> CommandReturn {
+        match command_number {
+            command::SET_ALGORITHM => {
+                if argument0 > CCM {
+                    return command_return::failure(ErrorCode::NoSupport);
+                }
+                self.algorithm.set(Some((argument0, argument1 != 0)));
+                self.cipher.replace(None);
+                command_return::success()
+            }
+            command::SETUP => {
+                let Some((algorithm, encrypting)) = self.algorithm.get() else {
+                    return command_return::failure(ErrorCode::Reserve);
+                };
+                let cipher = Cipher::new(
+                    algorithm,
+                    encrypting,
+                    &self.key_buffer.borrow(),
+                    &self.iv_buffer.borrow(),
+                );
+                match cipher {
+                    Ok(cipher) => {
+                        self.cipher.replace(Some(cipher));
+                        command_return::success()
+                    }
+                    Err(error) => command_return::failure(error),
+                }
+            }
+            command::CRYPT => {
+                if !self.is_busy() {
+                    return command_return::failure(ErrorCode::Reserve);
+                }
+                let upcall = match self.crypt() {
+                    Ok(authentic) => (0, authentic as u32, 0),
+                    Err(error) => (error as u32, 0, 0),
+                };
+                self.share_ref
+                    .schedule_upcall(subscribe::DONE, upcall)
+                    .expect("Unable to schedule upcall {}");
+                command_return::success()
+            }
+            command::FINISH => match self.cipher.take() {
+                Some(_) => command_return::success(),
+                None => command_return::failure(ErrorCode::Already),
+            },
+            command::CCM_SET_AAD_LEN => {
+                self.aad_len.set(argument0 as usize);
+                command_return::success()
+            }
+            command::CCM_SET_MIC_LEN => match argument0 {
+                4 | 6 | 8 | 10 | 12 | 14 | 16 => {
+                    self.mic_len.set(argument0 as usize);
+                    command_return::success()
+                }
+                _ => command_return::failure(ErrorCode::Invalid),
+            },
+            _ => command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+
+    fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            allow_ro::KEY => Ok(self.key_buffer.replace(buffer)),
+            allow_ro::IV => Ok(self.iv_buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            allow_rw::DATA => Ok(self.data_buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x40006;
+
+const KEY_LEN: usize = 16;
+const BLOCK_LEN: usize = 16;
+const CCM_NONCE_LEN: usize = 13;
+
+// Algorithms
+const CTR: u32 = 0;
+const CBC: u32 = 1;
+const ECB: u32 = 2;
+const CCM: u32 = 3;
+
+mod command {
+    pub const SET_ALGORITHM: u32 = 1;
+    pub const SETUP: u32 = 2;
+    pub const CRYPT: u32 = 3;
+    pub const FINISH: u32 = 4;
+    pub const CCM_SET_AAD_LEN: u32 = 5;
+    pub const CCM_SET_MIC_LEN: u32 = 6;
+}
+
+mod subscribe {
+    pub const DONE: u32 = 0;
+}
+
+mod allow_ro {
+    pub const KEY: u32 = 0;
+    pub const IV: u32 = 1;
+}
+
+mod allow_rw {
+    pub const DATA: u32 = 0;
+}
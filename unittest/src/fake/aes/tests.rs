@@ -0,0 +1,108 @@
+use crate::fake::{self, aes::*};
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::{share, DefaultConfig, Syscalls};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let aes = Aes::new();
+    assert_eq!(
+        aes.command(command::SETUP, 0, 0).get_failure(),
+        Some(ErrorCode::Reserve)
+    );
+    assert_eq!(
+        aes.command(command::SET_ALGORITHM, 4, 1).get_failure(),
+        Some(ErrorCode::NoSupport)
+    );
+    assert_eq!(aes.algorithm(), None);
+    assert!(aes.command(command::SET_ALGORITHM, ECB, 1).is_success());
+    assert_eq!(aes.algorithm(), Some(ECB));
+
+    // The key must be 16 bytes long.
+    assert_eq!(
+        aes.command(command::SETUP, 0, 0).get_failure(),
+        Some(ErrorCode::Size)
+    );
+    assert!(!aes.is_busy());
+    assert_eq!(
+        aes.command(command::CRYPT, 0, 0).get_failure(),
+        Some(ErrorCode::Reserve)
+    );
+    assert_eq!(
+        aes.command(command::FINISH, 0, 0).get_failure(),
+        Some(ErrorCode::Already)
+    );
+
+    assert!(aes.command(command::CCM_SET_AAD_LEN, 8, 0).is_success());
+    assert!(aes.command(command::CCM_SET_MIC_LEN, 8, 0).is_success());
+    assert_eq!(
+        aes.command(command::CCM_SET_MIC_LEN, 5, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+
+    assert!(aes
+        .allow_readonly(allow_ro::KEY, RoAllowBuffer::default())
+        .is_ok());
+    assert!(aes
+        .allow_readonly(allow_ro::IV, RoAllowBuffer::default())
+        .is_ok());
+    assert!(aes.allow_readonly(2, RoAllowBuffer::default()).is_err());
+    assert!(aes
+        .allow_readwrite(allow_rw::DATA, RwAllowBuffer::default())
+        .is_ok());
+    assert!(aes.allow_readwrite(1, RwAllowBuffer::default()).is_err());
+}
+
+// Integration test that verifies Aes works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let aes = Aes::new();
+    kernel.add_driver(&aes);
+
+    // AES-128 test vector from FIPS 197, appendix C.1.
+    let key = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+        0x0f,
+    ];
+    let mut block = [
+        0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee,
+        0xff,
+    ];
+    assert!(fake::Syscalls::command(DRIVER_NUM, command::SET_ALGORITHM, ECB, 1).is_success());
+    share::scope::<AllowRo<_, DRIVER_NUM, { allow_ro::KEY }>, _, _>(|allow| {
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, { allow_ro::KEY }>(allow, &key)
+            .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, command::SETUP, 0, 0).is_success());
+    });
+    assert!(aes.is_busy());
+
+    // ECB only works on whole blocks.
+    for (len, upcall) in [(15, (ErrorCode::Size as u32, 0, 0)), (16, (0, 1, 0))] {
+        share::scope::<AllowRw<_, DRIVER_NUM, { allow_rw::DATA }>, _, _>(|allow| {
+            fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, { allow_rw::DATA }>(
+                allow,
+                &mut block[..len],
+            )
+            .unwrap();
+            assert!(fake::Syscalls::command(DRIVER_NUM, command::CRYPT, 0, 0).is_success());
+        });
+        assert_eq!(
+            fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::DONE),
+            upcall
+        );
+    }
+    assert_eq!(
+        block,
+        [
+            0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4,
+            0xc5, 0x5a
+        ]
+    );
+    assert_eq!(aes.crypts(), 1);
+    assert!(fake::Syscalls::command(DRIVER_NUM, command::FINISH, 0, 0).is_success());
+    assert!(!aes.is_busy());
+}
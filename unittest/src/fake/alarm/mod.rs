@@ -1,7 +1,7 @@
 //! Fake implementation of the Alarm API.
 //!
-//! Supports frequency and set_relative.
-//! Will schedule the upcall immediately.
+//! Supports frequency, time, set_relative and stop.
+//! Will schedule the upcall immediately, so stop has no effect.
 
 use core::cell::Cell;
 use core::num::Wrapping;
@@ -37,6 +37,7 @@ impl crate::fake::SyscallDriver for Alarm {
     fn command(&self, command_number: u32, argument0: u32, _argument1: u32) -> CommandReturn {
         match command_number {
             command::FREQUENCY => crate::command_return::success_u32(self.frequency_hz),
+            command::TIME => crate::command_return::success_u32(self.now.get().0),
             command::SET_RELATIVE => {
                 // We're not actually sleeping, just ticking the timer.
                 // The semantics of sleeping aren't clear,
@@ -50,6 +51,7 @@ impl crate::fake::SyscallDriver for Alarm {
                 self.now.set(wake);
                 crate::command_return::success_u32(wake.0)
             }
+            command::STOP => crate::command_return::success(),
             _ => crate::command_return::failure(ErrorCode::NoSupport),
         }
     }
@@ -11,4 +11,18 @@ fn command() {
         alarm.command(command::FREQUENCY, 1, 2).get_success_u32(),
         Some(10)
     );
+    assert_eq!(
+        alarm.command(command::TIME, 0, 0).get_success_u32(),
+        Some(0)
+    );
+    assert_eq!(
+        alarm
+            .command(command::SET_RELATIVE, 25, 0)
+            .get_success_u32(),
+        Some(25)
+    );
+    assert_eq!(
+        alarm.command(command::TIME, 0, 0).get_success_u32(),
+        Some(25)
+    );
 }
@@ -0,0 +1,214 @@
+//! Fake implementation of the BLE advertising API.
+//!
+//! `Ble` records the advertisement the process sends (`advertisement`) and
+//! the configured transmit power. Scan results queued with
+//! `inject_scan_result` are delivered while the process is scanning and has
+//! shared its scan buffer.
+
+use core::cell::{Cell, RefCell};
+use libtock_platform::{CommandReturn, ErrorCode};
+use std::collections::VecDeque;
+
+use crate::{command_return, DriverInfo, DriverShareRef, RoAllowBuffer, RwAllowBuffer};
+
+/// The advertisement the process is sending.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Advertisement {
+    pub pdu_type: u8,
+    pub interval_ms: u32,
+    pub data: Vec<u8>,
+}
+
+pub struct Ble {
+    tx_power: Cell<i8>,
+    advertising: Cell<Option<(u8, u32)>>,
+    scanning: Cell<bool>,
+
+    adv_buf: RefCell<RoAllowBuffer>,
+    scan_buf: RefCell<RwAllowBuffer>,
+
+    scan_results: RefCell<VecDeque<Vec<u8>>>,
+
+    share_ref: DriverShareRef,
+}
+
+impl Ble {
+    pub fn new() -> std::rc::Rc<Ble> {
+        std::rc::Rc::new(Ble {
+            tx_power: Default::default(),
+            advertising: Default::default(),
+            scanning: Default::default(),
+            adv_buf: Default::default(),
+            scan_buf: Default::default(),
+            scan_results: Default::default(),
+            share_ref: Default::default(),
+        })
+    }
+
+    /// Returns the advertisement being sent, or `None` if the process is not
+    /// advertising. Like the real driver, the data is read from the buffer the
+    /// process currently shares.
+    pub fn advertisement(&self) -> Option<Advertisement> {
+        self.advertising
+            .get()
+            .map(|(pdu_type, interval_ms)| Advertisement {
+                pdu_type,
+                interval_ms,
+                data: Vec::from(&**self.adv_buf.borrow()),
+            })
+    }
+
+    pub fn tx_power(&self) -> i8 {
+        self.tx_power.get()
+    }
+
+    pub fn is_scanning(&self) -> bool {
+        self.scanning.get()
+    }
+
+    /// Queues an advertisement from another device, to be received by the
+    /// process. `address` is in transmission order (least significant byte
+    /// first).
+    pub fn inject_scan_result(&self, pdu_type: u8, address: [u8; 6], data: &[u8]) {
+        let mut pdu = vec![pdu_type, (address.len() + data.len()) as u8];
+        pdu.extend_from_slice(&address);
+        pdu.extend_from_slice(data);
+        self.scan_results.borrow_mut().push_back(pdu);
+        self.deliver();
+    }
+
+    /// Returns the number of queued scan results that have not been delivered.
+    pub fn pending_scan_results(&self) -> usize {
+        self.scan_results.borrow().len()
+    }
+
+    // Delivers the next queued scan result if the process is scanning and has
+    // shared its scan buffer.
+    fn deliver(&self) {
+        let mut scan_buf = self.scan_buf.borrow_mut();
+        if !self.scanning.get() || scan_buf.is_empty() {
+            return;
+        }
+        if let Some(pdu) = self.scan_results.borrow_mut().pop_front() {
+            let len = pdu.len().min(scan_buf.len());
+            scan_buf[..len].copy_from_slice(&pdu[..len]);
+            self.share_ref
+                .schedule_upcall(subscribe::SCAN, (0, len as u32, 0))
+                .expect("Unable to schedule upcall {}");
+        }
+    }
+}
+
+impl crate::fake::SyscallDriver for Ble {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(1)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn command(&self, command_number: u32, argument0: u32, argument1: u32) -> CommandReturn {
+        match command_number {
+            command::EXISTS => command_return::success(),
+            command::START_ADVERTISING => {
+                if self.advertising.get().is_some() || self.scanning.get() {
+                    return command_return::failure(ErrorCode::Busy);
+                }
+                if !matches!(argument0, 0x00 | 0x02 | 0x06)
+                    || !(MIN_INTERVAL_MS..=MAX_INTERVAL_MS).contains(&argument1)
+                {
+                    return command_return::failure(ErrorCode::Invalid);
+                }
+                if self.adv_buf.borrow().len() > MAX_ADV_DATA_LEN {
+                    return command_return::failure(ErrorCode::Size);
+                }
+                self.advertising.set(Some((argument0 as u8, argument1)));
+                command_return::success()
+            }
+            command::STOP => {
+                if self.advertising.take().is_none() && !self.scanning.replace(false) {
+                    return command_return::failure(ErrorCode::Already);
+                }
+                command_return::success()
+            }
+            command::SET_TX_POWER => match argument0 as i32 {
+                dbm @ MIN_TX_POWER..=MAX_TX_POWER => {
+                    self.tx_power.set(dbm as i8);
+                    command_return::success()
+                }
+                _ => command_return::failure(ErrorCode::Invalid),
+            },
+            command::PASSIVE_SCAN => {
+                if self.advertising.get().is_some() || self.scanning.get() {
+                    return command_return::failure(ErrorCode::Busy);
+                }
+                self.scanning.set(true);
+                self.deliver();
+                command_return::success()
+            }
+            _ => command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+
+    fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            allow_ro::ADV_DATA => Ok(self.adv_buf.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            allow_rw::SCAN_BUFFER => {
+                let old = self.scan_buf.replace(buffer);
+                self.deliver();
+                Ok(old)
+            }
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests;
+
+const MAX_ADV_DATA_LEN: usize = 31;
+const MIN_INTERVAL_MS: u32 = 20;
+const MAX_INTERVAL_MS: u32 = 10240;
+const MIN_TX_POWER: i32 = -40;
+const MAX_TX_POWER: i32 = 4;
+
+const DRIVER_NUM: u32 = 0x30000;
+
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const START_ADVERTISING: u32 = 1;
+    pub const STOP: u32 = 2;
+    pub const SET_TX_POWER: u32 = 3;
+    pub const PASSIVE_SCAN: u32 = 5;
+}
+
+mod subscribe {
+    pub const SCAN: u32 = 0;
+}
+
+mod allow_ro {
+    pub const ADV_DATA: u32 = 0;
+}
+
+mod allow_rw {
+    pub const SCAN_BUFFER: u32 = 0;
+}
@@ -0,0 +1,95 @@
+use crate::fake::{self, ble::*};
+use libtock_platform::{share, DefaultConfig, Syscalls};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let ble = Ble::new();
+    assert!(ble.command(command::EXISTS, 0, 0).is_success());
+
+    assert!(ble
+        .command(command::SET_TX_POWER, -4i32 as u32, 0)
+        .is_success());
+    assert_eq!(ble.tx_power(), -4);
+    assert_eq!(
+        ble.command(command::SET_TX_POWER, 10, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+
+    assert_eq!(
+        ble.command(command::START_ADVERTISING, 0x02, 5)
+            .get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert_eq!(
+        ble.command(command::START_ADVERTISING, 0x01, 100)
+            .get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert!(ble
+        .command(command::START_ADVERTISING, 0x02, 100)
+        .is_success());
+    assert_eq!(
+        ble.advertisement(),
+        Some(Advertisement {
+            pdu_type: 0x02,
+            interval_ms: 100,
+            data: vec![],
+        })
+    );
+    assert_eq!(
+        ble.command(command::PASSIVE_SCAN, 0, 0).get_failure(),
+        Some(ErrorCode::Busy)
+    );
+    assert!(ble.command(command::STOP, 0, 0).is_success());
+    assert_eq!(ble.advertisement(), None);
+    assert_eq!(
+        ble.command(command::STOP, 0, 0).get_failure(),
+        Some(ErrorCode::Already)
+    );
+
+    assert!(ble.command(command::PASSIVE_SCAN, 0, 0).is_success());
+    assert!(ble.is_scanning());
+    assert!(ble.command(command::STOP, 0, 0).is_success());
+    assert!(!ble.is_scanning());
+}
+
+// Integration test that verifies Ble works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let ble = Ble::new();
+    kernel.add_driver(&ble);
+
+    share::scope(|allow_ro| {
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, { allow_ro::ADV_DATA }>(
+            allow_ro,
+            &[2, 0x01, 0x06],
+        )
+        .unwrap();
+        assert!(
+            fake::Syscalls::command(DRIVER_NUM, command::START_ADVERTISING, 0x00, 200).is_success()
+        );
+        assert_eq!(ble.advertisement().unwrap().data, [2, 0x01, 0x06]);
+        assert!(fake::Syscalls::command(DRIVER_NUM, command::STOP, 0, 0).is_success());
+    });
+
+    ble.inject_scan_result(0x02, [1, 2, 3, 4, 5, 6], &[2, 0x01, 0x06]);
+    assert_eq!(ble.pending_scan_results(), 1);
+    let mut buf = [0; 16];
+    share::scope(|allow_rw| {
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, { allow_rw::SCAN_BUFFER }>(
+            allow_rw, &mut buf,
+        )
+        .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, command::PASSIVE_SCAN, 0, 0).is_success());
+        assert_eq!(
+            fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::SCAN),
+            (0, 11, 0)
+        );
+    });
+    assert_eq!(ble.pending_scan_results(), 0);
+    assert_eq!(&buf[..11], &[0x02, 9, 1, 2, 3, 4, 5, 6, 2, 0x01, 0x06]);
+}
@@ -0,0 +1,435 @@
+//! Fake implementation of the CAN API.
+//!
+//! `Can` records the frames the process sends (`take_transmitted_frames`) and
+//! delivers frames injected with `receive_frame` once the process has shared
+//! its receive ring buffer, as it does while waiting for a frame. Injected
+//! frames pass through the acceptance filters the process installed. The error
+//! counters are set with `set_error_counters`, which notifies the process when
+//! the bus state changes.
+
+use core::cell::{Cell, RefCell};
+use libtock_platform::{CommandReturn, ErrorCode};
+use std::collections::VecDeque;
+
+use crate::{command_return, DriverInfo, DriverShareRef, RoAllowBuffer, RwAllowBuffer};
+
+/// A frame sent by or to the process.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CanFrame {
+    pub id: u32,
+    pub extended: bool,
+    /// Whether this is a remote frame, which requests `data.len()` bytes.
+    pub remote: bool,
+    pub data: Vec<u8>,
+}
+
+impl CanFrame {
+    /// Creates a data frame with an 11-bit identifier.
+    pub fn standard(id: u32, data: &[u8]) -> CanFrame {
+        CanFrame {
+            id,
+            extended: false,
+            remote: false,
+            data: data.into(),
+        }
+    }
+
+    /// Creates a data frame with a 29-bit identifier.
+    pub fn extended(id: u32, data: &[u8]) -> CanFrame {
+        CanFrame {
+            id,
+            extended: true,
+            remote: false,
+            data: data.into(),
+        }
+    }
+
+    fn raw_id(&self) -> u32 {
+        let mut raw_id = self.id;
+        if self.extended {
+            raw_id |= EXTENDED;
+        }
+        if self.remote {
+            raw_id |= REMOTE;
+        }
+        raw_id
+    }
+}
+
+pub struct Can {
+    enabled: Cell<bool>,
+    bitrate: Cell<u32>,
+    mode: Cell<u32>,
+    // Installed filters, as (raw identifier, mask).
+    filters: RefCell<[Option<(u32, u32)>; FILTER_COUNT]>,
+    tx_errors: Cell<u32>,
+    rx_errors: Cell<u32>,
+    acknowledged: Cell<bool>,
+
+    tx_buf: RefCell<RoAllowBuffer>,
+    rx_buf: RefCell<RwAllowBuffer>,
+
+    transmitted: RefCell<Vec<CanFrame>>,
+    to_be_received: RefCell<VecDeque<CanFrame>>,
+
+    share_ref: DriverShareRef,
+}
+
+impl Can {
+    pub fn new() -> std::rc::Rc<Can> {
+        std::rc::Rc::new(Can {
+            enabled: Cell::new(false),
+            bitrate: Cell::new(DEFAULT_BITRATE),
+            mode: Cell::new(mode::NORMAL),
+            filters: Default::default(),
+            tx_errors: Cell::new(0),
+            rx_errors: Cell::new(0),
+            acknowledged: Cell::new(true),
+            tx_buf: Default::default(),
+            rx_buf: Default::default(),
+            transmitted: Default::default(),
+            to_be_received: Default::default(),
+            share_ref: Default::default(),
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.get()
+    }
+
+    pub fn bitrate(&self) -> u32 {
+        self.bitrate.get()
+    }
+
+    pub fn mode(&self) -> u32 {
+        self.mode.get()
+    }
+
+    /// Returns the frames the process has sent onto the bus, and forgets them.
+    pub fn take_transmitted_frames(&self) -> Vec<CanFrame> {
+        self.transmitted.take()
+    }
+
+    /// Sets whether other nodes acknowledge the frames the process sends.
+    /// Unacknowledged frames are retransmitted indefinitely, so they are never
+    /// recorded as transmitted and their transmission never completes.
+    pub fn set_acknowledged(&self, acknowledged: bool) {
+        self.acknowledged.set(acknowledged);
+    }
+
+    /// Delivers `frame` from the bus. Returns `false` if the controller is
+    /// disabled or bus-off, or the frame does not pass the filters, in which
+    /// case it is dropped.
+    pub fn receive_frame(&self, frame: CanFrame) -> bool {
+        if !self.enabled.get() || self.bus_state() == bus_state::BUS_OFF {
+            return false;
+        }
+        self.accept(frame)
+    }
+
+    /// Returns whether frames are waiting for the process to share its ring
+    /// buffer.
+    pub fn has_pending_rx_frames(&self) -> bool {
+        !self.to_be_received.borrow().is_empty()
+    }
+
+    /// Sets the transmit and receive error counters. If the bus state changes
+    /// as a result, schedules an error upcall.
+    pub fn set_error_counters(&self, tx_errors: u32, rx_errors: u32) {
+        let old_state = self.bus_state();
+        self.tx_errors.set(tx_errors);
+        self.rx_errors.set(rx_errors);
+        let state = self.bus_state();
+        if state != old_state {
+            self.share_ref
+                .schedule_upcall(subscribe::ERROR, (state, tx_errors, rx_errors))
+                .expect("Unable to schedule upcall {}");
+        }
+    }
+
+    pub fn bus_state(&self) -> u32 {
+        match (self.tx_errors.get(), self.rx_errors.get()) {
+            (256.., _) => bus_state::BUS_OFF,
+            (128.., _) | (_, 128..) => bus_state::ERROR_PASSIVE,
+            _ => bus_state::ERROR_ACTIVE,
+        }
+    }
+
+    // Queues `frame` for the process if it passes the filters.
+    fn accept(&self, frame: CanFrame) -> bool {
+        let raw_id = frame.raw_id() & !REMOTE;
+        let filters = self.filters.borrow();
+        let mut installed = filters.iter().flatten().peekable();
+        let passes = installed.peek().is_none()
+            || installed.any(|&(id, mask)| {
+                raw_id & EXTENDED == id & EXTENDED && (raw_id ^ id) & mask & !EXTENDED == 0
+            });
+        if passes {
+            self.to_be_received.borrow_mut().push_back(frame);
+            self.deliver_pending_frames();
+        }
+        passes
+    }
+
+    // Writes as many queued frames as fit into the shared ring buffer, and
+    // schedules an RX upcall if any were written.
+    fn deliver_pending_frames(&self) {
+        let mut rx_buf = self.rx_buf.borrow_mut();
+        if rx_buf.len() < RING_BUF_METADATA_SIZE + FRAME_SIZE {
+            return;
+        }
+        let capacity = (rx_buf.len() - RING_BUF_METADATA_SIZE) / FRAME_SIZE;
+        let read_index = rx_buf[0] as usize;
+        let mut write_index = rx_buf[1] as usize;
+        assert!(read_index < capacity && write_index < capacity);
+        let mut written = 0;
+        let mut to_be_received = self.to_be_received.borrow_mut();
+        while (write_index + 1) % capacity != read_index {
+            let Some(frame) = to_be_received.pop_front() else {
+                break;
+            };
+            let offset = RING_BUF_METADATA_SIZE + write_index * FRAME_SIZE;
+            let slot = &mut rx_buf[offset..offset + FRAME_SIZE];
+            slot.fill(0);
+            slot[..4].copy_from_slice(&frame.raw_id().to_le_bytes());
+            slot[4] = frame.data.len() as u8;
+            if !frame.remote {
+                slot[8..8 + frame.data.len()].copy_from_slice(&frame.data);
+            }
+            write_index = (write_index + 1) % capacity;
+            written += 1;
+        }
+        rx_buf[1] = write_index as u8;
+        if written > 0 {
+            self.share_ref
+                .schedule_upcall(subscribe::RX, (written, 0, 0))
+                .expect("Unable to schedule upcall {}");
+        }
+    }
+
+    // Sends a frame, returning whether its transmission completed.
+    fn send(&self, raw_id: u32, len: u32) -> Result<bool, ErrorCode> {
+        if !self.enabled.get() || self.bus_state() == bus_state::BUS_OFF {
+            return Err(ErrorCode::Off);
+        }
+        if self.mode.get() == mode::LISTEN_ONLY {
+            return Err(ErrorCode::Invalid);
+        }
+        let remote = raw_id & REMOTE != 0;
+        let len = len as usize;
+        let tx_buf = self.tx_buf.borrow();
+        if len > MAX_DATA_LEN || (!remote && tx_buf.len() < len) {
+            return Err(ErrorCode::Size);
+        }
+        let frame = CanFrame {
+            id: raw_id & !(EXTENDED | REMOTE),
+            extended: raw_id & EXTENDED != 0,
+            remote,
+            data: match remote {
+                true => vec![0; len],
+                false => tx_buf[..len].into(),
+            },
+        };
+        let max_id = match frame.extended {
+            true => MAX_EXTENDED_ID,
+            false => MAX_STANDARD_ID,
+        };
+        if frame.id > max_id {
+            return Err(ErrorCode::Invalid);
+        }
+        drop(tx_buf);
+        match self.mode.get() {
+            mode::LOOPBACK => {
+                self.accept(frame);
+            }
+            _ if !self.acknowledged.get() => return Ok(false),
+            _ => self.transmitted.borrow_mut().push(frame),
+        }
+        Ok(true)
+    }
+}
+
+impl crate::fake::SyscallDriver for Can {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(3)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn command(&self, command_number: u32, argument0: u32, argument1: u32) -> CommandReturn {
+        match command_number {
+            command::EXISTS => command_return::success(),
+            command::SET_BITRATE => {
+                if self.enabled.get() {
+                    return command_return::failure(ErrorCode::Busy);
+                }
+                if !BITRATES.contains(&argument0) {
+                    return command_return::failure(ErrorCode::Invalid);
+                }
+                self.bitrate.set(argument0);
+                command_return::success()
+            }
+            command::SET_MODE => {
+                if self.enabled.get() {
+                    return command_return::failure(ErrorCode::Busy);
+                }
+                if argument0 > mode::LISTEN_ONLY {
+                    return command_return::failure(ErrorCode::Invalid);
+                }
+                self.mode.set(argument0);
+                command_return::success()
+            }
+            command::ENABLE => {
+                if self.enabled.get() && self.bus_state() != bus_state::BUS_OFF {
+                    return command_return::failure(ErrorCode::Already);
+                }
+                if self.bus_state() == bus_state::BUS_OFF {
+                    // Bus-off recovery.
+                    self.tx_errors.set(0);
+                    self.rx_errors.set(0);
+                }
+                self.enabled.set(true);
+                command_return::success()
+            }
+            command::DISABLE => {
+                if !self.enabled.get() {
+                    return command_return::failure(ErrorCode::Already);
+                }
+                self.enabled.set(false);
+                command_return::success()
+            }
+            command::SEND => {
+                match self.send(argument0, argument1) {
+                    Ok(true) => self
+                        .share_ref
+                        .schedule_upcall(subscribe::TX_DONE, (0, 0, 0))
+                        .expect("Unable to schedule upcall {}"),
+                    Ok(false) => {}
+                    Err(error) => return command_return::failure(error),
+                }
+                command_return::success()
+            }
+            command::ADD_FILTER => {
+                let mut filters = self.filters.borrow_mut();
+                match filters.iter().position(Option::is_none) {
+                    Some(index) => {
+                        filters[index] = Some((argument0, argument1));
+                        command_return::success_u32(index as u32)
+                    }
+                    None => command_return::failure(ErrorCode::NoMem),
+                }
+            }
+            command::REMOVE_FILTER => {
+                let mut filters = self.filters.borrow_mut();
+                match filters.get_mut(argument0 as usize).and_then(Option::take) {
+                    Some(_) => command_return::success(),
+                    None => command_return::failure(ErrorCode::Invalid),
+                }
+            }
+            command::CLEAR_FILTERS => {
+                self.filters.replace(Default::default());
+                command_return::success()
+            }
+            command::ERROR_COUNTERS => {
+                command_return::success_2_u32(self.tx_errors.get(), self.rx_errors.get())
+            }
+            command::BUS_STATE => command_return::success_u32(self.bus_state()),
+            _ => command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+
+    fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            allow_ro::WRITE => Ok(self.tx_buf.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            allow_rw::READ => {
+                let old = self.rx_buf.replace(buffer);
+                self.deliver_pending_frames();
+                Ok(old)
+            }
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x20007;
+
+const FILTER_COUNT: usize = 4;
+const MAX_DATA_LEN: usize = 8;
+const MAX_STANDARD_ID: u32 = 0x7FF;
+const MAX_EXTENDED_ID: u32 = 0x1FFF_FFFF;
+const DEFAULT_BITRATE: u32 = 500_000;
+const BITRATES: [u32; 9] = [
+    10_000, 20_000, 50_000, 100_000, 125_000, 250_000, 500_000, 800_000, 1_000_000,
+];
+
+// Ring buffer layout: read index, write index, two reserved bytes, then frames
+// of a 4-byte raw identifier, a length, three reserved bytes and 8 data bytes.
+const RING_BUF_METADATA_SIZE: usize = 4;
+const FRAME_SIZE: usize = 16;
+
+// Raw identifier flags
+const EXTENDED: u32 = 1 << 31;
+const REMOTE: u32 = 1 << 30;
+
+mod mode {
+    pub const NORMAL: u32 = 0;
+    pub const LOOPBACK: u32 = 1;
+    pub const LISTEN_ONLY: u32 = 2;
+}
+
+mod bus_state {
+    pub const ERROR_ACTIVE: u32 = 0;
+    pub const ERROR_PASSIVE: u32 = 1;
+    pub const BUS_OFF: u32 = 2;
+}
+
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const SET_BITRATE: u32 = 1;
+    pub const SET_MODE: u32 = 2;
+    pub const ENABLE: u32 = 3;
+    pub const DISABLE: u32 = 4;
+    pub const SEND: u32 = 5;
+    pub const ADD_FILTER: u32 = 6;
+    pub const REMOVE_FILTER: u32 = 7;
+    pub const CLEAR_FILTERS: u32 = 8;
+    pub const ERROR_COUNTERS: u32 = 9;
+    pub const BUS_STATE: u32 = 10;
+}
+
+mod subscribe {
+    pub const TX_DONE: u32 = 0;
+    pub const RX: u32 = 1;
+    pub const ERROR: u32 = 2;
+}
+
+mod allow_ro {
+    pub const WRITE: u32 = 0;
+}
+
+mod allow_rw {
+    pub const READ: u32 = 0;
+}
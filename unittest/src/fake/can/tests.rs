@@ -0,0 +1,195 @@
+use crate::fake::{self, can::*};
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::{share, DefaultConfig, Syscalls, YieldNoWaitReturn};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let can = Can::new();
+    assert!(can.command(command::EXISTS, 0, 0).is_success());
+    assert_eq!(
+        can.command(command::SET_BITRATE, 123_456, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert!(can.command(command::SET_BITRATE, 250_000, 0).is_success());
+    assert_eq!(can.bitrate(), 250_000);
+    assert_eq!(
+        can.command(command::SET_MODE, 3, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert!(can
+        .command(command::SET_MODE, mode::LOOPBACK, 0)
+        .is_success());
+    assert_eq!(can.mode(), mode::LOOPBACK);
+    assert_eq!(
+        can.command(command::SEND, 0x123, 0).get_failure(),
+        Some(ErrorCode::Off)
+    );
+
+    assert!(can.command(command::ENABLE, 0, 0).is_success());
+    assert!(can.is_enabled());
+    assert_eq!(
+        can.command(command::ENABLE, 0, 0).get_failure(),
+        Some(ErrorCode::Already)
+    );
+    assert_eq!(
+        can.command(command::SET_BITRATE, 125_000, 0).get_failure(),
+        Some(ErrorCode::Busy)
+    );
+    assert_eq!(
+        can.command(command::SET_MODE, mode::NORMAL, 0)
+            .get_failure(),
+        Some(ErrorCode::Busy)
+    );
+
+    for index in 0..FILTER_COUNT as u32 {
+        assert_eq!(
+            can.command(command::ADD_FILTER, 0x100 + index, 0x7FF)
+                .get_success_u32(),
+            Some(index)
+        );
+    }
+    assert_eq!(
+        can.command(command::ADD_FILTER, 0x200, 0x7FF).get_failure(),
+        Some(ErrorCode::NoMem)
+    );
+    assert!(can.command(command::REMOVE_FILTER, 1, 0).is_success());
+    assert_eq!(
+        can.command(command::REMOVE_FILTER, 1, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert_eq!(
+        can.command(command::ADD_FILTER, 0x200, 0x7FF)
+            .get_success_u32(),
+        Some(1)
+    );
+    assert!(can.command(command::CLEAR_FILTERS, 0, 0).is_success());
+
+    can.set_error_counters(130, 5);
+    assert_eq!(
+        can.command(command::ERROR_COUNTERS, 0, 0)
+            .get_success_2_u32(),
+        Some((130, 5))
+    );
+    assert_eq!(
+        can.command(command::BUS_STATE, 0, 0).get_success_u32(),
+        Some(bus_state::ERROR_PASSIVE)
+    );
+
+    assert!(can.command(command::DISABLE, 0, 0).is_success());
+    assert_eq!(
+        can.command(command::DISABLE, 0, 0).get_failure(),
+        Some(ErrorCode::Already)
+    );
+    assert_eq!(
+        can.command(command::BUS_STATE + 1, 0, 0).get_failure(),
+        Some(ErrorCode::NoSupport)
+    );
+}
+
+// Integration test that verifies Can works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let can = Can::new();
+    kernel.add_driver(&can);
+    assert!(fake::Syscalls::command(DRIVER_NUM, command::ENABLE, 0, 0).is_success());
+
+    // Transmission.
+    share::scope::<AllowRo<_, DRIVER_NUM, { allow_ro::WRITE }>, _, _>(|allow_ro| {
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, { allow_ro::WRITE }>(
+            allow_ro,
+            &[1, 2, 3],
+        )
+        .unwrap();
+        assert!(
+            fake::Syscalls::command(DRIVER_NUM, command::SEND, EXTENDED | 0x18DA_F110, 3)
+                .is_success()
+        );
+        assert_eq!(
+            fake::Syscalls::command(DRIVER_NUM, command::SEND, 0x123, 4).get_failure(),
+            Some(ErrorCode::Size)
+        );
+        assert_eq!(
+            fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::TX_DONE),
+            (0, 0, 0)
+        );
+    });
+    assert_eq!(
+        can.take_transmitted_frames(),
+        [CanFrame::extended(0x18DA_F110, &[1, 2, 3])]
+    );
+
+    // Without an acknowledgement, the transmission never completes.
+    can.set_acknowledged(false);
+    assert!(fake::Syscalls::command(DRIVER_NUM, command::SEND, 0x123, 0).is_success());
+    assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+    assert_eq!(can.take_transmitted_frames(), []);
+    can.set_acknowledged(true);
+
+    // Reception, through a filter that only passes 0x7E0 to 0x7E7.
+    assert_eq!(
+        fake::Syscalls::command(DRIVER_NUM, command::ADD_FILTER, 0x7E0, 0x7F8).get_success_u32(),
+        Some(0)
+    );
+    assert!(can.receive_frame(CanFrame::standard(0x7E1, &[0xAA])));
+    assert!(!can.receive_frame(CanFrame::standard(0x7E8, &[0xBB])));
+    assert!(!can.receive_frame(CanFrame::extended(0x7E1, &[0xCC])));
+    assert!(can.receive_frame(CanFrame::standard(0x7E7, &[])));
+    assert!(can.has_pending_rx_frames());
+
+    // A ring buffer with room for one frame receives them one at a time.
+    let mut buf = [0; RING_BUF_METADATA_SIZE + 2 * FRAME_SIZE];
+    share::scope(|allow_rw| {
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, { allow_rw::READ }>(
+            allow_rw, &mut buf,
+        )
+        .unwrap();
+        assert_eq!(
+            fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::RX),
+            (1, 0, 0)
+        );
+    });
+    assert_eq!(buf[..4], [0, 1, 0, 0]);
+    assert_eq!(buf[4..14], [0xE1, 0x07, 0, 0, 1, 0, 0, 0, 0xAA, 0]);
+    assert!(can.has_pending_rx_frames());
+    buf[0] = 1;
+    share::scope(|allow_rw| {
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, { allow_rw::READ }>(
+            allow_rw, &mut buf,
+        )
+        .unwrap();
+        assert_eq!(
+            fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::RX),
+            (1, 0, 0)
+        );
+    });
+    assert_eq!(buf[..2], [1, 0]);
+    assert_eq!(buf[20..25], [0xE7, 0x07, 0, 0, 0]);
+    assert!(!can.has_pending_rx_frames());
+
+    // Bus-off stops the controller until it is enabled again.
+    let listener = Cell::<Option<(u32, u32, u32)>>::new(None);
+    share::scope(|subscribe| {
+        assert_eq!(
+            fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, { subscribe::ERROR }>(
+                subscribe, &listener
+            ),
+            Ok(())
+        );
+        can.set_error_counters(256, 0);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(listener.get(), Some((bus_state::BUS_OFF, 256, 0)));
+        can.set_error_counters(300, 0);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+    });
+    assert!(!can.receive_frame(CanFrame::standard(0x7E1, &[])));
+    assert_eq!(
+        fake::Syscalls::command(DRIVER_NUM, command::SEND, 0x123, 0).get_failure(),
+        Some(ErrorCode::Off)
+    );
+    assert!(fake::Syscalls::command(DRIVER_NUM, command::ENABLE, 0, 0).is_success());
+    assert_eq!(can.bus_state(), bus_state::ERROR_ACTIVE);
+}
@@ -0,0 +1,104 @@
+//! Fake implementation of the CRC API.
+//!
+//! `Crc` computes real CRCs on the host, so tests can compare against known
+//! values. `computations` counts the CRCs the process has requested.
+
+use core::cell::{Cell, RefCell};
+use libtock_platform::{CommandReturn, ErrorCode};
+
+use crate::{command_return, DriverInfo, DriverShareRef, RoAllowBuffer};
+
+pub struct Crc {
+    computations: Cell<usize>,
+    data_buffer: RefCell<RoAllowBuffer>,
+    share_ref: DriverShareRef,
+}
+
+impl Crc {
+    pub fn new() -> std::rc::Rc<Crc> {
+        std::rc::Rc::new(Crc {
+            computations: Cell::new(0),
+            data_buffer: Default::default(),
+            share_ref: Default::default(),
+        })
+    }
+
+    /// Returns the number of CRCs computed.
+    pub fn computations(&self) -> usize {
+        self.computations.get()
+    }
+}
+
+impl crate::fake::SyscallDriver for Crc {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(1)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn command(&self, command_number: u32, argument0: u32, argument1: u32) -> CommandReturn {
+        match command_number {
+            command::EXISTS => command_return::success(),
+            command::COMPUTE => {
+                let data = self.data_buffer.borrow();
+                let Some(data) = data.get(..argument1 as usize) else {
+                    return command_return::failure(ErrorCode::Size);
+                };
+                let crc = match argument0 {
+                    CRC_32 => crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC).checksum(data),
+                    CRC_32C => crc::Crc::<u32>::new(&crc::CRC_32_ISCSI).checksum(data),
+                    CRC_16_CCITT => {
+                        crc::Crc::<u16>::new(&crc::CRC_16_IBM_3740).checksum(data) as u32
+                    }
+                    _ => return command_return::failure(ErrorCode::Invalid),
+                };
+                self.computations.set(self.computations.get() + 1);
+                self.share_ref
+                    .schedule_upcall(subscribe::DONE, (0, crc, 0))
+                    .expect("Unable to schedule upcall {}");
+                command_return::success()
+            }
+            _ => command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+
+    fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            allow_ro::DATA => Ok(self.data_buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x40002;
+
+// Algorithms
+const CRC_32: u32 = 0;
+const CRC_32C: u32 = 1;
+const CRC_16_CCITT: u32 = 2;
+
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const COMPUTE: u32 = 1;
+}
+
+mod subscribe {
+    pub const DONE: u32 = 0;
+}
+
+mod allow_ro {
+    pub const DATA: u32 = 0;
+}
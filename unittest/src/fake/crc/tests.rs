@@ -0,0 +1,53 @@
+use crate::fake::{self, crc::*};
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::{share, DefaultConfig, Syscalls};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let crc = Crc::new();
+    assert!(crc.command(command::EXISTS, 0, 0).is_success());
+    assert_eq!(
+        crc.command(command::COMPUTE, CRC_32, 1).get_failure(),
+        Some(ErrorCode::Size)
+    );
+    assert_eq!(
+        crc.command(command::COMPUTE, 3, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert_eq!(crc.computations(), 0);
+
+    assert!(crc
+        .allow_readonly(allow_ro::DATA, RoAllowBuffer::default())
+        .is_ok());
+    assert!(crc.allow_readonly(1, RoAllowBuffer::default()).is_err());
+}
+
+// Integration test that verifies Crc works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let crc = Crc::new();
+    kernel.add_driver(&crc);
+
+    // Only the requested length of the buffer is used.
+    for (algorithm, expected) in [(CRC_32, 0xCBF4_3926), (CRC_16_CCITT, 0x29B1)] {
+        share::scope::<AllowRo<_, DRIVER_NUM, { allow_ro::DATA }>, _, _>(|allow| {
+            fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, { allow_ro::DATA }>(
+                allow,
+                b"1234567890",
+            )
+            .unwrap();
+            assert!(
+                fake::Syscalls::command(DRIVER_NUM, command::COMPUTE, algorithm, 9).is_success()
+            );
+        });
+        assert_eq!(
+            fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::DONE),
+            (0, expected, 0)
+        );
+    }
+    assert_eq!(crc.computations(), 2);
+}
@@ -0,0 +1,131 @@
+//! Fake implementation of the ECDSA P-256 signature verification API.
+//!
+//! `EcdsaP256` verifies signatures on the host with a pure-Rust P-256
+//! implementation, so tests can use real keys and signatures. `verifications`
+//! counts the signatures the process has asked it to check.
+
+use core::cell::{Cell, RefCell};
+use libtock_platform::{CommandReturn, ErrorCode};
+use p256::ecdsa::signature::hazmat::PrehashVerifier;
+use p256::ecdsa::{Signature, VerifyingKey};
+
+use crate::{command_return, DriverInfo, DriverShareRef, RoAllowBuffer};
+
+pub struct EcdsaP256 {
+    verifications: Cell<usize>,
+
+    hash_buffer: RefCell<RoAllowBuffer>,
+    signature_buffer: RefCell<RoAllowBuffer>,
+    public_key_buffer: RefCell<RoAllowBuffer>,
+
+    share_ref: DriverShareRef,
+}
+
+impl EcdsaP256 {
+    pub fn new() -> std::rc::Rc<EcdsaP256> {
+        std::rc::Rc::new(EcdsaP256 {
+            verifications: Cell::new(0),
+            hash_buffer: Default::default(),
+            signature_buffer: Default::default(),
+            public_key_buffer: Default::default(),
+            share_ref: Default::default(),
+        })
+    }
+
+    /// Returns the number of signatures checked.
+    pub fn verifications(&self) -> usize {
+        self.verifications.get()
+    }
+
+    // Checks the shared signature, returning whether it is valid, or Invalid
+    // if the public key is not a point on the curve.
+    fn verify(&self) -> Result<bool, ErrorCode> {
+        let mut public_key = [0; 1 + PUBLIC_KEY_LEN];
+        // An uncompressed SEC1 point.
+        public_key[0] = 0x04;
+        public_key[1..].copy_from_slice(&self.public_key_buffer.borrow());
+        let key = VerifyingKey::from_sec1_bytes(&public_key).map_err(|_| ErrorCode::Invalid)?;
+        // Signatures with r or s out of range are rejected rather than invalid.
+        let Ok(signature) = Signature::from_slice(&self.signature_buffer.borrow()) else {
+            return Ok(false);
+        };
+        Ok(key
+            .verify_prehash(&self.hash_buffer.borrow(), &signature)
+            .is_ok())
+    }
+}
+
+impl crate::fake::SyscallDriver for EcdsaP256 {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(1)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn command(&self, command_number: u32, _argument0: u32, _argument1: u32) -> CommandReturn {
+        match command_number {
+            command::EXISTS => command_return::success(),
+            command::VERIFY => {
+                if self.hash_buffer.borrow().len() != HASH_LEN
+                    || self.signature_buffer.borrow().len() != SIGNATURE_LEN
+                    || self.public_key_buffer.borrow().len() != PUBLIC_KEY_LEN
+                {
+                    return command_return::failure(ErrorCode::Size);
+                }
+                self.verifications.set(self.verifications.get() + 1);
+                let upcall = match self.verify() {
+                    Ok(verified) => (0, verified as u32, 0),
+                    Err(error) => (error as u32, 0, 0),
+                };
+                self.share_ref
+                    .schedule_upcall(subscribe::DONE, upcall)
+                    .expect("Unable to schedule upcall {}");
+                command_return::success()
+            }
+            _ => command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+
+    fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            allow_ro::HASH => Ok(self.hash_buffer.replace(buffer)),
+            allow_ro::SIGNATURE => Ok(self.signature_buffer.replace(buffer)),
+            allow_ro::PUBLIC_KEY => Ok(self.public_key_buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x40007;
+
+const HASH_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 64;
+const PUBLIC_KEY_LEN: usize = 64;
+
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const VERIFY: u32 = 1;
+}
+
+mod subscribe {
+    pub const DONE: u32 = 0;
+}
+
+mod allow_ro {
+    pub const HASH: u32 = 0;
+    pub const SIGNATURE: u32 = 1;
+    pub const PUBLIC_KEY: u32 = 2;
+}
@@ -0,0 +1,88 @@
+use crate::fake::{self, ecdsa_p256::*};
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::{share, DefaultConfig, Syscalls};
+use p256::ecdsa::signature::hazmat::PrehashSigner;
+use p256::ecdsa::SigningKey;
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let ecdsa = EcdsaP256::new();
+    assert!(ecdsa.command(command::EXISTS, 0, 0).is_success());
+    // Nothing has been shared, so the buffers are the wrong size.
+    assert_eq!(
+        ecdsa.command(command::VERIFY, 0, 0).get_failure(),
+        Some(ErrorCode::Size)
+    );
+    assert_eq!(ecdsa.verifications(), 0);
+
+    assert!(ecdsa
+        .allow_readonly(allow_ro::HASH, RoAllowBuffer::default())
+        .is_ok());
+    assert!(ecdsa
+        .allow_readonly(allow_ro::SIGNATURE, RoAllowBuffer::default())
+        .is_ok());
+    assert!(ecdsa
+        .allow_readonly(allow_ro::PUBLIC_KEY, RoAllowBuffer::default())
+        .is_ok());
+    assert!(ecdsa.allow_readonly(3, RoAllowBuffer::default()).is_err());
+}
+
+// Integration test that verifies EcdsaP256 works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let ecdsa = EcdsaP256::new();
+    kernel.add_driver(&ecdsa);
+
+    let signing_key = SigningKey::from_slice(&[7; 32]).unwrap();
+    let point = signing_key.verifying_key().to_encoded_point(false);
+    let public_key = &point.as_bytes()[1..];
+    let hash = [0x5a; HASH_LEN];
+    let signature: p256::ecdsa::Signature = signing_key.sign_prehash(&hash).unwrap();
+    let mut signature: [u8; SIGNATURE_LEN] = signature.to_bytes().into();
+
+    let check = |signature: &[u8], public_key: &[u8]| {
+        share::scope::<
+            (
+                AllowRo<_, DRIVER_NUM, { allow_ro::HASH }>,
+                AllowRo<_, DRIVER_NUM, { allow_ro::SIGNATURE }>,
+                AllowRo<_, DRIVER_NUM, { allow_ro::PUBLIC_KEY }>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_hash, allow_signature, allow_public_key) = handle.split();
+            fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, { allow_ro::HASH }>(
+                allow_hash, &hash,
+            )
+            .unwrap();
+            fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, { allow_ro::SIGNATURE }>(
+                allow_signature,
+                signature,
+            )
+            .unwrap();
+            fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, { allow_ro::PUBLIC_KEY }>(
+                allow_public_key,
+                public_key,
+            )
+            .unwrap();
+            assert!(fake::Syscalls::command(DRIVER_NUM, command::VERIFY, 0, 0).is_success());
+        });
+        fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::DONE)
+    };
+
+    assert_eq!(check(&signature, public_key), (0, 1, 0));
+    signature[10] ^= 1;
+    assert_eq!(check(&signature, public_key), (0, 0, 0));
+    // A signature with r = 0 is rejected.
+    assert_eq!(check(&[0; SIGNATURE_LEN], public_key), (0, 0, 0));
+    // A public key that is not on the curve is invalid.
+    assert_eq!(
+        check(&signature, &[1; PUBLIC_KEY_LEN]),
+        (ErrorCode::Invalid as u32, 0, 0)
+    );
+    assert_eq!(ecdsa.verifications(), 4);
+}
@@ -0,0 +1,220 @@
+//! Fake implementation of the HMAC API.
+//!
+//! `Hmac` computes real HMAC-SHA-256, HMAC-SHA-384 and HMAC-SHA-512 MACs on the
+//! host, so tests can compare against known vectors. `updates` counts the
+//! chunks of data the process has added to MACs.
+
+use core::cell::{Cell, RefCell};
+use hmac::Mac;
+use libtock_platform::{CommandReturn, ErrorCode};
+
+use crate::{command_return, DriverInfo, DriverShareRef, RoAllowBuffer, RwAllowBuffer};
+
+pub struct Hmac {
+    algorithm: Cell<Option<u32>>,
+    // The MAC in progress. It is keyed from the key buffer on the first update
+    // after an algorithm is selected or a MAC is finished.
+    mac: RefCell<Option<Authenticator>>,
+    updates: Cell<usize>,
+
+    key_buffer: RefCell<RoAllowBuffer>,
+    data_buffer: RefCell<RoAllowBuffer>,
+    dest_buffer: RefCell<RwAllowBuffer>,
+
+    share_ref: DriverShareRef,
+}
+
+enum Authenticator {
+    Sha256(hmac::Hmac<sha2::Sha256>),
+    Sha384(hmac::Hmac<sha2::Sha384>),
+    Sha512(hmac::Hmac<sha2::Sha512>),
+}
+
+impl Authenticator {
+    fn new(algorithm: u32, key: &[u8]) -> Authenticator {
+        // HMAC accepts keys of any length, so new_from_slice cannot fail.
+        match algorithm {
+            HMAC_SHA_256 => Authenticator::Sha256(Mac::new_from_slice(key).unwrap()),
+            HMAC_SHA_384 => Authenticator::Sha384(Mac::new_from_slice(key).unwrap()),
+            _ => Authenticator::Sha512(Mac::new_from_slice(key).unwrap()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Authenticator::Sha256(mac) => mac.update(data),
+            Authenticator::Sha384(mac) => mac.update(data),
+            Authenticator::Sha512(mac) => mac.update(data),
+        }
+    }
+
+    fn finish(self, dest: &mut [u8]) {
+        match self {
+            Authenticator::Sha256(mac) => dest.copy_from_slice(&mac.finalize().into_bytes()),
+            Authenticator::Sha384(mac) => dest.copy_from_slice(&mac.finalize().into_bytes()),
+            Authenticator::Sha512(mac) => dest.copy_from_slice(&mac.finalize().into_bytes()),
+        }
+    }
+}
+
+fn mac_len(algorithm: u32) -> Option<usize> {
+    match algorithm {
+        HMAC_SHA_256 => Some(32),
+        HMAC_SHA_384 => Some(48),
+        HMAC_SHA_512 => Some(64),
+        _ => None,
+    }
+}
+
+impl Hmac {
+    pub fn new() -> std::rc::Rc<Hmac> {
+        std::rc::Rc::new(Hmac {
+            algorithm: Cell::new(None),
+            mac: RefCell::new(None),
+            updates: Cell::new(0),
+            key_buffer: Default::default(),
+            data_buffer: Default::default(),
+            dest_buffer: Default::default(),
+            share_ref: Default::default(),
+        })
+    }
+
+    /// Returns the algorithm selected by the process, if any.
+    pub fn algorithm(&self) -> Option<u32> {
+        self.algorithm.get()
+    }
+
+    /// Returns the number of times data was added to a MAC.
+    pub fn updates(&self) -> usize {
+        self.updates.get()
+    }
+
+    // Adds the data buffer to the MAC in progress, starting a new MAC with the
+    // key buffer if necessary.
+    fn update(&self, algorithm: u32) {
+        self.mac
+            .borrow_mut()
+            .get_or_insert_with(|| Authenticator::new(algorithm, &self.key_buffer.borrow()))
+            .update(&self.data_buffer.borrow());
+        self.updates.set(self.updates.get() + 1);
+    }
+
+    // Writes the MAC in progress to the destination buffer. The next update
+    // starts a new MAC.
+    fn finish(&self, algorithm: u32) -> Result<(), ErrorCode> {
+        let len = mac_len(algorithm).unwrap();
+        let mut dest = self.dest_buffer.borrow_mut();
+        if dest.len() < len {
+            return Err(ErrorCode::Size);
+        }
+        let mac = self.mac.take();
+        let mac = mac.unwrap_or_else(|| Authenticator::new(algorithm, &self.key_buffer.borrow()));
+        mac.finish(&mut dest[..len]);
+        Ok(())
+    }
+
+    fn schedule_done(&self, result: Result<(), ErrorCode>) {
+        let status = match result {
+            Ok(()) => 0,
+            Err(error) => error as u32,
+        };
+        self.share_ref
+            .schedule_upcall(subscribe::DONE, (status, 0, 0))
+            .expect("Unable to schedule upcall {}");
+    }
+}
+
+impl crate::fake::SyscallDriver for Hmac {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(1)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn command(&self, command_number: u32, argument0: u32, _argument1: u32) -> CommandReturn {
+        if command_number == command::SET_ALGORITHM {
+            if mac_len(argument0).is_none() {
+                return command_return::failure(ErrorCode::NoSupport);
+            }
+            self.algorithm.set(Some(argument0));
+            self.mac.replace(None);
+            return command_return::success();
+        }
+        let Some(algorithm) = self.algorithm.get() else {
+            return command_return::failure(ErrorCode::Reserve);
+        };
+        match command_number {
+            command::RUN => {
+                self.mac.replace(None);
+                self.update(algorithm);
+                self.schedule_done(self.finish(algorithm));
+            }
+            command::UPDATE => {
+                self.update(algorithm);
+                self.schedule_done(Ok(()));
+            }
+            command::FINISH => self.schedule_done(self.finish(algorithm)),
+            _ => return command_return::failure(ErrorCode::NoSupport),
+        }
+        command_return::success()
+    }
+
+    fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            allow_ro::KEY => Ok(self.key_buffer.replace(buffer)),
+            allow_ro::DATA => Ok(self.data_buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            allow_rw::DEST => Ok(self.dest_buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x40003;
+
+// Algorithms
+const HMAC_SHA_256: u32 = 0;
+const HMAC_SHA_384: u32 = 1;
+const HMAC_SHA_512: u32 = 2;
+
+mod command {
+    pub const SET_ALGORITHM: u32 = 0;
+    pub const RUN: u32 = 1;
+    pub const UPDATE: u32 = 2;
+    pub const FINISH: u32 = 3;
+}
+
+mod subscribe {
+    pub const DONE: u32 = 0;
+}
+
+mod allow_ro {
+    pub const KEY: u32 = 0;
+    pub const DATA: u32 = 1;
+}
+
+mod allow_rw {
+    pub const DEST: u32 = 0;
+}
@@ -0,0 +1,102 @@
+use crate::fake::{self, hmac::*};
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::{share, DefaultConfig, Syscalls};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let hmac = Hmac::new();
+    assert_eq!(
+        hmac.command(command::UPDATE, 0, 0).get_failure(),
+        Some(ErrorCode::Reserve)
+    );
+    assert_eq!(
+        hmac.command(command::SET_ALGORITHM, 3, 0).get_failure(),
+        Some(ErrorCode::NoSupport)
+    );
+    assert_eq!(hmac.algorithm(), None);
+    assert!(hmac
+        .command(command::SET_ALGORITHM, HMAC_SHA_512, 0)
+        .is_success());
+    assert_eq!(hmac.algorithm(), Some(HMAC_SHA_512));
+
+    assert!(hmac
+        .allow_readonly(allow_ro::KEY, RoAllowBuffer::default())
+        .is_ok());
+    assert!(hmac
+        .allow_readonly(allow_ro::DATA, RoAllowBuffer::default())
+        .is_ok());
+    assert!(hmac.allow_readonly(2, RoAllowBuffer::default()).is_err());
+    assert!(hmac
+        .allow_readwrite(allow_rw::DEST, RwAllowBuffer::default())
+        .is_ok());
+    assert!(hmac.allow_readwrite(1, RwAllowBuffer::default()).is_err());
+}
+
+// Integration test that verifies Hmac works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let hmac = Hmac::new();
+    kernel.add_driver(&hmac);
+
+    assert!(
+        fake::Syscalls::command(DRIVER_NUM, command::SET_ALGORITHM, HMAC_SHA_256, 0).is_success()
+    );
+    for data in [&b"what do ya "[..], b"want for nothing?"] {
+        share::scope::<
+            (
+                AllowRo<_, DRIVER_NUM, { allow_ro::KEY }>,
+                AllowRo<_, DRIVER_NUM, { allow_ro::DATA }>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_key, allow_data) = handle.split();
+            fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, { allow_ro::KEY }>(
+                allow_key, b"Jefe",
+            )
+            .unwrap();
+            fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, { allow_ro::DATA }>(
+                allow_data, data,
+            )
+            .unwrap();
+            assert!(fake::Syscalls::command(DRIVER_NUM, command::UPDATE, 0, 0).is_success());
+        });
+        assert_eq!(
+            fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::DONE),
+            (0, 0, 0)
+        );
+    }
+    assert_eq!(hmac.updates(), 2);
+
+    // The destination buffer must be large enough.
+    let mut mac = [0; 33];
+    for (len, status) in [(31, ErrorCode::Size as u32), (33, 0)] {
+        share::scope::<AllowRw<_, DRIVER_NUM, { allow_rw::DEST }>, _, _>(|allow| {
+            fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, { allow_rw::DEST }>(
+                allow,
+                &mut mac[..len],
+            )
+            .unwrap();
+            assert!(fake::Syscalls::command(DRIVER_NUM, command::FINISH, 0, 0).is_success());
+        });
+        assert_eq!(
+            fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::DONE),
+            (status, 0, 0)
+        );
+    }
+    // RFC 4231 test case 2.
+    assert_eq!(
+        mac[..32],
+        [
+            0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95,
+            0x75, 0xc7, 0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58, 0xb9,
+            0x64, 0xec, 0x38, 0x43
+        ]
+    );
+    assert_eq!(mac[32], 0);
+}
@@ -0,0 +1,279 @@
+//! Fake implementation of the I2C master-slave API.
+//!
+//! In master mode, `I2CMasterSlave` records the writes the process makes
+//! (`take_master_writes`) and answers its reads with the data set by
+//! `set_master_read_data`. Devices that do not acknowledge are emulated with
+//! `set_acknowledged`. In slave mode, writes from other masters are injected
+//! with `write_to_slave` and delivered once the process listens, and the data
+//! other masters read from the process is recorded (`take_slave_reads`).
+
+use core::cell::{Cell, RefCell};
+use libtock_platform::{CommandReturn, ErrorCode};
+use std::collections::VecDeque;
+
+use crate::{command_return, DriverInfo, DriverShareRef, RoAllowBuffer, RwAllowBuffer};
+
+pub struct I2CMasterSlave {
+    slave_address: Cell<Option<u8>>,
+    listening: Cell<bool>,
+    acknowledged: Cell<bool>,
+    master_read_data: RefCell<Vec<u8>>,
+
+    master_tx: RefCell<RoAllowBuffer>,
+    master_rx: RefCell<RwAllowBuffer>,
+    slave_tx: RefCell<RoAllowBuffer>,
+    slave_rx: RefCell<RwAllowBuffer>,
+
+    master_writes: RefCell<Vec<(u8, Vec<u8>)>>,
+    slave_reads: RefCell<Vec<Vec<u8>>>,
+    to_be_received: RefCell<VecDeque<Vec<u8>>>,
+
+    share_ref: DriverShareRef,
+}
+
+impl I2CMasterSlave {
+    pub fn new() -> std::rc::Rc<I2CMasterSlave> {
+        std::rc::Rc::new(I2CMasterSlave {
+            slave_address: Cell::new(None),
+            listening: Cell::new(false),
+            acknowledged: Cell::new(true),
+            master_read_data: Default::default(),
+            master_tx: Default::default(),
+            master_rx: Default::default(),
+            slave_tx: Default::default(),
+            slave_rx: Default::default(),
+            master_writes: Default::default(),
+            slave_reads: Default::default(),
+            to_be_received: Default::default(),
+            share_ref: Default::default(),
+        })
+    }
+
+    /// Returns the address the process responds to in slave mode, if set.
+    pub fn slave_address(&self) -> Option<u8> {
+        self.slave_address.get()
+    }
+
+    /// Returns the writes the process has made in master mode, as the address
+    /// written to and the data, and forgets them.
+    pub fn take_master_writes(&self) -> Vec<(u8, Vec<u8>)> {
+        self.master_writes.take()
+    }
+
+    /// Returns the data other masters have read from the process in slave
+    /// mode, and forgets it.
+    pub fn take_slave_reads(&self) -> Vec<Vec<u8>> {
+        self.slave_reads.take()
+    }
+
+    /// Sets the data returned to reads in master mode. Reads longer than the
+    /// data are padded with 0xFF, as on an idle bus.
+    pub fn set_master_read_data(&self, data: &[u8]) {
+        self.master_read_data.replace(data.into());
+    }
+
+    /// Sets whether the devices the process addresses in master mode
+    /// acknowledge. Transfers to devices that do not fail with `NoAck`.
+    pub fn set_acknowledged(&self, acknowledged: bool) {
+        self.acknowledged.set(acknowledged);
+    }
+
+    /// Delivers a write of `data` from another master to the process. It is
+    /// queued until the process listens in slave mode.
+    pub fn write_to_slave(&self, data: &[u8]) {
+        self.to_be_received.borrow_mut().push_back(data.into());
+        self.deliver_pending_write();
+    }
+
+    /// Returns whether writes are waiting for the process to listen.
+    pub fn has_pending_writes(&self) -> bool {
+        !self.to_be_received.borrow().is_empty()
+    }
+
+    // Copies the oldest queued write into the slave receive buffer, truncated
+    // to its length, if the process is listening.
+    fn deliver_pending_write(&self) {
+        if !self.listening.get() {
+            return;
+        }
+        let Some(data) = self.to_be_received.borrow_mut().pop_front() else {
+            return;
+        };
+        let mut slave_rx = self.slave_rx.borrow_mut();
+        let len = data.len().min(slave_rx.len());
+        slave_rx[..len].copy_from_slice(&data[..len]);
+        self.listening.set(false);
+        self.share_ref
+            .schedule_upcall(
+                subscribe::COMPLETE,
+                (command::SLAVE_START_LISTEN, len as u32, 0),
+            )
+            .expect("Unable to schedule upcall {}");
+    }
+
+    // Records a master write of `len` bytes of the transmit buffer to
+    // `address`.
+    fn master_write(&self, address: u32, len: u32) -> Result<(), ErrorCode> {
+        if address > MAX_ADDRESS {
+            return Err(ErrorCode::Invalid);
+        }
+        let master_tx = self.master_tx.borrow();
+        let Some(data) = master_tx.get(..len as usize) else {
+            return Err(ErrorCode::Size);
+        };
+        if self.acknowledged.get() {
+            self.master_writes
+                .borrow_mut()
+                .push((address as u8, data.into()));
+        }
+        Ok(())
+    }
+
+    // Fills `len` bytes of the master receive buffer with the read data.
+    fn master_read(&self, address: u32, len: u32) -> Result<(), ErrorCode> {
+        if address > MAX_ADDRESS {
+            return Err(ErrorCode::Invalid);
+        }
+        let mut master_rx = self.master_rx.borrow_mut();
+        let Some(buffer) = master_rx.get_mut(..len as usize) else {
+            return Err(ErrorCode::Size);
+        };
+        let data = self.master_read_data.borrow();
+        for (i, byte) in buffer.iter_mut().enumerate() {
+            *byte = data.get(i).copied().unwrap_or(0xFF);
+        }
+        Ok(())
+    }
+
+    // The status a completed master transfer reports.
+    fn status(&self) -> u32 {
+        match self.acknowledged.get() {
+            true => 0,
+            false => ErrorCode::NoAck as u32,
+        }
+    }
+}
+
+impl crate::fake::SyscallDriver for I2CMasterSlave {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(1)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn command(&self, command_number: u32, argument0: u32, _argument1: u32) -> CommandReturn {
+        // Transfers report their status in the second argument for writes, and
+        // the third for the others.
+        let upcall = match command_number {
+            command::EXISTS => return command_return::success(),
+            command::MASTER_WRITE => self
+                .master_write(argument0 & 0xFFFF, argument0 >> 16)
+                .map(|()| (0, self.status(), 0)),
+            command::MASTER_READ => self
+                .master_read(argument0 & 0xFFFF, argument0 >> 16)
+                .map(|()| (1, 0, self.status())),
+            command::MASTER_WRITE_READ => {
+                let address = argument0 & 0xFF;
+                self.master_write(address, argument0 >> 16)
+                    .and_then(|()| self.master_read(address, (argument0 >> 8) & 0xFF))
+                    .map(|()| (command::MASTER_WRITE_READ, 0, self.status()))
+            }
+            command::SLAVE_SET_ADDR => {
+                if argument0 > MAX_ADDRESS {
+                    return command_return::failure(ErrorCode::Invalid);
+                }
+                self.slave_address.set(Some(argument0 as u8));
+                return command_return::success();
+            }
+            command::SLAVE_START_LISTEN => {
+                if self.slave_address.get().is_none() {
+                    return command_return::failure(ErrorCode::Off);
+                }
+                self.listening.set(true);
+                self.deliver_pending_write();
+                return command_return::success();
+            }
+            command::SLAVE_READ_SEND => {
+                let slave_tx = self.slave_tx.borrow();
+                match slave_tx.get(..argument0 as usize) {
+                    Some(data) => {
+                        self.slave_reads.borrow_mut().push(data.into());
+                        Ok((command::SLAVE_READ_SEND, argument0, 0))
+                    }
+                    None => Err(ErrorCode::Size),
+                }
+            }
+            _ => return command_return::failure(ErrorCode::NoSupport),
+        };
+        match upcall {
+            Ok(upcall) => {
+                self.share_ref
+                    .schedule_upcall(subscribe::COMPLETE, upcall)
+                    .expect("Unable to schedule upcall {}");
+                command_return::success()
+            }
+            Err(error) => command_return::failure(error),
+        }
+    }
+
+    fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            allow_ro::MASTER_TX => Ok(self.master_tx.replace(buffer)),
+            allow_ro::SLAVE_TX => Ok(self.slave_tx.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            allow_rw::MASTER_RX => Ok(self.master_rx.replace(buffer)),
+            allow_rw::SLAVE_RX => Ok(self.slave_rx.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x20006;
+
+const MAX_ADDRESS: u32 = 0x7F;
+
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const MASTER_WRITE: u32 = 1;
+    pub const MASTER_READ: u32 = 2;
+    pub const SLAVE_START_LISTEN: u32 = 3;
+    pub const SLAVE_READ_SEND: u32 = 4;
+    pub const SLAVE_SET_ADDR: u32 = 6;
+    pub const MASTER_WRITE_READ: u32 = 7;
+}
+
+mod subscribe {
+    pub const COMPLETE: u32 = 0;
+}
+
+mod allow_ro {
+    pub const MASTER_TX: u32 = 0;
+    pub const SLAVE_TX: u32 = 2;
+}
+
+mod allow_rw {
+    pub const MASTER_RX: u32 = 1;
+    pub const SLAVE_RX: u32 = 3;
+}
@@ -0,0 +1,138 @@
+use crate::fake::{self, i2c_master_slave::*};
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::{share, DefaultConfig, Syscalls};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let i2c = I2CMasterSlave::new();
+    assert!(i2c.command(command::EXISTS, 0, 0).is_success());
+    assert_eq!(
+        i2c.command(command::MASTER_WRITE, 1 << 16 | 0x20, 0)
+            .get_failure(),
+        Some(ErrorCode::Size)
+    );
+    assert_eq!(
+        i2c.command(command::MASTER_WRITE, 0x80, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert_eq!(
+        i2c.command(command::SLAVE_START_LISTEN, 0, 0).get_failure(),
+        Some(ErrorCode::Off)
+    );
+    assert_eq!(
+        i2c.command(command::SLAVE_SET_ADDR, 0x80, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert!(i2c.command(command::SLAVE_SET_ADDR, 0x34, 0).is_success());
+    assert_eq!(i2c.slave_address(), Some(0x34));
+    assert!(i2c.command(command::SLAVE_START_LISTEN, 0, 0).is_success());
+    assert_eq!(
+        i2c.command(command::SLAVE_READ_SEND, 1, 0).get_failure(),
+        Some(ErrorCode::Size)
+    );
+    assert!(i2c.take_master_writes().is_empty());
+
+    assert!(i2c
+        .allow_readonly(allow_ro::MASTER_TX, RoAllowBuffer::default())
+        .is_ok());
+    assert!(i2c.allow_readonly(1, RoAllowBuffer::default()).is_err());
+    assert!(i2c
+        .allow_readwrite(allow_rw::SLAVE_RX, RwAllowBuffer::default())
+        .is_ok());
+    assert!(i2c.allow_readwrite(0, RwAllowBuffer::default()).is_err());
+}
+
+// Integration test that verifies I2CMasterSlave works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let i2c = I2CMasterSlave::new();
+    kernel.add_driver(&i2c);
+
+    // Master mode: only the requested length is written.
+    share::scope::<AllowRo<_, DRIVER_NUM, { allow_ro::MASTER_TX }>, _, _>(|allow| {
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, { allow_ro::MASTER_TX }>(
+            allow,
+            &[1, 2, 3],
+        )
+        .unwrap();
+        assert!(
+            fake::Syscalls::command(DRIVER_NUM, command::MASTER_WRITE, 2 << 16 | 0x20, 0)
+                .is_success()
+        );
+        assert_eq!(
+            fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::COMPLETE),
+            (0, 0, 0)
+        );
+        i2c.set_acknowledged(false);
+        assert!(
+            fake::Syscalls::command(DRIVER_NUM, command::MASTER_WRITE, 2 << 16 | 0x21, 0)
+                .is_success()
+        );
+        assert_eq!(
+            fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::COMPLETE),
+            (0, ErrorCode::NoAck as u32, 0)
+        );
+    });
+    assert_eq!(i2c.take_master_writes(), [(0x20, vec![1, 2])]);
+
+    i2c.set_acknowledged(true);
+    i2c.set_master_read_data(&[0xAB]);
+    let mut buffer = [0; 3];
+    share::scope::<AllowRw<_, DRIVER_NUM, { allow_rw::MASTER_RX }>, _, _>(|allow| {
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, { allow_rw::MASTER_RX }>(
+            allow,
+            &mut buffer,
+        )
+        .unwrap();
+        assert!(
+            fake::Syscalls::command(DRIVER_NUM, command::MASTER_READ, 2 << 16 | 0x20, 0)
+                .is_success()
+        );
+        assert_eq!(
+            fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::COMPLETE),
+            (1, 0, 0)
+        );
+    });
+    assert_eq!(buffer, [0xAB, 0xFF, 0]);
+
+    // Slave mode: writes wait until the process listens, and are truncated to
+    // its buffer.
+    assert!(fake::Syscalls::command(DRIVER_NUM, command::SLAVE_SET_ADDR, 0x34, 0).is_success());
+    i2c.write_to_slave(&[4, 5, 6, 7]);
+    assert!(i2c.has_pending_writes());
+    share::scope::<AllowRw<_, DRIVER_NUM, { allow_rw::SLAVE_RX }>, _, _>(|allow| {
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, { allow_rw::SLAVE_RX }>(
+            allow,
+            &mut buffer,
+        )
+        .unwrap();
+        assert!(
+            fake::Syscalls::command(DRIVER_NUM, command::SLAVE_START_LISTEN, 0, 0).is_success()
+        );
+        assert_eq!(
+            fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::COMPLETE),
+            (command::SLAVE_START_LISTEN, 3, 0)
+        );
+    });
+    assert_eq!(buffer, [4, 5, 6]);
+    assert!(!i2c.has_pending_writes());
+
+    share::scope::<AllowRo<_, DRIVER_NUM, { allow_ro::SLAVE_TX }>, _, _>(|allow| {
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, { allow_ro::SLAVE_TX }>(
+            allow,
+            &[8, 9],
+        )
+        .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, command::SLAVE_READ_SEND, 2, 0).is_success());
+        assert_eq!(
+            fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::COMPLETE),
+            (command::SLAVE_READ_SEND, 2, 0)
+        );
+    });
+    assert_eq!(i2c.take_slave_reads(), [vec![8, 9]]);
+}
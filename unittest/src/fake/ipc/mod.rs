@@ -0,0 +1,250 @@
+//! Fake implementation of the IPC API, documented here:
+//! https://github.com/tock/tock/blob/master/capsules/core/src/ipc.rs
+//!
+//! `Ipc` simulates a system with several processes. The process under test is
+//! process 0; the other processes are added with `add_process` and are driven
+//! by the test: they can notify the process under test (`notify_as_client`,
+//! `notify_as_service`), share a buffer with it (`set_buffer`), and record the
+//! notifications they receive from it (`take_notifications`). The buffers the
+//! process under test shares with them can be read and modified with
+//! `shared_buffer` and `write_shared_buffer`.
+//!
+//! The buffers simulated processes share live outside the process under test's
+//! address space, so the addresses passed to its upcalls are made up (see
+//! `buffer_address`) and cannot be dereferenced.
+
+use core::cell::RefCell;
+use libtock_platform::{CommandReturn, ErrorCode};
+use std::collections::HashMap;
+
+use crate::{DriverInfo, DriverShareRef, RoAllowBuffer, RwAllowBuffer};
+
+/// A notification sent by the process under test to a simulated process.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Notification {
+    /// The process under test notified the simulated process as a service
+    /// (i.e. it acted as a client).
+    Service,
+    /// The process under test notified the simulated process as a client (i.e.
+    /// it acted as a service).
+    Client,
+}
+
+struct Process {
+    name: String,
+    buffer: Vec<u8>,
+    notifications: Vec<Notification>,
+}
+
+pub struct Ipc<const NUM_PROCS: usize> {
+    // processes[0] is the process under test.
+    processes: RefCell<Vec<Process>>,
+    search_buffer: RefCell<RoAllowBuffer>,
+    // Buffers shared by the process under test, by the ID of the process they
+    // are shared with.
+    shared_buffers: RefCell<HashMap<u32, RwAllowBuffer>>,
+    share_ref: DriverShareRef,
+}
+
+impl<const NUM_PROCS: usize> Ipc<NUM_PROCS> {
+    /// Creates a fake IPC driver. `name` is the package name of the process
+    /// under test.
+    pub fn new(name: &str) -> std::rc::Rc<Ipc<NUM_PROCS>> {
+        assert!(NUM_PROCS > 0, "Ipc needs room for the process under test");
+        std::rc::Rc::new(Ipc {
+            processes: RefCell::new(vec![Process {
+                name: name.into(),
+                buffer: Vec::new(),
+                notifications: Vec::new(),
+            }]),
+            search_buffer: Default::default(),
+            shared_buffers: Default::default(),
+            share_ref: Default::default(),
+        })
+    }
+
+    /// Adds a simulated process with the given package name, and returns its
+    /// ID. Panics if there are already `NUM_PROCS` processes.
+    pub fn add_process(&self, name: &str) -> u32 {
+        let mut processes = self.processes.borrow_mut();
+        assert!(processes.len() < NUM_PROCS, "Too many processes");
+        processes.push(Process {
+            name: name.into(),
+            buffer: Vec::new(),
+            notifications: Vec::new(),
+        });
+        (processes.len() - 1) as u32
+    }
+
+    /// Sets the buffer simulated process `process` shares with the process
+    /// under test. An empty buffer means no buffer is shared.
+    pub fn set_buffer(&self, process: u32, buffer: &[u8]) {
+        self.with_process(process, |p| p.buffer = buffer.into());
+    }
+
+    /// Returns the buffer simulated process `process` shares with the process
+    /// under test.
+    pub fn buffer(&self, process: u32) -> Vec<u8> {
+        self.with_process(process, |p| p.buffer.clone())
+    }
+
+    /// Returns the made-up address passed to the process under test's upcalls
+    /// for the buffer simulated process `process` shares with it.
+    pub fn buffer_address(&self, process: u32) -> u32 {
+        BUFFER_ADDRESS_BASE + process * BUFFER_ADDRESS_STRIDE
+    }
+
+    /// Simulates `client` notifying the process under test, which acts as a
+    /// service.
+    pub fn notify_as_client(&self, client: u32) {
+        let args = self.upcall_args(client);
+        self.share_ref
+            .schedule_upcall(SERVICE_UPCALL, args)
+            .expect("Unable to schedule upcall {}");
+    }
+
+    /// Simulates `service` notifying the process under test, which acts as a
+    /// client.
+    pub fn notify_as_service(&self, service: u32) {
+        let args = self.upcall_args(service);
+        self.share_ref
+            .schedule_upcall(service + 1, args)
+            .expect("Unable to schedule upcall {}");
+    }
+
+    /// Returns the notifications simulated process `process` has received
+    /// from the process under test, and clears them.
+    pub fn take_notifications(&self, process: u32) -> Vec<Notification> {
+        self.with_process(process, |p| std::mem::take(&mut p.notifications))
+    }
+
+    /// Returns the contents of the buffer the process under test shares with
+    /// `process`. The buffer is empty if it did not share one.
+    pub fn shared_buffer(&self, process: u32) -> Vec<u8> {
+        self.shared_buffers
+            .borrow()
+            .get(&process)
+            .map_or(Vec::new(), |buffer| buffer.to_vec())
+    }
+
+    /// Writes `data` to the start of the buffer the process under test shares
+    /// with `process`, as `process` could through the buffer's address. Panics
+    /// if the buffer is too small.
+    pub fn write_shared_buffer(&self, process: u32, data: &[u8]) {
+        let mut shared_buffers = self.shared_buffers.borrow_mut();
+        let buffer = shared_buffers
+            .get_mut(&process)
+            .filter(|buffer| buffer.len() >= data.len())
+            .expect("The shared buffer is too small");
+        buffer[..data.len()].copy_from_slice(data);
+    }
+
+    fn with_process<R>(&self, process: u32, f: impl FnOnce(&mut Process) -> R) -> R {
+        let mut processes = self.processes.borrow_mut();
+        match processes.get_mut(process as usize) {
+            Some(p) if process != PROCESS_UNDER_TEST => f(p),
+            _ => panic!("No simulated process with ID {}", process),
+        }
+    }
+
+    // Returns the arguments of an upcall from `sender` to the process under
+    // test.
+    fn upcall_args(&self, sender: u32) -> (u32, u32, u32) {
+        let len = self.with_process(sender, |p| p.buffer.len() as u32);
+        match len {
+            0 => (sender, 0, 0),
+            _ => (sender, len, self.buffer_address(sender)),
+        }
+    }
+
+    fn notify(&self, target: u32, notification: Notification) -> CommandReturn {
+        let mut processes = self.processes.borrow_mut();
+        match processes.get_mut(target as usize) {
+            Some(p) if target != PROCESS_UNDER_TEST => {
+                p.notifications.push(notification);
+                crate::command_return::success()
+            }
+            _ => crate::command_return::failure(ErrorCode::Invalid),
+        }
+    }
+}
+
+impl<const NUM_PROCS: usize> crate::fake::SyscallDriver for Ipc<NUM_PROCS> {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(NUM_PROCS as u32 + 1)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            ALLOW_RO_SEARCH => Ok(self.search_buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        if buffer_num as usize >= NUM_PROCS {
+            return Err((buffer, ErrorCode::Invalid));
+        }
+        Ok(self
+            .shared_buffers
+            .borrow_mut()
+            .insert(buffer_num, buffer)
+            .unwrap_or_default())
+    }
+
+    fn command(&self, command_num: u32, argument0: u32, _argument1: u32) -> CommandReturn {
+        match command_num {
+            EXISTS => crate::command_return::success(),
+            DISCOVER => {
+                let search = self.search_buffer.borrow();
+                self.processes
+                    .borrow()
+                    .iter()
+                    .position(|p| p.name.as_bytes() == &search[..])
+                    .map_or(crate::command_return::failure(ErrorCode::NoDevice), |i| {
+                        crate::command_return::success_u32(i as u32)
+                    })
+            }
+            NOTIFY_SERVICE => self.notify(argument0, Notification::Service),
+            NOTIFY_CLIENT => self.notify(argument0, Notification::Client),
+            _ => crate::command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests;
+
+const DRIVER_NUM: u32 = 0x10000;
+
+// Command numbers
+const EXISTS: u32 = 0;
+const DISCOVER: u32 = 1;
+const NOTIFY_SERVICE: u32 = 2;
+const NOTIFY_CLIENT: u32 = 3;
+
+const SERVICE_UPCALL: u32 = 0;
+const ALLOW_RO_SEARCH: u32 = 0;
+
+const PROCESS_UNDER_TEST: u32 = 0;
+
+// Simulated processes' buffers appear to be at BUFFER_ADDRESS_BASE +
+// ID * BUFFER_ADDRESS_STRIDE.
+const BUFFER_ADDRESS_BASE: u32 = 0x2000_0000;
+const BUFFER_ADDRESS_STRIDE: u32 = 0x1_0000;
@@ -0,0 +1,93 @@
+use crate::fake::{self, ipc::*};
+use crate::{RoAllowBuffer, RwAllowBuffer};
+use libtock_platform::{share, DefaultConfig, Syscalls};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let ipc = Ipc::<3>::new("test");
+    let service = ipc.add_process("service");
+    assert_eq!(service, 1);
+
+    assert!(ipc.command(EXISTS, 0, 0).is_success());
+    assert_eq!(
+        ipc.command(NOTIFY_SERVICE, PROCESS_UNDER_TEST, 0)
+            .get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert_eq!(
+        ipc.command(NOTIFY_CLIENT, 2, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert!(ipc.command(NOTIFY_SERVICE, service, 0).is_success());
+    assert!(ipc.command(NOTIFY_CLIENT, service, 0).is_success());
+    assert_eq!(
+        ipc.take_notifications(service),
+        [Notification::Service, Notification::Client]
+    );
+    assert_eq!(ipc.take_notifications(service), []);
+
+    assert!(ipc
+        .allow_readonly(ALLOW_RO_SEARCH, RoAllowBuffer::default())
+        .is_ok());
+    assert!(ipc.allow_readonly(1, RoAllowBuffer::default()).is_err());
+    assert!(ipc.allow_readwrite(2, RwAllowBuffer::default()).is_ok());
+    assert!(ipc.allow_readwrite(3, RwAllowBuffer::default()).is_err());
+}
+
+// Integration test that verifies Ipc works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let ipc = Ipc::<4>::new("test");
+    kernel.add_driver(&ipc);
+    let client = ipc.add_process("client");
+    let service = ipc.add_process("service");
+
+    share::scope(|allow_ro| {
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, ALLOW_RO_SEARCH>(
+            allow_ro, b"service",
+        )
+        .unwrap();
+        assert_eq!(
+            fake::Syscalls::command(DRIVER_NUM, DISCOVER, 0, 0).get_success_u32(),
+            Some(service)
+        );
+    });
+    share::scope(|allow_ro| {
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, ALLOW_RO_SEARCH>(
+            allow_ro, b"missing",
+        )
+        .unwrap();
+        assert_eq!(
+            fake::Syscalls::command(DRIVER_NUM, DISCOVER, 0, 0).get_failure(),
+            Some(ErrorCode::NoDevice)
+        );
+    });
+
+    // The process under test shares a buffer with the service, which replies
+    // through it.
+    let mut buffer = *b"ping";
+    share::scope(|allow_rw| {
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, 2>(allow_rw, &mut buffer).unwrap();
+        assert_eq!(ipc.shared_buffer(service), b"ping");
+        ipc.write_shared_buffer(service, b"pong");
+    });
+    assert_eq!(&buffer, b"pong");
+    assert_eq!(ipc.shared_buffer(service), b"");
+
+    // Simulated processes notifying the process under test.
+    ipc.set_buffer(client, b"request");
+    ipc.notify_as_client(client);
+    assert_eq!(
+        fake::Syscalls::yield_wait_for(DRIVER_NUM, SERVICE_UPCALL),
+        (client, 7, ipc.buffer_address(client))
+    );
+    ipc.notify_as_service(service);
+    assert_eq!(
+        fake::Syscalls::yield_wait_for(DRIVER_NUM, service + 1),
+        (service, 0, 0)
+    );
+}
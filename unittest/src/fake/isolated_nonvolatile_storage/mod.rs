@@ -0,0 +1,274 @@
+//! Fake implementation of the isolated nonvolatile storage API.
+//!
+//! `IsolatedNonvolatileStorage` keeps the process' region in memory and
+//! simulates the flash the kernel stores it on. The region is divided into
+//! pages that start out erased (all bytes 0xFF). Like flash, a write can only
+//! clear bits; when a write needs to set a bit, the fake erases and reprograms
+//! the affected page, as the kernel would. Each page counts its erases, and
+//! once a page has been erased `max_erase_cycles` times it is worn out: writes
+//! that need to erase it again fail with `ErrorCode::Fail`.
+//!
+//! To test power-loss resilience, `cut_power_after` makes a later write only
+//! program the first half of its bytes before failing. Until `restore_power`
+//! is called, every read and write then fails with `ErrorCode::Off`, just as
+//! nothing runs on a board without power.
+
+use core::cell::{Cell, RefCell};
+use libtock_platform::{CommandReturn, ErrorCode};
+
+use crate::{command_return, DriverInfo, DriverShareRef, RoAllowBuffer, RwAllowBuffer};
+
+pub struct IsolatedNonvolatileStorage {
+    page_size: usize,
+    contents: RefCell<Vec<u8>>,
+    erase_counts: RefCell<Vec<u32>>,
+    max_erase_cycles: Cell<u32>,
+    writes_before_power_loss: Cell<Option<usize>>,
+    powered: Cell<bool>,
+
+    read_buffer: RefCell<RwAllowBuffer>,
+    write_buffer: RefCell<RoAllowBuffer>,
+
+    share_ref: DriverShareRef,
+}
+
+impl IsolatedNonvolatileStorage {
+    /// Creates an erased region of `size` bytes, with 512-byte pages.
+    pub fn new(size: usize) -> std::rc::Rc<IsolatedNonvolatileStorage> {
+        Self::new_with_page_size(size, DEFAULT_PAGE_SIZE)
+    }
+
+    /// Creates an erased region of `size` bytes. `size` must be a multiple of
+    /// `page_size`.
+    pub fn new_with_page_size(
+        size: usize,
+        page_size: usize,
+    ) -> std::rc::Rc<IsolatedNonvolatileStorage> {
+        assert!(
+            page_size > 0 && size % page_size == 0,
+            "Region size must be a multiple of the page size"
+        );
+        std::rc::Rc::new(IsolatedNonvolatileStorage {
+            page_size,
+            contents: RefCell::new(vec![ERASED; size]),
+            erase_counts: RefCell::new(vec![0; size / page_size]),
+            max_erase_cycles: Cell::new(u32::MAX),
+            writes_before_power_loss: Cell::new(None),
+            powered: Cell::new(true),
+            read_buffer: Default::default(),
+            write_buffer: Default::default(),
+            share_ref: Default::default(),
+        })
+    }
+
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    /// Returns the contents of the whole region.
+    pub fn contents(&self) -> Vec<u8> {
+        self.contents.borrow().clone()
+    }
+
+    /// Overwrites the start of the region, without erasing or wearing any
+    /// pages. Useful to set up the state left by a previous run.
+    pub fn set_contents(&self, data: &[u8]) {
+        self.contents.borrow_mut()[..data.len()].copy_from_slice(data);
+    }
+
+    /// Returns the number of times `page` has been erased.
+    pub fn erase_count(&self, page: usize) -> u32 {
+        self.erase_counts.borrow()[page]
+    }
+
+    /// Sets the number of erases each page survives.
+    pub fn set_max_erase_cycles(&self, max_erase_cycles: u32) {
+        self.max_erase_cycles.set(max_erase_cycles);
+    }
+
+    /// Lets `writes` more writes complete, then cuts the power in the middle
+    /// of the next one.
+    pub fn cut_power_after(&self, writes: usize) {
+        self.writes_before_power_loss.set(Some(writes));
+    }
+
+    /// Restores power after a power loss, as if the board rebooted. Also
+    /// cancels a pending `cut_power_after`.
+    pub fn restore_power(&self) {
+        self.writes_before_power_loss.set(None);
+        self.powered.set(true);
+    }
+
+    /// Returns false once the power has been cut, until it is restored.
+    pub fn is_powered(&self) -> bool {
+        self.powered.get()
+    }
+
+    // Programs `data` at `offset`, erasing pages where bits need to be set.
+    fn program(&self, offset: usize, data: &[u8]) -> Result<(), ErrorCode> {
+        let mut contents = self.contents.borrow_mut();
+        let mut erase_counts = self.erase_counts.borrow_mut();
+        let end = offset + data.len();
+        let mut page_start = offset - offset % self.page_size;
+        while page_start < end {
+            let page_end = page_start + self.page_size;
+            let start = page_start.max(offset);
+            let stop = page_end.min(end);
+            let new = &data[start - offset..stop - offset];
+            let needs_erase = contents[start..stop]
+                .iter()
+                .zip(new)
+                .any(|(&old, &new)| new & !old != 0);
+            if needs_erase {
+                let page = page_start / self.page_size;
+                if erase_counts[page] >= self.max_erase_cycles.get() {
+                    return Err(ErrorCode::Fail);
+                }
+                erase_counts[page] += 1;
+                // The kernel preserves the rest of the page across the erase.
+                contents[start..stop].copy_from_slice(new);
+            } else {
+                for (old, &new) in contents[start..stop].iter_mut().zip(new) {
+                    *old &= new;
+                }
+            }
+            page_start = page_end;
+        }
+        Ok(())
+    }
+
+    // Checks that [offset, offset + len) lies within the region.
+    fn check_range(&self, offset_low: u32, offset_high: u32, len: usize) -> Option<usize> {
+        let offset = (offset_high as u64) << 32 | offset_low as u64;
+        let end = offset.checked_add(len as u64)?;
+        if end > self.contents.borrow().len() as u64 {
+            return None;
+        }
+        Some(offset as usize)
+    }
+}
+
+impl crate::fake::SyscallDriver for IsolatedNonvolatileStorage {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(3)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn command(&self, command_number: u32, argument0: u32, argument1: u32) -> CommandReturn {
+        match command_number {
+            command::DRIVER_CHECK => command_return::success(),
+            command::GET_SIZE => {
+                let size = self.contents.borrow().len() as u64;
+                self.share_ref
+                    .schedule_upcall(
+                        subscribe::GET_SIZE_DONE,
+                        (0, size as u32, (size >> 32) as u32),
+                    )
+                    .expect("Unable to schedule upcall {}");
+                command_return::success()
+            }
+            command::READ | command::WRITE if !self.powered.get() => {
+                command_return::failure(ErrorCode::Off)
+            }
+            command::READ => {
+                let mut buffer = self.read_buffer.borrow_mut();
+                let offset = match self.check_range(argument0, argument1, buffer.len()) {
+                    Some(offset) => offset,
+                    None => return command_return::failure(ErrorCode::Invalid),
+                };
+                let len = buffer.len();
+                buffer.copy_from_slice(&self.contents.borrow()[offset..offset + len]);
+                self.share_ref
+                    .schedule_upcall(subscribe::READ_DONE, (0, len as u32, 0))
+                    .expect("Unable to schedule upcall {}");
+                command_return::success()
+            }
+            command::WRITE => {
+                let buffer = self.write_buffer.borrow();
+                let offset = match self.check_range(argument0, argument1, buffer.len()) {
+                    Some(offset) => offset,
+                    None => return command_return::failure(ErrorCode::Invalid),
+                };
+                let status = match self.writes_before_power_loss.get() {
+                    Some(0) => {
+                        // Tear the write: the power fails after the first
+                        // half of the bytes were programmed.
+                        self.powered.set(false);
+                        let _ = self.program(offset, &buffer[..buffer.len() / 2]);
+                        ErrorCode::Fail as u32
+                    }
+                    writes => {
+                        self.writes_before_power_loss.set(writes.map(|w| w - 1));
+                        match self.program(offset, &buffer) {
+                            Ok(()) => 0,
+                            Err(error) => error as u32,
+                        }
+                    }
+                };
+                self.share_ref
+                    .schedule_upcall(subscribe::WRITE_DONE, (status, buffer.len() as u32, 0))
+                    .expect("Unable to schedule upcall {}");
+                command_return::success()
+            }
+            _ => command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+
+    fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            allow_ro::WRITE => Ok(self.write_buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            allow_rw::READ => Ok(self.read_buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests;
+
+const ERASED: u8 = 0xFF;
+const DEFAULT_PAGE_SIZE: usize = 512;
+
+const DRIVER_NUM: u32 = 0x50004;
+
+mod command {
+    pub const DRIVER_CHECK: u32 = 0;
+    pub const GET_SIZE: u32 = 1;
+    pub const READ: u32 = 2;
+    pub const WRITE: u32 = 3;
+}
+
+mod subscribe {
+    pub const GET_SIZE_DONE: u32 = 0;
+    pub const READ_DONE: u32 = 1;
+    pub const WRITE_DONE: u32 = 2;
+}
+
+mod allow_ro {
+    pub const WRITE: u32 = 0;
+}
+
+mod allow_rw {
+    pub const READ: u32 = 0;
+}
@@ -0,0 +1,115 @@
+use crate::fake::{self, isolated_nonvolatile_storage::*};
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::{share, DefaultConfig, Syscalls};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let storage = IsolatedNonvolatileStorage::new(1024);
+    assert!(storage.command(command::DRIVER_CHECK, 0, 0).is_success());
+    assert!(storage.command(command::GET_SIZE, 0, 0).is_success());
+    assert!(storage.command(command::READ, 1024, 0).is_success());
+    assert_eq!(
+        storage.command(command::READ, 1025, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert_eq!(
+        storage.command(command::WRITE, 0, 1).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert!(storage
+        .allow_readonly(allow_ro::WRITE, RoAllowBuffer::default())
+        .is_ok());
+    assert!(storage.allow_readonly(1, RoAllowBuffer::default()).is_err());
+    assert!(storage
+        .allow_readwrite(allow_rw::READ, RwAllowBuffer::default())
+        .is_ok());
+    assert!(storage
+        .allow_readwrite(1, RwAllowBuffer::default())
+        .is_err());
+}
+
+#[test]
+fn erase_and_wear() {
+    let storage = IsolatedNonvolatileStorage::new_with_page_size(8, 4);
+    assert_eq!(storage.contents(), [0xFF; 8]);
+
+    // Clearing bits does not need an erase.
+    assert_eq!(storage.program(2, &[0xF0, 0x0F, 0x00]), Ok(()));
+    assert_eq!(storage.program(3, &[0x0E]), Ok(()));
+    assert_eq!(
+        storage.contents(),
+        [0xFF, 0xFF, 0xF0, 0x0E, 0x00, 0xFF, 0xFF, 0xFF]
+    );
+    assert_eq!((storage.erase_count(0), storage.erase_count(1)), (0, 0));
+
+    // Setting a bit erases the page, but keeps the rest of its contents.
+    assert_eq!(storage.program(2, &[0xF1]), Ok(()));
+    assert_eq!(
+        storage.contents(),
+        [0xFF, 0xFF, 0xF1, 0x0E, 0x00, 0xFF, 0xFF, 0xFF]
+    );
+    assert_eq!((storage.erase_count(0), storage.erase_count(1)), (1, 0));
+
+    storage.set_max_erase_cycles(1);
+    assert_eq!(storage.program(0, &[0x00]), Ok(()));
+    assert_eq!(storage.program(0, &[0x01]), Err(ErrorCode::Fail));
+    // The second page still has an erase left.
+    assert_eq!(storage.program(4, &[0xFF]), Ok(()));
+    assert_eq!(storage.program(4, &[0x01]), Ok(()));
+    assert_eq!(storage.erase_count(1), 1);
+}
+
+// Integration test that verifies IsolatedNonvolatileStorage works with
+// fake::Kernel and libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let storage = IsolatedNonvolatileStorage::new(1024);
+    kernel.add_driver(&storage);
+
+    // The size is reported by an upcall.
+    assert!(fake::Syscalls::command(DRIVER_NUM, command::GET_SIZE, 0, 0).is_success());
+    assert_eq!(
+        fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::GET_SIZE_DONE),
+        (0, 1024, 0)
+    );
+
+    share::scope::<AllowRo<_, DRIVER_NUM, { allow_ro::WRITE }>, _, _>(|allow| {
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, { allow_ro::WRITE }>(allow, b"hello")
+            .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, command::WRITE, 510, 0).is_success());
+    });
+    assert_eq!(
+        fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::WRITE_DONE),
+        (0, 5, 0)
+    );
+    assert_eq!(storage.contents()[510..515], *b"hello");
+
+    // A torn write keeps the first half of the data and reports a failure.
+    storage.cut_power_after(0);
+    share::scope::<AllowRo<_, DRIVER_NUM, { allow_ro::WRITE }>, _, _>(|allow| {
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, { allow_ro::WRITE }>(allow, b"world")
+            .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, command::WRITE, 0, 0).is_success());
+    });
+    assert_eq!(
+        fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::WRITE_DONE),
+        (ErrorCode::Fail as u32, 5, 0)
+    );
+    assert_eq!(storage.contents()[..5], *b"wo\xFF\xFF\xFF");
+    assert!(!storage.is_powered());
+
+    // Without power, nothing can be read or written until it is restored.
+    assert_eq!(
+        fake::Syscalls::command(DRIVER_NUM, command::READ, 0, 0).get_failure(),
+        Some(ErrorCode::Off)
+    );
+    assert_eq!(
+        fake::Syscalls::command(DRIVER_NUM, command::WRITE, 0, 0).get_failure(),
+        Some(ErrorCode::Off)
+    );
+    storage.restore_power();
+    assert!(fake::Syscalls::command(DRIVER_NUM, command::READ, 0, 0).is_success());
+}
@@ -38,6 +38,7 @@ impl Kernel {
                 syscall_log: Vec::new(),
                 upcall_queue: Default::default(),
                 memory_break: core::ptr::null(),
+                memory_map: Default::default(),
             }))
         });
         if let Some(old_kernel_data) = old_option {
@@ -105,6 +106,12 @@ impl Kernel {
         });
     }
 
+    /// Replaces the memory map reported by the Memop system call. The memory
+    /// map starts as `MemoryMap::default()`.
+    pub fn set_memory_map(&self, memory_map: MemoryMap) {
+        with_kernel_data(|kernel_data| kernel_data.unwrap().memory_map = memory_map);
+    }
+
     /// Returns the system call log and empties it.
     pub fn take_syscall_log(&self) -> Vec<SyscallLogEntry> {
         with_kernel_data(|kernel_data| std::mem::take(&mut kernel_data.unwrap().syscall_log))
@@ -117,10 +124,16 @@ impl Kernel {
         })
     }
 
-    /// Returns true if there are any pending upcalls.
+    /// Returns true if there are any pending upcalls. Null upcalls (which can
+    /// only be received by yield-wait-for) are not counted.
     pub fn is_upcall_pending() -> bool {
         with_kernel_data(|kernel_data| {
-            kernel_data.is_some_and(|kernel| !kernel.upcall_queue.is_empty())
+            kernel_data.is_some_and(|kernel| {
+                kernel
+                    .upcall_queue
+                    .iter()
+                    .any(|queue_entry| !queue_entry.upcall.is_null())
+            })
         })
     }
 }
@@ -130,3 +143,35 @@ impl Drop for Kernel {
         KERNEL_DATA.with(|kernel_data| kernel_data.replace(None));
     }
 }
+
+/// The process memory layout reported by `fake::Kernel`'s implementation of the
+/// Memop system call. Addresses are given as integers, as the fake kernel never
+/// dereferences them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MemoryMap {
+    /// Returned by memop 2 (`memop_app_ram_start`).
+    pub app_ram_start: usize,
+    /// Returned by memop 3 (`memop_app_ram_end`).
+    pub app_ram_end: usize,
+    /// Returned by memop 4 (`memop_flash_start`).
+    pub flash_start: usize,
+    /// Returned by memop 5 (`memop_flash_end`).
+    pub flash_end: usize,
+    /// Returned by memop 6 (`memop_grant_start`).
+    pub grant_start: usize,
+    /// The writeable flash regions, as used by memop 7, 8, and 9.
+    pub writeable_flash_regions: Vec<std::ops::Range<usize>>,
+}
+
+impl Default for MemoryMap {
+    fn default() -> MemoryMap {
+        MemoryMap {
+            app_ram_start: 0x123400,
+            app_ram_end: 0x133400,
+            flash_start: 0x40000,
+            flash_end: 0x50000,
+            grant_start: 0x132400,
+            writeable_flash_regions: Vec::new(),
+        }
+    }
+}
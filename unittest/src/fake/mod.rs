@@ -10,44 +10,72 @@
 //! (e.g. `fake::Console`).
 
 mod adc;
+mod aes;
 mod air_quality;
 mod alarm;
 mod ambient_light;
+mod ble;
 mod buttons;
 mod buzzer;
+mod can;
 mod console;
+mod crc;
+mod ecdsa_p256;
 mod gpio;
+mod hmac;
+mod i2c_master_slave;
 pub mod ieee802154;
+mod ipc;
+mod isolated_nonvolatile_storage;
 mod kernel;
 mod key_value;
 mod leds;
 mod low_level_debug;
 mod ninedof;
 mod proximity;
+mod pwm;
+pub mod screen;
+mod sha;
 mod sound_pressure;
 mod syscall_driver;
 mod syscalls;
 mod temperature;
+pub mod touch;
+pub mod udp;
 
 pub use adc::Adc;
+pub use aes::Aes;
 pub use air_quality::AirQuality;
 pub use alarm::Alarm;
 pub use ambient_light::AmbientLight;
+pub use ble::{Advertisement, Ble};
 pub use buttons::Buttons;
 pub use buzzer::Buzzer;
+pub use can::{Can, CanFrame};
 pub use console::Console;
+pub use crc::Crc;
+pub use ecdsa_p256::EcdsaP256;
 pub use gpio::{Gpio, GpioMode, InterruptEdge, PullMode};
+pub use hmac::Hmac;
+pub use i2c_master_slave::I2CMasterSlave;
 pub use ieee802154::Ieee802154Phy;
-pub use kernel::Kernel;
+pub use ipc::{Ipc, Notification};
+pub use isolated_nonvolatile_storage::IsolatedNonvolatileStorage;
+pub use kernel::{Kernel, MemoryMap};
 pub use key_value::KeyValue;
 pub use leds::Leds;
 pub use low_level_debug::{LowLevelDebug, Message};
 pub use ninedof::{NineDof, NineDofData};
 pub use proximity::Proximity;
+pub use pwm::{Pwm, PwmOutput};
+pub use screen::Screen;
+pub use sha::Sha;
 pub use sound_pressure::SoundPressure;
 pub use syscall_driver::SyscallDriver;
 pub use syscalls::Syscalls;
 pub use temperature::Temperature;
+pub use touch::Touch;
+pub use udp::Udp;
 
 #[cfg(test)]
 mod kernel_tests;
@@ -0,0 +1,104 @@
+//! Fake implementation of the PWM API.
+//!
+//! Like the real API, `Pwm` controls a set of PWM pins. It provides the
+//! function `output` to retrieve the signal a pin is generating, and
+//! `set_max_frequency` to change the highest frequency the pins accept.
+
+use core::cell::Cell;
+use libtock_platform::{CommandReturn, ErrorCode};
+
+use crate::{command_return, DriverInfo};
+
+/// The signal a PWM pin is generating. `duty_cycle` is out of 10000.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PwmOutput {
+    pub frequency_hz: u32,
+    pub duty_cycle: u32,
+}
+
+pub struct Pwm<const NUM_PINS: usize> {
+    pins: [Cell<Option<PwmOutput>>; NUM_PINS],
+    max_frequency_hz: Cell<u32>,
+}
+
+impl<const NUM_PINS: usize> Pwm<NUM_PINS> {
+    pub fn new() -> std::rc::Rc<Pwm<NUM_PINS>> {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const STOPPED: Cell<Option<PwmOutput>> = Cell::new(None);
+        std::rc::Rc::new(Pwm {
+            pins: [STOPPED; NUM_PINS],
+            max_frequency_hz: Cell::new(DEFAULT_MAX_FREQUENCY_HZ),
+        })
+    }
+
+    pub fn set_max_frequency(&self, max_frequency_hz: u32) {
+        self.max_frequency_hz.set(max_frequency_hz);
+    }
+
+    /// Returns the signal `pin` is generating, or `None` if it is stopped.
+    pub fn output(&self, pin: u32) -> Option<PwmOutput> {
+        self.pins.get(pin as usize).and_then(Cell::get)
+    }
+}
+
+impl<const NUM_PINS: usize> crate::fake::SyscallDriver for Pwm<NUM_PINS> {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM)
+    }
+
+    fn command(&self, command_number: u32, argument0: u32, argument1: u32) -> CommandReturn {
+        match command_number {
+            EXISTS => command_return::success(),
+            PWM_START => {
+                let pin = match self.pins.get((argument0 & 0xFFFF) as usize) {
+                    Some(pin) => pin,
+                    None => return command_return::failure(ErrorCode::Invalid),
+                };
+                let duty_cycle = argument0 >> 16;
+                if argument1 > self.max_frequency_hz.get() || duty_cycle > MAX_DUTY_CYCLE {
+                    return command_return::failure(ErrorCode::Invalid);
+                }
+                pin.set(Some(PwmOutput {
+                    frequency_hz: argument1,
+                    duty_cycle,
+                }));
+                command_return::success()
+            }
+            PWM_STOP => match self.pins.get(argument0 as usize) {
+                Some(pin) => {
+                    pin.set(None);
+                    command_return::success()
+                }
+                None => command_return::failure(ErrorCode::Invalid),
+            },
+            PWM_MAX_FREQUENCY => {
+                if argument0 < NUM_PINS as u32 {
+                    command_return::success_u32(self.max_frequency_hz.get())
+                } else {
+                    command_return::failure(ErrorCode::Invalid)
+                }
+            }
+            PWM_COUNT => command_return::success_u32(NUM_PINS as u32),
+            _ => command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DEFAULT_MAX_FREQUENCY_HZ: u32 = 1_000_000;
+const MAX_DUTY_CYCLE: u32 = 10000;
+
+const DRIVER_NUM: u32 = 0x10;
+
+// Command IDs
+const EXISTS: u32 = 0;
+const PWM_START: u32 = 1;
+const PWM_STOP: u32 = 2;
+const PWM_MAX_FREQUENCY: u32 = 3;
+const PWM_COUNT: u32 = 4;
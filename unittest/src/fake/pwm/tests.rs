@@ -0,0 +1,74 @@
+use crate::fake;
+use fake::pwm::*;
+use libtock_platform::ErrorCode;
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let pwm = Pwm::<2>::new();
+
+    assert!(pwm.command(EXISTS, 0, 0).is_success());
+    assert_eq!(pwm.command(PWM_COUNT, 0, 0).get_success_u32(), Some(2));
+    assert_eq!(
+        pwm.command(PWM_MAX_FREQUENCY, 1, 0).get_success_u32(),
+        Some(DEFAULT_MAX_FREQUENCY_HZ)
+    );
+    assert_eq!(
+        pwm.command(PWM_MAX_FREQUENCY, 2, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+
+    assert!(pwm.command(PWM_START, 2500 << 16 | 1, 500).is_success());
+    assert_eq!(pwm.output(0), None);
+    assert_eq!(
+        pwm.output(1),
+        Some(PwmOutput {
+            frequency_hz: 500,
+            duty_cycle: 2500
+        })
+    );
+    assert_eq!(
+        pwm.command(PWM_START, 2, 500).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert_eq!(
+        pwm.command(PWM_START, 10001 << 16, 500).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    pwm.set_max_frequency(100);
+    assert_eq!(
+        pwm.command(PWM_START, 0, 500).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+
+    assert!(pwm.command(PWM_STOP, 1, 0).is_success());
+    assert_eq!(pwm.output(1), None);
+    assert_eq!(
+        pwm.command(PWM_STOP, 2, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+}
+
+// Integration test that verifies Pwm works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    use libtock_platform::Syscalls;
+    let kernel = fake::Kernel::new();
+    let pwm = Pwm::<4>::new();
+    kernel.add_driver(&pwm);
+
+    assert_eq!(
+        fake::Syscalls::command(DRIVER_NUM, PWM_COUNT, 0, 0).get_success_u32(),
+        Some(4)
+    );
+    assert!(fake::Syscalls::command(DRIVER_NUM, PWM_START, 10000 << 16 | 3, 1000).is_success());
+    assert_eq!(
+        pwm.output(3),
+        Some(PwmOutput {
+            frequency_hz: 1000,
+            duty_cycle: 10000
+        })
+    );
+}
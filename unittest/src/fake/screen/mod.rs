@@ -0,0 +1,358 @@
+//! Fake implementation of the screen API.
+//!
+//! `Screen` keeps a framebuffer that writes and fills are applied to, which
+//! tests can inspect with `framebuffer` and `pixel`. The framebuffer is laid
+//! out in the screen's current orientation and pixel format, and is cleared
+//! whenever the resolution, rotation, or pixel format changes.
+//!
+//! The fake supports every pixel format except `Mono`, but only advertises the
+//! ones passed to `set_supported_pixel_formats` (RGB565 and RGB888 by
+//! default).
+
+use core::cell::{Cell, RefCell};
+use libtock_platform::{CommandReturn, ErrorCode};
+
+use crate::{command_return, DriverInfo, DriverShareRef, RoAllowBuffer};
+
+pub struct Screen {
+    resolution_modes: Vec<(u32, u32)>,
+    pixel_formats: RefCell<Vec<u32>>,
+
+    resolution: Cell<(u32, u32)>,
+    rotation: Cell<u32>,
+    pixel_format: Cell<u32>,
+    brightness: Cell<u16>,
+    powered: Cell<bool>,
+    inverted: Cell<bool>,
+
+    write_frame: Cell<(u32, u32, u32, u32)>,
+    // The number of pixels written to the write frame since it was set.
+    cursor: Cell<usize>,
+    framebuffer: RefCell<Vec<u8>>,
+
+    write_buffer: RefCell<RoAllowBuffer>,
+
+    share_ref: DriverShareRef,
+}
+
+impl Screen {
+    /// Creates a 240x320 screen with a second, 120x160 resolution mode.
+    pub fn new() -> std::rc::Rc<Screen> {
+        Self::new_with_resolution_modes(&[(240, 320), (120, 160)])
+    }
+
+    /// Creates a screen that supports the given (width, height) resolutions.
+    /// The screen starts at the first one.
+    pub fn new_with_resolution_modes(modes: &[(u32, u32)]) -> std::rc::Rc<Screen> {
+        let screen = Screen {
+            resolution_modes: modes.into(),
+            pixel_formats: RefCell::new(vec![RGB_565, RGB_888]),
+            resolution: Cell::new(modes[0]),
+            rotation: Cell::new(0),
+            pixel_format: Cell::new(RGB_565),
+            brightness: Cell::new(0),
+            powered: Cell::new(false),
+            inverted: Cell::new(false),
+            write_frame: Cell::new((0, 0, modes[0].0, modes[0].1)),
+            cursor: Cell::new(0),
+            framebuffer: Default::default(),
+            write_buffer: Default::default(),
+            share_ref: Default::default(),
+        };
+        screen.reset_framebuffer();
+        std::rc::Rc::new(screen)
+    }
+
+    /// Sets the pixel formats the screen reports as supported. The current
+    /// pixel format becomes the first one.
+    pub fn set_supported_pixel_formats(&self, formats: &[u32]) {
+        assert!(
+            formats.iter().all(|&f| bytes_per_pixel(f).is_some()),
+            "Unsupported pixel format"
+        );
+        self.pixel_formats.replace(formats.into());
+        self.pixel_format.set(formats[0]);
+        self.reset_framebuffer();
+    }
+
+    /// Returns the width and height of the screen, taking the rotation into
+    /// account.
+    pub fn resolution(&self) -> (u32, u32) {
+        let (width, height) = self.resolution.get();
+        match self.rotation.get() {
+            1 | 3 => (height, width),
+            _ => (width, height),
+        }
+    }
+
+    pub fn rotation(&self) -> u32 {
+        self.rotation.get()
+    }
+
+    pub fn pixel_format(&self) -> u32 {
+        self.pixel_format.get()
+    }
+
+    pub fn brightness(&self) -> u16 {
+        self.brightness.get()
+    }
+
+    pub fn is_powered(&self) -> bool {
+        self.powered.get()
+    }
+
+    pub fn is_inverted(&self) -> bool {
+        self.inverted.get()
+    }
+
+    /// Returns the write frame as (x, y, width, height).
+    pub fn write_frame(&self) -> (u32, u32, u32, u32) {
+        self.write_frame.get()
+    }
+
+    /// Returns the contents of the framebuffer, row by row.
+    pub fn framebuffer(&self) -> Vec<u8> {
+        self.framebuffer.borrow().clone()
+    }
+
+    /// Returns the pixel at (x, y), with its bytes interpreted as a big-endian
+    /// integer (so an RGB565 pixel is returned as its 16-bit value, and an
+    /// RGB888 pixel as 0xRRGGBB).
+    pub fn pixel(&self, x: u32, y: u32) -> u32 {
+        let (width, height) = self.resolution();
+        assert!(x < width && y < height, "Pixel out of bounds");
+        let bytes = self.bytes_per_pixel();
+        let start = (y * width + x) as usize * bytes;
+        self.framebuffer.borrow()[start..start + bytes]
+            .iter()
+            .fold(0, |value, &byte| value << 8 | byte as u32)
+    }
+
+    fn bytes_per_pixel(&self) -> usize {
+        bytes_per_pixel(self.pixel_format.get()).unwrap()
+    }
+
+    // Clears the framebuffer and resets the write frame to the whole screen.
+    fn reset_framebuffer(&self) {
+        let (width, height) = self.resolution();
+        let len = (width * height) as usize * self.bytes_per_pixel();
+        self.framebuffer.replace(vec![0; len]);
+        self.write_frame.set((0, 0, width, height));
+        self.cursor.set(0);
+    }
+
+    // Writes `pixel` to the next position in the write frame, wrapping around
+    // to the start of the frame at the end.
+    fn write_pixel(&self, pixel: &[u8]) {
+        let (x, y, width, height) = self.write_frame.get();
+        let index = self.cursor.get() % (width * height) as usize;
+        self.cursor.set(index + 1);
+        let x = x + index as u32 % width;
+        let y = y + index as u32 / width;
+        let start = (y * self.resolution().0 + x) as usize * pixel.len();
+        self.framebuffer.borrow_mut()[start..start + pixel.len()].copy_from_slice(pixel);
+    }
+
+    fn done(&self) -> CommandReturn {
+        self.done_with(0, 0)
+    }
+
+    // Completes a command that reports values through the DONE upcall, as the
+    // getters do.
+    fn done_with(&self, data1: u32, data2: u32) -> CommandReturn {
+        self.share_ref
+            .schedule_upcall(subscribe::DONE, (0, data1, data2))
+            .expect("Unable to schedule upcall {}");
+        command_return::success()
+    }
+}
+
+impl crate::fake::SyscallDriver for Screen {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(1)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn command(&self, command_number: u32, argument0: u32, argument1: u32) -> CommandReturn {
+        match command_number {
+            command::EXISTS => command_return::success(),
+            command::SET_POWER => {
+                self.powered.set(argument0 != 0);
+                self.done()
+            }
+            command::SET_BRIGHTNESS => match u16::try_from(argument0) {
+                Ok(brightness) => {
+                    self.brightness.set(brightness);
+                    self.done()
+                }
+                Err(_) => command_return::failure(ErrorCode::Invalid),
+            },
+            command::INVERT_ON => {
+                self.inverted.set(true);
+                self.done()
+            }
+            command::INVERT_OFF => {
+                self.inverted.set(false);
+                self.done()
+            }
+            command::RESOLUTION_MODES_COUNT => {
+                command_return::success_u32(self.resolution_modes.len() as u32)
+            }
+            command::RESOLUTION_MODE => match self.resolution_modes.get(argument0 as usize) {
+                Some(&(width, height)) => command_return::success_2_u32(width, height),
+                None => command_return::failure(ErrorCode::Invalid),
+            },
+            command::PIXEL_FORMATS_COUNT => {
+                command_return::success_u32(self.pixel_formats.borrow().len() as u32)
+            }
+            command::PIXEL_FORMAT => match self.pixel_formats.borrow().get(argument0 as usize) {
+                Some(&format) => command_return::success_u32(format),
+                None => command_return::failure(ErrorCode::Invalid),
+            },
+            command::GET_ROTATION => self.done_with(self.rotation.get(), 0),
+            command::SET_ROTATION => {
+                if argument0 > 3 {
+                    return command_return::failure(ErrorCode::Invalid);
+                }
+                self.rotation.set(argument0);
+                self.reset_framebuffer();
+                self.done()
+            }
+            command::GET_RESOLUTION => {
+                let (width, height) = self.resolution();
+                self.done_with(width, height)
+            }
+            command::SET_RESOLUTION => {
+                if !self.resolution_modes.contains(&(argument0, argument1)) {
+                    return command_return::failure(ErrorCode::Invalid);
+                }
+                self.resolution.set((argument0, argument1));
+                self.reset_framebuffer();
+                self.done()
+            }
+            command::GET_PIXEL_FORMAT => self.done_with(self.pixel_format.get(), 0),
+            command::SET_PIXEL_FORMAT => {
+                if !self.pixel_formats.borrow().contains(&argument0) {
+                    return command_return::failure(ErrorCode::Invalid);
+                }
+                self.pixel_format.set(argument0);
+                self.reset_framebuffer();
+                self.done()
+            }
+            command::SET_WRITE_FRAME => {
+                let (x, y) = (argument0 >> 16, argument0 & 0xFFFF);
+                let (width, height) = (argument1 >> 16, argument1 & 0xFFFF);
+                let (screen_width, screen_height) = self.resolution();
+                if width == 0
+                    || height == 0
+                    || x + width > screen_width
+                    || y + height > screen_height
+                {
+                    return command_return::failure(ErrorCode::Invalid);
+                }
+                self.write_frame.set((x, y, width, height));
+                self.cursor.set(0);
+                self.done()
+            }
+            command::WRITE => {
+                let buffer = self.write_buffer.borrow();
+                let len = argument0 as usize;
+                let bytes = self.bytes_per_pixel();
+                if len > buffer.len() || len % bytes != 0 {
+                    return command_return::failure(ErrorCode::Invalid);
+                }
+                for pixel in buffer[..len].chunks_exact(bytes) {
+                    self.write_pixel(pixel);
+                }
+                self.done()
+            }
+            command::FILL => {
+                let buffer = self.write_buffer.borrow();
+                let bytes = self.bytes_per_pixel();
+                if buffer.len() < bytes {
+                    return command_return::failure(ErrorCode::Invalid);
+                }
+                let (_, _, width, height) = self.write_frame.get();
+                self.cursor.set(0);
+                for _ in 0..width * height {
+                    self.write_pixel(&buffer[..bytes]);
+                }
+                self.cursor.set(0);
+                self.done()
+            }
+            _ => command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+
+    fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            allow_ro::WRITE_BUFFER => Ok(self.write_buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests;
+
+// Pixel formats, as numbered by the kernel.
+pub const MONO: u32 = 0;
+pub const RGB_233: u32 = 1;
+pub const RGB_565: u32 = 2;
+pub const RGB_888: u32 = 3;
+pub const ARGB_8888: u32 = 4;
+
+fn bytes_per_pixel(format: u32) -> Option<usize> {
+    match format {
+        RGB_233 => Some(1),
+        RGB_565 => Some(2),
+        RGB_888 => Some(3),
+        ARGB_8888 => Some(4),
+        _ => None,
+    }
+}
+
+const DRIVER_NUM: u32 = 0x90001;
+
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const SET_POWER: u32 = 2;
+    pub const SET_BRIGHTNESS: u32 = 3;
+    pub const INVERT_ON: u32 = 4;
+    pub const INVERT_OFF: u32 = 5;
+
+    pub const RESOLUTION_MODES_COUNT: u32 = 11;
+    pub const RESOLUTION_MODE: u32 = 12;
+    pub const PIXEL_FORMATS_COUNT: u32 = 13;
+    pub const PIXEL_FORMAT: u32 = 14;
+
+    pub const GET_ROTATION: u32 = 21;
+    pub const SET_ROTATION: u32 = 22;
+    pub const GET_RESOLUTION: u32 = 23;
+    pub const SET_RESOLUTION: u32 = 24;
+    pub const GET_PIXEL_FORMAT: u32 = 25;
+    pub const SET_PIXEL_FORMAT: u32 = 26;
+
+    pub const SET_WRITE_FRAME: u32 = 100;
+    pub const WRITE: u32 = 200;
+    pub const FILL: u32 = 300;
+}
+
+mod subscribe {
+    pub const DONE: u32 = 0;
+}
+
+mod allow_ro {
+    pub const WRITE_BUFFER: u32 = 0;
+}
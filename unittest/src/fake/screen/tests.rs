@@ -0,0 +1,134 @@
+use crate::fake::{self, screen::*};
+use crate::RoAllowBuffer;
+use libtock_platform::{share, DefaultConfig, Syscalls};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let screen = Screen::new_with_resolution_modes(&[(4, 2), (2, 1)]);
+    assert!(screen.command(command::EXISTS, 0, 0).is_success());
+
+    assert_eq!(
+        screen
+            .command(command::RESOLUTION_MODES_COUNT, 0, 0)
+            .get_success_u32(),
+        Some(2)
+    );
+    assert_eq!(
+        screen
+            .command(command::RESOLUTION_MODE, 1, 0)
+            .get_success_2_u32(),
+        Some((2, 1))
+    );
+    assert_eq!(
+        screen.command(command::RESOLUTION_MODE, 2, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert!(screen.command(command::GET_RESOLUTION, 0, 0).is_success());
+    assert!(screen.command(command::SET_ROTATION, 1, 0).is_success());
+    assert_eq!(screen.resolution(), (2, 4));
+    assert_eq!(
+        screen.command(command::SET_ROTATION, 4, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+
+    assert_eq!(
+        screen
+            .command(command::PIXEL_FORMAT, 1, 0)
+            .get_success_u32(),
+        Some(RGB_888)
+    );
+    assert_eq!(
+        screen
+            .command(command::SET_PIXEL_FORMAT, ARGB_8888, 0)
+            .get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert!(screen
+        .command(command::SET_PIXEL_FORMAT, RGB_888, 0)
+        .is_success());
+    assert_eq!(screen.framebuffer().len(), 2 * 4 * 3);
+
+    assert_eq!(
+        screen
+            .command(command::SET_WRITE_FRAME, 1 << 16, 2 << 16 | 1)
+            .get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert!(screen
+        .command(command::SET_WRITE_FRAME, 1 << 16 | 3, 1 << 16 | 1)
+        .is_success());
+    assert_eq!(screen.write_frame(), (1, 3, 1, 1));
+    // Nothing has been shared to write from.
+    assert_eq!(
+        screen.command(command::WRITE, 3, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert_eq!(
+        screen.command(command::FILL, 0, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+
+    assert!(screen.command(command::SET_BRIGHTNESS, 300, 0).is_success());
+    assert_eq!(screen.brightness(), 300);
+    assert!(screen.command(command::SET_POWER, 1, 0).is_success());
+    assert!(screen.is_powered());
+    assert!(screen.command(command::INVERT_ON, 0, 0).is_success());
+    assert!(screen.is_inverted());
+    assert!(screen.command(command::INVERT_OFF, 0, 0).is_success());
+    assert!(!screen.is_inverted());
+
+    assert!(screen
+        .allow_readonly(allow_ro::WRITE_BUFFER, RoAllowBuffer::default())
+        .is_ok());
+    assert!(screen.allow_readonly(1, RoAllowBuffer::default()).is_err());
+}
+
+// Integration test that verifies Screen works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    use libtock_platform::allow_ro::AllowRo;
+    let kernel = fake::Kernel::new();
+    let screen = Screen::new_with_resolution_modes(&[(3, 2)]);
+    kernel.add_driver(&screen);
+
+    assert!(
+        fake::Syscalls::command(DRIVER_NUM, command::SET_WRITE_FRAME, 1, 2 << 16 | 1).is_success()
+    );
+    assert_eq!(
+        fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::DONE),
+        (0, 0, 0)
+    );
+
+    // The getters report their values through the upcall.
+    assert!(fake::Syscalls::command(DRIVER_NUM, command::GET_RESOLUTION, 0, 0).is_success());
+    assert_eq!(
+        fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::DONE),
+        (0, 3, 2)
+    );
+    assert!(fake::Syscalls::command(DRIVER_NUM, command::GET_PIXEL_FORMAT, 0, 0).is_success());
+    assert_eq!(
+        fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::DONE),
+        (0, RGB_565, 0)
+    );
+
+    // Three pixels are written to a 2x1 frame, so the last one wraps around.
+    let pixels = [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC];
+    share::scope::<AllowRo<_, DRIVER_NUM, { allow_ro::WRITE_BUFFER }>, _, _>(|allow| {
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, { allow_ro::WRITE_BUFFER }>(
+            allow, &pixels,
+        )
+        .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, command::WRITE, 6, 0).is_success());
+    });
+    assert_eq!(
+        fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::DONE),
+        (0, 0, 0)
+    );
+    assert_eq!(screen.pixel(0, 1), 0x9ABC);
+    assert_eq!(screen.pixel(1, 1), 0x5678);
+    assert_eq!(screen.pixel(2, 1), 0);
+    assert_eq!(screen.pixel(0, 0), 0);
+}
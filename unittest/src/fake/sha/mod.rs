@@ -0,0 +1,211 @@
+//! Fake implementation of the SHA API.
+//!
+//! `Sha` computes real SHA-256, SHA-384 and SHA-512 digests on the host, so
+//! tests can compare against known vectors. `updates` counts the chunks of data
+//! the process has added to digests.
+
+use core::cell::{Cell, RefCell};
+use libtock_platform::{CommandReturn, ErrorCode};
+use sha2::Digest;
+
+use crate::{command_return, DriverInfo, DriverShareRef, RoAllowBuffer, RwAllowBuffer};
+
+pub struct Sha {
+    algorithm: Cell<Option<u32>>,
+    hasher: RefCell<Option<Hasher>>,
+    updates: Cell<usize>,
+
+    data_buffer: RefCell<RoAllowBuffer>,
+    digest_buffer: RefCell<RwAllowBuffer>,
+
+    share_ref: DriverShareRef,
+}
+
+enum Hasher {
+    Sha256(sha2::Sha256),
+    Sha384(sha2::Sha384),
+    Sha512(sha2::Sha512),
+}
+
+impl Hasher {
+    fn new(algorithm: u32) -> Option<Hasher> {
+        match algorithm {
+            SHA_256 => Some(Hasher::Sha256(sha2::Sha256::new())),
+            SHA_384 => Some(Hasher::Sha384(sha2::Sha384::new())),
+            SHA_512 => Some(Hasher::Sha512(sha2::Sha512::new())),
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha256(hasher) => hasher.update(data),
+            Hasher::Sha384(hasher) => hasher.update(data),
+            Hasher::Sha512(hasher) => hasher.update(data),
+        }
+    }
+
+    fn digest_len(&self) -> usize {
+        match self {
+            Hasher::Sha256(_) => 32,
+            Hasher::Sha384(_) => 48,
+            Hasher::Sha512(_) => 64,
+        }
+    }
+
+    fn finish(self, digest: &mut [u8]) {
+        match self {
+            Hasher::Sha256(hasher) => digest.copy_from_slice(&hasher.finalize()),
+            Hasher::Sha384(hasher) => digest.copy_from_slice(&hasher.finalize()),
+            Hasher::Sha512(hasher) => digest.copy_from_slice(&hasher.finalize()),
+        }
+    }
+}
+
+impl Sha {
+    pub fn new() -> std::rc::Rc<Sha> {
+        std::rc::Rc::new(Sha {
+            algorithm: Cell::new(None),
+            hasher: RefCell::new(None),
+            updates: Cell::new(0),
+            data_buffer: Default::default(),
+            digest_buffer: Default::default(),
+            share_ref: Default::default(),
+        })
+    }
+
+    /// Returns the algorithm selected by the process, if any.
+    pub fn algorithm(&self) -> Option<u32> {
+        self.algorithm.get()
+    }
+
+    /// Returns the number of times data was added to a digest.
+    pub fn updates(&self) -> usize {
+        self.updates.get()
+    }
+
+    // Adds the data buffer to the digest in progress.
+    fn update(&self) -> Result<(), ErrorCode> {
+        let mut hasher = self.hasher.borrow_mut();
+        let hasher = hasher.as_mut().ok_or(ErrorCode::Reserve)?;
+        hasher.update(&self.data_buffer.borrow());
+        self.updates.set(self.updates.get() + 1);
+        Ok(())
+    }
+
+    // Writes the digest in progress to the digest buffer, and starts a new one.
+    fn finish(&self) -> Result<(), ErrorCode> {
+        let mut digest = self.digest_buffer.borrow_mut();
+        let mut hasher = self.hasher.borrow_mut();
+        let len = hasher.as_ref().ok_or(ErrorCode::Reserve)?.digest_len();
+        if digest.len() < len {
+            return Err(ErrorCode::Size);
+        }
+        let finished = hasher.take().unwrap();
+        *hasher = self.algorithm.get().and_then(Hasher::new);
+        finished.finish(&mut digest[..len]);
+        Ok(())
+    }
+
+    fn schedule_done(&self, result: Result<(), ErrorCode>) {
+        let status = match result {
+            Ok(()) => 0,
+            Err(error) => error as u32,
+        };
+        self.share_ref
+            .schedule_upcall(subscribe::DONE, (status, 0, 0))
+            .expect("Unable to schedule upcall {}");
+    }
+}
+
+impl crate::fake::SyscallDriver for Sha {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(1)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn command(&self, command_number: u32, argument0: u32, _argument1: u32) -> CommandReturn {
+        match command_number {
+            command::SET_ALGORITHM => match Hasher::new(argument0) {
+                Some(hasher) => {
+                    self.algorithm.set(Some(argument0));
+                    self.hasher.replace(Some(hasher));
+                    command_return::success()
+                }
+                None => command_return::failure(ErrorCode::NoSupport),
+            },
+            _ if self.algorithm.get().is_none() => command_return::failure(ErrorCode::Reserve),
+            command::RUN => {
+                self.schedule_done(self.update().and_then(|()| self.finish()));
+                command_return::success()
+            }
+            command::UPDATE => {
+                self.schedule_done(self.update());
+                command_return::success()
+            }
+            command::FINISH => {
+                self.schedule_done(self.finish());
+                command_return::success()
+            }
+            _ => command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+
+    fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            allow_ro::DATA => Ok(self.data_buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            allow_rw::DIGEST => Ok(self.digest_buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x40005;
+
+// Algorithms
+const SHA_256: u32 = 0;
+const SHA_384: u32 = 1;
+const SHA_512: u32 = 2;
+
+mod command {
+    pub const SET_ALGORITHM: u32 = 0;
+    pub const RUN: u32 = 1;
+    pub const UPDATE: u32 = 2;
+    pub const FINISH: u32 = 3;
+}
+
+mod subscribe {
+    pub const DONE: u32 = 0;
+}
+
+mod allow_ro {
+    pub const DATA: u32 = 1;
+}
+
+mod allow_rw {
+    pub const DIGEST: u32 = 2;
+}
@@ -0,0 +1,81 @@
+use crate::fake::{self, sha::*};
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::{share, DefaultConfig, Syscalls};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let sha = Sha::new();
+    assert_eq!(
+        sha.command(command::UPDATE, 0, 0).get_failure(),
+        Some(ErrorCode::Reserve)
+    );
+    assert_eq!(
+        sha.command(command::SET_ALGORITHM, 3, 0).get_failure(),
+        Some(ErrorCode::NoSupport)
+    );
+    assert_eq!(sha.algorithm(), None);
+    assert!(sha.command(command::SET_ALGORITHM, SHA_384, 0).is_success());
+    assert_eq!(sha.algorithm(), Some(SHA_384));
+
+    assert!(sha
+        .allow_readonly(allow_ro::DATA, RoAllowBuffer::default())
+        .is_ok());
+    assert!(sha.allow_readonly(0, RoAllowBuffer::default()).is_err());
+    assert!(sha
+        .allow_readwrite(allow_rw::DIGEST, RwAllowBuffer::default())
+        .is_ok());
+    assert!(sha.allow_readwrite(0, RwAllowBuffer::default()).is_err());
+}
+
+// Integration test that verifies Sha works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let sha = Sha::new();
+    kernel.add_driver(&sha);
+
+    assert!(fake::Syscalls::command(DRIVER_NUM, command::SET_ALGORITHM, SHA_256, 0).is_success());
+    for data in [&b"a"[..], b"bc"] {
+        share::scope::<AllowRo<_, DRIVER_NUM, { allow_ro::DATA }>, _, _>(|allow| {
+            fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, { allow_ro::DATA }>(allow, data)
+                .unwrap();
+            assert!(fake::Syscalls::command(DRIVER_NUM, command::UPDATE, 0, 0).is_success());
+        });
+        assert_eq!(
+            fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::DONE),
+            (0, 0, 0)
+        );
+    }
+    assert_eq!(sha.updates(), 2);
+
+    // The digest buffer must be large enough.
+    let mut digest = [0; 33];
+    for (len, status) in [(31, ErrorCode::Size as u32), (33, 0)] {
+        share::scope::<AllowRw<_, DRIVER_NUM, { allow_rw::DIGEST }>, _, _>(|allow| {
+            fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, { allow_rw::DIGEST }>(
+                allow,
+                &mut digest[..len],
+            )
+            .unwrap();
+            assert!(fake::Syscalls::command(DRIVER_NUM, command::FINISH, 0, 0).is_success());
+        });
+        assert_eq!(
+            fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::DONE),
+            (status, 0, 0)
+        );
+    }
+    // SHA-256("abc"), from FIPS 180-2.
+    assert_eq!(
+        digest[..32],
+        [
+            0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae,
+            0x22, 0x23, 0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61,
+            0xf2, 0x00, 0x15, 0xad
+        ]
+    );
+    assert_eq!(digest[32], 0);
+}
@@ -42,7 +42,8 @@ pub(super) fn memop(memop_num: Register, argument0: Register) -> [Register; 2] {
             Some(expected_syscall) => expected_syscall.panic_wrong_call("Memop"),
         };
 
-        // Emulate the memop call
+        // Emulate the memop call. Memop operations 2-9 report the memory map
+        // configured through fake::Kernel::set_memory_map.
         // TODO: This emulation could be improved by adding data to kernel_data to allow us to
         // better track what input arguments might be expected to return errors.
         let (memop_return, memop_r1) = match memop_num {
@@ -64,8 +65,55 @@ pub(super) fn memop(memop_num: Register, argument0: Register) -> [Register; 2] {
             }
             2 => {
                 /* app_ram_start */
-                // just pick a random number to always return, for now
-                (return_variant::SUCCESS, 0x123400.into())
+                (
+                    return_variant::SUCCESS,
+                    kernel_data.memory_map.app_ram_start.into(),
+                )
+            }
+            3 => {
+                /* app_ram_end */
+                (
+                    return_variant::SUCCESS_U32,
+                    kernel_data.memory_map.app_ram_end.into(),
+                )
+            }
+            4 => {
+                /* flash_start */
+                (
+                    return_variant::SUCCESS_U32,
+                    kernel_data.memory_map.flash_start.into(),
+                )
+            }
+            5 => {
+                /* flash_end */
+                (
+                    return_variant::SUCCESS_U32,
+                    kernel_data.memory_map.flash_end.into(),
+                )
+            }
+            6 => {
+                /* grant_start */
+                (
+                    return_variant::SUCCESS_U32,
+                    kernel_data.memory_map.grant_start.into(),
+                )
+            }
+            7 => {
+                /* flash_region_count */
+                let count = kernel_data.memory_map.writeable_flash_regions.len();
+                (return_variant::SUCCESS_U32, count.into())
+            }
+            8 | 9 => {
+                /* flash_region_start and flash_region_end */
+                let region = kernel_data
+                    .memory_map
+                    .writeable_flash_regions
+                    .get(usize::from(argument0));
+                match (region, memop_num) {
+                    (None, _) => (return_variant::FAILURE, ErrorCode::Invalid.into()),
+                    (Some(region), 8) => (return_variant::SUCCESS_U32, region.start.into()),
+                    (Some(region), _) => (return_variant::SUCCESS_U32, region.end.into()),
+                }
             }
             10 => {
                 /* debug_stack_start */
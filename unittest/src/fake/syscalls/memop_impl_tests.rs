@@ -82,3 +82,29 @@ fn memop_using_syscall1() {
     assert_eq!(return_variant, return_variant::SUCCESS);
     // No assertion for return value, could be any value from real kernel.
 }
+
+#[test]
+fn memory_map() {
+    let kernel = fake::Kernel::new();
+    kernel.set_memory_map(fake::MemoryMap {
+        writeable_flash_regions: vec![0x1000..0x2000, 0x2000..0x2400],
+        ..Default::default()
+    });
+    let [r0, r1] = memop(7u32.into(), 0u32.into());
+    let r0: u32 = r0.try_into().expect("too large r0");
+    let r1: u32 = r1.try_into().expect("too large r1");
+    assert_eq!(ReturnVariant::from(r0), return_variant::SUCCESS_U32);
+    assert_eq!(r1, 2);
+
+    let [r0, r1] = memop(9u32.into(), 0u32.into());
+    let r0: u32 = r0.try_into().expect("too large r0");
+    let r1: u32 = r1.try_into().expect("too large r1");
+    assert_eq!(ReturnVariant::from(r0), return_variant::SUCCESS_U32);
+    assert_eq!(r1, 0x2000);
+
+    let [r0, r1] = memop(8u32.into(), 2u32.into());
+    let r0: u32 = r0.try_into().expect("too large r0");
+    let r1: u32 = r1.try_into().expect("too large r1");
+    assert_eq!(ReturnVariant::from(r0), return_variant::FAILURE);
+    assert_eq!(r1, ErrorCode::Invalid as u32);
+}
@@ -7,6 +7,7 @@ unsafe impl RawSyscalls for crate::fake::Syscalls {
         match r0.try_into().expect("too-large Yield ID passed") {
             yield_id::NO_WAIT => panic!("yield-no-wait called without an argument"),
             yield_id::WAIT => super::yield_impl::yield_wait(),
+            yield_id::WAIT_FOR => panic!("yield-wait-for called without arguments"),
             id => panic!("unknown yield ID {}", id),
         }
     }
@@ -21,6 +22,23 @@ unsafe impl RawSyscalls for crate::fake::Syscalls {
                 // we fail the test case regardless.
                 panic!("yield-wait called with an argument");
             }
+            yield_id::WAIT_FOR => panic!("yield-wait-for called without a subscribe number"),
+            id => panic!("unknown yield ID {}", id),
+        }
+    }
+
+    unsafe fn yield3([r0, r1, r2]: [Register; 3]) -> [Register; 3] {
+        crate::fake::syscalls::assert_valid((r0, r1, r2));
+        match r0.try_into().expect("too-large Yield ID passed") {
+            yield_id::NO_WAIT => panic!("yield-no-wait called with too many arguments"),
+            yield_id::WAIT => panic!("yield-wait called with arguments"),
+            yield_id::WAIT_FOR => {
+                let (arg0, arg1, arg2) = super::yield_impl::yield_wait_for(
+                    r1.try_into().expect("too-large driver number"),
+                    r2.try_into().expect("too-large subscribe number"),
+                );
+                [arg0.into(), arg1.into(), arg2.into()]
+            }
             id => panic!("unknown yield ID {}", id),
         }
     }
@@ -1,6 +1,7 @@
 //! Implementations of Yield system calls.
 
 use crate::kernel_data::{with_kernel_data, KERNEL_DATA};
+use crate::upcall::UpcallId;
 use crate::{ExpectedSyscall, SyscallLogEntry};
 
 /// # Safety
@@ -64,12 +65,72 @@ pub(super) fn yield_wait() {
     );
 }
 
-// Pops the next upcall off the kernel data's upcall queue and invokes it, or
-// does nothing if the upcall queue was entry. The return value indicates
-// whether an upcall was run. Panics if no kernel data is present.
+pub(super) fn yield_wait_for(driver_num: u32, subscribe_num: u32) -> (u32, u32, u32) {
+    with_kernel_data(|option_kernel_data| {
+        let kernel_data =
+            option_kernel_data.expect("yield-wait-for called but no fake::Kernel exists");
+
+        kernel_data.syscall_log.push(SyscallLogEntry::YieldWaitFor {
+            driver_num,
+            subscribe_num,
+        });
+
+        // Check for an expected syscall entry. Returns early if the expected
+        // syscall overrides the return value. Panics if a different syscall
+        // was expected.
+        match kernel_data.expected_syscalls.pop_front() {
+            None => {}
+            Some(ExpectedSyscall::YieldWaitFor {
+                driver_num: expected_driver_num,
+                subscribe_num: expected_subscribe_num,
+                override_return,
+            }) => {
+                assert_eq!(
+                    driver_num, expected_driver_num,
+                    "expected different driver_num"
+                );
+                assert_eq!(
+                    subscribe_num, expected_subscribe_num,
+                    "expected different subscribe_num"
+                );
+                if let Some(override_return) = override_return {
+                    return override_return;
+                }
+            }
+            Some(expected_syscall) => expected_syscall.panic_wrong_call("yield-wait-for"),
+        }
+
+        // yield-wait-for does not invoke upcalls, so we can remove the upcall
+        // from the queue while holding the kernel data. Like yield-wait, we
+        // panic rather than hang if the upcall is not queued.
+        let upcall_id = UpcallId {
+            driver_num,
+            subscribe_num,
+        };
+        let index = kernel_data
+            .upcall_queue
+            .iter()
+            .position(|queue_entry| queue_entry.id == upcall_id)
+            .expect("yield-wait-for called with no queued upcall");
+        kernel_data.upcall_queue.remove(index).unwrap().args
+    })
+}
+
+// Pops the next non-null upcall off the kernel data's upcall queue and invokes
+// it, or does nothing if the upcall queue contains no non-null upcalls. Null
+// upcalls are only queued for the benefit of yield-wait-for, so they are
+// discarded. The return value indicates whether an upcall was run. Panics if no
+// kernel data is present.
 fn invoke_next_upcall() -> bool {
-    let option_queue_entry =
-        with_kernel_data(|option_kernel_data| option_kernel_data.unwrap().upcall_queue.pop_front());
+    let option_queue_entry = with_kernel_data(|option_kernel_data| {
+        let upcall_queue = &mut option_kernel_data.unwrap().upcall_queue;
+        while let Some(queue_entry) = upcall_queue.pop_front() {
+            if !queue_entry.upcall.is_null() {
+                return Some(queue_entry);
+            }
+        }
+        None
+    });
     match option_queue_entry {
         None => false,
         Some(queue_entry) => {
@@ -190,6 +190,156 @@ fn yield_wait_test() {
     assert_eq!(kernel.take_syscall_log(), [SyscallLogEntry::YieldWait]);
 }
 
+#[test]
+fn yield_wait_for_test() {
+    // Test calling yield_wait_for with no fake::Kernel present.
+    assert!(catch_unwind(|| yield_wait_for(1, 2))
+        .expect_err("failed to catch missing fake::Kernel")
+        .downcast_ref::<String>()
+        .expect("wrong panic payload type")
+        .contains("no fake::Kernel"));
+
+    let kernel = fake::Kernel::new();
+
+    // Test yield_wait_for with a mismatched expected syscall.
+    kernel.add_expected_syscall(ExpectedSyscall::YieldWait { skip_upcall: false });
+    assert!(catch_unwind(|| yield_wait_for(1, 2))
+        .expect_err("failed to catch mismatched expected syscall")
+        .downcast_ref::<String>()
+        .expect("wrong panic payload type")
+        .contains("yield-wait-for was called instead"));
+    assert_eq!(
+        kernel.take_syscall_log(),
+        [SyscallLogEntry::YieldWaitFor {
+            driver_num: 1,
+            subscribe_num: 2
+        }]
+    );
+
+    // Test yield_wait_for with an expected syscall with different arguments.
+    kernel.add_expected_syscall(ExpectedSyscall::YieldWaitFor {
+        driver_num: 1,
+        subscribe_num: 3,
+        override_return: None,
+    });
+    assert!(catch_unwind(|| yield_wait_for(1, 2))
+        .expect_err("failed to catch wrong subscribe_num")
+        .downcast_ref::<String>()
+        .expect("wrong panic payload type")
+        .contains("expected different subscribe_num"));
+    assert_eq!(kernel.take_syscall_log().len(), 1);
+
+    // Test yield_wait_for with no queued upcall.
+    assert!(catch_unwind(|| yield_wait_for(1, 2))
+        .expect_err("failed to catch missing upcall")
+        .downcast_ref::<String>()
+        .expect("wrong panic payload type")
+        .contains("no queued upcall"));
+    assert_eq!(kernel.take_syscall_log().len(), 1);
+
+    // Test yield_wait_for with a return override in an expected syscall.
+    kernel.add_expected_syscall(ExpectedSyscall::YieldWaitFor {
+        driver_num: 1,
+        subscribe_num: 2,
+        override_return: Some((7, 8, 9)),
+    });
+    assert_eq!(yield_wait_for(1, 2), (7, 8, 9));
+    assert_eq!(
+        kernel.take_syscall_log(),
+        [SyscallLogEntry::YieldWaitFor {
+            driver_num: 1,
+            subscribe_num: 2
+        }]
+    );
+
+    // Upcall structures for using copy_args.
+    let mut output_array = [0u32; 3];
+    let upcall = Upcall {
+        fn_pointer: Some(copy_args),
+        data: (&mut output_array as *mut u32).into(),
+    };
+    let null_upcall = Upcall {
+        fn_pointer: None,
+        data: 0usize.into(),
+    };
+
+    // Queue a non-null upcall that yield_wait_for should not touch, followed
+    // by two upcalls (one null, one non-null) for the awaited upcall ID.
+    with_kernel_data(|option_kernel_data| {
+        let upcall_queue = &mut option_kernel_data.unwrap().upcall_queue;
+        upcall_queue.push_back(UpcallQueueEntry {
+            args: (1, 2, 3),
+            id: UpcallId {
+                driver_num: 1,
+                subscribe_num: 1,
+            },
+            upcall,
+        });
+        upcall_queue.push_back(UpcallQueueEntry {
+            args: (4, 5, 6),
+            id: UpcallId {
+                driver_num: 1,
+                subscribe_num: 2,
+            },
+            upcall: null_upcall,
+        });
+        upcall_queue.push_back(UpcallQueueEntry {
+            args: (7, 8, 9),
+            id: UpcallId {
+                driver_num: 1,
+                subscribe_num: 2,
+            },
+            upcall,
+        });
+    });
+
+    // yield_wait_for should return the awaited upcalls' arguments in order,
+    // without invoking any upcall.
+    assert_eq!(yield_wait_for(1, 2), (4, 5, 6));
+    assert_eq!(yield_wait_for(1, 2), (7, 8, 9));
+    assert_eq!(output_array, [0; 3]);
+    assert_eq!(kernel.take_syscall_log().len(), 2);
+
+    // The other upcall should still be queued.
+    yield_wait();
+    assert_eq!(output_array, [1, 2, 3]);
+}
+
+// Tests that yield_wait and yield_no_wait discard null upcalls.
+#[test]
+fn yield_skips_null_upcalls() {
+    let kernel = fake::Kernel::new();
+    let null_upcall = Upcall {
+        fn_pointer: None,
+        data: 0usize.into(),
+    };
+    with_kernel_data(|option_kernel_data| {
+        option_kernel_data
+            .unwrap()
+            .upcall_queue
+            .push_back(UpcallQueueEntry {
+                args: (1, 2, 3),
+                id: UpcallId {
+                    driver_num: 1,
+                    subscribe_num: 2,
+                },
+                upcall: null_upcall,
+            });
+    });
+    assert!(!fake::Kernel::is_upcall_pending());
+    let mut return_value = core::mem::MaybeUninit::<YieldNoWaitReturn>::uninit();
+    unsafe {
+        yield_no_wait(return_value.as_mut_ptr());
+    }
+    let return_value = unsafe { return_value.assume_init() };
+    assert_eq!(return_value, YieldNoWaitReturn::NoUpcall);
+    assert!(with_kernel_data(|option_kernel_data| option_kernel_data
+        .unwrap()
+        .upcall_queue
+        .is_empty()));
+    assert_eq!(kernel.take_syscall_log(), [SyscallLogEntry::YieldNoWait]);
+}
+
 // TODO: Move the yield1 and yield2 tests into a raw_syscalls_impl test module,
 // once all system calls have been implemented.
 
@@ -223,8 +373,16 @@ fn yield1() {
     }
     assert_eq!(kernel.take_syscall_log(), [SyscallLogEntry::YieldWait]);
 
-    // Call yield1 with a yield ID that is unknown but which fits in a u32.
+    // Call yield-wait-for through yield1, which is not valid.
     let result = catch_unwind(|| unsafe { fake::Syscalls::yield1([2u32.into()]) });
+    assert!(result
+        .expect_err("failed to catch yield-wait-for without args")
+        .downcast_ref::<&'static str>()
+        .expect("wrong panic payload type")
+        .contains("yield-wait-for called without arguments"));
+
+    // Call yield1 with a yield ID that is unknown but which fits in a u32.
+    let result = catch_unwind(|| unsafe { fake::Syscalls::yield1([3u32.into()]) });
     assert!(result
         .expect_err("failed to catch incorrect yield ID -- new ID added?")
         .downcast_ref::<String>()
@@ -270,8 +428,76 @@ fn yield2() {
         .expect("wrong panic payload type")
         .contains("yield-wait called with an argument"));
 
-    // Call yield2 with a yield ID that is unknown but which fits in a u32.
+    // Call yield-wait-for through yield2, which should be rejected.
     let result = catch_unwind(|| unsafe { fake::Syscalls::yield2([2u32.into(), 0u32.into()]) });
+    assert!(result
+        .expect_err("failed to catch yield-wait-for without subscribe number")
+        .downcast_ref::<&'static str>()
+        .expect("wrong panic payload type")
+        .contains("yield-wait-for called without a subscribe number"));
+
+    // Call yield2 with a yield ID that is unknown but which fits in a u32.
+    let result = catch_unwind(|| unsafe { fake::Syscalls::yield2([3u32.into(), 0u32.into()]) });
+    assert!(result
+        .expect_err("failed to catch incorrect yield ID -- new ID added?")
+        .downcast_ref::<String>()
+        .expect("wrong panic payload type")
+        .contains("unknown yield ID"));
+}
+
+// Tests RawSyscalls::yield3's handling of bad yield IDs.
+#[test]
+fn yield3() {
+    let kernel = fake::Kernel::new();
+
+    #[cfg(target_pointer_width = "64")]
+    {
+        let result = catch_unwind(|| unsafe {
+            fake::Syscalls::yield3([(u32::MAX as usize + 1).into(), 0u32.into(), 0u32.into()])
+        });
+        assert!(result
+            .expect_err("failed to catch too large yield ID")
+            .downcast_ref::<String>()
+            .expect("wrong panic payload type")
+            .contains("too-large Yield ID"));
+    }
+
+    // Test a successful invocation of yield-wait-for.
+    kernel.add_expected_syscall(ExpectedSyscall::YieldWaitFor {
+        driver_num: 1,
+        subscribe_num: 2,
+        override_return: Some((3, 4, 5)),
+    });
+    let [r0, r1, r2] = unsafe { fake::Syscalls::yield3([2u32.into(), 1u32.into(), 2u32.into()]) };
+    assert_eq!(
+        kernel.take_syscall_log(),
+        [SyscallLogEntry::YieldWaitFor {
+            driver_num: 1,
+            subscribe_num: 2
+        }]
+    );
+    assert_eq!((r0.as_u32(), r1.as_u32(), r2.as_u32()), (3, 4, 5));
+
+    // Call yield-no-wait and yield-wait through yield3, which should be
+    // rejected.
+    let result =
+        catch_unwind(|| unsafe { fake::Syscalls::yield3([0u32.into(), 0u32.into(), 0u32.into()]) });
+    assert!(result
+        .expect_err("failed to catch yield-no-wait with extra args")
+        .downcast_ref::<&'static str>()
+        .expect("wrong panic payload type")
+        .contains("yield-no-wait called with too many arguments"));
+    let result =
+        catch_unwind(|| unsafe { fake::Syscalls::yield3([1u32.into(), 0u32.into(), 0u32.into()]) });
+    assert!(result
+        .expect_err("failed to catch yield-wait with args")
+        .downcast_ref::<&'static str>()
+        .expect("wrong panic payload type")
+        .contains("yield-wait called with arguments"));
+
+    // Call yield3 with a yield ID that is unknown but which fits in a u32.
+    let result =
+        catch_unwind(|| unsafe { fake::Syscalls::yield3([3u32.into(), 0u32.into(), 0u32.into()]) });
     assert!(result
         .expect_err("failed to catch incorrect yield ID -- new ID added?")
         .downcast_ref::<String>()
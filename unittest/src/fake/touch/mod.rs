@@ -0,0 +1,240 @@
+//! Fake implementation of the touch panel API.
+//!
+//! Touches injected with `inject_touch` or `inject_touch_sequence` are
+//! delivered as single touch upcalls while single touch is enabled, and
+//! gestures injected with `inject_gesture` are always delivered. Multi-touch
+//! events injected with `inject_multi_touch` are queued, and each is written
+//! to the process' buffer once multi-touch is enabled, the buffer is shared,
+//! and the previous event has been acknowledged.
+
+use core::cell::{Cell, RefCell};
+use libtock_platform::{CommandReturn, ErrorCode};
+use std::collections::VecDeque;
+
+use crate::{command_return, DriverInfo, DriverShareRef, RwAllowBuffer};
+
+/// A touch, with its status encoded as the kernel encodes it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TouchEvent {
+    pub id: u8,
+    pub status: u32,
+    pub x: u16,
+    pub y: u16,
+    pub size: u16,
+    pub pressure: u16,
+}
+
+// Touch statuses, as numbered by the kernel.
+pub const RELEASED: u32 = 0;
+pub const PRESSED: u32 = 1;
+pub const MOVED: u32 = 2;
+pub const UNSTARTED: u32 = 3;
+
+pub struct Touch {
+    max_touches: u32,
+    single_touch_enabled: Cell<bool>,
+    multi_touch_enabled: Cell<bool>,
+
+    multi_touch_buffer: RefCell<RwAllowBuffer>,
+    multi_touches: RefCell<VecDeque<Vec<TouchEvent>>>,
+    awaiting_ack: Cell<bool>,
+    dropped_events: Cell<u32>,
+
+    share_ref: DriverShareRef,
+}
+
+impl Touch {
+    /// Creates a panel that can track up to 5 simultaneous touches.
+    pub fn new() -> std::rc::Rc<Touch> {
+        std::rc::Rc::new(Touch {
+            max_touches: 5,
+            single_touch_enabled: Default::default(),
+            multi_touch_enabled: Default::default(),
+            multi_touch_buffer: Default::default(),
+            multi_touches: Default::default(),
+            awaiting_ack: Default::default(),
+            dropped_events: Default::default(),
+            share_ref: Default::default(),
+        })
+    }
+
+    pub fn is_single_touch_enabled(&self) -> bool {
+        self.single_touch_enabled.get()
+    }
+
+    pub fn is_multi_touch_enabled(&self) -> bool {
+        self.multi_touch_enabled.get()
+    }
+
+    /// Reports a single touch. Ignored unless single touch is enabled.
+    pub fn inject_touch(&self, event: TouchEvent) {
+        if !self.single_touch_enabled.get() {
+            return;
+        }
+        self.share_ref
+            .schedule_upcall(
+                subscribe::SINGLE_TOUCH,
+                (
+                    event.status,
+                    (event.x as u32) << 16 | event.y as u32,
+                    (event.pressure as u32) << 16 | event.size as u32,
+                ),
+            )
+            .expect("Unable to schedule upcall {}");
+    }
+
+    /// Reports a series of single touches, such as a press, several moves,
+    /// and a release.
+    pub fn inject_touch_sequence(&self, events: &[TouchEvent]) {
+        for &event in events {
+            self.inject_touch(event);
+        }
+    }
+
+    pub fn inject_gesture(&self, gesture: u32) {
+        self.share_ref
+            .schedule_upcall(subscribe::GESTURE, (gesture, 0, 0))
+            .expect("Unable to schedule upcall {}");
+    }
+
+    /// Queues a multi-touch event.
+    pub fn inject_multi_touch(&self, touches: &[TouchEvent]) {
+        assert!(
+            touches.len() <= self.max_touches as usize,
+            "More touches than the panel supports"
+        );
+        self.multi_touches.borrow_mut().push_back(touches.into());
+        self.deliver_multi_touch();
+    }
+
+    /// Simulates the kernel dropping `count` multi-touch events. The count is
+    /// reported along with the next multi-touch event.
+    pub fn drop_multi_touch_events(&self, count: u32) {
+        self.dropped_events.set(self.dropped_events.get() + count);
+    }
+
+    /// Returns the number of queued multi-touch events that have not been
+    /// delivered.
+    pub fn pending_multi_touches(&self) -> usize {
+        self.multi_touches.borrow().len()
+    }
+
+    // Writes the next queued multi-touch event into the shared buffer, if the
+    // process is ready for it.
+    fn deliver_multi_touch(&self) {
+        let mut buffer = self.multi_touch_buffer.borrow_mut();
+        if !self.multi_touch_enabled.get() || self.awaiting_ack.get() || buffer.is_empty() {
+            return;
+        }
+        let touches = match self.multi_touches.borrow_mut().pop_front() {
+            Some(touches) => touches,
+            None => return,
+        };
+        let count = touches.len().min(buffer.len() / TOUCH_LEN);
+        for (touch, bytes) in touches.iter().zip(buffer.chunks_exact_mut(TOUCH_LEN)) {
+            bytes[0] = touch.id;
+            bytes[1] = touch.status as u8;
+            bytes[2..4].copy_from_slice(&touch.x.to_le_bytes());
+            bytes[4..6].copy_from_slice(&touch.y.to_le_bytes());
+            bytes[6] = touch.size as u8;
+            bytes[7] = touch.pressure as u8;
+        }
+        self.awaiting_ack.set(true);
+        self.share_ref
+            .schedule_upcall(
+                subscribe::MULTI_TOUCH,
+                (
+                    count as u32,
+                    self.dropped_events.take(),
+                    buffer.len() as u32,
+                ),
+            )
+            .expect("Unable to schedule upcall {}");
+    }
+}
+
+impl crate::fake::SyscallDriver for Touch {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(3)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn command(&self, command_number: u32, _argument0: u32, _argument1: u32) -> CommandReturn {
+        match command_number {
+            command::EXISTS => command_return::success(),
+            command::ENABLE_SINGLE_TOUCH => {
+                self.single_touch_enabled.set(true);
+                command_return::success()
+            }
+            command::DISABLE_SINGLE_TOUCH => {
+                self.single_touch_enabled.set(false);
+                command_return::success()
+            }
+            command::MULTI_TOUCH_ACK => {
+                self.awaiting_ack.set(false);
+                self.deliver_multi_touch();
+                command_return::success()
+            }
+            command::ENABLE_MULTI_TOUCH => {
+                self.multi_touch_enabled.set(true);
+                self.deliver_multi_touch();
+                command_return::success()
+            }
+            command::DISABLE_MULTI_TOUCH => {
+                self.multi_touch_enabled.set(false);
+                command_return::success()
+            }
+            command::NUMBER_OF_TOUCHES => command_return::success_u32(self.max_touches),
+            _ => command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            allow_rw::MULTI_TOUCH => {
+                let old = self.multi_touch_buffer.replace(buffer);
+                self.deliver_multi_touch();
+                Ok(old)
+            }
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests;
+
+const TOUCH_LEN: usize = 8;
+
+const DRIVER_NUM: u32 = 0x90002;
+
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const ENABLE_SINGLE_TOUCH: u32 = 1;
+    pub const DISABLE_SINGLE_TOUCH: u32 = 2;
+    pub const MULTI_TOUCH_ACK: u32 = 10;
+    pub const ENABLE_MULTI_TOUCH: u32 = 11;
+    pub const DISABLE_MULTI_TOUCH: u32 = 12;
+    pub const NUMBER_OF_TOUCHES: u32 = 100;
+}
+
+mod subscribe {
+    pub const SINGLE_TOUCH: u32 = 0;
+    pub const GESTURE: u32 = 1;
+    pub const MULTI_TOUCH: u32 = 2;
+}
+
+mod allow_rw {
+    pub const MULTI_TOUCH: u32 = 2;
+}
@@ -0,0 +1,114 @@
+use crate::fake::{self, touch::*};
+use crate::RwAllowBuffer;
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::{share, DefaultConfig, Syscalls};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let touch = Touch::new();
+    assert!(touch.command(command::EXISTS, 0, 0).is_success());
+    assert_eq!(
+        touch
+            .command(command::NUMBER_OF_TOUCHES, 0, 0)
+            .get_success_u32(),
+        Some(5)
+    );
+
+    assert!(touch
+        .command(command::ENABLE_SINGLE_TOUCH, 0, 0)
+        .is_success());
+    assert!(touch.is_single_touch_enabled());
+    assert!(touch
+        .command(command::DISABLE_SINGLE_TOUCH, 0, 0)
+        .is_success());
+    assert!(!touch.is_single_touch_enabled());
+
+    assert!(touch
+        .command(command::ENABLE_MULTI_TOUCH, 0, 0)
+        .is_success());
+    assert!(touch.is_multi_touch_enabled());
+    assert!(touch
+        .command(command::DISABLE_MULTI_TOUCH, 0, 0)
+        .is_success());
+    assert!(!touch.is_multi_touch_enabled());
+
+    assert!(touch
+        .allow_readwrite(allow_rw::MULTI_TOUCH, RwAllowBuffer::default())
+        .is_ok());
+    assert!(touch.allow_readwrite(0, RwAllowBuffer::default()).is_err());
+}
+
+// Integration test that verifies Touch works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let touch = Touch::new();
+    kernel.add_driver(&touch);
+
+    // Single touches are only delivered while enabled.
+    touch.inject_touch(TouchEvent::default());
+    assert!(fake::Syscalls::command(DRIVER_NUM, command::ENABLE_SINGLE_TOUCH, 0, 0).is_success());
+    touch.inject_touch_sequence(&[
+        TouchEvent {
+            status: PRESSED,
+            x: 10,
+            y: 20,
+            ..Default::default()
+        },
+        TouchEvent {
+            status: RELEASED,
+            x: 11,
+            y: 21,
+            size: 3,
+            pressure: 7,
+            ..Default::default()
+        },
+    ]);
+    assert_eq!(
+        fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::SINGLE_TOUCH),
+        (PRESSED, 10 << 16 | 20, 0)
+    );
+    assert_eq!(
+        fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::SINGLE_TOUCH),
+        (RELEASED, 11 << 16 | 21, 7 << 16 | 3)
+    );
+
+    touch.inject_gesture(3);
+    assert_eq!(
+        fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::GESTURE),
+        (3, 0, 0)
+    );
+
+    // Multi-touch events wait until multi-touch is enabled and the buffer is
+    // shared.
+    touch.inject_multi_touch(
+        &[TouchEvent {
+            id: 1,
+            status: MOVED,
+            x: 0x102,
+            y: 3,
+            size: 4,
+            pressure: 5,
+        }; 2],
+    );
+    touch.drop_multi_touch_events(2);
+    assert!(fake::Syscalls::command(DRIVER_NUM, command::ENABLE_MULTI_TOUCH, 0, 0).is_success());
+    assert_eq!(touch.pending_multi_touches(), 1);
+    let mut buffer = [0; 12];
+    share::scope::<AllowRw<_, DRIVER_NUM, { allow_rw::MULTI_TOUCH }>, _, _>(|allow| {
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, { allow_rw::MULTI_TOUCH }>(
+            allow,
+            &mut buffer,
+        )
+        .unwrap();
+        assert_eq!(
+            fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::MULTI_TOUCH),
+            (1, 2, 12)
+        );
+    });
+    assert_eq!(buffer[..8], [1, MOVED as u8, 2, 1, 3, 0, 4, 5]);
+    assert_eq!(touch.pending_multi_touches(), 0);
+}
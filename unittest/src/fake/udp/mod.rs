@@ -0,0 +1,256 @@
+//! Fake implementation of the UDP API.
+//!
+//! `Udp` records the datagrams the process sends (`take_sent`) and delivers
+//! datagrams queued with `receive_datagram` once the process has shared its
+//! receive buffers, as it does while waiting in `receive_from`.
+
+use core::cell::{Cell, RefCell};
+use libtock_platform::{CommandReturn, ErrorCode};
+use std::collections::VecDeque;
+use std::net::{Ipv6Addr, SocketAddrV6};
+
+use crate::{command_return, DriverInfo, DriverShareRef, RoAllowBuffer, RwAllowBuffer};
+
+/// A datagram sent by or to the process.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Datagram {
+    pub src: SocketAddrV6,
+    pub dest: SocketAddrV6,
+    pub payload: Vec<u8>,
+}
+
+pub struct Udp {
+    interfaces: Vec<Ipv6Addr>,
+    max_payload_len: Cell<u32>,
+    bound: Cell<Option<SocketAddrV6>>,
+
+    tx_buf: Cell<RoAllowBuffer>,
+    cfg_buf: RefCell<RwAllowBuffer>,
+    rx_buf: RefCell<RwAllowBuffer>,
+    rx_cfg_buf: RefCell<RwAllowBuffer>,
+
+    sent: Cell<Vec<Datagram>>,
+    to_be_received: RefCell<VecDeque<Datagram>>,
+
+    share_ref: DriverShareRef,
+}
+
+impl Udp {
+    /// Creates a fake UDP driver with a single interface, `fe80::1`.
+    pub fn new() -> std::rc::Rc<Udp> {
+        Self::new_with_interfaces(&[Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1)])
+    }
+
+    pub fn new_with_interfaces(interfaces: &[Ipv6Addr]) -> std::rc::Rc<Udp> {
+        std::rc::Rc::new(Udp {
+            interfaces: interfaces.into(),
+            max_payload_len: Cell::new(DEFAULT_MAX_PAYLOAD_LEN),
+            bound: Default::default(),
+            tx_buf: Default::default(),
+            cfg_buf: Default::default(),
+            rx_buf: Default::default(),
+            rx_cfg_buf: Default::default(),
+            sent: Default::default(),
+            to_be_received: Default::default(),
+            share_ref: Default::default(),
+        })
+    }
+
+    pub fn set_max_payload_len(&self, len: u32) {
+        self.max_payload_len.set(len);
+    }
+
+    /// Returns the address the process is bound to, if any.
+    pub fn bound(&self) -> Option<SocketAddrV6> {
+        self.bound.get()
+    }
+
+    /// Returns the datagrams sent so far, and clears them.
+    pub fn take_sent(&self) -> Vec<Datagram> {
+        self.sent.take()
+    }
+
+    /// Queues a datagram to be received by the process. It is delivered once
+    /// the process has shared its receive buffers. Datagrams that are not
+    /// addressed to the bound port are dropped when they are delivered.
+    pub fn receive_datagram(&self, datagram: Datagram) {
+        self.to_be_received.borrow_mut().push_back(datagram);
+        self.deliver();
+    }
+
+    /// Returns `true` if there are datagrams that have not been delivered yet.
+    pub fn has_pending_datagrams(&self) -> bool {
+        !self.to_be_received.borrow().is_empty()
+    }
+
+    // Delivers the next queued datagram if the process is bound and both its
+    // receive buffers are shared.
+    fn deliver(&self) {
+        let bound = match self.bound.get() {
+            Some(bound) => bound,
+            None => return,
+        };
+        let mut rx_buf = self.rx_buf.borrow_mut();
+        let mut rx_cfg_buf = self.rx_cfg_buf.borrow_mut();
+        if rx_buf.is_empty() || rx_cfg_buf.len() < 2 * SOCK_ADDR_LEN {
+            return;
+        }
+        while let Some(datagram) = self.to_be_received.borrow_mut().pop_front() {
+            if datagram.dest.port() != bound.port() {
+                continue;
+            }
+            let len = datagram.payload.len().min(rx_buf.len());
+            rx_buf[..len].copy_from_slice(&datagram.payload[..len]);
+            encode_sock_addr(&mut rx_cfg_buf[..SOCK_ADDR_LEN], datagram.src);
+            self.share_ref
+                .schedule_upcall(subscribe::PACKET_RECEIVED, (len as u32, 0, 0))
+                .expect("Unable to schedule upcall {}");
+            return;
+        }
+    }
+
+    fn send(&self) -> CommandReturn {
+        let bound = match self.bound.get() {
+            Some(bound) => bound,
+            None => return command_return::failure(ErrorCode::Invalid),
+        };
+        let cfg_buf = self.cfg_buf.borrow();
+        if cfg_buf.len() < 2 * SOCK_ADDR_LEN {
+            return command_return::failure(ErrorCode::Invalid);
+        }
+        let src = decode_sock_addr(&cfg_buf[..SOCK_ADDR_LEN]);
+        let dest = decode_sock_addr(&cfg_buf[SOCK_ADDR_LEN..2 * SOCK_ADDR_LEN]);
+        if src != bound {
+            return command_return::failure(ErrorCode::Invalid);
+        }
+        let tx_buf = self.tx_buf.take();
+        let payload = Vec::from(&*tx_buf);
+        self.tx_buf.set(tx_buf);
+        if payload.len() > self.max_payload_len.get() as usize {
+            return command_return::failure(ErrorCode::Size);
+        }
+        let mut sent = self.sent.take();
+        sent.push(Datagram { src, dest, payload });
+        self.sent.set(sent);
+        self.share_ref
+            .schedule_upcall(subscribe::PACKET_TRANSMITTED, (0, 0, 0))
+            .expect("Unable to schedule upcall {}");
+        command_return::success()
+    }
+}
+
+impl crate::fake::SyscallDriver for Udp {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(2)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn command(&self, command_number: u32, argument0: u32, _argument1: u32) -> CommandReturn {
+        match command_number {
+            command::EXISTS => command_return::success(),
+            command::INTERFACES => {
+                let mut cfg_buf = self.cfg_buf.borrow_mut();
+                for (interface, bytes) in self
+                    .interfaces
+                    .iter()
+                    .take(argument0 as usize)
+                    .zip(cfg_buf.chunks_exact_mut(IPV6_ADDR_LEN))
+                {
+                    bytes.copy_from_slice(&interface.octets());
+                }
+                command_return::success_u32(self.interfaces.len() as u32)
+            }
+            command::SEND => self.send(),
+            command::BIND => {
+                let rx_cfg_buf = self.rx_cfg_buf.borrow();
+                if rx_cfg_buf.len() < 2 * SOCK_ADDR_LEN {
+                    return command_return::failure(ErrorCode::Invalid);
+                }
+                let addr = decode_sock_addr(&rx_cfg_buf[SOCK_ADDR_LEN..2 * SOCK_ADDR_LEN]);
+                if !self.interfaces.contains(addr.ip()) {
+                    return command_return::failure(ErrorCode::Invalid);
+                }
+                self.bound.set(Some(addr));
+                command_return::success()
+            }
+            command::MAX_PAYLOAD_LEN => command_return::success_u32(self.max_payload_len.get()),
+            _ => command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+
+    fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            allow_ro::WRITE => Ok(self.tx_buf.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        let old = match buffer_num {
+            allow_rw::READ => self.rx_buf.replace(buffer),
+            allow_rw::CFG => self.cfg_buf.replace(buffer),
+            allow_rw::RX_CFG => self.rx_cfg_buf.replace(buffer),
+            _ => return Err((buffer, ErrorCode::Invalid)),
+        };
+        self.deliver();
+        Ok(old)
+    }
+}
+
+fn encode_sock_addr(buf: &mut [u8], addr: SocketAddrV6) {
+    buf[..IPV6_ADDR_LEN].copy_from_slice(&addr.ip().octets());
+    buf[IPV6_ADDR_LEN..SOCK_ADDR_LEN].copy_from_slice(&addr.port().to_ne_bytes());
+}
+
+fn decode_sock_addr(buf: &[u8]) -> SocketAddrV6 {
+    let ip: [u8; IPV6_ADDR_LEN] = buf[..IPV6_ADDR_LEN].try_into().unwrap();
+    let port = u16::from_ne_bytes([buf[IPV6_ADDR_LEN], buf[IPV6_ADDR_LEN + 1]]);
+    SocketAddrV6::new(ip.into(), port, 0, 0)
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests;
+
+const DEFAULT_MAX_PAYLOAD_LEN: u32 = 1280 - 40 - 8;
+const IPV6_ADDR_LEN: usize = 16;
+const SOCK_ADDR_LEN: usize = IPV6_ADDR_LEN + 2;
+
+const DRIVER_NUM: u32 = 0x30002;
+
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const INTERFACES: u32 = 1;
+    pub const SEND: u32 = 2;
+    pub const BIND: u32 = 3;
+    pub const MAX_PAYLOAD_LEN: u32 = 4;
+}
+
+mod subscribe {
+    pub const PACKET_RECEIVED: u32 = 0;
+    pub const PACKET_TRANSMITTED: u32 = 1;
+}
+
+mod allow_ro {
+    pub const WRITE: u32 = 0;
+}
+
+mod allow_rw {
+    pub const READ: u32 = 0;
+    pub const CFG: u32 = 1;
+    pub const RX_CFG: u32 = 2;
+}
@@ -0,0 +1,137 @@
+use crate::fake::{self, udp::*};
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::{share, DefaultConfig, Syscalls};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let udp = Udp::new();
+    assert!(udp.command(command::EXISTS, 0, 0).is_success());
+    assert_eq!(
+        udp.command(command::MAX_PAYLOAD_LEN, 0, 0)
+            .get_success_u32(),
+        Some(DEFAULT_MAX_PAYLOAD_LEN)
+    );
+    udp.set_max_payload_len(100);
+    assert_eq!(
+        udp.command(command::MAX_PAYLOAD_LEN, 0, 0)
+            .get_success_u32(),
+        Some(100)
+    );
+    assert_eq!(
+        udp.command(command::SEND, 0, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert_eq!(
+        udp.command(command::BIND, 0, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert!(udp
+        .allow_readonly(allow_ro::WRITE, RoAllowBuffer::default())
+        .is_ok());
+    assert!(udp.allow_readonly(1, RoAllowBuffer::default()).is_err());
+    assert!(udp
+        .allow_readwrite(allow_rw::RX_CFG, RwAllowBuffer::default())
+        .is_ok());
+    assert!(udp.allow_readwrite(3, RwAllowBuffer::default()).is_err());
+}
+
+// Integration test that verifies Udp works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let local_ip = Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1);
+    let udp = Udp::new_with_interfaces(&[local_ip]);
+    kernel.add_driver(&udp);
+    let local = SocketAddrV6::new(local_ip, 1000, 0, 0);
+    let peer = SocketAddrV6::new(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 2), 2000, 0, 0);
+
+    let mut cfg = [0; 2 * SOCK_ADDR_LEN];
+    encode_sock_addr(&mut cfg[SOCK_ADDR_LEN..], local);
+    share::scope(|allow_rw| {
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, { allow_rw::RX_CFG }>(
+            allow_rw, &mut cfg,
+        )
+        .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, command::BIND, 0, 0).is_success());
+    });
+    assert_eq!(udp.bound(), Some(local));
+
+    // Datagrams are only delivered while the receive buffers are shared, and
+    // datagrams for other ports are dropped.
+    udp.receive_datagram(Datagram {
+        src: peer,
+        dest: SocketAddrV6::new(local_ip, 1001, 0, 0),
+        payload: b"dropped".to_vec(),
+    });
+    udp.receive_datagram(Datagram {
+        src: peer,
+        dest: local,
+        payload: b"hello".to_vec(),
+    });
+    assert!(udp.has_pending_datagrams());
+    let mut buf = [0; 4];
+    share::scope::<
+        (
+            AllowRw<_, DRIVER_NUM, { allow_rw::READ }>,
+            AllowRw<_, DRIVER_NUM, { allow_rw::RX_CFG }>,
+        ),
+        _,
+        _,
+    >(|handle| {
+        let (allow_read, allow_cfg) = handle.split();
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, { allow_rw::READ }>(
+            allow_read, &mut buf,
+        )
+        .unwrap();
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, { allow_rw::RX_CFG }>(
+            allow_cfg, &mut cfg,
+        )
+        .unwrap();
+        assert_eq!(
+            fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::PACKET_RECEIVED),
+            (4, 0, 0)
+        );
+    });
+    assert!(!udp.has_pending_datagrams());
+    assert_eq!(&buf, b"hell");
+    assert_eq!(decode_sock_addr(&cfg[..SOCK_ADDR_LEN]), peer);
+
+    // Send the datagram back.
+    encode_sock_addr(&mut cfg[..SOCK_ADDR_LEN], local);
+    encode_sock_addr(&mut cfg[SOCK_ADDR_LEN..], peer);
+    share::scope::<
+        (
+            AllowRo<_, DRIVER_NUM, { allow_ro::WRITE }>,
+            AllowRw<_, DRIVER_NUM, { allow_rw::CFG }>,
+        ),
+        _,
+        _,
+    >(|handle| {
+        let (allow_ro, allow_rw) = handle.split();
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, { allow_ro::WRITE }>(
+            allow_ro, b"hell",
+        )
+        .unwrap();
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, { allow_rw::CFG }>(
+            allow_rw, &mut cfg,
+        )
+        .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, command::SEND, 0, 0).is_success());
+        assert_eq!(
+            fake::Syscalls::yield_wait_for(DRIVER_NUM, subscribe::PACKET_TRANSMITTED),
+            (0, 0, 0)
+        );
+    });
+    assert_eq!(
+        udp.take_sent(),
+        [Datagram {
+            src: local,
+            dest: peer,
+            payload: b"hell".to_vec(),
+        }]
+    );
+}
@@ -24,6 +24,7 @@ pub(crate) struct KernelData {
     pub syscall_log: Vec<crate::SyscallLogEntry>,
     pub upcall_queue: crate::upcall::UpcallQueue,
     pub memory_break: *const u8,
+    pub memory_map: crate::fake::MemoryMap,
 }
 
 // KERNEL_DATA is set to Some in `fake::Kernel::new` and set to None when the
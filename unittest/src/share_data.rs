@@ -1,5 +1,5 @@
 use crate::kernel_data::with_kernel_data;
-use crate::upcall::{UpcallId, UpcallQueueEntry};
+use crate::upcall::{Upcall, UpcallId, UpcallQueueEntry};
 use std::cell::Cell;
 
 /// A reference used by a `fake::SyscallDriver` to access data shared between it
@@ -23,8 +23,9 @@ impl DriverShareRef {
     }
 
     /// Schedules the upcall with the specified subscribe number. Like the real
-    /// kernel, this does nothing if there is no upcall with number
-    /// `subscribe_num` or the upcall is the null upcall.
+    /// kernel, if there is no upcall with number `subscribe_num` or the upcall
+    /// is the null upcall, the queued upcall is only visible to
+    /// yield-wait-for; other Yield calls discard it without invoking it.
     pub fn schedule_upcall(
         &self,
         subscribe_num: u32,
@@ -45,16 +46,16 @@ impl DriverShareRef {
                     requested: subscribe_num,
                 });
             }
+            // Null upcalls don't do anything when invoked, but we still queue
+            // them (as the core kernel does) so that yield-wait-for can
+            // receive them.
             let upcall = match driver_data.upcalls.get(&subscribe_num) {
                 Some(&upcall) => upcall,
-                None => return Ok(()),
+                None => Upcall {
+                    fn_pointer: None,
+                    data: 0usize.into(),
+                },
             };
-            // Don't bother queueing a null upcall, as they don't do anything
-            // when invoked anyway, and the core kernel does not queue them
-            // either.
-            if upcall.is_null() {
-                return Ok(());
-            }
             kernel_data.upcall_queue.push_back(UpcallQueueEntry {
                 args,
                 id: UpcallId {
@@ -125,8 +126,14 @@ mod tests {
         with_kernel_data(|kernel_data| {
             let kernel_data = kernel_data.unwrap();
 
-            // There was no upcall to schedule, so the queue should still be
-            // empty.
+            // There was no upcall registered, so a null upcall should have
+            // been queued (for yield-wait-for's benefit).
+            let upcall_queue_entry = kernel_data
+                .upcall_queue
+                .pop_front()
+                .expect("Upcall not queued");
+            assert!(upcall_queue_entry.upcall.is_null());
+            assert_eq!(upcall_queue_entry.args, (3, 4, 5));
             assert!(kernel_data.upcall_queue.is_empty());
 
             // Register a null upcall.
@@ -138,8 +145,7 @@ mod tests {
                 },
             );
         });
-        // Call schedule again. This should still do nothing, because the upcall
-        // is a null upcall.
+        // Call schedule again. This should queue the null upcall.
         assert_eq!(mock_driver.share_ref.schedule_upcall(2, (3, 4, 5)), Ok(()));
         unsafe extern "C" fn upcall(_: u32, _: u32, _: u32, _: libtock_platform::Register) {}
         // Cast to a pointer to get a stable address.
@@ -147,7 +153,14 @@ mod tests {
         with_kernel_data(|kernel_data| {
             let kernel_data = kernel_data.unwrap();
 
-            // Verify the upcall was not queued.
+            // Verify the null upcall was queued, then clear the queue.
+            let upcall_queue_entry = kernel_data
+                .upcall_queue
+                .pop_front()
+                .expect("Upcall not queued");
+            assert!(upcall_queue_entry.upcall.is_null());
+            let data: usize = upcall_queue_entry.upcall.data.into();
+            assert_eq!(data, 1234);
             assert!(kernel_data.upcall_queue.is_empty());
 
             // Register a non-null upcall.
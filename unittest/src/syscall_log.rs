@@ -10,6 +10,11 @@ pub enum SyscallLogEntry {
 
     YieldWait,
 
+    YieldWaitFor {
+        driver_num: u32,
+        subscribe_num: u32,
+    },
+
     // -------------------------------------------------------------------------
     // Subscribe
     // -------------------------------------------------------------------------